@@ -0,0 +1,257 @@
+//! 隐藏信息下的公平 AI：以多组“确定化”局面代替对手真实的手牌与牌库，
+//! 分别搜索后按票数汇总，使 AI 不再读取它本不该知道的信息。
+//!
+//! 提供卡池时，隐藏的卡牌从卡池中按对手已公开卡牌的费用曲线加权抽样；
+//! 未提供卡池时假定对手的牌表已知，只在手牌与牌库之间重新洗混。
+
+use std::collections::HashMap;
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::minimax::{AiAgent, AiConfig, AiDecision, SearchSession, WasmInstant};
+use crate::game::{Card, CardDefinition, CardRegistry, GameAction, GameState, PlayerId};
+
+impl AiAgent {
+    /// 确定化时从 `registry` 的可收集卡牌中抽样代替对手的隐藏卡牌。
+    pub fn with_card_pool(mut self, registry: &CardRegistry) -> Self {
+        self.pool = registry.collectible().cloned().collect();
+        self
+    }
+
+    /// 生成一个确定化局面：对手的手牌与牌库替换为抽样的卡牌，卡牌 ID 保持不变。
+    pub(super) fn determinize(&mut self, state: &GameState, player_id: PlayerId) -> GameState {
+        let mut sampled = state.clone();
+        for enemy in state.enemies_of(player_id) {
+            let Some(index) = sampled.player_index(enemy) else {
+                continue;
+            };
+            let opponent = &mut sampled.players[index];
+            if self.pool.is_empty() {
+                let hand_size = opponent.hand.len();
+                let mut hidden = std::mem::take(&mut opponent.hand);
+                hidden.append(&mut opponent.deck);
+                hidden.shuffle(&mut self.rng);
+                opponent.deck = hidden.split_off(hand_size);
+                opponent.hand = hidden;
+                continue;
+            }
+
+            let weights =
+                curve_weights(&self.pool, opponent.board.iter().chain(&opponent.graveyard));
+            let Ok(distribution) = WeightedIndex::new(&weights) else {
+                continue;
+            };
+            for card in opponent.hand.iter_mut().chain(opponent.deck.iter_mut()) {
+                let definition = &self.pool[distribution.sample(&mut self.rng)];
+                *card = definition.instantiate(card.id);
+            }
+            opponent.deck.shuffle(&mut self.rng);
+        }
+        sampled
+    }
+
+    /// 开始在 `determinizations` 个确定化局面上的搜索，见 `determinized_step`。
+    pub(super) fn start_determinized(&self) -> DeterminizedSearch {
+        let samples = self.config.determinizations;
+        let mut config = self.config.clone();
+        config.determinizations = 0;
+        config.time_limit /= u32::from(samples);
+        DeterminizedSearch {
+            config,
+            samples,
+            remaining: samples,
+            current: None,
+            votes: HashMap::new(),
+            order: Vec::new(),
+            nodes: 0,
+            depth_reached: 0,
+            timed_out: false,
+            cutoff_rate: 0.0,
+        }
+    }
+
+    /// 推进当前确定化局面上的子搜索一步，子搜索得出结果时计入票数并开始下一个局面；
+    /// 所有局面都搜索完毕时返回汇总的决策。
+    pub(super) fn determinized_step(
+        &mut self,
+        search: &mut DeterminizedSearch,
+        state: &GameState,
+        player_id: PlayerId,
+        start: WasmInstant,
+    ) -> Option<AiDecision> {
+        if search.current.is_none() {
+            let sampled = self.determinize(state, player_id);
+            let mut agent = AiAgent::with_seed(search.config.clone(), self.rng.gen())
+                .with_rules(self.rules.clone());
+            let session = agent.start_search(&sampled, player_id);
+            search.current = Some((agent, session));
+            search.remaining -= 1;
+        }
+        let (agent, session) = search.current.as_mut()?;
+        let decision = agent.step_search(session)?;
+        search.current = None;
+        search.record(decision);
+        if search.remaining > 0 {
+            return None;
+        }
+        Some(self.finish_determinized(search, state, player_id, start))
+    }
+
+    /// 选出得票最多的操作，票数相同时取平均评估更高者；只采纳在真实局面上合法的操作。
+    fn finish_determinized(
+        &mut self,
+        search: &mut DeterminizedSearch,
+        state: &GameState,
+        player_id: PlayerId,
+        start: WasmInstant,
+    ) -> AiDecision {
+        let mut ranked: Vec<(GameAction, u32, f64)> = std::mem::take(&mut search.order)
+            .into_iter()
+            .map(|action| {
+                let (count, total) = search.votes[&action];
+                (action, count, total / f64::from(count))
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then(b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        let chosen = ranked.into_iter().find_map(|(action, _, evaluation)| {
+            self.simulate_resolution(state, &action)
+                .ok()
+                .map(|resolution| (action, evaluation, resolution))
+        });
+
+        let (action, evaluation, resolution) = match chosen {
+            Some((action, evaluation, resolution)) => (Some(action), evaluation, Some(resolution)),
+            None => (None, self.evaluate(state, player_id), None),
+        };
+        AiDecision {
+            action,
+            evaluation,
+            depth_reached: search.depth_reached,
+            nodes: search.nodes,
+            timed_out: search.timed_out,
+            duration_ms: start.elapsed().as_millis() as u64,
+            resolution,
+            strategy: self.config.strategy,
+            cutoff_rate: search.cutoff_rate,
+        }
+    }
+}
+
+/// 可分段执行的确定化搜索：依次在每个确定化局面上进行一次可分段的子搜索并汇总投票。
+pub(super) struct DeterminizedSearch {
+    /// 子搜索的配置，不再确定化，时间预算按局面数均分。
+    config: AiConfig,
+    /// 确定化局面总数与尚未开始的局面数。
+    samples: u8,
+    remaining: u8,
+    /// 正在搜索的局面上的子搜索。
+    current: Option<(AiAgent, SearchSession)>,
+    /// 各操作的得票数与评估之和，`order` 记录首次得票的顺序。
+    votes: HashMap<GameAction, (u32, f64)>,
+    order: Vec<GameAction>,
+    nodes: u64,
+    depth_reached: u8,
+    timed_out: bool,
+    cutoff_rate: f64,
+}
+
+impl DeterminizedSearch {
+    fn record(&mut self, decision: AiDecision) {
+        let samples = f64::from(self.samples);
+        self.nodes += decision.nodes;
+        self.depth_reached = self.depth_reached.max(decision.depth_reached);
+        self.timed_out |= decision.timed_out;
+        self.cutoff_rate += decision.cutoff_rate / samples;
+        if let Some(action) = decision.action {
+            let entry = self.votes.entry(action.clone()).or_insert_with(|| {
+                self.order.push(action);
+                (0, 0.0)
+            });
+            entry.0 += 1;
+            entry.1 += decision.evaluation;
+        }
+    }
+}
+
+/// 卡池中每张卡牌的抽样权重：对手已公开的卡牌中同费用的越多，权重越高。
+fn curve_weights<'a>(
+    pool: &[CardDefinition],
+    revealed: impl Iterator<Item = &'a Card>,
+) -> Vec<u32> {
+    let mut curve: HashMap<u8, u32> = HashMap::new();
+    for card in revealed {
+        *curve.entry(card.cost).or_default() += 1;
+    }
+    pool.iter()
+        .map(|definition| 1 + curve.get(&definition.cost).copied().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::{AiConfig, AiDifficulty};
+    use crate::game::{CardType, GamePhase, Player, RuleEngine};
+
+    #[test]
+    fn fair_agents_search_sampled_hidden_zones() {
+        let card = |id, name| Card::new(id, name, 2, 2, 2, CardType::Unit, Vec::new());
+        let mut raider = card(1, "Raider");
+        raider.refresh();
+        let player_one = Player::new(
+            0,
+            30,
+            0,
+            2,
+            vec![card(2, "Recruit")],
+            vec![raider],
+            Vec::new(),
+        );
+        let secret_hand = vec![card(10, "Secret"), card(11, "Secret")];
+        let secret_deck = vec![card(12, "Secret"), card(13, "Secret")];
+        let player_two = Player::new(1, 30, 0, 0, secret_hand, Vec::new(), secret_deck);
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let registry = CardRegistry::from(vec![CardDefinition::new(
+            500,
+            "Sampled",
+            3,
+            3,
+            3,
+            CardType::Unit,
+            Vec::new(),
+        )]);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert).with_determinizations(3);
+        config.time_limit = Duration::ZERO;
+        let mut agent = AiAgent::with_seed(config, 5).with_card_pool(&registry);
+        let sampled = agent.determinize(&state, 0);
+        let opponent = &sampled.players[1];
+        let ids: Vec<_> = opponent.hand.iter().map(|card| card.id).collect();
+        assert_eq!(ids, vec![10, 11]);
+        assert!(opponent
+            .hand
+            .iter()
+            .chain(&opponent.deck)
+            .all(|card| card.name == "Sampled"));
+        assert_eq!(sampled.players[0], state.players[0]);
+
+        let decision = agent.decide_action(&state, 0);
+        let action = decision.action.expect("a legal move is chosen");
+        let resolution = decision
+            .resolution
+            .expect("the move applies to the real state");
+        assert!(resolution.state.players[1]
+            .hand
+            .iter()
+            .all(|card| card.name == "Secret"));
+        assert!(RuleEngine::new().legal_actions(&state, 0).contains(&action));
+    }
+}
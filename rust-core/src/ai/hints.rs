@@ -0,0 +1,191 @@
+//! 提示：为玩家列出评估最高的若干合法操作，并附上机器可读的理由，
+//! 供前端的提示按钮与教学浮层展示。
+
+use serde::{Deserialize, Serialize};
+
+use super::lethal::find_lethal;
+use super::minimax::AiAgent;
+use crate::game::{Card, GameAction, GameState, PlayerId};
+
+/// 提示搜索的最大深度，保证提示能即时返回。
+const HINT_DEPTH: u8 = 2;
+
+/// 推荐某个操作的理由。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum HintReason {
+    /// 按斩杀序列执行即可在本回合取胜。
+    Lethal,
+    /// 对敌方英雄造成的伤害（含护甲）。
+    DamageDealt { amount: u32 },
+    /// 消灭的敌方单位数。
+    UnitsDestroyed { count: u32 },
+    /// 双方场面攻击力与生命总和之差的增加量。
+    TempoGained { amount: u32 },
+}
+
+/// 一个推荐的操作及其评估。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveSuggestion {
+    pub action: GameAction,
+    pub evaluation: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<HintReason>,
+}
+
+impl AiAgent {
+    /// 按评估从高到低返回 `player_id` 至多 `count` 个合法操作；能斩杀时斩杀的第一步排在最前。
+    pub fn suggest_moves(
+        &mut self,
+        state: &GameState,
+        player_id: PlayerId,
+        count: usize,
+    ) -> Vec<MoveSuggestion> {
+        if state.is_finished() || count == 0 {
+            return Vec::new();
+        }
+        let transitions = self.generate_transitions(state, player_id, None);
+        let depth = self.config.depth.clamp(1, HINT_DEPTH);
+        let scores = self.score_transitions(&transitions, depth, player_id);
+        let lethal = find_lethal(&self.rules, state, player_id)
+            .and_then(|sequence| sequence.first().cloned());
+
+        let mut suggestions: Vec<MoveSuggestion> = transitions
+            .into_iter()
+            .zip(scores)
+            .map(|((action, next), evaluation)| {
+                let mut reasons = Vec::new();
+                if lethal.as_ref() == Some(&action) {
+                    reasons.push(HintReason::Lethal);
+                }
+                reasons.extend(outcome_reasons(state, &next, player_id));
+                MoveSuggestion {
+                    action,
+                    evaluation,
+                    reasons,
+                }
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            let lethal_first = b
+                .reasons
+                .contains(&HintReason::Lethal)
+                .cmp(&a.reasons.contains(&HintReason::Lethal));
+            lethal_first.then(
+                b.evaluation
+                    .partial_cmp(&a.evaluation)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+        suggestions.truncate(count);
+        suggestions
+    }
+}
+
+/// 比较操作前后的局面，得出造成的伤害、消灭的单位与场面优势的变化。
+fn outcome_reasons(before: &GameState, after: &GameState, player_id: PlayerId) -> Vec<HintReason> {
+    let enemies = before.enemies_of(player_id);
+    let sum = |state: &GameState, ids: &[PlayerId], value: &dyn Fn(&GameState, PlayerId) -> i64| {
+        ids.iter().map(|&id| value(state, id)).sum::<i64>()
+    };
+    let health = |state: &GameState, id: PlayerId| {
+        state.get_player(id).map_or(0, |player| {
+            i64::from(player.health.max(0)) + i64::from(player.armor)
+        })
+    };
+    let units = |state: &GameState, id: PlayerId| {
+        state
+            .get_player(id)
+            .map_or(0, |player| player.board.len() as i64)
+    };
+    let board = |state: &GameState, id: PlayerId| {
+        state
+            .get_player(id)
+            .map_or(0, |player| player.board.iter().map(board_stats).sum())
+    };
+    let board_lead = |state: &GameState| {
+        let own = sum(state, &state.allies_of(player_id), &board);
+        own - sum(state, &enemies, &board)
+    };
+
+    let mut reasons = Vec::new();
+    let damage = sum(before, &enemies, &health) - sum(after, &enemies, &health);
+    if damage > 0 {
+        reasons.push(HintReason::DamageDealt {
+            amount: damage as u32,
+        });
+    }
+    let destroyed = sum(before, &enemies, &units) - sum(after, &enemies, &units);
+    if destroyed > 0 {
+        reasons.push(HintReason::UnitsDestroyed {
+            count: destroyed as u32,
+        });
+    }
+    let tempo = board_lead(after) - board_lead(before);
+    if tempo > 0 {
+        reasons.push(HintReason::TempoGained {
+            amount: tempo as u32,
+        });
+    }
+    reasons
+}
+
+fn board_stats(card: &Card) -> i64 {
+    i64::from(card.attack.max(0)) + i64::from(card.health.max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::{AiConfig, AiDifficulty};
+    use crate::game::{AttackAction, CardType, GamePhase, Player};
+
+    #[test]
+    fn suggestions_are_ranked_with_reasons() {
+        let ready = |id, attack, health| {
+            let mut card = Card::new(id, "Raider", 2, attack, health, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![ready(1, 4, 4)], Vec::new());
+        let player_two = Player::new(1, 4, 0, 0, Vec::new(), vec![ready(2, 1, 1)], Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let suggestions = AiAgent::with_seed(config, 1).suggest_moves(&state, 0, 2);
+        assert_eq!(suggestions.len(), 2);
+        let best = &suggestions[0];
+        assert_eq!(
+            best.action,
+            GameAction::Attack {
+                action: AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 1,
+                    defender_owner: 1,
+                    defender_card: None,
+                },
+            }
+        );
+        assert_eq!(
+            best.reasons,
+            vec![HintReason::Lethal, HintReason::DamageDealt { amount: 4 }]
+        );
+        assert!(suggestions[1].evaluation <= best.evaluation);
+        let trade = AiAgent::with_seed(AiConfig::from_difficulty(AiDifficulty::Expert), 1)
+            .suggest_moves(&state, 0, 8)
+            .into_iter()
+            .find(|suggestion| {
+                matches!(
+                    &suggestion.action,
+                    GameAction::Attack { action } if action.defender_card == Some(2)
+                )
+            })
+            .expect("the trade is listed");
+        assert!(trade
+            .reasons
+            .contains(&HintReason::UnitsDestroyed { count: 1 }));
+    }
+}
@@ -0,0 +1,207 @@
+//! 斩杀检测：穷举玩家本回合内的攻击顺序与直伤法术，判断能否在结束回合前取胜，
+//! 能取胜时返回致胜的操作序列。
+
+use std::collections::HashSet;
+
+use super::minimax::WasmInstant;
+use crate::game::{GameAction, GameOutcome, GameState, PlayerId, RuleEngine};
+
+/// 单次检测最多展开的局面数，超出后视为没有找到斩杀。
+const LETHAL_NODE_LIMIT: usize = 20_000;
+
+/// 检查 `player_id` 能否在本回合内获胜，返回从当前局面起的致胜操作序列。
+///
+/// 只在 `player_id` 的回合内搜索，不包含结束回合；不同顺序到达的相同局面只检查一次。
+/// 操作按 `rules` 的设置枚举与执行，与实际对局所用的引擎保持一致。
+pub fn find_lethal(
+    rules: &RuleEngine,
+    state: &GameState,
+    player_id: PlayerId,
+) -> Option<Vec<GameAction>> {
+    find_lethal_within(rules, state, player_id, None)
+}
+
+/// 同 `find_lethal`，但到达 `deadline` 时放弃搜索，视为没有找到斩杀。
+pub(super) fn find_lethal_within(
+    rules: &RuleEngine,
+    state: &GameState,
+    player_id: PlayerId,
+    deadline: Option<WasmInstant>,
+) -> Option<Vec<GameAction>> {
+    let mut search = LethalSearch::new(rules.clone(), state, player_id, deadline)?;
+    loop {
+        if let Some(result) = search.step() {
+            return result;
+        }
+    }
+}
+
+/// 可分段执行的斩杀搜索：以显式栈代替递归做深度优先搜索，每次 `step` 只尝试一个操作，
+/// 供分段搜索在两段之间让出主线程。
+pub(super) struct LethalSearch {
+    rules: RuleEngine,
+    player_id: PlayerId,
+    deadline: Option<WasmInstant>,
+    /// 已展开的局面哈希，不同顺序到达的相同局面只展开一次。
+    visited: HashSet<u64>,
+    /// 搜索路径上每个局面尚未尝试的后续操作。
+    stack: Vec<std::vec::IntoIter<(GameAction, GameState)>>,
+    /// 从根到栈顶局面的操作序列。
+    sequence: Vec<GameAction>,
+}
+
+impl LethalSearch {
+    /// 对局已结束或不是 `player_id` 行动时返回 `None`。
+    pub(super) fn new(
+        rules: RuleEngine,
+        state: &GameState,
+        player_id: PlayerId,
+        deadline: Option<WasmInstant>,
+    ) -> Option<Self> {
+        if state.is_finished() || state.acting_player() != player_id {
+            return None;
+        }
+        let mut search = Self {
+            rules,
+            player_id,
+            deadline,
+            visited: HashSet::new(),
+            stack: Vec::new(),
+            sequence: Vec::new(),
+        };
+        search.expand(state);
+        Some(search)
+    }
+
+    /// 尝试一个操作。找到斩杀时返回 `Some(Some(序列))`，搜索完毕、超出局面上限或到达期限时
+    /// 返回 `Some(None)`，仍需继续时返回 `None`。
+    pub(super) fn step(&mut self) -> Option<Option<Vec<GameAction>>> {
+        if self
+            .deadline
+            .is_some_and(|deadline| WasmInstant::now() >= deadline)
+        {
+            self.stack.clear();
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            return Some(None);
+        };
+        let Some((action, next)) = frame.next() else {
+            self.stack.pop();
+            self.sequence.pop();
+            return None;
+        };
+
+        self.sequence.push(action);
+        let won = match next.game_outcome() {
+            Some(GameOutcome::Victory { winner, .. }) => next.are_allies(winner, self.player_id),
+            Some(GameOutcome::Draw { .. }) => false,
+            None => {
+                if next.acting_player() == self.player_id && self.expand(&next) {
+                    return None;
+                }
+                false
+            }
+        };
+        if won {
+            return Some(Some(std::mem::take(&mut self.sequence)));
+        }
+        self.sequence.pop();
+        None
+    }
+
+    /// 将局面的后续操作压栈；已展开过或超出局面上限时返回 `false`。
+    fn expand(&mut self, state: &GameState) -> bool {
+        if self.visited.len() >= LETHAL_NODE_LIMIT || !self.visited.insert(state.hash()) {
+            return false;
+        }
+        let mut transitions: Vec<(GameAction, GameState)> = self
+            .rules
+            .legal_transitions(state, self.player_id)
+            .into_iter()
+            .filter(|(action, _)| *action != GameAction::EndTurn)
+            .collect();
+        // 先尝试让敌方剩余生命最少的操作，通常能更快找到斩杀
+        transitions.sort_by_cached_key(|(_, next)| enemy_health(next, self.player_id));
+        self.stack.push(transitions.into_iter());
+        true
+    }
+}
+
+/// 敌方存活玩家的生命与护甲之和。
+fn enemy_health(state: &GameState, player_id: PlayerId) -> i32 {
+    state
+        .enemies_of(player_id)
+        .into_iter()
+        .filter_map(|id| state.get_player(id))
+        .map(|player| i32::from(player.health.max(0)) + i32::from(player.armor))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::game::{Card, CardType, GamePhase, Player, RuleError};
+
+    #[test]
+    fn finds_the_sequence_that_wins_this_turn() {
+        let ready = |id, attack| {
+            let mut card = Card::new(id, "Raider", 1, attack, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let board = vec![ready(1, 3), ready(2, 2)];
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), board, Vec::new());
+        let player_two = Player::new(1, 5, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let rules = RuleEngine::new();
+        let sequence = find_lethal(&rules, &state, 0).expect("five damage is lethal");
+        let mut replayed = state.clone();
+        rules
+            .clone()
+            .apply_all(&mut replayed, sequence)
+            .expect("sequence applies");
+        assert!(matches!(
+            replayed.game_outcome(),
+            Some(GameOutcome::Victory { winner: 0, .. })
+        ));
+
+        let mut sturdier = state.clone();
+        sturdier.players[1].health = 6;
+        assert_eq!(find_lethal(&rules, &sturdier, 0), None);
+        assert_eq!(find_lethal(&rules, &state, 1), None);
+
+        let expired = WasmInstant::now();
+        assert_eq!(find_lethal_within(&rules, &state, 0, Some(expired)), None);
+        let later = WasmInstant::now() + Duration::from_secs(60);
+        assert!(find_lethal_within(&rules, &state, 0, Some(later)).is_some());
+    }
+
+    #[test]
+    fn lethal_search_respects_the_engines_action_hooks() {
+        let ready = |id| {
+            let mut card = Card::new(id, "Raider", 1, 5, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![ready(1)], Vec::new());
+        let player_two = Player::new(1, 5, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+        assert!(find_lethal(&RuleEngine::new(), &state, 0).is_some());
+
+        let mut pacifist = RuleEngine::new();
+        pacifist.add_pre_action_hook(|_, action| match action {
+            GameAction::Attack { .. } => Err(RuleError::ActionVetoed {
+                reason: "attacks are disabled".to_string(),
+            }),
+            _ => Ok(()),
+        });
+        assert_eq!(
+            find_lethal(&pacifist, &state, 0),
+            None,
+            "a vetoed attack is never part of a lethal sequence"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! 蒙特卡洛树搜索：以 UCT 选择节点、随机或启发式推演估值，在迭代次数或时间预算内搜索。
+//!
+//! 展开与推演都通过 `RuleEngine` 模拟，与规则判定保持一致。
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::minimax::{AiAgent, AiDecision, WasmInstant};
+use crate::game::{GameAction, GameState, PlayerId};
+
+/// 评估值映射到 [0, 1] 收益时的缩放，评估差 20 分约对应 73% 的收益。
+const REWARD_SCALE: f64 = 20.0;
+/// 启发式推演中随机选择操作的概率。
+const ROLLOUT_EXPLORATION: f64 = 0.25;
+
+/// 推演阶段的走子策略。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutPolicy {
+    /// 均匀随机选择合法操作。
+    Random,
+    /// 多数时候选择评估最高的操作，偶尔随机探索。
+    #[default]
+    Heuristic,
+}
+
+/// 蒙特卡洛树搜索的参数。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MctsConfig {
+    /// 最多迭代次数，为 0 时只受时间预算限制（两者都为 0 时使用默认次数）。
+    pub iterations: u32,
+    /// UCT 的探索常数，越大越倾向访问次数少的分支。
+    pub exploration: f64,
+    /// 单次推演最多模拟的操作数，到达后以评估函数估值。
+    pub rollout_depth: u8,
+    pub rollout: RolloutPolicy,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 400,
+            exploration: std::f64::consts::SQRT_2,
+            rollout_depth: 8,
+            rollout: RolloutPolicy::Heuristic,
+        }
+    }
+}
+
+impl MctsConfig {
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_rollout(mut self, rollout: RolloutPolicy) -> Self {
+        self.rollout = rollout;
+        self
+    }
+}
+
+struct Node {
+    state: GameState,
+    action: Option<GameAction>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// 尚未展开的后续操作及其结果状态。
+    untried: Vec<(GameAction, GameState)>,
+    /// 走出这一步的玩家，累计收益以其视角计算。
+    mover: PlayerId,
+    visits: u32,
+    reward: f64,
+    depth: u8,
+}
+
+impl Node {
+    fn mean(&self) -> f64 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.reward / self.visits as f64
+        }
+    }
+}
+
+/// 可分段执行的 MCTS：保存搜索树与迭代进度，每次 `AiAgent::mcts_step` 只做一次迭代。
+pub(super) struct MctsSearch {
+    nodes: Vec<Node>,
+    /// 迭代次数上限，为 0 时只受时间预算限制。
+    iterations: u32,
+    completed: u32,
+    depth_reached: u8,
+    timed_out: bool,
+}
+
+impl AiAgent {
+    pub(super) fn start_mcts(
+        &mut self,
+        state: &GameState,
+        player_id: PlayerId,
+        deadline: Option<WasmInstant>,
+    ) -> MctsSearch {
+        let mut iterations = self.config.mcts.iterations;
+        if iterations == 0 && deadline.is_none() {
+            iterations = MctsConfig::default().iterations;
+        }
+        let root = Node {
+            state: state.clone(),
+            action: None,
+            parent: None,
+            children: Vec::new(),
+            untried: self.expand_state(state),
+            mover: player_id,
+            visits: 0,
+            reward: 0.0,
+            depth: 0,
+        };
+        MctsSearch {
+            nodes: vec![root],
+            iterations,
+            completed: 0,
+            depth_reached: 0,
+            timed_out: false,
+        }
+    }
+
+    /// 进行一次迭代：选择、展开、推演与回传；达到迭代次数、时间预算或根节点无可展开的操作时
+    /// 返回 `true`。
+    pub(super) fn mcts_step(
+        &mut self,
+        search: &mut MctsSearch,
+        player_id: PlayerId,
+        deadline: Option<WasmInstant>,
+    ) -> bool {
+        if search.iterations != 0 && search.completed >= search.iterations {
+            return true;
+        }
+        if deadline.is_some_and(|deadline| WasmInstant::now() >= deadline) {
+            search.timed_out = true;
+            return true;
+        }
+        let nodes = &mut search.nodes;
+        let leaf = self.select_and_expand(nodes);
+        search.depth_reached = search.depth_reached.max(nodes[leaf].depth);
+        let reward = self.rollout(&nodes[leaf].state, player_id);
+        backpropagate(nodes, leaf, reward, player_id);
+        search.completed += 1;
+        let exhausted = nodes[0].children.is_empty() && nodes[0].untried.is_empty();
+        exhausted || (search.iterations != 0 && search.completed >= search.iterations)
+    }
+
+    /// 选出访问次数最多（相同时平均收益更高）的根操作作为决策。
+    pub(super) fn finish_mcts(
+        &mut self,
+        search: MctsSearch,
+        state: &GameState,
+        player_id: PlayerId,
+        start: WasmInstant,
+    ) -> AiDecision {
+        let nodes = &search.nodes;
+        let best = nodes[0]
+            .children
+            .iter()
+            .map(|&index| &nodes[index])
+            .max_by(|a, b| {
+                a.visits.cmp(&b.visits).then(
+                    a.mean()
+                        .partial_cmp(&b.mean())
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+            });
+        let action = best.and_then(|node| node.action.clone());
+        let evaluation = self.evaluate(best.map_or(state, |node| &node.state), player_id);
+        let resolution = action
+            .as_ref()
+            .and_then(|action| self.simulate_resolution(state, action).ok());
+
+        AiDecision {
+            action,
+            evaluation,
+            depth_reached: search.depth_reached,
+            nodes: nodes.len() as u64,
+            timed_out: search.timed_out,
+            duration_ms: start.elapsed().as_millis() as u64,
+            resolution,
+            strategy: self.config.strategy,
+            cutoff_rate: 0.0,
+        }
+    }
+
+    /// 从根沿 UCT 最优的子节点下行，在第一个仍有未展开操作的节点上展开一个子节点。
+    fn select_and_expand(&mut self, nodes: &mut Vec<Node>) -> usize {
+        let exploration = self.config.mcts.exploration;
+        let mut current = 0;
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            let parent_visits = (nodes[current].visits.max(1) as f64).ln();
+            current = nodes[current]
+                .children
+                .iter()
+                .copied()
+                .max_by(|&a, &b| {
+                    let uct = |index: usize| {
+                        let node = &nodes[index];
+                        let visits = node.visits.max(1) as f64;
+                        node.mean() + exploration * (parent_visits / visits).sqrt()
+                    };
+                    uct(a)
+                        .partial_cmp(&uct(b))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(current);
+        }
+
+        if nodes[current].untried.is_empty() {
+            return current;
+        }
+        let pick = self.rng.gen_range(0..nodes[current].untried.len());
+        let (action, child_state) = nodes[current].untried.swap_remove(pick);
+        let untried = self.expand_state(&child_state);
+        let child = Node {
+            mover: nodes[current].state.acting_player(),
+            state: child_state,
+            action: Some(action),
+            parent: Some(current),
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            reward: 0.0,
+            depth: nodes[current].depth.saturating_add(1),
+        };
+        nodes.push(child);
+        let index = nodes.len() - 1;
+        nodes[current].children.push(index);
+        index
+    }
+
+    fn expand_state(&mut self, state: &GameState) -> Vec<(GameAction, GameState)> {
+        if state.is_finished() {
+            return Vec::new();
+        }
+        self.generate_transitions(state, state.acting_player(), None)
+    }
+
+    /// 从 `state` 起模拟至多 `rollout_depth` 步，返回以 `root_player` 视角计的收益。
+    fn rollout(&mut self, state: &GameState, root_player: PlayerId) -> f64 {
+        let mut current = state.clone();
+        for _ in 0..self.config.mcts.rollout_depth {
+            if current.is_finished() {
+                break;
+            }
+            let mover = current.acting_player();
+            let mut transitions = self.generate_transitions(&current, mover, None);
+            if transitions.is_empty() {
+                break;
+            }
+            let greedy = self.config.mcts.rollout == RolloutPolicy::Heuristic
+                && !self.rng.gen_bool(ROLLOUT_EXPLORATION);
+            let pick = if greedy {
+                let scores: Vec<f64> = transitions
+                    .iter()
+                    .map(|(_, next)| self.evaluate(next, mover))
+                    .collect();
+                (0..scores.len())
+                    .max_by(|&a, &b| {
+                        scores[a]
+                            .partial_cmp(&scores[b])
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .unwrap_or(0)
+            } else {
+                self.rng.gen_range(0..transitions.len())
+            };
+            current = transitions.swap_remove(pick).1;
+        }
+        let evaluation = self.evaluate(&current, root_player);
+        1.0 / (1.0 + (-evaluation / REWARD_SCALE).exp())
+    }
+}
+
+/// 将收益沿父节点回传，每个节点按走出该步的玩家视角累计。
+fn backpropagate(nodes: &mut [Node], leaf: usize, reward: f64, root_player: PlayerId) {
+    let mut current = Some(leaf);
+    while let Some(index) = current {
+        let node = &mut nodes[index];
+        node.visits += 1;
+        node.reward += if node.state.are_allies(node.mover, root_player) {
+            reward
+        } else {
+            1.0 - reward
+        };
+        current = node.parent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{AiAlgorithm, AiConfig, AiDifficulty};
+    use crate::game::{AttackAction, Card, CardType, GamePhase, Player};
+
+    #[test]
+    fn mcts_finds_the_winning_attack() {
+        let mut raider = Card::new(1, "Raider", 3, 3, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let mut guard = Card::new(2, "Guard", 2, 1, 4, CardType::Unit, Vec::new());
+        guard.refresh();
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let player_two = Player::new(1, 3, 0, 0, Vec::new(), vec![guard], Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Hard)
+            .with_algorithm(AiAlgorithm::Mcts)
+            .with_mcts(MctsConfig::default().with_iterations(120));
+        config.time_limit = std::time::Duration::ZERO;
+        config.check_lethal = false;
+        let mut agent = AiAgent::with_seed(config, 11);
+        let decision = agent.decide_action(&state, 0);
+        assert_eq!(
+            decision.action,
+            Some(GameAction::Attack {
+                action: AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 1,
+                    defender_owner: 1,
+                    defender_card: None,
+                },
+            })
+        );
+        assert!(decision.nodes > 1);
+        assert!(!decision.timed_out);
+        let resolution = decision.resolution.expect("attack applies");
+        assert!(resolution.state.is_finished());
+    }
+}
@@ -1,6 +1,6 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::time::Duration;
-use web_sys::js_sys::Date;
 
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
@@ -8,29 +8,52 @@ use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use crate::game::{
-    AttackAction, Card, CardId, CardType, GameEvent, GamePhase, GameState, MulliganAction,
-    PlayCardAction, PlayerId, RuleEngine, RuleError, RuleResolution,
+    Card, CardDefinition, CardId, CardType, DiscardCardAction, GameAction, GameOutcome, GameState,
+    Keyword, Player, PlayerId, RuleEngine, RuleError, RuleResolution,
 };
 
 use self::learning::bias as learning_bias;
+use super::determinization::DeterminizedSearch;
+use super::lethal::LethalSearch;
+use super::mcts::{MctsConfig, MctsSearch};
+use super::ordering::MoveOrdering;
+use super::transposition::{Bound, TableEntry, TranspositionTable, DEFAULT_TABLE_CAPACITY};
 
 const LEARNING_IMPORTANCE: f64 = 0.45;
+/// 已决出胜负的局面的评估值。
+const WIN_SCORE: f64 = 1_000_000.0;
+/// 搜索树内部节点默认保留的单位目标数。
+const DEFAULT_TARGET_LIMIT: u8 = 3;
 
 #[derive(Debug, Clone, Copy)]
-struct WasmInstant {
+pub(super) struct WasmInstant {
     timestamp: f64,
 }
 
+/// 当前时间戳（毫秒）。wasm 下使用 `Date::now`，原生目标（如单元测试）使用系统时钟。
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    web_sys::js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
 impl WasmInstant {
-    fn now() -> Self {
+    pub(super) fn now() -> Self {
         Self {
-            timestamp: Date::now(),
+            timestamp: now_ms(),
         }
     }
 
-    fn elapsed(&self) -> Duration {
-        let elapsed_ms = Date::now() - self.timestamp;
-        Duration::from_millis(elapsed_ms as u64)
+    pub(super) fn elapsed(&self) -> Duration {
+        let elapsed_ms = now_ms() - self.timestamp;
+        Duration::from_millis(elapsed_ms.max(0.0) as u64)
     }
 }
 
@@ -56,16 +79,6 @@ impl PartialEq for WasmInstant {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(tag = "type")]
-pub enum GameAction {
-    PlayCard { action: PlayCardAction },
-    Mulligan { action: MulliganAction },
-    Attack { action: AttackAction },
-    AdvancePhase,
-    EndTurn,
-}
-
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AiStrategy {
@@ -91,7 +104,28 @@ impl FromStr for AiStrategy {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// 搜索算法：带 Alpha-Beta 剪枝的 Minimax，或蒙特卡洛树搜索。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AiAlgorithm {
+    #[default]
+    Minimax,
+    Mcts,
+}
+
+impl FromStr for AiAlgorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "minimax" | "alphabeta" => Ok(AiAlgorithm::Minimax),
+            "mcts" | "montecarlo" => Ok(AiAlgorithm::Mcts),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AiDifficulty {
     Easy,
@@ -129,6 +163,46 @@ pub struct AiConfig {
     pub time_limit: Duration,
     pub strategy: AiStrategy,
     pub weights: DifficultyWeights,
+    #[serde(default)]
+    pub algorithm: AiAlgorithm,
+    /// 仅在 `algorithm` 为 `Mcts` 时使用，时间预算沿用 `time_limit`。
+    #[serde(default)]
+    pub mcts: MctsConfig,
+    /// Minimax 置换表最多缓存的局面数，为 0 时不使用置换表。
+    #[serde(default = "default_table_capacity")]
+    pub table_capacity: usize,
+    /// 搜索前先检查本回合能否斩杀，能斩杀时直接按致胜序列行动。
+    #[serde(default = "default_check_lethal")]
+    pub check_lethal: bool,
+    /// 搜索树内部节点中，同一张卡牌或同一攻击者最多保留的单位目标数，按威胁从高到低保留；
+    /// 根节点总是考虑全部目标。为 0 时不做剪枝。
+    #[serde(default = "default_target_limit")]
+    pub target_limit: u8,
+    /// 自定义的评估权重，设置后代替策略自带的权重；难度系数与局势调整仍然生效。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation_weights: Option<StrategyWeights>,
+    /// 对嘲讽单位的重视程度：越高越看重己方嘲讽的保护、也越在意敌方嘲讽的阻挡。
+    #[serde(default)]
+    pub taunt_respect: f64,
+    /// 打脸与交换的取向，取值 [-1, 1]：为正时更看重英雄伤害，为负时更看重场面交换。
+    #[serde(default)]
+    pub face_bias: f64,
+    /// 公平模式下的确定化次数：对手的手牌与牌库替换为抽样的卡牌后分别搜索再汇总。
+    /// 为 0 时直接读取对手真实的隐藏区域。
+    #[serde(default)]
+    pub determinizations: u8,
+}
+
+fn default_table_capacity() -> usize {
+    DEFAULT_TABLE_CAPACITY
+}
+
+fn default_check_lethal() -> bool {
+    true
+}
+
+fn default_target_limit() -> u8 {
+    DEFAULT_TARGET_LIMIT
 }
 
 impl AiConfig {
@@ -145,6 +219,15 @@ impl AiConfig {
                     resources: 1.1,
                     combo: 0.9,
                 },
+                algorithm: AiAlgorithm::Minimax,
+                mcts: MctsConfig::default(),
+                table_capacity: DEFAULT_TABLE_CAPACITY,
+                check_lethal: true,
+                target_limit: DEFAULT_TARGET_LIMIT,
+                evaluation_weights: None,
+                taunt_respect: 0.0,
+                face_bias: 0.0,
+                determinizations: 0,
             },
             AiDifficulty::Normal => Self {
                 depth: 2,
@@ -157,6 +240,15 @@ impl AiConfig {
                     resources: 1.0,
                     combo: 1.0,
                 },
+                algorithm: AiAlgorithm::Minimax,
+                mcts: MctsConfig::default(),
+                table_capacity: DEFAULT_TABLE_CAPACITY,
+                check_lethal: true,
+                target_limit: DEFAULT_TARGET_LIMIT,
+                evaluation_weights: None,
+                taunt_respect: 0.0,
+                face_bias: 0.0,
+                determinizations: 0,
             },
             AiDifficulty::Hard => Self {
                 depth: 3,
@@ -169,6 +261,15 @@ impl AiConfig {
                     resources: 0.95,
                     combo: 1.1,
                 },
+                algorithm: AiAlgorithm::Minimax,
+                mcts: MctsConfig::default(),
+                table_capacity: DEFAULT_TABLE_CAPACITY,
+                check_lethal: true,
+                target_limit: DEFAULT_TARGET_LIMIT,
+                evaluation_weights: None,
+                taunt_respect: 0.0,
+                face_bias: 0.0,
+                determinizations: 0,
             },
             AiDifficulty::Expert => Self {
                 depth: 4,
@@ -181,6 +282,15 @@ impl AiConfig {
                     resources: 1.05,
                     combo: 1.2,
                 },
+                algorithm: AiAlgorithm::Minimax,
+                mcts: MctsConfig::default(),
+                table_capacity: DEFAULT_TABLE_CAPACITY,
+                check_lethal: true,
+                target_limit: DEFAULT_TARGET_LIMIT,
+                evaluation_weights: None,
+                taunt_respect: 0.0,
+                face_bias: 0.0,
+                determinizations: 0,
             },
         }
     }
@@ -192,6 +302,26 @@ impl AiConfig {
         }
         self
     }
+
+    pub fn with_algorithm(mut self, algorithm: AiAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_mcts(mut self, mcts: MctsConfig) -> Self {
+        self.mcts = mcts;
+        self
+    }
+
+    pub fn with_evaluation_weights(mut self, weights: StrategyWeights) -> Self {
+        self.evaluation_weights = Some(weights);
+        self
+    }
+
+    pub fn with_determinizations(mut self, determinizations: u8) -> Self {
+        self.determinizations = determinizations;
+        self
+    }
 }
 
 impl Default for AiConfig {
@@ -212,27 +342,109 @@ pub struct AiDecision {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolution: Option<RuleResolution>,
     pub strategy: AiStrategy,
+    /// 展开的节点中因 Alpha-Beta 提前剪枝的比例，用于观察走法排序的效果。
+    #[serde(default)]
+    pub cutoff_rate: f64,
 }
 
 struct SearchStats {
     nodes: u64,
     depth_reached: u8,
     timed_out: bool,
+    /// 展开了后续操作的节点数与其中发生剪枝的节点数。
+    expanded: u64,
+    cutoffs: u64,
+    /// 当前这一轮迭代加深的深度，用于换算节点距根的层数。
+    iteration_depth: u8,
+    /// 在迭代加深的各层之间共享。
+    table: TranspositionTable,
+    ordering: MoveOrdering,
 }
 
 impl SearchStats {
-    fn new() -> Self {
+    fn new(table_capacity: usize) -> Self {
         Self {
             nodes: 0,
             depth_reached: 0,
             timed_out: false,
+            expanded: 0,
+            cutoffs: 0,
+            iteration_depth: 0,
+            table: TranspositionTable::new(table_capacity),
+            ordering: MoveOrdering::default(),
         }
     }
+
+    fn cutoff_rate(&self) -> f64 {
+        if self.expanded == 0 {
+            0.0
+        } else {
+            self.cutoffs as f64 / self.expanded as f64
+        }
+    }
+}
+
+/// 可分段执行的搜索，由 `AiAgent::start_search` 创建，用 `AiAgent::search_slice` 推进，
+/// 使浏览器主线程上的长时间搜索不会冻结界面。确定化、斩杀检测与 MCTS 同样分段执行，
+/// 斩杀检测未找到斩杀时再开始 MCTS 或迭代加深 Alpha-Beta 搜索。
+pub struct SearchSession {
+    state: GameState,
+    player_id: PlayerId,
+    start: WasmInstant,
+    deadline: Option<WasmInstant>,
+    stats: SearchStats,
+    transitions: Vec<(GameAction, GameState)>,
+    maximizing: bool,
+    /// 最近一层完整搜索的最佳操作与分数。
+    best: Option<(GameAction, f64)>,
+    /// 正在搜索的一层，搜索结束后为 `None`。
+    iteration: Option<RootIteration>,
+    /// 已得出但尚未取走的决策。
+    decision: Option<AiDecision>,
+    /// 正在进行的非 Alpha-Beta 搜索阶段。
+    stage: Option<SearchStage>,
+}
+
+/// `SearchSession` 中先于或代替 Alpha-Beta 搜索执行的阶段。
+enum SearchStage {
+    Determinized(Box<DeterminizedSearch>),
+    Lethal(Box<LethalSearch>),
+    Mcts(MctsSearch),
+}
+
+impl SearchSession {
+    fn begin_iteration(&mut self, depth: u8) {
+        self.stats.iteration_depth = depth;
+        self.iteration = Some(RootIteration {
+            depth,
+            next: 0,
+            alpha: f64::NEG_INFINITY,
+            beta: f64::INFINITY,
+            best: None,
+            best_cmp: f64::NEG_INFINITY,
+        });
+    }
+}
+
+/// 迭代加深中一层的根节点搜索进度。
+struct RootIteration {
+    depth: u8,
+    /// 下一个要搜索的根节点操作的下标。
+    next: usize,
+    alpha: f64,
+    beta: f64,
+    /// 本层已完整搜索的操作中最佳者的下标与分数。
+    best: Option<(usize, f64)>,
+    best_cmp: f64,
 }
 
 pub struct AiAgent {
-    config: AiConfig,
-    rng: SmallRng,
+    pub(super) config: AiConfig,
+    pub(super) rng: SmallRng,
+    /// 确定化时代替隐藏卡牌的候选卡牌，见 `with_card_pool`。
+    pub(super) pool: Vec<CardDefinition>,
+    /// 枚举与模拟操作所用的规则引擎，见 `with_rules`。
+    pub(super) rules: RuleEngine,
 }
 
 impl AiAgent {
@@ -240,9 +452,18 @@ impl AiAgent {
         Self {
             config,
             rng: SmallRng::from_entropy(),
+            pool: Vec::new(),
+            rules: RuleEngine::new(),
         }
     }
 
+    /// 以 `rules` 的设置（交互优先权、结算上限与拦截器）枚举和模拟操作，
+    /// 使 AI 只会选出该引擎实际接受的操作。
+    pub fn with_rules(mut self, rules: RuleEngine) -> Self {
+        self.rules = rules;
+        self
+    }
+
     pub fn record_reward(&self, action: &GameAction, reward: f64) {
         learning::record(action, reward);
     }
@@ -255,6 +476,8 @@ impl AiAgent {
         Self {
             config,
             rng: SmallRng::seed_from_u64(seed),
+            pool: Vec::new(),
+            rules: RuleEngine::new(),
         }
     }
 
@@ -265,7 +488,7 @@ impl AiAgent {
         start: WasmInstant,
         deadline: Option<WasmInstant>,
     ) -> AiDecision {
-        let mut transitions = self.generate_transitions(state, state.current_player, deadline);
+        let mut transitions = self.generate_transitions(state, state.acting_player(), deadline);
         if transitions.is_empty() {
             return AiDecision {
                 action: None,
@@ -276,6 +499,7 @@ impl AiAgent {
                 duration_ms: start.elapsed().as_millis() as u64,
                 resolution: None,
                 strategy: AiStrategy::Random,
+                cutoff_rate: 0.0,
             };
         }
 
@@ -292,120 +516,340 @@ impl AiAgent {
             duration_ms: start.elapsed().as_millis() as u64,
             resolution,
             strategy: AiStrategy::Random,
+            cutoff_rate: 0.0,
         }
     }
 
     pub fn decide_action(&mut self, state: &GameState, player_id: PlayerId) -> AiDecision {
-        let mut stats = SearchStats::new();
+        let mut session = self.start_search(state, player_id);
+        loop {
+            if let Some(decision) = self.step_search(&mut session) {
+                return decision;
+            }
+        }
+    }
+
+    /// 开始一次可分段执行的搜索；无需搜索即可决定的情形（随机策略、对局已结束、弃牌）
+    /// 在这里直接得出结果，之后的第一次 `search_slice` 即返回。
+    pub fn start_search(&mut self, state: &GameState, player_id: PlayerId) -> SearchSession {
         let start = WasmInstant::now();
         let deadline = if self.config.time_limit.is_zero() {
             None
         } else {
             Some(start + self.config.time_limit)
         };
+        let mut session = SearchSession {
+            state: state.clone(),
+            player_id,
+            start,
+            deadline,
+            stats: SearchStats::new(self.config.table_capacity),
+            transitions: Vec::new(),
+            maximizing: state.acting_player() == player_id,
+            best: None,
+            iteration: None,
+            decision: None,
+            stage: None,
+        };
+        if let Some(decision) = self.immediate_decision(state, player_id, start, deadline) {
+            session.decision = Some(decision);
+            return session;
+        }
+        if self.config.determinizations > 0 {
+            let search = self.start_determinized();
+            session.stage = Some(SearchStage::Determinized(Box::new(search)));
+            return session;
+        }
+        if let Some(action) = choose_discard(state, player_id) {
+            session.decision = Some(self.forced_decision(state, player_id, action, start));
+            return session;
+        }
 
-        let strategy = self.config.strategy;
-
-        if strategy == AiStrategy::Random {
-            return self.random_decision(state, player_id, start, deadline);
+        // 先检测本回合能否直接取胜，找到斩杀时按斩杀序列的第一步行动，无需再搜索
+        let lethal = self
+            .config
+            .check_lethal
+            .then(|| LethalSearch::new(self.rules.clone(), state, player_id, deadline))
+            .flatten();
+        match lethal {
+            Some(search) => session.stage = Some(SearchStage::Lethal(Box::new(search))),
+            None => self.begin_search(&mut session),
         }
+        session
+    }
 
-        let mut best_action = None;
-        let mut best_score = f64::NEG_INFINITY;
-        let mut best_cmp = f64::NEG_INFINITY;
+    /// 开始 MCTS 或迭代加深 Alpha-Beta 搜索。
+    fn begin_search(&mut self, session: &mut SearchSession) {
+        let state = &session.state;
+        let player_id = session.player_id;
+        if self.config.algorithm == AiAlgorithm::Mcts {
+            let search = self.start_mcts(state, player_id, session.deadline);
+            session.stage = Some(SearchStage::Mcts(search));
+            return;
+        }
 
-        if state.is_finished() {
-            return AiDecision {
+        // 根节点的候选操作不受时间限制，保证超时时仍有操作可返回
+        let mut transitions = self.generate_transitions(state, state.acting_player(), None);
+        self.prioritize_actions(state, &mut transitions, self.config.strategy, player_id);
+        if transitions.is_empty() {
+            session.decision = Some(AiDecision {
                 action: None,
                 evaluation: self.evaluate(state, player_id),
                 depth_reached: 0,
                 nodes: 0,
                 timed_out: false,
-                duration_ms: start.elapsed().as_millis() as u64,
+                duration_ms: session.start.elapsed().as_millis() as u64,
                 resolution: None,
-                strategy,
-            };
+                strategy: self.config.strategy,
+                cutoff_rate: 0.0,
+            });
+            return;
         }
+        session.transitions = transitions;
+        session.begin_iteration(1);
+    }
 
-        let depth = self.config.depth.saturating_sub(1);
-        let maximizing = state.current_player == player_id;
-        let mut transitions = self.generate_transitions(state, state.current_player, deadline);
-        self.prioritize_actions(state, &mut transitions, strategy, player_id);
+    /// 继续搜索，直到完成或本段用时达到 `slice`；完成时返回决策，此后会话不再产出结果。
+    /// 每段至少搜索一个根节点操作，调用方可在两段之间让出主线程。
+    pub fn search_slice(
+        &mut self,
+        session: &mut SearchSession,
+        slice: Duration,
+    ) -> Option<AiDecision> {
+        let slice_start = WasmInstant::now();
+        loop {
+            if let Some(decision) = self.step_search(session) {
+                return Some(decision);
+            }
+            if slice_start.elapsed() >= slice {
+                return None;
+            }
+        }
+    }
 
-        if transitions.is_empty() {
-            return AiDecision {
+    /// 不经过 Alpha-Beta 搜索即可得出的决策。
+    fn immediate_decision(
+        &mut self,
+        state: &GameState,
+        player_id: PlayerId,
+        start: WasmInstant,
+        deadline: Option<WasmInstant>,
+    ) -> Option<AiDecision> {
+        let strategy = self.config.strategy;
+
+        if strategy == AiStrategy::Random {
+            return Some(self.random_decision(state, player_id, start, deadline));
+        }
+
+        if state.is_finished() {
+            return Some(AiDecision {
                 action: None,
                 evaluation: self.evaluate(state, player_id),
-                depth_reached: stats.depth_reached,
-                nodes: stats.nodes,
-                timed_out: stats.timed_out,
+                depth_reached: 0,
+                nodes: 0,
+                timed_out: false,
                 duration_ms: start.elapsed().as_millis() as u64,
                 resolution: None,
                 strategy,
-            };
+                cutoff_rate: 0.0,
+            });
         }
 
-        let mut alpha = f64::NEG_INFINITY;
-        let mut beta = f64::INFINITY;
-
-        for (action, child_state) in transitions {
-            let score = self.minimax_rec(
-                &child_state,
-                depth,
-                alpha,
-                beta,
-                player_id,
-                deadline,
-                &mut stats,
-            );
+        None
+    }
 
-            if stats.timed_out {
-                break;
-            }
+    /// 不经搜索直接采取的操作（弃牌或斩杀的第一步）的决策，`evaluation` 为操作后的局面评估。
+    fn forced_decision(
+        &mut self,
+        state: &GameState,
+        player_id: PlayerId,
+        action: GameAction,
+        start: WasmInstant,
+    ) -> AiDecision {
+        let resolution = self.simulate_resolution(state, &action).ok();
+        let evaluation = resolution
+            .as_ref()
+            .map(|resolution| self.evaluate(&resolution.state, player_id))
+            .unwrap_or_else(|| self.evaluate(state, player_id));
+        AiDecision {
+            action: Some(action),
+            evaluation,
+            depth_reached: 0,
+            nodes: 1,
+            timed_out: false,
+            duration_ms: start.elapsed().as_millis() as u64,
+            resolution,
+            strategy: self.config.strategy,
+            cutoff_rate: 0.0,
+        }
+    }
 
-            if maximizing {
-                alpha = alpha.max(score);
-            } else {
-                beta = beta.min(score);
+    /// 推进确定化、斩杀检测或 MCTS 阶段一步；阶段结束时得出决策或开始后续的搜索。
+    fn step_stage(&mut self, session: &mut SearchSession) -> Option<AiDecision> {
+        let player_id = session.player_id;
+        match session.stage.as_mut()? {
+            SearchStage::Determinized(search) => {
+                let decision =
+                    self.determinized_step(search, &session.state, player_id, session.start)?;
+                session.stage = None;
+                Some(decision)
+            }
+            SearchStage::Lethal(search) => {
+                let sequence = search.step()?;
+                session.stage = None;
+                match sequence.and_then(|sequence| sequence.into_iter().next()) {
+                    Some(action) => {
+                        let mut decision =
+                            self.forced_decision(&session.state, player_id, action, session.start);
+                        decision.evaluation = WIN_SCORE;
+                        Some(decision)
+                    }
+                    None => {
+                        self.begin_search(session);
+                        session.decision.take()
+                    }
+                }
+            }
+            SearchStage::Mcts(search) => {
+                if !self.mcts_step(search, player_id, session.deadline) {
+                    return None;
+                }
+                let Some(SearchStage::Mcts(search)) = session.stage.take() else {
+                    return None;
+                };
+                Some(self.finish_mcts(search, &session.state, player_id, session.start))
             }
+        }
+    }
 
-            let comparison_score = if self.config.randomness > 0.0 {
-                score + self.random_noise()
-            } else {
-                score
-            };
+    /// 搜索当前这一层的下一个根节点操作；整个搜索结束时返回决策。
+    pub(super) fn step_search(&mut self, session: &mut SearchSession) -> Option<AiDecision> {
+        if session.stage.is_some() {
+            return self.step_stage(session);
+        }
+        let Some(iteration) = session.iteration.as_mut() else {
+            return session.decision.take();
+        };
+        let child_state = &session.transitions[iteration.next].1;
+        let score = self.minimax_rec(
+            child_state,
+            iteration.depth - 1,
+            iteration.alpha,
+            iteration.beta,
+            session.player_id,
+            session.deadline,
+            &mut session.stats,
+        );
+        if session.stats.timed_out {
+            self.end_iteration(session);
+            return session.decision.take();
+        }
 
-            if comparison_score > best_cmp {
-                best_cmp = comparison_score;
-                best_score = score;
-                best_action = Some(action);
-            }
+        if session.maximizing {
+            iteration.alpha = iteration.alpha.max(score);
+        } else {
+            iteration.beta = iteration.beta.min(score);
+        }
+        let comparison_score = if self.config.randomness > 0.0 {
+            score + self.random_noise()
+        } else {
+            score
+        };
+        if comparison_score > iteration.best_cmp {
+            iteration.best_cmp = comparison_score;
+            iteration.best = Some((iteration.next, score));
+        }
+        iteration.next += 1;
+        if iteration.alpha >= iteration.beta || iteration.next == session.transitions.len() {
+            self.end_iteration(session);
+        }
+        session.decision.take()
+    }
 
-            if alpha >= beta {
-                break;
+    /// 迭代加深的一层结束：超时时丢弃未完成的一层，沿用上一层的完整结果，
+    /// 否则记录本层结果并开始下一层，直到配置的深度或已分出胜负。
+    fn end_iteration(&mut self, session: &mut SearchSession) {
+        let Some(iteration) = session.iteration.take() else {
+            return;
+        };
+        let found = iteration.best;
+        if session.stats.timed_out {
+            if session.best.is_none() {
+                session.best =
+                    found.map(|(index, score)| (session.transitions[index].0.clone(), score));
             }
+            self.finish_search(session);
+            return;
         }
+        let Some((index, score)) = found else {
+            self.finish_search(session);
+            return;
+        };
+        session.stats.depth_reached = iteration.depth;
+        session.best = Some((session.transitions[index].0.clone(), score));
+        if score.abs() >= WIN_SCORE || iteration.depth >= self.config.depth.max(1) {
+            self.finish_search(session);
+            return;
+        }
+        // 上一层的最佳操作在下一层中最先搜索，以便尽早收紧剪枝窗口
+        let principal = session.transitions.remove(index);
+        session.transitions.insert(0, principal);
+        session.begin_iteration(iteration.depth + 1);
+    }
 
+    fn finish_search(&mut self, session: &mut SearchSession) {
+        let player_id = session.player_id;
+        let (best_action, best_score) = match session.best.take() {
+            Some((action, score)) => (Some(action), score),
+            None => {
+                let (action, child_state) = &session.transitions[0];
+                (Some(action.clone()), self.evaluate(child_state, player_id))
+            }
+        };
         let resolution = best_action
             .as_ref()
-            .and_then(|action| self.simulate_resolution(state, action).ok());
-
-        if best_action.is_none() {
-            best_score = self.evaluate(state, player_id);
-        }
+            .and_then(|action| self.simulate_resolution(&session.state, action).ok());
+        let stats = &session.stats;
 
-        AiDecision {
+        session.decision = Some(AiDecision {
             action: best_action,
             evaluation: best_score,
             depth_reached: stats.depth_reached,
             nodes: stats.nodes,
             timed_out: stats.timed_out,
-            duration_ms: start.elapsed().as_millis() as u64,
+            duration_ms: session.start.elapsed().as_millis() as u64,
             resolution,
-            strategy,
-        }
+            strategy: self.config.strategy,
+            cutoff_rate: stats.cutoff_rate(),
+        });
+    }
+
+    /// 以完整窗口搜索每个根操作，返回各自以 `player_id` 视角计的精确分数，供提示排序使用。
+    pub(super) fn score_transitions(
+        &mut self,
+        transitions: &[(GameAction, GameState)],
+        depth: u8,
+        player_id: PlayerId,
+    ) -> Vec<f64> {
+        let mut stats = SearchStats::new(self.config.table_capacity);
+        stats.iteration_depth = depth;
+        transitions
+            .iter()
+            .map(|(_, child_state)| {
+                self.minimax_rec(
+                    child_state,
+                    depth.saturating_sub(1),
+                    f64::NEG_INFINITY,
+                    f64::INFINITY,
+                    player_id,
+                    None,
+                    &mut stats,
+                )
+            })
+            .collect()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn minimax_rec(
         &mut self,
         state: &GameState,
@@ -417,10 +861,6 @@ impl AiAgent {
         stats: &mut SearchStats,
     ) -> f64 {
         stats.nodes += 1;
-        let depth_explored = self.config.depth.saturating_sub(depth_remaining);
-        if depth_explored > stats.depth_reached {
-            stats.depth_reached = depth_explored;
-        }
 
         if let Some(deadline) = deadline {
             if WasmInstant::now() >= deadline {
@@ -429,21 +869,59 @@ impl AiAgent {
             }
         }
 
+        let key = stats.table.is_enabled().then(|| state.hash());
+        let mut hint = None;
+        if let Some(entry) = key.and_then(|key| stats.table.get(key)) {
+            if let Some(score) = entry.cutoff(depth_remaining, alpha, beta) {
+                return score;
+            }
+            hint = entry.best.clone();
+        }
+
         if depth_remaining == 0 || state.is_finished() {
-            return self.evaluate(state, root_player);
+            let score = self.evaluate(state, root_player);
+            if let Some(key) = key {
+                stats.table.store(
+                    key,
+                    TableEntry {
+                        depth: depth_remaining,
+                        score,
+                        bound: Bound::Exact,
+                        best: None,
+                    },
+                );
+            }
+            return score;
         }
 
-        let actor = state.current_player;
-        let maximizing_player = actor == root_player;
+        let actor = state.acting_player();
+        let maximizing_player = state.are_allies(actor, root_player);
         let mut transitions = self.generate_transitions(state, actor, deadline);
+        prune_targets(state, &mut transitions, self.config.target_limit);
         self.prioritize_actions(state, &mut transitions, self.config.strategy, root_player);
         if transitions.is_empty() {
+            // 枚举途中到达时限时，空列表并不代表没有可执行的操作
+            if deadline.is_some_and(|deadline| WasmInstant::now() >= deadline) {
+                stats.timed_out = true;
+            }
             return self.evaluate(state, root_player);
         }
+        let ply = usize::from(stats.iteration_depth.saturating_sub(depth_remaining));
+        stats.ordering.order(ply, &mut transitions);
+        // 置换表记录的最佳操作最先搜索，其后是杀手走法与历史分数高的操作
+        let hinted =
+            hint.and_then(|hint| transitions.iter().position(|(action, _)| *action == hint));
+        if let Some(position) = hinted {
+            transitions[..=position].rotate_right(1);
+        }
 
-        if maximizing_player {
+        let (alpha_before, beta_before) = (alpha, beta);
+        let mut best_action = None;
+        let mut cutoff = None;
+        stats.expanded += 1;
+        let value = if maximizing_player {
             let mut value = f64::NEG_INFINITY;
-            for (_, child_state) in transitions {
+            for (action, child_state) in transitions {
                 let score = self.minimax_rec(
                     &child_state,
                     depth_remaining.saturating_sub(1),
@@ -453,16 +931,23 @@ impl AiAgent {
                     deadline,
                     stats,
                 );
-                value = value.max(score);
+                if score > value {
+                    value = score;
+                    best_action = Some(action);
+                }
                 alpha = alpha.max(value);
-                if stats.timed_out || beta <= alpha {
+                if stats.timed_out {
+                    break;
+                }
+                if beta <= alpha {
+                    cutoff = best_action.clone();
                     break;
                 }
             }
             value
         } else {
             let mut value = f64::INFINITY;
-            for (_, child_state) in transitions {
+            for (action, child_state) in transitions {
                 let score = self.minimax_rec(
                     &child_state,
                     depth_remaining.saturating_sub(1),
@@ -472,179 +957,72 @@ impl AiAgent {
                     deadline,
                     stats,
                 );
-                value = value.min(score);
+                if score < value {
+                    value = score;
+                    best_action = Some(action);
+                }
                 beta = beta.min(value);
-                if stats.timed_out || beta <= alpha {
+                if stats.timed_out {
+                    break;
+                }
+                if beta <= alpha {
+                    cutoff = best_action.clone();
                     break;
                 }
             }
             value
+        };
+        if let Some(action) = cutoff.filter(|_| !stats.timed_out) {
+            stats.cutoffs += 1;
+            stats.ordering.record_cutoff(ply, &action, depth_remaining);
+        }
+
+        if let Some(key) = key.filter(|_| !stats.timed_out) {
+            let bound = if value <= alpha_before {
+                Bound::Upper
+            } else if value >= beta_before {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            stats.table.store(
+                key,
+                TableEntry {
+                    depth: depth_remaining,
+                    score: value,
+                    bound,
+                    best: best_action,
+                },
+            );
         }
+        value
     }
 
-    fn generate_transitions(
+    pub(super) fn generate_transitions(
         &mut self,
         state: &GameState,
         actor: PlayerId,
         deadline: Option<WasmInstant>,
     ) -> Vec<(GameAction, GameState)> {
-        let mut seen: Vec<GameAction> = Vec::new();
-        let mut actions = Vec::new();
-
         if let Some(deadline) = deadline {
             if WasmInstant::now() >= deadline {
-                return actions;
+                return Vec::new();
             }
         }
 
-        if state.current_player != actor {
-            if let Ok(new_state) = self.simulate_state(state, &GameAction::EndTurn) {
-                actions.push((GameAction::EndTurn, new_state));
+        if state.acting_player() != actor {
+            // 等待其他玩家响应时本方没有可执行的操作
+            if state.priority_window.is_some() {
+                return Vec::new();
             }
-            return actions;
+            return self
+                .simulate_state(state, &GameAction::EndTurn)
+                .map(|new_state| vec![(GameAction::EndTurn, new_state)])
+                .unwrap_or_default();
         }
 
-        if state.phase == GamePhase::Main {
-            let advance = GameAction::AdvancePhase;
-            if !seen.contains(&advance) {
-                if let Ok(new_state) = self.simulate_state(state, &advance) {
-                    seen.push(advance.clone());
-                    actions.push((advance, new_state));
-                }
-            }
-        }
-
-        if let Some(player) = state.get_player(actor) {
-            // Playable cards
-            for card in &player.hand {
-                if let Some(deadline) = deadline {
-                    if WasmInstant::now() >= deadline {
-                        break;
-                    }
-                }
-                if card.cost > player.mana {
-                    continue;
-                }
-
-                let mut candidates: Vec<PlayCardAction> = Vec::new();
-                candidates.push(PlayCardAction {
-                    player_id: actor,
-                    card_id: card.id,
-                    target_player: None,
-                    target_card: None,
-                });
-
-                // 友方目标（英雄与随从）
-                candidates.push(PlayCardAction {
-                    player_id: actor,
-                    card_id: card.id,
-                    target_player: Some(actor),
-                    target_card: None,
-                });
-                for ally in &player.board {
-                    candidates.push(PlayCardAction {
-                        player_id: actor,
-                        card_id: card.id,
-                        target_player: Some(actor),
-                        target_card: Some(ally.id),
-                    });
-                }
-
-                if let Some(opponent) = state.opponent_of(actor) {
-                    candidates.push(PlayCardAction {
-                        player_id: actor,
-                        card_id: card.id,
-                        target_player: Some(opponent),
-                        target_card: None,
-                    });
-
-                    if let Some(opponent_player) = state.get_player(opponent) {
-                        for target in &opponent_player.board {
-                            candidates.push(PlayCardAction {
-                                player_id: actor,
-                                card_id: card.id,
-                                target_player: Some(opponent),
-                                target_card: Some(target.id),
-                            });
-                        }
-                    }
-                }
-
-                for action in candidates {
-                    let play_action = GameAction::PlayCard {
-                        action: action.clone(),
-                    };
-                    if !seen.contains(&play_action) {
-                        match self.simulate_state(state, &play_action) {
-                            Ok(new_state) => {
-                                seen.push(play_action.clone());
-                                actions.push((play_action, new_state));
-                            }
-                            Err(_) => {}
-                        }
-                    }
-                }
-            }
-
-            // Attacks
-            if state.phase == GamePhase::Combat {
-                if let Some(opponent) = state.opponent_of(actor) {
-                    let defender_board: Vec<CardId> = state
-                        .get_player(opponent)
-                        .map(|p| p.board.iter().map(|c| c.id).collect())
-                        .unwrap_or_default();
-
-                    for card in &player.board {
-                        if let Some(deadline) = deadline {
-                            if WasmInstant::now() >= deadline {
-                                break;
-                            }
-                        }
-                        if card.exhausted || card.attack <= 0 {
-                            continue;
-                        }
-
-                        let mut candidates: Vec<AttackAction> = Vec::new();
-                        candidates.push(AttackAction {
-                            attacker_owner: actor,
-                            attacker_id: card.id,
-                            defender_owner: opponent,
-                            defender_card: None,
-                        });
-
-                        for defender_card in &defender_board {
-                            candidates.push(AttackAction {
-                                attacker_owner: actor,
-                                attacker_id: card.id,
-                                defender_owner: opponent,
-                                defender_card: Some(*defender_card),
-                            });
-                        }
-
-                        for action in candidates {
-                            let attack_action = GameAction::Attack {
-                                action: action.clone(),
-                            };
-                            if !seen.contains(&attack_action) {
-                                match self.simulate_state(state, &attack_action) {
-                                    Ok(new_state) => {
-                                        seen.push(attack_action.clone());
-                                        actions.push((attack_action, new_state));
-                                    }
-                                    Err(_) => {}
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        if !seen.contains(&GameAction::EndTurn) {
-            if let Ok(new_state) = self.simulate_state(state, &GameAction::EndTurn) {
-                actions.push((GameAction::EndTurn, new_state));
-            }
-        }
+        // 与规则引擎共用同一份合法操作枚举，避免 AI 与规则判定不一致
+        let mut actions = self.rules.legal_transitions(state, actor);
 
         if self.config.randomness > 0.0 {
             actions.shuffle(&mut self.rng);
@@ -653,10 +1031,10 @@ impl AiAgent {
         actions
     }
 
-    fn prioritize_actions(
+    pub(super) fn prioritize_actions(
         &mut self,
         base_state: &GameState,
-        actions: &mut Vec<(GameAction, GameState)>,
+        actions: &mut [(GameAction, GameState)],
         strategy: AiStrategy,
         player_id: PlayerId,
     ) {
@@ -669,22 +1047,27 @@ impl AiAgent {
             AiStrategy::Aggressive => actions.sort_by(|a, b| {
                 (aggressive_score(base_state, b, player_id)
                     + learning_bias(&b.0) * LEARNING_IMPORTANCE)
-                    .partial_cmp(&(aggressive_score(base_state, a, player_id)
-                        + learning_bias(&a.0) * LEARNING_IMPORTANCE))
+                    .partial_cmp(
+                        &(aggressive_score(base_state, a, player_id)
+                            + learning_bias(&a.0) * LEARNING_IMPORTANCE),
+                    )
                     .unwrap_or(std::cmp::Ordering::Equal)
             }),
             AiStrategy::Control => actions.sort_by(|a, b| {
                 (control_score(base_state, b, player_id)
                     + learning_bias(&b.0) * LEARNING_IMPORTANCE)
-                    .partial_cmp(&(control_score(base_state, a, player_id)
-                        + learning_bias(&a.0) * LEARNING_IMPORTANCE))
+                    .partial_cmp(
+                        &(control_score(base_state, a, player_id)
+                            + learning_bias(&a.0) * LEARNING_IMPORTANCE),
+                    )
                     .unwrap_or(std::cmp::Ordering::Equal)
             }),
             AiStrategy::Combo => actions.sort_by(|a, b| {
-                (combo_score(base_state, b, player_id)
-                    + learning_bias(&b.0) * LEARNING_IMPORTANCE)
-                    .partial_cmp(&(combo_score(base_state, a, player_id)
-                        + learning_bias(&a.0) * LEARNING_IMPORTANCE))
+                (combo_score(base_state, b, player_id) + learning_bias(&b.0) * LEARNING_IMPORTANCE)
+                    .partial_cmp(
+                        &(combo_score(base_state, a, player_id)
+                            + learning_bias(&a.0) * LEARNING_IMPORTANCE),
+                    )
                     .unwrap_or(std::cmp::Ordering::Equal)
             }),
             AiStrategy::Adaptive => actions.sort_by(|a, b| {
@@ -699,60 +1082,38 @@ impl AiAgent {
         }
     }
 
-    fn simulate_state(
+    pub(super) fn simulate_state(
         &mut self,
         state: &GameState,
         action: &GameAction,
     ) -> Result<GameState, RuleError> {
         let mut next_state = state.clone();
-        let mut engine = RuleEngine::new();
-        let result: Result<Vec<GameEvent>, RuleError> = match action {
-            GameAction::PlayCard { action } => engine.play_card(&mut next_state, action.clone()),
-            GameAction::Mulligan { action } => engine.mulligan(&mut next_state, action.clone()),
-            GameAction::Attack { action } => engine.attack(&mut next_state, action.clone()),
-            GameAction::AdvancePhase => match RuleEngine::advance_phase(&mut next_state) {
-                Ok(_) => Ok(Vec::new()),
-                Err(err) => Err(err),
-            },
-            GameAction::EndTurn => engine.end_turn(&mut next_state),
-        };
-        match result {
-            Ok(_) => Ok(next_state),
-            Err(err) => Err(err),
-        }
+        self.rules.clone().apply_action(&mut next_state, action)?;
+        Ok(next_state)
     }
 
-    fn simulate_resolution(
+    pub(super) fn simulate_resolution(
         &mut self,
         state: &GameState,
         action: &GameAction,
     ) -> Result<RuleResolution, RuleError> {
         let mut next_state = state.clone();
-        let mut engine = RuleEngine::new();
-        let events = match action {
-            GameAction::PlayCard { action } => engine.play_card(&mut next_state, action.clone())?,
-            GameAction::Mulligan { action } => engine.mulligan(&mut next_state, action.clone())?,
-            GameAction::Attack { action } => engine.attack(&mut next_state, action.clone())?,
-            GameAction::AdvancePhase => {
-                RuleEngine::advance_phase(&mut next_state)?;
-                Vec::new()
-            }
-            GameAction::EndTurn => engine.end_turn(&mut next_state)?,
-        };
+        let events = self.rules.clone().apply_action(&mut next_state, action)?;
         Ok(RuleResolution::new(next_state, events))
     }
 
-    fn evaluate(&self, state: &GameState, player_id: PlayerId) -> f64 {
-        if let Some(outcome) = &state.outcome {
-            if outcome.winner == player_id {
-                return 1_000_000.0;
-            } else {
-                return -1_000_000.0;
+    pub(super) fn evaluate(&self, state: &GameState, player_id: PlayerId) -> f64 {
+        match state.game_outcome() {
+            Some(GameOutcome::Victory { winner, .. }) if state.are_allies(winner, player_id) => {
+                return WIN_SCORE
             }
+            Some(GameOutcome::Victory { .. }) => return -WIN_SCORE,
+            Some(GameOutcome::Draw { .. }) => return 0.0,
+            None => {}
         }
 
         let Some(player) = state.get_player(player_id) else {
-            return -1_000_000.0;
+            return -WIN_SCORE;
         };
         let opponent_id = state.opponent_of(player_id).unwrap_or(player_id);
         let opponent = state.get_player(opponent_id);
@@ -767,6 +1128,7 @@ impl AiAgent {
                 hand: 0.6,
                 mana: 0.4,
                 combo: 0.4,
+                ..StrategyWeights::default()
             },
             AiStrategy::Control => StrategyWeights {
                 hero: 1.2,
@@ -774,6 +1136,7 @@ impl AiAgent {
                 hand: 1.6,
                 mana: 0.8,
                 combo: 0.5,
+                ..StrategyWeights::default()
             },
             AiStrategy::Combo => StrategyWeights {
                 hero: 1.0,
@@ -781,16 +1144,18 @@ impl AiAgent {
                 hand: 1.8,
                 mana: 0.9,
                 combo: 2.6,
+                ..StrategyWeights::default()
             },
             AiStrategy::Adaptive => adaptive_weights(hero_diff, board_diff),
-            AiStrategy::Random => StrategyWeights {
-                hero: 1.0,
-                board: 1.0,
-                hand: 1.0,
-                mana: 0.5,
-                combo: 0.3,
-            },
+            AiStrategy::Random => StrategyWeights::default(),
         };
+        if let Some(custom) = self.config.evaluation_weights {
+            weights = custom;
+        }
+
+        let face_bias = self.config.face_bias.clamp(-1.0, 1.0);
+        weights.hero *= 1.0 + face_bias;
+        weights.board *= 1.0 - face_bias;
 
         let difficulty_weights = self.config.weights;
         weights.hero *= difficulty_weights.hero;
@@ -824,11 +1189,17 @@ impl AiAgent {
         }
 
         let armor_bonus =
-            (player.armor as f64 - opponent.map(|p| p.armor as f64).unwrap_or(0.0)) * 0.6;
+            (player.armor as f64 - opponent.map(|p| p.armor as f64).unwrap_or(0.0)) * weights.armor;
         let turn_bonus = if state.current_player == player_id {
-            0.3
+            weights.initiative
         } else {
-            -0.3
+            -weights.initiative
+        };
+
+        let taunt_bonus = if self.config.taunt_respect == 0.0 {
+            0.0
+        } else {
+            taunt_diff(state, player_id) * self.config.taunt_respect
         };
 
         hero_diff * weights.hero
@@ -838,6 +1209,7 @@ impl AiAgent {
             + combo_value * weights.combo
             + armor_bonus
             + turn_bonus
+            + taunt_bonus
     }
 
     fn random_noise(&mut self) -> f64 {
@@ -855,7 +1227,13 @@ fn board_value(cards: &[Card]) -> f64 {
         .map(|card| {
             let atk = card.attack.max(0) as f64;
             let hp = card.health.max(0) as f64;
-            atk * 1.6 + hp
+            // 远程单位换子时不掉血，攻击力更有价值
+            let ranged = if card.has_keyword(Keyword::Ranged) {
+                atk * 0.5
+            } else {
+                0.0
+            };
+            atk * 1.6 + hp + ranged
         })
         .sum()
 }
@@ -875,23 +1253,106 @@ fn combo_potential(cards: &[Card]) -> f64 {
         .sum()
 }
 
+/// 手牌价值的粗略估计：费用、身材与效果数。
+fn card_value(card: &Card) -> f64 {
+    card.cost as f64
+        + card.attack.max(0) as f64 * 1.6
+        + card.health.max(0) as f64
+        + card.effects.len() as f64 * 0.8
+}
+
+/// 场上单位的威胁：攻击力权重最高，其次是生命与效果数。
+fn threat_score(card: &Card) -> f64 {
+    card.attack.max(0) as f64 * 2.0 + card.health.max(0) as f64 + card.effects.len() as f64
+}
+
+/// 以单位为目标的操作：去掉目标后的操作（用于区分同一张卡牌或同一攻击者）与目标单位。
+fn unit_target(action: &GameAction) -> Option<(GameAction, PlayerId, CardId)> {
+    let mut subject = action.clone();
+    let (owner, card_id) = match &mut subject {
+        GameAction::PlayCard { action } => (action.target_player?, action.target_card.take()?),
+        GameAction::UseHeroPower { action } => (action.target_player?, action.target_card.take()?),
+        GameAction::Attack { action } => (action.defender_owner, action.defender_card.take()?),
+        GameAction::HeroAttack { action } => (action.defender_owner, action.defender_card.take()?),
+        _ => return None,
+    };
+    Some((subject, owner, card_id))
+}
+
+/// 同一张卡牌或同一攻击者的单位目标只保留威胁最高的 `limit` 个，英雄目标与其他操作不受影响。
+fn prune_targets(state: &GameState, transitions: &mut Vec<(GameAction, GameState)>, limit: u8) {
+    if limit == 0 {
+        return;
+    }
+    let threat = |owner: PlayerId, card_id: CardId| {
+        state
+            .get_player(owner)
+            .and_then(|player| player.find_card_on_board(card_id))
+            .map_or(0.0, threat_score)
+    };
+    let mut ranked: HashMap<GameAction, Vec<(f64, CardId)>> = HashMap::new();
+    for (action, _) in transitions.iter() {
+        if let Some((subject, owner, card_id)) = unit_target(action) {
+            ranked
+                .entry(subject)
+                .or_default()
+                .push((threat(owner, card_id), card_id));
+        }
+    }
+    let mut kept: HashMap<GameAction, Vec<CardId>> = HashMap::new();
+    for (subject, mut targets) in ranked {
+        if targets.len() <= limit as usize {
+            continue;
+        }
+        targets.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        targets.truncate(limit as usize);
+        kept.insert(subject, targets.into_iter().map(|(_, id)| id).collect());
+    }
+    if kept.is_empty() {
+        return;
+    }
+    transitions.retain(|(action, _)| match unit_target(action) {
+        Some((subject, _, card_id)) => kept
+            .get(&subject)
+            .is_none_or(|targets| targets.contains(&card_id)),
+        None => true,
+    });
+}
+
+/// 玩家有待处理的弃牌时，弃掉手牌与新抽到的牌中价值最低的一张。
+fn choose_discard(state: &GameState, player_id: PlayerId) -> Option<GameAction> {
+    let pending = state
+        .pending_choices
+        .iter()
+        .find(|pending| pending.player_id == player_id)?;
+    let drawn_card = pending.drawn_card()?;
+    let player = state.get_player(player_id)?;
+    let discard = player
+        .hand
+        .iter()
+        .chain(std::iter::once(drawn_card))
+        .min_by(|a, b| {
+            card_value(a)
+                .partial_cmp(&card_value(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+    Some(GameAction::DiscardCard {
+        action: DiscardCardAction {
+            player_id,
+            pending_id: pending.id,
+            discard_card_id: discard.id,
+        },
+    })
+}
+
 fn aggressive_score(
     base: &GameState,
     action_state: &(GameAction, GameState),
     player_id: PlayerId,
 ) -> f64 {
     let (_, new_state) = action_state;
-    let opponent_before = base
-        .opponent_of(player_id)
-        .and_then(|id| base.get_player(id))
-        .map(|p| (p.health + p.armor as i16) as f64)
-        .unwrap_or(0.0);
-    let opponent_after = new_state
-        .opponent_of(player_id)
-        .and_then(|id| new_state.get_player(id))
-        .map(|p| (p.health + p.armor as i16) as f64)
-        .unwrap_or(0.0);
-    let damage = opponent_before - opponent_after;
+    let hero_total = |state: &GameState| sum_over(state, &state.enemies_of(player_id), hero_value);
+    let damage = hero_total(base) - hero_total(new_state);
     let attacker_board = new_state
         .get_player(player_id)
         .map(|p| board_value(&p.board))
@@ -913,11 +1374,9 @@ fn control_score(
         .get_player(player_id)
         .map(|p| board_value(&p.board))
         .unwrap_or(0.0);
-    let opponent_board = new_state
-        .opponent_of(player_id)
-        .and_then(|id| new_state.get_player(id))
-        .map(|p| board_value(&p.board))
-        .unwrap_or(0.0);
+    let opponent_board = sum_over(new_state, &new_state.enemies_of(player_id), |p| {
+        board_value(&p.board)
+    });
     (board_after - board_before) - opponent_board
 }
 
@@ -947,29 +1406,84 @@ fn evaluation_components(state: &GameState, player_id: PlayerId) -> (f64, f64, f
         Some(p) => p,
         None => return (0.0, 0.0, 0.0, 0.0, 0.0),
     };
-    let opponent_id = state.opponent_of(player_id).unwrap_or(player_id);
-    let opponent = state.get_player(opponent_id);
-
-    let hero_diff = (player.health + player.armor as i16) as f64
-        - opponent
-            .map(|p| (p.health + p.armor as i16) as f64)
-            .unwrap_or(0.0);
-    let board_diff =
-        board_value(&player.board) - opponent.map(|p| board_value(&p.board)).unwrap_or(0.0);
-    let hand_diff = player.hand.len() as f64 - opponent.map(|p| p.hand.len() as f64).unwrap_or(0.0);
-    let mana_diff = player.mana as f64 - opponent.map(|p| p.mana as f64).unwrap_or(0.0);
+    // 组队时以全队合计对比敌方全体
+    let allies = state.allies_of(player_id);
+    let enemies = state.enemies_of(player_id);
+    let diff = |value: fn(&Player) -> f64| {
+        sum_over(state, &allies, value) - sum_over(state, &enemies, value)
+    };
+
+    let hero_diff = diff(hero_value);
+    let board_diff = diff(|p| board_value(&p.board));
+    let hand_diff = diff(|p| p.hand.len() as f64);
+    let mana_diff = diff(|p| p.mana as f64);
     let combo_value = combo_potential(&player.hand);
 
     (hero_diff, board_diff, hand_diff, mana_diff, combo_value)
 }
 
-#[derive(Debug, Clone, Copy)]
-struct StrategyWeights {
-    hero: f64,
-    board: f64,
-    hand: f64,
-    mana: f64,
-    combo: f64,
+/// 双方场上嘲讽单位的身材（攻击力加生命）之差。
+fn taunt_diff(state: &GameState, player_id: PlayerId) -> f64 {
+    let taunts = |player: &Player| {
+        player
+            .board
+            .iter()
+            .filter(|card| card.has_keyword(Keyword::Taunt))
+            .map(|card| (card.attack.max(0) + card.health.max(0)) as f64)
+            .sum()
+    };
+    sum_over(state, &state.allies_of(player_id), taunts)
+        - sum_over(state, &state.enemies_of(player_id), taunts)
+}
+
+/// 英雄的有效生命（生命加护甲）；已阵亡的英雄不计入。
+fn hero_value(player: &Player) -> f64 {
+    if player.is_alive() {
+        (player.health + player.armor as i16) as f64
+    } else {
+        0.0
+    }
+}
+
+fn sum_over(state: &GameState, ids: &[PlayerId], value: impl Fn(&Player) -> f64) -> f64 {
+    ids.iter()
+        .filter_map(|&id| state.get_player(id))
+        .map(value)
+        .sum()
+}
+
+/// 评估函数各项的权重；缺省的字段取中性值。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StrategyWeights {
+    /// 双方英雄生命差。
+    pub hero: f64,
+    /// 双方场面价值差。
+    pub board: f64,
+    /// 双方手牌数差。
+    pub hand: f64,
+    /// 双方可用法力差。
+    pub mana: f64,
+    /// 己方手牌中的连击潜力。
+    pub combo: f64,
+    /// 双方护甲差。
+    pub armor: f64,
+    /// 轮到己方行动时的加分（轮到对手时为同等扣分）。
+    pub initiative: f64,
+}
+
+impl Default for StrategyWeights {
+    fn default() -> Self {
+        Self {
+            hero: 1.0,
+            board: 1.0,
+            hand: 1.0,
+            mana: 0.5,
+            combo: 0.3,
+            armor: 0.6,
+            initiative: 0.3,
+        }
+    }
 }
 
 fn adaptive_weights(hero_diff: f64, board_diff: f64) -> StrategyWeights {
@@ -981,6 +1495,7 @@ fn adaptive_weights(hero_diff: f64, board_diff: f64) -> StrategyWeights {
         hand: 1.3,
         mana: 0.9,
         combo: 1.1,
+        ..StrategyWeights::default()
     }
 }
 
@@ -994,7 +1509,13 @@ mod learning {
     enum ActionKind {
         PlayCard,
         Attack,
+        HeroAttack,
+        HeroPower,
         Mulligan,
+        ResolveChoice,
+        DiscardCard,
+        PassPriority,
+        ResolveCombat,
         AdvancePhase,
         EndTurn,
     }
@@ -1055,10 +1576,34 @@ mod learning {
                 kind: ActionKind::Attack,
                 card: Some(action.attacker_id),
             },
+            GameAction::HeroAttack { .. } => ActionSignature {
+                kind: ActionKind::HeroAttack,
+                card: None,
+            },
+            GameAction::UseHeroPower { .. } => ActionSignature {
+                kind: ActionKind::HeroPower,
+                card: None,
+            },
             GameAction::Mulligan { .. } => ActionSignature {
                 kind: ActionKind::Mulligan,
                 card: None,
             },
+            GameAction::ResolveChoice { .. } => ActionSignature {
+                kind: ActionKind::ResolveChoice,
+                card: None,
+            },
+            GameAction::DiscardCard { action } => ActionSignature {
+                kind: ActionKind::DiscardCard,
+                card: Some(action.discard_card_id),
+            },
+            GameAction::PassPriority { .. } => ActionSignature {
+                kind: ActionKind::PassPriority,
+                card: None,
+            },
+            GameAction::ResolveCombat { .. } => ActionSignature {
+                kind: ActionKind::ResolveCombat,
+                card: None,
+            },
             GameAction::AdvancePhase => ActionSignature {
                 kind: ActionKind::AdvancePhase,
                 card: None,
@@ -1074,7 +1619,11 @@ mod learning {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::game::{GameState, VictoryReason};
+    use crate::game::{
+        AttackAction, Card, CardEffect, CardType, EffectKind, EffectTarget, EffectTrigger,
+        GamePhase, GameState, PassPriorityAction, PlayCardAction, Player, ResolveCombatAction,
+        RuleConfig, VictoryReason,
+    };
 
     #[test]
     fn ai_handles_finished_game() {
@@ -1090,4 +1639,351 @@ mod tests {
         assert!(decision.action.is_none());
         assert!(decision.evaluation > 0.0);
     }
+
+    #[test]
+    fn simultaneous_defeat_is_scored_as_a_draw() {
+        let blast = |id: u32, target: EffectTarget| {
+            CardEffect::new(
+                id,
+                "Cataclysm: deal 5 damage to a hero",
+                EffectTrigger::OnPlay,
+                1,
+                EffectKind::DirectDamage {
+                    amount: 5,
+                    target,
+                    scaling: None,
+                },
+            )
+        };
+        let spell = Card::new(
+            120,
+            "Cataclysm",
+            1,
+            0,
+            0,
+            CardType::Spell,
+            vec![
+                blast(9010, EffectTarget::OpponentOfSource),
+                blast(9011, EffectTarget::SourcePlayer),
+            ],
+        );
+        let player_one = Player::new(0, 5, 0, 3, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 5, 0, 3, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 120,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+        RuleEngine::new()
+            .apply_action(&mut state, &play)
+            .expect("play");
+
+        let agent = AiAgent::new(AiConfig::from_difficulty(AiDifficulty::Hard));
+        assert!(state.is_finished());
+        assert_eq!(agent.evaluate_state(&state, 0), 0.0);
+        assert_eq!(agent.evaluate_state(&state, 1), 0.0);
+    }
+
+    #[test]
+    fn ai_passes_priority_and_resolves_two_step_combat() {
+        let mut state = GameState::sample()
+            .with_config(RuleConfig::default().with_two_step_combat(true))
+            .with_phase(GamePhase::Combat);
+        let declare = GameAction::Attack {
+            action: AttackAction {
+                attacker_owner: 0,
+                attacker_id: 2,
+                defender_owner: 1,
+                defender_card: None,
+            },
+        };
+        let mut engine = RuleEngine::new();
+        engine.apply_action(&mut state, &declare).expect("declare");
+        state.players[1].hand.clear();
+
+        let config = AiConfig::from_difficulty(AiDifficulty::Normal);
+        let pass = GameAction::PassPriority {
+            action: PassPriorityAction { player_id: 1 },
+        };
+        let response = AiAgent::with_seed(config.clone(), 5).decide_action(&state, 1);
+        assert_eq!(response.action, Some(pass.clone()));
+        engine.apply_action(&mut state, &pass).expect("pass");
+
+        let decision = AiAgent::with_seed(config, 5).decide_action(&state, 0);
+        assert_eq!(
+            decision.action,
+            Some(GameAction::ResolveCombat {
+                action: ResolveCombatAction { player_id: 0 }
+            })
+        );
+    }
+
+    #[test]
+    fn agent_enumerates_and_simulates_with_its_rule_engine() {
+        let state = GameState::sample().with_phase(GamePhase::Combat);
+        let attacks = |transitions: &[(GameAction, GameState)]| {
+            transitions
+                .iter()
+                .any(|(action, _)| matches!(action, GameAction::Attack { .. }))
+        };
+        let config = AiConfig::from_difficulty(AiDifficulty::Hard);
+        let mut stock = AiAgent::with_seed(config.clone(), 3);
+        assert!(attacks(&stock.generate_transitions(&state, 0, None)));
+
+        let mut pacifist = RuleEngine::new();
+        pacifist.add_pre_action_hook(|_, action| match action {
+            GameAction::Attack { .. } => Err(RuleError::ActionVetoed {
+                reason: "attacks are disabled".to_string(),
+            }),
+            _ => Ok(()),
+        });
+        let mut agent = AiAgent::with_seed(config, 3).with_rules(pacifist.clone());
+        let transitions = agent.generate_transitions(&state, 0, None);
+        assert!(
+            !attacks(&transitions),
+            "the veto hook applies to the search"
+        );
+        let legal = pacifist.legal_actions(&state, 0);
+        assert_eq!(transitions.len(), legal.len());
+        assert!(transitions.iter().all(|(action, _)| legal.contains(action)));
+
+        let action = agent
+            .decide_action(&state, 0)
+            .action
+            .expect("ending the turn is still legal");
+        assert!(pacifist.validate(&state, &action).is_ok());
+    }
+
+    #[test]
+    fn ai_discards_the_weakest_card_on_overdraw() {
+        let unit = |id: CardId, cost: u8, stats: i16| {
+            Card::new(
+                id,
+                "Recruit",
+                cost,
+                stats,
+                stats,
+                CardType::Unit,
+                Vec::new(),
+            )
+        };
+        let hand = vec![unit(1, 4, 4), unit(2, 1, 1)];
+        let player_one = Player::new(0, 30, 0, 1, hand, Vec::new(), vec![unit(3, 5, 5)]);
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_config(RuleConfig::default().with_max_hand_size(2))
+            .with_phase(GamePhase::Main);
+        state.draw_card(0);
+        assert_eq!(state.pending_choices.len(), 1);
+
+        let mut agent = AiAgent::with_seed(AiConfig::from_difficulty(AiDifficulty::Normal), 7);
+        let decision = agent.decide_action(&state, 0);
+        let Some(GameAction::DiscardCard { action }) = decision.action else {
+            panic!("expected a discard, got {:?}", decision.action);
+        };
+        assert_eq!(action.discard_card_id, 2);
+        let resolution = decision.resolution.expect("discard applies");
+        let hand: Vec<CardId> = resolution.state.players[0]
+            .hand
+            .iter()
+            .map(|card| card.id)
+            .collect();
+        assert_eq!(hand, vec![1, 3]);
+    }
+
+    #[test]
+    fn iterative_deepening_keeps_the_last_complete_iteration() {
+        let mut raider = Card::new(1, "Raider", 3, 3, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let player_two = Player::new(1, 3, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let lethal = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+        let winning_attack = GameAction::Attack {
+            action: AttackAction {
+                attacker_owner: 0,
+                attacker_id: 1,
+                defender_owner: 1,
+                defender_card: None,
+            },
+        };
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        config.check_lethal = false;
+        let decision = AiAgent::with_seed(config.clone(), 3).decide_action(&lethal, 0);
+        assert_eq!(decision.action, Some(winning_attack));
+        assert_eq!(decision.depth_reached, 1, "a proven win stops deepening");
+
+        let sample = GameState::sample();
+        config.depth = 2;
+        let decision =
+            AiAgent::with_seed(config.clone(), 3).decide_action(&sample, sample.current_player);
+        assert!(decision.action.is_some());
+        assert_eq!(decision.depth_reached, 2);
+        assert!(!decision.timed_out);
+
+        config.time_limit = Duration::from_nanos(1);
+        let decision = AiAgent::with_seed(config, 3).decide_action(&sample, sample.current_player);
+        assert!(decision.timed_out);
+        assert_eq!(decision.depth_reached, 0);
+        assert!(decision.action.is_some(), "still returns a move");
+    }
+
+    #[test]
+    fn every_enemy_unit_can_be_targeted() {
+        let ready = |id, name, attack, health| {
+            let mut card = Card::new(id, name, 2, attack, health, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let mut enemies: Vec<Card> = (11..17).map(|id| ready(id, "Wall", 0, 8)).collect();
+        enemies.push(ready(17, "Assassin", 6, 1));
+        let player_one = Player::new(
+            0,
+            30,
+            0,
+            0,
+            Vec::new(),
+            vec![ready(1, "Knight", 3, 8)],
+            Vec::new(),
+        );
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), enemies, Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let mut transitions = RuleEngine::new().legal_transitions(&state, 0);
+        prune_targets(&state, &mut transitions, 3);
+        let unit_targets = transitions
+            .iter()
+            .filter_map(|(action, _)| unit_target(action))
+            .map(|(_, _, card_id)| card_id)
+            .collect::<Vec<_>>();
+        assert_eq!(unit_targets.len(), 3);
+        assert!(
+            unit_targets.contains(&17),
+            "the biggest threat survives pruning"
+        );
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let decision = AiAgent::with_seed(config, 7).decide_action(&state, 0);
+        assert_eq!(
+            decision.action,
+            Some(GameAction::Attack {
+                action: AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 1,
+                    defender_owner: 1,
+                    defender_card: Some(17),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn evaluation_weights_can_be_tuned_from_json() {
+        let player_one = Player::new(0, 30, 5, 0, Vec::new(), Vec::new(), Vec::new());
+        let player_two = Player::new(1, 20, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0);
+
+        let weights: StrategyWeights =
+            serde_json::from_str(r#"{"hero": 0, "armor": 2, "initiative": 0}"#).expect("parses");
+        assert_eq!(weights.board, StrategyWeights::default().board);
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Normal);
+        config.weights = DifficultyWeights {
+            hero: 1.0,
+            board: 1.0,
+            resources: 1.0,
+            combo: 1.0,
+        };
+        let stock = AiAgent::with_seed(config.clone(), 1).evaluate_state(&state, 0);
+        let tuned = AiAgent::with_seed(config.with_evaluation_weights(weights), 1);
+        assert_eq!(tuned.evaluate_state(&state, 0), 10.0);
+        assert_ne!(stock, 10.0);
+    }
+
+    #[test]
+    fn sliced_search_matches_the_blocking_search() {
+        let sample = GameState::sample();
+        let player_id = sample.current_player;
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Hard);
+        config.time_limit = Duration::ZERO;
+        config.randomness = 0.1;
+        let blocking = AiAgent::with_seed(config.clone(), 9).decide_action(&sample, player_id);
+
+        let mut agent = AiAgent::with_seed(config, 9);
+        let mut session = agent.start_search(&sample, player_id);
+        let mut slices = 1;
+        let sliced = loop {
+            if let Some(decision) = agent.search_slice(&mut session, Duration::ZERO) {
+                break decision;
+            }
+            slices += 1;
+        };
+        assert!(slices > 1, "each slice searches a single root move");
+        assert_eq!(sliced.action, blocking.action);
+        assert_eq!(sliced.evaluation, blocking.evaluation);
+        assert_eq!(sliced.nodes, blocking.nodes);
+        assert_eq!(sliced.depth_reached, blocking.depth_reached);
+        assert!(agent.search_slice(&mut session, Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn lethal_mcts_and_determinized_searches_are_sliced() {
+        let run = |config: &AiConfig, state: &GameState| {
+            let player_id = state.current_player;
+            let blocking = AiAgent::with_seed(config.clone(), 5).decide_action(state, player_id);
+            let mut agent = AiAgent::with_seed(config.clone(), 5);
+            let mut session = agent.start_search(state, player_id);
+            let mut slices = 1;
+            let sliced = loop {
+                if let Some(decision) = agent.search_slice(&mut session, Duration::ZERO) {
+                    break decision;
+                }
+                slices += 1;
+            };
+            assert!(slices > 1, "the search yields between slices");
+            assert_eq!(sliced.action, blocking.action);
+            assert_eq!(sliced.evaluation, blocking.evaluation);
+            assert_eq!(sliced.nodes, blocking.nodes);
+            sliced
+        };
+        let ready = |id, attack| {
+            let mut card = Card::new(id, "Raider", 1, attack, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let player_one = Player::new(
+            0,
+            30,
+            0,
+            0,
+            Vec::new(),
+            vec![ready(1, 3), ready(2, 2)],
+            Vec::new(),
+        );
+        let player_two = Player::new(1, 5, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Hard);
+        config.time_limit = Duration::ZERO;
+        let lethal = run(&config, &state);
+        assert_eq!(lethal.evaluation, WIN_SCORE);
+
+        config.check_lethal = false;
+        let mcts = config
+            .clone()
+            .with_algorithm(AiAlgorithm::Mcts)
+            .with_mcts(MctsConfig::default().with_iterations(40));
+        assert!(run(&mcts, &state).nodes > 1);
+
+        config.determinizations = 3;
+        let sample = GameState::sample();
+        let sampled = run(&config, &sample);
+        assert!(sampled.action.is_some());
+    }
 }
@@ -1,5 +1,28 @@
 //! AI 算法模块（如 MCTS、启发式策略等）。
 
+pub mod determinization;
+pub mod hints;
+pub mod lethal;
+pub mod mcts;
 pub mod minimax;
+pub mod ordering;
+pub mod planner;
+pub mod profile;
+pub mod transposition;
+pub mod worker;
 
-pub use minimax::{AiAgent, AiConfig, AiDecision, AiDifficulty, AiStrategy, GameAction};
+// `GameAction` 已移至规则模块，这里保留旧的导出路径
+pub use crate::game::GameAction;
+pub use hints::{HintReason, MoveSuggestion};
+pub use lethal::find_lethal;
+pub use mcts::{MctsConfig, RolloutPolicy};
+pub use minimax::{
+    AiAgent, AiAlgorithm, AiConfig, AiDecision, AiDifficulty, AiStrategy, SearchSession,
+    StrategyWeights,
+};
+pub use planner::{PlannedStep, TurnPlan};
+pub use profile::{AiProfile, AiProfiles};
+pub use worker::{
+    AiSettings, AiTask, AiTaskResult, AiWorker, AiWorkerRequest, AiWorkerResponse,
+    AI_WORKER_PROTOCOL,
+};
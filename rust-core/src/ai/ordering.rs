@@ -0,0 +1,93 @@
+//! 走法排序：杀手走法与历史启发，让 Alpha-Beta 更早遇到能剪枝的操作。
+//!
+//! 杀手走法按层记录最近引发剪枝的操作，历史表累计每个操作引发剪枝时的剩余深度平方，
+//! 两者都只在一次决策内有效。
+
+use std::collections::HashMap;
+
+use crate::game::{GameAction, GameState};
+
+/// 每层保留的杀手走法数量。
+const KILLER_SLOTS: usize = 2;
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct MoveOrdering {
+    /// 按距根节点的层数索引，越靠前越新。
+    killers: Vec<[Option<GameAction>; KILLER_SLOTS]>,
+    history: HashMap<GameAction, u64>,
+}
+
+impl MoveOrdering {
+    /// 记下在第 `ply` 层、剩余 `depth` 层时引发剪枝的操作。
+    pub fn record_cutoff(&mut self, ply: usize, action: &GameAction, depth: u8) {
+        if self.killers.len() <= ply {
+            self.killers.resize_with(ply + 1, Default::default);
+        }
+        let slots = &mut self.killers[ply];
+        if slots[0].as_ref() != Some(action) {
+            slots.rotate_right(1);
+            slots[0] = Some(action.clone());
+        }
+        *self.history.entry(action.clone()).or_default() += u64::from(depth) * u64::from(depth);
+    }
+
+    /// 杀手走法排在最前，其余按历史分数从高到低；分数相同的操作保持原有顺序。
+    pub fn order(&self, ply: usize, transitions: &mut [(GameAction, GameState)]) {
+        let killers = self.killers.get(ply);
+        transitions.sort_by_cached_key(|(action, _)| {
+            let killer = killers
+                .and_then(|slots| slots.iter().position(|slot| slot.as_ref() == Some(action)))
+                .unwrap_or(KILLER_SLOTS);
+            let history = self.history.get(action).copied().unwrap_or(0);
+            (killer, std::cmp::Reverse(history))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::{AiAgent, AiConfig, AiDifficulty};
+    use crate::game::{Card, CardType, GamePhase, Player, ResolveCombatAction};
+
+    #[test]
+    fn killers_and_history_reorder_moves() {
+        let state = GameState::default();
+        let resolve = GameAction::ResolveCombat {
+            action: ResolveCombatAction { player_id: 0 },
+        };
+        let mut transitions = vec![
+            (GameAction::EndTurn, state.clone()),
+            (GameAction::AdvancePhase, state.clone()),
+            (resolve.clone(), state.clone()),
+        ];
+        let mut ordering = MoveOrdering::default();
+        ordering.record_cutoff(3, &resolve, 1);
+        ordering.record_cutoff(1, &GameAction::AdvancePhase, 2);
+        ordering.order(1, &mut transitions);
+        let order: Vec<_> = transitions
+            .iter()
+            .map(|(action, _)| action.clone())
+            .collect();
+        assert_eq!(
+            order,
+            vec![GameAction::AdvancePhase, resolve, GameAction::EndTurn]
+        );
+
+        let ready = |id| {
+            let mut card = Card::new(id, "Raider", 1, 1, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let board = vec![ready(1), ready(2), ready(3)];
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), board, Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![ready(4)], Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let decision = AiAgent::with_seed(config, 1).decide_action(&state, 0);
+        assert!(decision.cutoff_rate > 0.0 && decision.cutoff_rate <= 1.0);
+    }
+}
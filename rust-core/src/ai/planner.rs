@@ -0,0 +1,258 @@
+//! 整回合规划：搜索己方一回合内的出牌与攻击序列，直到结束回合，
+//! 一次性返回整个计划及每一步的结算结果，前端无需逐步回调引擎即可播放 AI 的回合。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::lethal::find_lethal_within;
+use super::minimax::{AiAgent, WasmInstant};
+use crate::game::{GameAction, GameState, PlayerId, RuleEngine, RuleError, RuleResolution};
+
+/// 单次规划最多展开的局面数，超出后剩余的分支直接结束回合。
+const PLAN_NODE_LIMIT: u64 = 4_000;
+/// 计划的最大步数，防止异常局面下无限延长回合。
+const MAX_PLAN_STEPS: usize = 32;
+
+/// 计划中的一步及其模拟结算结果。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedStep {
+    pub action: GameAction,
+    pub resolution: RuleResolution,
+}
+
+/// 一整个回合的计划，通常以结束回合收尾；对局在回合内决出胜负时止于制胜的操作。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnPlan {
+    #[serde(default)]
+    pub steps: Vec<PlannedStep>,
+    /// 执行完整个计划后的局面评估。
+    pub evaluation: f64,
+    pub nodes: u64,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+impl TurnPlan {
+    pub fn actions(&self) -> Vec<GameAction> {
+        self.steps.iter().map(|step| step.action.clone()).collect()
+    }
+
+    /// 在 `state` 上依次执行计划，以实际结算结果替换每一步的模拟结果。
+    /// 与 `RuleEngine::apply_all` 一样是原子的：任一步失败时状态与引擎都回滚到执行前。
+    pub fn apply(
+        &mut self,
+        engine: &mut RuleEngine,
+        state: &mut GameState,
+    ) -> Result<(), RuleError> {
+        let state_snapshot = state.clone();
+        let engine_snapshot = engine.clone();
+        for (index, step) in self.steps.iter_mut().enumerate() {
+            match engine.apply_action(state, &step.action) {
+                Ok(events) => {
+                    step.resolution = RuleResolution::new(state.clone(), events)
+                        .with_trace(engine.take_effect_trace());
+                }
+                Err(error) => {
+                    *state = state_snapshot;
+                    *engine = engine_snapshot;
+                    return Err(RuleError::BatchActionFailed {
+                        index,
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 规划过程中的共享状态：以局面哈希缓存每个局面的最佳后续序列，
+/// 不同出牌顺序到达的相同局面只需搜索一次。
+struct PlanSearch {
+    best: HashMap<u64, (f64, Vec<GameAction>)>,
+    nodes: u64,
+    timed_out: bool,
+    deadline: Option<WasmInstant>,
+}
+
+impl AiAgent {
+    /// 规划 `player_id` 本回合的完整操作序列，不是其回合时返回空计划。
+    pub fn plan_turn(&mut self, state: &GameState, player_id: PlayerId) -> TurnPlan {
+        let start = WasmInstant::now();
+        let deadline = if self.config.time_limit.is_zero() {
+            None
+        } else {
+            Some(start + self.config.time_limit)
+        };
+        let mut search = PlanSearch {
+            best: HashMap::new(),
+            nodes: 0,
+            timed_out: false,
+            deadline,
+        };
+        // 能斩杀时直接采用致胜序列
+        let lethal = self
+            .config
+            .check_lethal
+            .then(|| find_lethal_within(&self.rules, state, player_id, deadline))
+            .flatten();
+        let (evaluation, actions) = match lethal {
+            Some(sequence) => (None, sequence),
+            None => {
+                let (score, actions) = self.plan_from(state, player_id, 0, &mut search);
+                (Some(score), actions)
+            }
+        };
+
+        let mut steps = Vec::with_capacity(actions.len());
+        let mut current = state.clone();
+        for action in actions {
+            let Ok(resolution) = self.simulate_resolution(&current, &action) else {
+                break;
+            };
+            current = resolution.state.clone();
+            steps.push(PlannedStep { action, resolution });
+        }
+        let evaluation = evaluation.unwrap_or_else(|| self.evaluate(&current, player_id));
+
+        TurnPlan {
+            steps,
+            evaluation,
+            nodes: search.nodes,
+            timed_out: search.timed_out,
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// 从 `state` 起的最佳后续序列及其收尾局面的评估。
+    fn plan_from(
+        &mut self,
+        state: &GameState,
+        player_id: PlayerId,
+        length: usize,
+        search: &mut PlanSearch,
+    ) -> (f64, Vec<GameAction>) {
+        if state.is_finished() || state.acting_player() != player_id {
+            return (self.evaluate(state, player_id), Vec::new());
+        }
+        let key = state.hash();
+        if let Some(known) = search.best.get(&key) {
+            return known.clone();
+        }
+        search.nodes += 1;
+
+        let out_of_time = search
+            .deadline
+            .is_some_and(|deadline| WasmInstant::now() >= deadline);
+        search.timed_out |= out_of_time;
+        if out_of_time || search.nodes > PLAN_NODE_LIMIT || length >= MAX_PLAN_STEPS {
+            return match self.simulate_state(state, &GameAction::EndTurn) {
+                Ok(ended) => (self.evaluate(&ended, player_id), vec![GameAction::EndTurn]),
+                Err(_) => (self.evaluate(state, player_id), Vec::new()),
+            };
+        }
+
+        let mut transitions = self.generate_transitions(state, player_id, None);
+        self.prioritize_actions(state, &mut transitions, self.config.strategy, player_id);
+        let mut best: Option<(f64, Vec<GameAction>)> = None;
+        for (action, next) in transitions {
+            let (score, mut rest) = if action == GameAction::EndTurn {
+                (self.evaluate(&next, player_id), Vec::new())
+            } else {
+                self.plan_from(&next, player_id, length + 1, search)
+            };
+            let improves = best.as_ref().is_none_or(|(known, _)| score > *known);
+            if improves {
+                rest.insert(0, action);
+                best = Some((score, rest));
+            }
+        }
+        let best = best.unwrap_or_else(|| (self.evaluate(state, player_id), Vec::new()));
+        if !search.timed_out {
+            search.best.insert(key, best.clone());
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::{AiAgent, AiConfig, AiDifficulty};
+    use crate::game::{Card, CardType, GamePhase, PlayCardAction, Player};
+
+    #[test]
+    fn plans_cover_the_whole_turn() {
+        let mut raider = Card::new(1, "Raider", 1, 2, 2, CardType::Unit, Vec::new());
+        raider.refresh();
+        let recruit = Card::new(2, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![recruit], vec![raider], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let plan = AiAgent::with_seed(config, 1).plan_turn(&state, 0);
+        let actions = plan.actions();
+
+        assert_eq!(actions.last(), Some(&GameAction::EndTurn));
+        assert!(actions
+            .iter()
+            .any(|action| matches!(action, GameAction::PlayCard { .. })));
+        assert!(actions
+            .iter()
+            .any(|action| matches!(action, GameAction::Attack { .. })));
+        let last = &plan.steps.last().expect("plan has steps").resolution.state;
+        assert_eq!(last.current_player, 1);
+        assert_eq!(last.get_player(1).map(|player| player.health), Some(28));
+    }
+
+    #[test]
+    fn a_plan_that_fails_partway_leaves_the_game_untouched() {
+        let recruit = Card::new(2, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![recruit], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let original = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let plan = AiAgent::with_seed(config, 1).plan_turn(&original, 0);
+        assert!(
+            plan.steps.len() >= 2,
+            "plays the recruit, then ends the turn"
+        );
+
+        let mut broken = plan.clone();
+        let stale = PlannedStep {
+            action: GameAction::PlayCard {
+                action: PlayCardAction {
+                    player_id: 0,
+                    card_id: 2,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            },
+            resolution: plan.steps[0].resolution.clone(),
+        };
+        broken.steps.insert(1, stale);
+        let mut engine = RuleEngine::new();
+        let mut state = original.clone();
+        let error = broken.apply(&mut engine, &mut state).unwrap_err();
+        assert!(matches!(
+            error,
+            RuleError::BatchActionFailed { index: 1, .. }
+        ));
+        assert_eq!(state, original, "the first step is rolled back");
+        assert!(engine.effect_stack().is_empty());
+
+        let mut plan = plan;
+        plan.apply(&mut engine, &mut state)
+            .expect("the planned turn applies");
+        assert_eq!(state.current_player, 1);
+        assert_eq!(plan.steps.last().unwrap().resolution.state, state);
+    }
+}
@@ -0,0 +1,144 @@
+//! AI 性格档案：以名称登记的一组 AI 参数（权重、策略、随机性、嘲讽重视度、打脸取向等），
+//! 可从 JSON 载入，单人模式的不同对手只需引用不同的档案。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::minimax::{AiAlgorithm, AiConfig, AiDifficulty, AiStrategy, StrategyWeights};
+
+/// 一个具名的 AI 性格；未填写的字段沿用难度的默认配置。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AiProfile {
+    pub name: String,
+    /// 作为基础的难度，缺省为普通。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<AiDifficulty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<AiStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<AiAlgorithm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<StrategyWeights>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub randomness: Option<f64>,
+    #[serde(default)]
+    pub taunt_respect: f64,
+    #[serde(default)]
+    pub face_bias: f64,
+}
+
+impl AiProfile {
+    /// 按档案生成 AI 配置。
+    pub fn config(&self) -> AiConfig {
+        let mut config = AiConfig::from_difficulty(self.difficulty.unwrap_or(AiDifficulty::Normal));
+        if let Some(strategy) = self.strategy {
+            config = config.with_strategy(strategy);
+        }
+        if let Some(algorithm) = self.algorithm {
+            config = config.with_algorithm(algorithm);
+        }
+        if let Some(weights) = self.weights {
+            config = config.with_evaluation_weights(weights);
+        }
+        if let Some(randomness) = self.randomness {
+            config.randomness = randomness.max(0.0);
+        }
+        config.taunt_respect = self.taunt_respect;
+        config.face_bias = self.face_bias.clamp(-1.0, 1.0);
+        config
+    }
+}
+
+/// 按名称索引的性格档案集合，以档案列表的形式序列化。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(from = "Vec<AiProfile>", into = "Vec<AiProfile>")]
+pub struct AiProfiles {
+    profiles: BTreeMap<String, AiProfile>,
+}
+
+impl From<Vec<AiProfile>> for AiProfiles {
+    fn from(profiles: Vec<AiProfile>) -> Self {
+        let mut collection = Self::default();
+        for profile in profiles {
+            collection.register(profile);
+        }
+        collection
+    }
+}
+
+impl From<AiProfiles> for Vec<AiProfile> {
+    fn from(collection: AiProfiles) -> Self {
+        collection.profiles.into_values().collect()
+    }
+}
+
+impl AiProfiles {
+    /// 登记档案，返回被替换的同名旧档案。
+    pub fn register(&mut self, profile: AiProfile) -> Option<AiProfile> {
+        self.profiles.insert(profile.name.clone(), profile)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AiProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// 按名称升序列出已登记的档案名。
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AiAgent;
+    use crate::game::{Card, CardType, GameState, Keyword, Player};
+
+    #[test]
+    fn profiles_load_from_json_and_shape_the_agent() {
+        let json = r#"[
+            {"name": "berserker", "difficulty": "hard", "strategy": "aggressive",
+             "randomness": 0, "face_bias": 0.8},
+            {"name": "warden", "taunt_respect": 1.5, "weights": {"hero": 0.5}}
+        ]"#;
+        let profiles: AiProfiles = serde_json::from_str(json).expect("profiles parse");
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["berserker", "warden"]
+        );
+
+        let berserker = profiles.get("berserker").expect("registered").config();
+        assert_eq!(berserker.strategy, AiStrategy::Aggressive);
+        assert_eq!(berserker.randomness, 0.0);
+        assert_eq!(berserker.face_bias, 0.8);
+        let warden = profiles.get("warden").expect("registered").config();
+        assert_eq!(
+            warden.depth,
+            AiConfig::from_difficulty(AiDifficulty::Normal).depth
+        );
+        assert_eq!(
+            warden.evaluation_weights.map(|weights| weights.hero),
+            Some(0.5)
+        );
+
+        let guard = Card::new(1, "Guard", 2, 1, 5, CardType::Unit, Vec::new())
+            .with_keywords([Keyword::Taunt]);
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![guard], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0);
+        let mut indifferent = warden.clone();
+        indifferent.taunt_respect = 0.0;
+        let respectful = AiAgent::with_seed(warden, 1).evaluate_state(&state, 0);
+        let baseline = AiAgent::with_seed(indifferent, 1).evaluate_state(&state, 0);
+        assert_eq!(respectful - baseline, 6.0 * 1.5);
+    }
+}
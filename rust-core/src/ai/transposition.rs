@@ -0,0 +1,116 @@
+//! 置换表：以局面哈希缓存 Minimax 的搜索结果与最佳操作，
+//! 不同操作顺序到达的相同局面只需搜索一次。
+
+use std::collections::HashMap;
+
+use crate::game::GameAction;
+
+/// 新建 AI 时置换表的默认容量（条目数）。
+pub const DEFAULT_TABLE_CAPACITY: usize = 1 << 14;
+
+/// 缓存的分数与真实值的关系：Alpha-Beta 剪枝后得到的可能只是上界或下界。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Bound {
+    Exact,
+    /// 搜索因超过 beta 而剪枝，真实值不低于缓存的分数。
+    Lower,
+    /// 所有操作都未超过 alpha，真实值不高于缓存的分数。
+    Upper,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TableEntry {
+    /// 产生该结果时剩余的搜索深度。
+    pub depth: u8,
+    pub score: f64,
+    pub bound: Bound,
+    pub best: Option<GameAction>,
+}
+
+impl TableEntry {
+    /// 在 `depth` 层、窗口为 (`alpha`, `beta`) 的搜索中可直接使用的分数。
+    pub fn cutoff(&self, depth: u8, alpha: f64, beta: f64) -> Option<f64> {
+        if self.depth < depth {
+            return None;
+        }
+        match self.bound {
+            Bound::Exact => Some(self.score),
+            Bound::Lower if self.score >= beta => Some(self.score),
+            Bound::Upper if self.score <= alpha => Some(self.score),
+            _ => None,
+        }
+    }
+}
+
+/// 以 `GameState::hash` 为键的置换表，容量用尽后只替换已有条目。
+#[derive(Debug, Clone, Default)]
+pub(super) struct TranspositionTable {
+    entries: HashMap<u64, TableEntry>,
+    capacity: usize,
+}
+
+impl TranspositionTable {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn get(&self, key: u64) -> Option<&TableEntry> {
+        self.entries.get(&key)
+    }
+
+    /// 写入结果；同一局面只保留搜索更深的结果。
+    pub fn store(&mut self, key: u64, entry: TableEntry) {
+        let full = self.entries.len() >= self.capacity;
+        match self.entries.get_mut(&key) {
+            Some(existing) if existing.depth > entry.depth => {}
+            Some(existing) => *existing = entry,
+            None if !full => {
+                self.entries.insert(key, entry);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::ai::{AiAgent, AiConfig, AiDifficulty};
+    use crate::game::{Card, CardType, GamePhase, GameState, Player};
+
+    #[test]
+    fn transpositions_are_searched_once() {
+        let ready = |id| {
+            let mut card = Card::new(id, "Raider", 1, 1, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let board = vec![ready(1), ready(2), ready(3)];
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), board, Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let mut config = AiConfig::from_difficulty(AiDifficulty::Expert);
+        config.time_limit = Duration::ZERO;
+        let cached = AiAgent::with_seed(config.clone(), 1).decide_action(&state, 0);
+        config.table_capacity = 0;
+        let uncached = AiAgent::with_seed(config, 1).decide_action(&state, 0);
+
+        assert_eq!(cached.action, uncached.action);
+        assert_eq!(cached.evaluation, uncached.evaluation);
+        assert!(
+            cached.nodes < uncached.nodes,
+            "{} nodes with the table, {} without",
+            cached.nodes,
+            uncached.nodes
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! AI worker 协议：主线程以 JSON 消息把搜索请求发给运行在 Web Worker 中的 `AiWorker`，
+//! 每条请求带有调用方分配的 `id`，响应以相同的 `id` 返回，主线程据此对应各自的 Promise。
+//!
+//! worker 自己持有卡牌定义与性格档案，主线程只需在启动时各发送一次，
+//! 之后的请求可以携带 `lightweight_state_json` 导出的精简状态。
+
+use serde::{Deserialize, Serialize};
+
+use super::hints::MoveSuggestion;
+use super::lethal::find_lethal;
+use super::minimax::{
+    AiAgent, AiAlgorithm, AiConfig, AiDecision, AiDifficulty, AiStrategy, StrategyWeights,
+};
+use super::planner::TurnPlan;
+use super::profile::{AiProfile, AiProfiles};
+use crate::game::{CardDefinition, CardRegistry, GameAction, GameState, PlayerId, RuleEngine};
+
+/// 协议版本，双方不一致时主线程应重新加载 worker。
+pub const AI_WORKER_PROTOCOL: u32 = 1;
+
+/// 单次请求的 AI 设置；指定 `profile` 时以档案为准，忽略难度、策略、算法与权重。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AiSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<AiDifficulty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<AiStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<AiAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<StrategyWeights>,
+    /// 公平模式的确定化次数，0 表示直接读取完整局面。
+    pub determinizations: u8,
+}
+
+/// 主线程发给 worker 的请求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiWorkerRequest {
+    pub id: u32,
+    #[serde(flatten)]
+    pub task: AiTask,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AiTask {
+    /// 查询协议版本。
+    Handshake,
+    /// 替换 worker 的卡牌定义，用于还原精简状态与公平模式的抽样。
+    LoadCards { cards: Vec<CardDefinition> },
+    /// 登记性格档案，同名档案被替换。
+    LoadProfiles { profiles: Vec<AiProfile> },
+    /// 为 `player_id` 选出下一步操作。
+    Decide {
+        state: GameState,
+        player_id: PlayerId,
+        #[serde(default)]
+        settings: AiSettings,
+    },
+    /// 规划 `player_id` 的整个回合。
+    PlanTurn {
+        state: GameState,
+        player_id: PlayerId,
+        #[serde(default)]
+        settings: AiSettings,
+    },
+    /// 为 `player_id` 列出至多 `count` 个推荐操作。
+    SuggestMoves {
+        state: GameState,
+        player_id: PlayerId,
+        count: usize,
+    },
+    /// 检查 `player_id` 本回合能否斩杀。
+    LethalHint {
+        state: GameState,
+        player_id: PlayerId,
+    },
+}
+
+/// worker 回复主线程的响应，`id` 与对应请求相同。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiWorkerResponse {
+    pub id: u32,
+    #[serde(flatten)]
+    pub result: AiTaskResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AiTaskResult {
+    Handshake {
+        protocol: u32,
+    },
+    CardsLoaded {
+        count: usize,
+    },
+    ProfilesLoaded {
+        count: usize,
+    },
+    Decision {
+        decision: Box<AiDecision>,
+    },
+    Plan {
+        plan: TurnPlan,
+    },
+    Suggestions {
+        suggestions: Vec<MoveSuggestion>,
+    },
+    Lethal {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actions: Option<Vec<GameAction>>,
+    },
+    /// 请求无法完成，例如引用了未登记的档案。
+    Error {
+        message: String,
+    },
+}
+
+/// 运行在 worker 中的 AI 服务，逐条处理请求。
+#[derive(Debug, Clone, Default)]
+pub struct AiWorker {
+    registry: CardRegistry,
+    profiles: AiProfiles,
+}
+
+impl AiWorker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle(&mut self, request: AiWorkerRequest) -> AiWorkerResponse {
+        let result = self
+            .run(request.task)
+            .unwrap_or_else(|message| AiTaskResult::Error { message });
+        AiWorkerResponse {
+            id: request.id,
+            result,
+        }
+    }
+
+    fn run(&mut self, task: AiTask) -> Result<AiTaskResult, String> {
+        let result = match task {
+            AiTask::Handshake => AiTaskResult::Handshake {
+                protocol: AI_WORKER_PROTOCOL,
+            },
+            AiTask::LoadCards { cards } => {
+                self.registry = CardRegistry::from(cards);
+                AiTaskResult::CardsLoaded {
+                    count: self.registry.len(),
+                }
+            }
+            AiTask::LoadProfiles { profiles } => {
+                for profile in profiles {
+                    self.profiles.register(profile);
+                }
+                AiTaskResult::ProfilesLoaded {
+                    count: self.profiles.len(),
+                }
+            }
+            AiTask::Decide {
+                mut state,
+                player_id,
+                settings,
+            } => {
+                self.registry.hydrate(&mut state);
+                let decision = self.agent(&settings)?.decide_action(&state, player_id);
+                AiTaskResult::Decision {
+                    decision: Box::new(decision),
+                }
+            }
+            AiTask::PlanTurn {
+                mut state,
+                player_id,
+                settings,
+            } => {
+                self.registry.hydrate(&mut state);
+                let plan = self.agent(&settings)?.plan_turn(&state, player_id);
+                AiTaskResult::Plan { plan }
+            }
+            AiTask::SuggestMoves {
+                mut state,
+                player_id,
+                count,
+            } => {
+                self.registry.hydrate(&mut state);
+                // 提示只在玩家可见的局面上搜索
+                let view = state.redacted_for(player_id);
+                let suggestions = self
+                    .agent(&AiSettings::default())?
+                    .suggest_moves(&view, player_id, count);
+                AiTaskResult::Suggestions { suggestions }
+            }
+            AiTask::LethalHint {
+                mut state,
+                player_id,
+            } => {
+                self.registry.hydrate(&mut state);
+                AiTaskResult::Lethal {
+                    actions: find_lethal(
+                        &RuleEngine::new(),
+                        &state.redacted_for(player_id),
+                        player_id,
+                    ),
+                }
+            }
+        };
+        Ok(result)
+    }
+
+    fn agent(&self, settings: &AiSettings) -> Result<AiAgent, String> {
+        let config = match &settings.profile {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .map(AiProfile::config)
+                .ok_or_else(|| format!("unknown AI profile: {name}"))?,
+            None => {
+                let mut config =
+                    AiConfig::from_difficulty(settings.difficulty.unwrap_or(AiDifficulty::Normal));
+                if let Some(strategy) = settings.strategy {
+                    config = config.with_strategy(strategy);
+                }
+                if let Some(algorithm) = settings.algorithm {
+                    config = config.with_algorithm(algorithm);
+                }
+                if let Some(weights) = settings.weights {
+                    config = config.with_evaluation_weights(weights);
+                }
+                config
+            }
+        };
+        Ok(
+            AiAgent::new(config.with_determinizations(settings.determinizations))
+                .with_card_pool(&self.registry),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_round_trip_through_json() {
+        let mut worker = AiWorker::new();
+        let handshake =
+            worker.handle(serde_json::from_str(r#"{"id": 1, "type": "handshake"}"#).unwrap());
+        assert_eq!(
+            serde_json::to_value(&handshake).unwrap(),
+            serde_json::json!({"id": 1, "type": "handshake", "protocol": AI_WORKER_PROTOCOL})
+        );
+
+        let profiles = r#"{"id": 2, "type": "load_profiles",
+            "profiles": [{"name": "rookie", "difficulty": "easy"}]}"#;
+        let loaded = worker.handle(serde_json::from_str(profiles).unwrap());
+        assert!(matches!(
+            loaded.result,
+            AiTaskResult::ProfilesLoaded { count: 1 }
+        ));
+
+        let state = GameState::sample();
+        let decide = |id: u32, profile: &str| AiWorkerRequest {
+            id,
+            task: AiTask::Decide {
+                state: state.clone(),
+                player_id: state.current_player,
+                settings: AiSettings {
+                    profile: Some(profile.to_string()),
+                    ..AiSettings::default()
+                },
+            },
+        };
+        let json = serde_json::to_string(&decide(3, "rookie")).unwrap();
+        let response = worker.handle(serde_json::from_str(&json).unwrap());
+        assert_eq!(response.id, 3);
+        let AiTaskResult::Decision { decision } = response.result else {
+            panic!("expected a decision, got {:?}", response.result);
+        };
+        assert!(decision.action.is_some());
+
+        let response = worker.handle(decide(4, "veteran"));
+        assert!(matches!(
+            response.result,
+            AiTaskResult::Error { message } if message.contains("veteran")
+        ));
+    }
+
+    #[test]
+    fn hints_ignore_the_opponents_hidden_cards() {
+        let mut worker = AiWorker::new();
+        let state = GameState::sample();
+        let player_id = state.current_player;
+        let mut other = state.clone();
+        for opponent in other
+            .players
+            .iter_mut()
+            .filter(|player| player.id != player_id)
+        {
+            for card in opponent.hand.iter_mut().chain(opponent.deck.iter_mut()) {
+                card.name = "Pyroblast".to_string();
+                card.cost = 0;
+                card.attack = 10;
+            }
+        }
+        // 请求全部提示并排序，避免评估相同的操作之间的先后影响比较
+        let mut suggest = |state: &GameState| {
+            let response = worker.handle(AiWorkerRequest {
+                id: 1,
+                task: AiTask::SuggestMoves {
+                    state: state.clone(),
+                    player_id,
+                    count: usize::MAX,
+                },
+            });
+            let AiTaskResult::Suggestions { suggestions } = response.result else {
+                panic!("expected suggestions");
+            };
+            let mut suggestions: Vec<String> = suggestions
+                .iter()
+                .map(|suggestion| serde_json::to_string(suggestion).unwrap())
+                .collect();
+            suggestions.sort();
+            suggestions
+        };
+        assert_eq!(suggest(&state), suggest(&other));
+    }
+}
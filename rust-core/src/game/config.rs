@@ -0,0 +1,268 @@
+//! 对局规则配置，让不同游戏模式共用同一套引擎。
+
+use serde::{Deserialize, Serialize};
+
+use super::state::{
+    is_zero_u8, AlternativeVictory, Card, DEFAULT_MAX_BOARD_SIZE, DEFAULT_MAX_HAND_SIZE, MAX_MANA,
+};
+
+pub const DEFAULT_STARTING_HEALTH: i16 = 30;
+
+/// 牌库抽空后再抽牌的处理方式。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeckOutRule {
+    /// 从空牌库抽牌的玩家直接判负。
+    #[default]
+    Lose,
+    /// 每次从空牌库抽牌受到递增的疲劳伤害。
+    Fatigue,
+}
+
+/// 达到回合上限时的判定方式。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Tiebreaker {
+    /// 英雄剩余生命最高者获胜，相同则平局。
+    #[default]
+    HighestHealth,
+    /// 直接判为平局。
+    Draw,
+}
+
+/// 对局规则参数，随 `GameState` 一起序列化（字段平铺在状态顶层）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RuleConfig {
+    /// 开局英雄生命值，由 `GameState::with_config` 应用。
+    pub starting_health: i16,
+    /// 法力水晶上限，不能超过 `MAX_MANA`。
+    pub max_mana: u8,
+    pub max_hand_size: u8,
+    pub max_board_size: u8,
+    /// 攻击单位时是否受到被攻击单位的反击伤害。
+    pub retaliation: bool,
+    pub deck_out: DeckOutRule,
+    /// 调度阶段最多可替换的手牌数量，`None` 表示不限。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mulligan_limit: Option<u8>,
+    /// 回合上限，第 `turn_limit` 个回合结束后按 `tiebreaker` 结束对局。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_limit: Option<u32>,
+    pub tiebreaker: Tiebreaker,
+    /// 调度结束后发给后手玩家的补偿卡牌（如幸运币），`None` 表示不发。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_player_bonus: Option<Card>,
+    /// 起手时后手玩家额外多抽的牌数。
+    #[serde(skip_serializing_if = "is_zero_u8")]
+    pub second_player_extra_cards: u8,
+    /// 每回合最多可打出的卡牌数，`None` 表示不限。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cards_per_turn: Option<u8>,
+    /// 每回合最多可施放的法术数，`None` 表示不限。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_spells_per_turn: Option<u8>,
+    /// 两段式战斗：宣告攻击后防守方获得响应窗口，再由 `resolve_combat` 结算伤害。
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub two_step_combat: bool,
+    /// 事件日志最多保留的条数，超出时丢弃最早的记录；`None` 表示不限。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_event_log: Option<usize>,
+    /// 对所有玩家生效的额外胜利条件，每次检查胜负时在生命判定之后评估。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternative_victories: Vec<AlternativeVictory>,
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        Self {
+            starting_health: DEFAULT_STARTING_HEALTH,
+            max_mana: MAX_MANA,
+            max_hand_size: DEFAULT_MAX_HAND_SIZE,
+            max_board_size: DEFAULT_MAX_BOARD_SIZE,
+            retaliation: true,
+            deck_out: DeckOutRule::Lose,
+            mulligan_limit: None,
+            turn_limit: None,
+            tiebreaker: Tiebreaker::HighestHealth,
+            second_player_bonus: None,
+            second_player_extra_cards: 0,
+            max_cards_per_turn: None,
+            max_spells_per_turn: None,
+            two_step_combat: false,
+            max_event_log: None,
+            alternative_victories: Vec::new(),
+        }
+    }
+}
+
+impl RuleConfig {
+    /// 检查外部传入的配置是否合法：法力上限不能超过 `MAX_MANA`，
+    /// 开局生命、手牌与战场上限以及回合上限都必须为正，否则对局一开始就会结束或无法出牌。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_mana > MAX_MANA {
+            return Err(format!(
+                "max_mana {} exceeds the cap of {MAX_MANA}",
+                self.max_mana
+            ));
+        }
+        if self.starting_health <= 0 {
+            return Err(format!(
+                "starting_health must be positive, got {}",
+                self.starting_health
+            ));
+        }
+        if self.max_hand_size == 0 {
+            return Err("max_hand_size must be positive".to_string());
+        }
+        if self.max_board_size == 0 {
+            return Err("max_board_size must be positive".to_string());
+        }
+        if self.turn_limit == Some(0) {
+            return Err("turn_limit must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn with_starting_health(mut self, health: i16) -> Self {
+        self.starting_health = health;
+        self
+    }
+
+    pub fn with_max_mana(mut self, max_mana: u8) -> Self {
+        self.max_mana = max_mana.min(MAX_MANA);
+        self
+    }
+
+    pub fn with_max_hand_size(mut self, size: u8) -> Self {
+        self.max_hand_size = size;
+        self
+    }
+
+    pub fn with_max_board_size(mut self, size: u8) -> Self {
+        self.max_board_size = size;
+        self
+    }
+
+    pub fn with_retaliation(mut self, enabled: bool) -> Self {
+        self.retaliation = enabled;
+        self
+    }
+
+    pub fn with_deck_out(mut self, rule: DeckOutRule) -> Self {
+        self.deck_out = rule;
+        self
+    }
+
+    pub fn with_mulligan_limit(mut self, limit: Option<u8>) -> Self {
+        self.mulligan_limit = limit;
+        self
+    }
+
+    pub fn with_turn_limit(mut self, limit: Option<u32>, tiebreaker: Tiebreaker) -> Self {
+        self.turn_limit = limit;
+        self.tiebreaker = tiebreaker;
+        self
+    }
+
+    pub fn with_second_player_bonus(mut self, card: Option<Card>) -> Self {
+        self.second_player_bonus = card;
+        self
+    }
+
+    /// 以幸运币作为后手补偿卡牌。
+    pub fn with_coin(self) -> Self {
+        self.with_second_player_bonus(Some(Card::coin()))
+    }
+
+    pub fn with_second_player_extra_cards(mut self, cards: u8) -> Self {
+        self.second_player_extra_cards = cards;
+        self
+    }
+
+    pub fn with_max_cards_per_turn(mut self, limit: Option<u8>) -> Self {
+        self.max_cards_per_turn = limit;
+        self
+    }
+
+    pub fn with_max_spells_per_turn(mut self, limit: Option<u8>) -> Self {
+        self.max_spells_per_turn = limit;
+        self
+    }
+
+    pub fn with_two_step_combat(mut self, enabled: bool) -> Self {
+        self.two_step_combat = enabled;
+        self
+    }
+
+    pub fn with_max_event_log(mut self, limit: Option<usize>) -> Self {
+        self.max_event_log = limit;
+        self
+    }
+
+    pub fn with_alternative_victory(mut self, victory: AlternativeVictory) -> Self {
+        self.alternative_victories.push(victory);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEvent, GameState};
+
+    #[test]
+    fn config_fields_are_flattened_into_the_state_and_round_trip() {
+        let config = RuleConfig::default()
+            .with_starting_health(20)
+            .with_max_hand_size(8)
+            .with_turn_limit(Some(40), Tiebreaker::Draw);
+        let state = GameState::sample().with_config(config.clone());
+        assert!(state.players.iter().all(|player| player.health == 20));
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["max_hand_size"], 8);
+        assert_eq!(json["turn_limit"], 40);
+        assert!(json.get("config").is_none());
+
+        let restored: GameState = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.config, config);
+    }
+
+    #[test]
+    fn fatigue_rule_deals_growing_damage_instead_of_losing() {
+        let mut state = GameState::sample()
+            .with_config(RuleConfig::default().with_deck_out(DeckOutRule::Fatigue));
+        state.players[0].deck.clear();
+        for amount in 1..=2 {
+            assert_eq!(
+                state.draw_card(0),
+                Some(GameEvent::FatigueDamage {
+                    player_id: 0,
+                    amount
+                })
+            );
+        }
+        assert_eq!(state.players[0].health, 27);
+        assert!(!state.is_finished());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_limits() {
+        assert!(RuleConfig::default().validate().is_ok());
+        assert_eq!(RuleConfig::default().with_max_mana(12).max_mana, MAX_MANA);
+
+        for (json, field) in [
+            (r#"{"max_mana": 12}"#, "max_mana"),
+            (r#"{"starting_health": 0}"#, "starting_health"),
+            (r#"{"starting_health": -5}"#, "starting_health"),
+            (r#"{"max_hand_size": 0}"#, "max_hand_size"),
+            (r#"{"max_board_size": 0}"#, "max_board_size"),
+            (r#"{"turn_limit": 0}"#, "turn_limit"),
+        ] {
+            let config: RuleConfig = serde_json::from_str(json).unwrap();
+            let error = config.validate().unwrap_err();
+            assert!(error.contains(field), "{json}: {error}");
+        }
+        let config: RuleConfig =
+            serde_json::from_str(r#"{"starting_health": 1, "turn_limit": 1}"#).unwrap();
+        assert!(config.validate().is_ok());
+    }
+}
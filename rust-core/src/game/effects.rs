@@ -1,26 +1,54 @@
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
-use super::state::{Card, CardEffect, CardId, EffectId, GameEvent, GameState, PlayerId};
+use super::registry::{CardDefinition, CardFilter};
+use super::state::{
+    AlternativeVictory, Card, CardEffect, CardId, CardType, ChoiceKind, ChoiceTarget, EffectId,
+    Enchantment, EnchantmentDuration, Expiry, GameEvent, GamePhase, GameState, Keyword, Player,
+    PlayerEnchantment, PlayerId, PlayerModifier, Tribe,
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum EffectTrigger {
+    #[default]
     OnPlay,
     OnDeath,
     OnTurnStart,
     OnTurnEnd,
     OnAttack,
+    /// 该单位受到伤害时触发。
+    OnDamage,
+    /// 己方英雄受到伤害时触发（场上单位监听）。
+    OnHeroDamage,
+    /// 己方英雄或单位受到治疗时触发。
+    OnHeal,
+    /// 己方抽牌时触发。
+    OnDraw,
+    /// 其他友方单位进入战场时触发。
+    OnSummon,
+    /// 己方施放法术时触发（场上单位监听）。
+    OnSpellCast,
+    /// 任意玩家因手牌已满爆牌时触发（双方场上单位监听）。
+    OnCardBurned,
+    /// 连击：本回合已打出过其他卡牌时，随打出一同触发。
+    Combo,
+    /// 双方调度完毕、对局开始时触发一次（牌库、手牌与战场中的卡牌均可响应）。
+    OnGameStart,
+    /// 决出胜负时触发一次（牌库、手牌与战场中的卡牌均可响应）。
+    OnVictory,
+    /// 当前玩家进入某个阶段时触发（场上单位监听）；回合开始进入主阶段时由 `OnTurnStart` 代替。
+    OnPhaseEnter {
+        phase: GamePhase,
+    },
+    /// 当前玩家离开某个阶段时触发（场上单位监听）。
+    OnPhaseExit {
+        phase: GamePhase,
+    },
     Passive,
 }
 
-impl Default for EffectTrigger {
-    fn default() -> Self {
-        EffectTrigger::OnPlay
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum EffectTarget {
@@ -28,6 +56,162 @@ pub enum EffectTarget {
     SourcePlayer,
     TargetPlayer,
     OpponentOfSource,
+    /// 效果来源卡牌本身（如“获得 +1/+1”）。
+    SourceCard,
+    /// 结算时所有敌方玩家场上生命值最低的单位（同值取座位靠前、位置靠左者）。
+    WeakestEnemyUnit,
+    /// 结算时所有敌方玩家场上攻击力最高的单位（同值取座位靠前、位置靠左者）。
+    StrongestEnemyUnit,
+    /// 随机一个满足筛选条件的敌方单位。
+    RandomEnemyUnit {
+        #[serde(default)]
+        filter: TargetFilter,
+    },
+    /// 所有满足筛选条件的敌方单位。
+    AllEnemyUnits {
+        #[serde(default)]
+        filter: TargetFilter,
+    },
+    /// 所有满足筛选条件的友方单位（如“你的野兽获得 +1 攻击力”）。
+    AllFriendlyUnits {
+        #[serde(default)]
+        filter: TargetFilter,
+    },
+    /// 与来源卡牌相邻的单位。
+    AdjacentToSource,
+    /// 与上下文目标单位相邻的单位（如“对一个单位及其相邻单位造成伤害”）。
+    AdjacentToTarget,
+    /// 来源玩家及其全部存活队友的英雄（如“所有友方英雄获得 5 点护甲”）。
+    AllAlliedHeroes,
+    /// 全部存活的敌方英雄。
+    AllEnemyHeroes,
+}
+
+/// 目标筛选条件，所有已设置的条件都需满足；英雄只能通过空筛选。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TargetFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_type: Option<CardType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tribe: Option<Tribe>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub damaged_only: bool,
+}
+
+/// 目标未通过的具体筛选条件，供界面提示。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum FilterFailure {
+    NotAUnit,
+    CardType {
+        expected: CardType,
+        actual: CardType,
+    },
+    CostTooHigh {
+        max_cost: u8,
+        actual: u8,
+    },
+    MissingTribe {
+        tribe: Tribe,
+    },
+    NotDamaged,
+}
+
+impl TargetFilter {
+    pub fn is_empty(&self) -> bool {
+        self == &TargetFilter::default()
+    }
+
+    /// 检查目标，`None` 表示目标为英雄。
+    pub fn check(&self, card: Option<&Card>) -> Result<(), FilterFailure> {
+        let Some(card) = card else {
+            return if self.is_empty() {
+                Ok(())
+            } else {
+                Err(FilterFailure::NotAUnit)
+            };
+        };
+        if let Some(expected) = self.card_type {
+            if card.card_type != expected {
+                return Err(FilterFailure::CardType {
+                    expected,
+                    actual: card.card_type,
+                });
+            }
+        }
+        if let Some(max_cost) = self.max_cost {
+            if card.cost > max_cost {
+                return Err(FilterFailure::CostTooHigh {
+                    max_cost,
+                    actual: card.cost,
+                });
+            }
+        }
+        if let Some(tribe) = self.tribe {
+            if !card.has_tribe(tribe) {
+                return Err(FilterFailure::MissingTribe { tribe });
+            }
+        }
+        if self.damaged_only && !card.is_damaged() {
+            return Err(FilterFailure::NotDamaged);
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, card: &Card) -> bool {
+        self.check(Some(card)).is_ok()
+    }
+}
+
+/// 效果持续时间。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EffectDuration {
+    #[default]
+    Permanent,
+    /// 持续到本回合结束。
+    ThisTurn,
+    /// 持续到效果来源玩家的下个回合开始。
+    UntilNextTurn,
+}
+
+impl EffectDuration {
+    pub fn expiry(self, ctx: &EffectContext) -> Option<Expiry> {
+        match self {
+            EffectDuration::Permanent => None,
+            EffectDuration::ThisTurn => Some(Expiry::EndOfTurn {
+                player_id: ctx.current_player,
+            }),
+            EffectDuration::UntilNextTurn => Some(Expiry::StartOfTurn {
+                player_id: ctx.source_player,
+            }),
+        }
+    }
+}
+
+/// 光环作用范围（相对光环来源卡牌）。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AuraScope {
+    OtherFriendlyUnits,
+    FriendlyUnits,
+    EnemyUnits,
+}
+
+impl AuraScope {
+    fn covers(
+        self,
+        source_owner: PlayerId,
+        source_id: CardId,
+        owner: PlayerId,
+        card_id: CardId,
+    ) -> bool {
+        match self {
+            AuraScope::OtherFriendlyUnits => owner == source_owner && card_id != source_id,
+            AuraScope::FriendlyUnits => owner == source_owner,
+            AuraScope::EnemyUnits => owner != source_owner,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -51,6 +235,49 @@ pub enum EffectCondition {
     All {
         conditions: Vec<EffectCondition>,
     },
+    /// 来源玩家本回合已打出过来源卡牌以外的卡牌。
+    ComboActive,
+    /// 来源玩家场上有该种族的单位（来源卡牌本身除外）。
+    ControlsTribe {
+        tribe: Tribe,
+    },
+    /// 来源卡牌（场上）的具名计数器至少为 `min`。
+    CounterAtLeast {
+        counter: String,
+        min: u16,
+    },
+    TurnAtLeast {
+        turn: u32,
+    },
+    HandSizeAtLeast {
+        target: EffectTarget,
+        min: usize,
+    },
+    /// 本局已死亡的单位数至少为 `min`；指定 `target` 时只统计该玩家的单位。
+    UnitsDiedThisGameAtLeast {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<EffectTarget>,
+        min: u32,
+    },
+    /// 来源卡牌在场上且处于受伤状态。
+    SourceCardDamaged,
+    /// 目标玩家牌库剩余卡牌不超过 `max` 张。
+    DeckSizeAtMost {
+        target: EffectTarget,
+        max: usize,
+    },
+    /// 目标玩家墓地中至少有 `min` 张卡牌；指定 `card_type` 时只统计该类型。
+    GraveyardSizeAtLeast {
+        target: EffectTarget,
+        min: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        card_type: Option<CardType>,
+    },
+    /// 目标玩家的英雄属于该职业。
+    HeroClass {
+        target: EffectTarget,
+        class: String,
+    },
 }
 
 impl EffectCondition {
@@ -77,6 +304,155 @@ impl EffectCondition {
             EffectCondition::All { conditions } => conditions
                 .iter()
                 .all(|condition| condition.is_satisfied(ctx, state)),
+            EffectCondition::ComboActive => state
+                .get_player(ctx.source_player)
+                .map(|player| {
+                    player
+                        .cards_played_this_turn
+                        .iter()
+                        .any(|card_id| Some(*card_id) != ctx.source_card)
+                })
+                .unwrap_or(false),
+            EffectCondition::ControlsTribe { tribe } => state
+                .get_player(ctx.source_player)
+                .map(|player| {
+                    player
+                        .board
+                        .iter()
+                        .any(|card| Some(card.id) != ctx.source_card && card.has_tribe(*tribe))
+                })
+                .unwrap_or(false),
+            EffectCondition::CounterAtLeast { counter, min } => ctx
+                .source_card
+                .and_then(|card_id| {
+                    state
+                        .get_player(ctx.source_player)?
+                        .find_card_on_board(card_id)
+                })
+                .map(|card| card.counter(counter) >= *min)
+                .unwrap_or(false),
+            EffectCondition::TurnAtLeast { turn } => state.turn >= *turn,
+            EffectCondition::HandSizeAtLeast { target, min } => target
+                .resolve_player(ctx, state)
+                .and_then(|id| state.get_player(id))
+                .map(|player| player.hand.len() >= *min)
+                .unwrap_or(false),
+            EffectCondition::UnitsDiedThisGameAtLeast { target, min } => {
+                let died: u32 = match target {
+                    Some(target) => target
+                        .resolve_player(ctx, state)
+                        .and_then(|id| state.get_player(id))
+                        .map(|player| player.units_died)
+                        .unwrap_or(0),
+                    None => state.players.iter().map(|player| player.units_died).sum(),
+                };
+                died >= *min
+            }
+            EffectCondition::SourceCardDamaged => ctx
+                .source_card
+                .and_then(|card_id| {
+                    state
+                        .get_player(ctx.source_player)?
+                        .find_card_on_board(card_id)
+                })
+                .map(Card::is_damaged)
+                .unwrap_or(false),
+            EffectCondition::DeckSizeAtMost { target, max } => target
+                .resolve_player(ctx, state)
+                .and_then(|id| state.get_player(id))
+                .map(|player| player.deck.len() <= *max)
+                .unwrap_or(false),
+            EffectCondition::GraveyardSizeAtLeast {
+                target,
+                min,
+                card_type,
+            } => target
+                .resolve_player(ctx, state)
+                .and_then(|id| state.get_player(id))
+                .map(|player| {
+                    player
+                        .graveyard
+                        .iter()
+                        .filter(|card| card_type.is_none_or(|kind| card.card_type == kind))
+                        .count()
+                        >= *min
+                })
+                .unwrap_or(false),
+            EffectCondition::HeroClass { target, class } => target
+                .resolve_player(ctx, state)
+                .and_then(|id| state.get_player(id))
+                .is_some_and(|player| player.class() == Some(class.as_str())),
+        }
+    }
+}
+
+/// 发现效果给出的候选数量。
+const DISCOVER_OPTIONS: usize = 3;
+
+/// 抉择卡牌的一个可选模式。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChoiceOption {
+    pub description: String,
+    pub effect: EffectKind,
+}
+
+impl ChoiceOption {
+    pub fn new(description: impl Into<String>, effect: EffectKind) -> Self {
+        Self {
+            description: description.into(),
+            effect,
+        }
+    }
+}
+
+/// 结算时根据场面计算的附加数值，叠加在效果的基础数值上。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum AmountScaling {
+    /// 来源玩家每有一个场上单位，增加 `multiplier`。
+    PerFriendlyUnit {
+        #[serde(default = "default_multiplier")]
+        multiplier: i16,
+    },
+    /// 来源玩家每有一张手牌，增加 `multiplier`。
+    PerCardInHand {
+        #[serde(default = "default_multiplier")]
+        multiplier: i16,
+    },
+    /// 等于来源玩家英雄已损失的生命值。
+    EqualsMissingHealth,
+}
+
+fn default_multiplier() -> i16 {
+    1
+}
+
+impl AmountScaling {
+    pub fn evaluate(&self, ctx: &EffectContext, state: &GameState) -> i16 {
+        let Some(player) = state.get_player(ctx.source_player) else {
+            return 0;
+        };
+        match self {
+            AmountScaling::PerFriendlyUnit { multiplier } => {
+                (player.board.len() as i16).saturating_mul(*multiplier)
+            }
+            AmountScaling::PerCardInHand { multiplier } => {
+                (player.hand.len() as i16).saturating_mul(*multiplier)
+            }
+            AmountScaling::EqualsMissingHealth => (player.max_health - player.health).max(0),
+        }
+    }
+
+    /// 计算基础数值加上附加数值后的结果。
+    pub fn apply_to(
+        base: i16,
+        scaling: &Option<AmountScaling>,
+        ctx: &EffectContext,
+        state: &GameState,
+    ) -> i16 {
+        match scaling {
+            Some(scaling) => base.saturating_add(scaling.evaluate(ctx, state)),
+            None => base,
         }
     }
 }
@@ -87,14 +463,73 @@ pub enum EffectKind {
     DirectDamage {
         amount: i16,
         target: EffectTarget,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scaling: Option<AmountScaling>,
     },
     Heal {
         amount: i16,
         target: EffectTarget,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scaling: Option<AmountScaling>,
     },
     DrawCard {
         count: u8,
         target: EffectTarget,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scaling: Option<AmountScaling>,
+    },
+    Mill {
+        count: u8,
+        target: EffectTarget,
+    },
+    GainArmor {
+        amount: u8,
+        target: EffectTarget,
+    },
+    /// 抵消目标（英雄或单位）接下来受到的 `amount` 点伤害。
+    PreventDamage {
+        amount: u8,
+        target: EffectTarget,
+    },
+    /// 修改上下文目标单位的属性。
+    ModifyStats {
+        #[serde(default)]
+        attack: i16,
+        #[serde(default)]
+        health: i16,
+        target: EffectTarget,
+        #[serde(default)]
+        duration: EffectDuration,
+    },
+    /// 冻结上下文目标单位，使其无法攻击。
+    Freeze {
+        target: EffectTarget,
+        #[serde(default)]
+        duration: EffectDuration,
+    },
+    GainMana {
+        amount: u8,
+        target: EffectTarget,
+    },
+    GainManaCrystal {
+        amount: u8,
+        target: EffectTarget,
+        #[serde(default)]
+        filled: bool,
+    },
+    /// 持续性费用修正（配合 `Passive` 触发）：场上存在时调整己方手牌费用，负数为减费。
+    CostModifier {
+        delta: i8,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        card_type: Option<CardType>,
+    },
+    /// 持续性属性光环（配合 `Passive` 触发）：来源在场时为范围内的单位提供属性加成。
+    StatAura {
+        #[serde(default)]
+        attack: i16,
+        #[serde(default)]
+        health: i16,
+        scope: AuraScope,
     },
     Composite {
         effects: Vec<EffectKind>,
@@ -103,79 +538,365 @@ pub enum EffectKind {
         condition: Box<EffectCondition>,
         effect: Box<EffectKind>,
     },
+    /// 延迟效果：登记到状态中，在来源玩家下一次 `trigger` 时机结算（来源卡牌离场也不影响）。
+    Delayed {
+        trigger: EffectTrigger,
+        effect: Box<EffectKind>,
+    },
+    /// 抉择：登记一个待选择项，玩家选定后以当前上下文结算对应模式。
+    ChooseOne {
+        options: Vec<ChoiceOption>,
+    },
+    /// 发现：从状态卡池中满足筛选条件的可收集定义里随机生成至多三张不同的卡牌供来源玩家选择，
+    /// 选中的卡牌以新 ID 置入手牌。
+    Discover {
+        #[serde(default)]
+        filter: CardFilter,
+    },
+    /// 选择目标：来源玩家从满足筛选条件、未潜行的敌方单位中选择一个，再以其为上下文目标结算 `effect`。
+    SelectTarget {
+        #[serde(default)]
+        filter: TargetFilter,
+        effect: Box<EffectKind>,
+    },
+    /// 反制：移除效果栈中由 `related_card`（缺省为 `target_card`）产生、尚未结算的效果。
+    ///
+    /// 需作为顶层效果并以高于被反制效果的优先级入栈才能生效。
+    Counter,
+    /// 过载：来源玩家下个回合有 `amount` 个法力水晶被锁定。
+    Overload {
+        amount: u8,
+    },
+    /// 将目标单位的属性设为固定值（如“变为 1/1”），覆盖此前的附魔与伤害。
+    SetStats {
+        attack: i16,
+        health: i16,
+        target: EffectTarget,
+    },
+    /// 从来源玩家墓地随机复活至多 `count` 个单位。
+    Resurrect {
+        count: u8,
+    },
+    GrantKeyword {
+        keyword: Keyword,
+        target: EffectTarget,
+        #[serde(default)]
+        duration: EffectDuration,
+    },
+    /// 沉默目标单位，移除其效果、关键字与附魔。
+    Silence {
+        target: EffectTarget,
+    },
+    /// 夺取目标敌方单位的控制权，移到来源玩家场上；限时控制到期后归还原拥有者。
+    TakeControl {
+        target: EffectTarget,
+        #[serde(default)]
+        duration: EffectDuration,
+    },
+    /// 为目标玩家附加玩家级附魔（如费用或受到伤害的修正）。
+    EnchantPlayer {
+        modifier: PlayerModifier,
+        target: EffectTarget,
+        #[serde(default)]
+        duration: EffectDuration,
+    },
+    /// 随机揭示目标玩家至多 `count` 张尚未公开的手牌，此后这些牌对来源玩家可见。
+    RevealHand {
+        count: u8,
+        target: EffectTarget,
+    },
+    /// 为目标单位添加 `amount` 个具名计数器。
+    AddCounters {
+        counter: String,
+        amount: u8,
+        target: EffectTarget,
+    },
+    /// 移除目标单位至多 `amount` 个具名计数器，缺省时全部移除。
+    RemoveCounters {
+        counter: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        amount: Option<u8>,
+        target: EffectTarget,
+    },
+    /// 为来源玩家登记一个额外胜利条件，同一 ID 只登记一次。
+    AddAlternativeVictory {
+        victory: AlternativeVictory,
+    },
 }
 
 impl EffectKind {
     pub fn can_trigger(&self, ctx: &EffectContext, state: &GameState) -> bool {
         match self {
-            EffectKind::DirectDamage { .. } | EffectKind::Heal { .. } => true,
-            EffectKind::DrawCard { target, .. } => target
+            EffectKind::DirectDamage { .. }
+            | EffectKind::Heal { .. }
+            | EffectKind::PreventDamage { .. } => true,
+            EffectKind::ModifyStats { target, .. }
+            | EffectKind::Freeze { target, .. }
+            | EffectKind::SetStats { target, .. }
+            | EffectKind::GrantKeyword { target, .. }
+            | EffectKind::Silence { target } => !target.candidate_cards(ctx, state).is_empty(),
+            EffectKind::TakeControl { target, .. } => target
+                .candidate_cards(ctx, state)
+                .into_iter()
+                .any(|(controller, _)| !state.are_allies(controller, ctx.source_player)),
+            EffectKind::GainArmor { target, .. }
+            | EffectKind::EnchantPlayer { target, .. }
+            | EffectKind::GainMana { target, .. }
+            | EffectKind::GainManaCrystal { target, .. } => target
+                .resolve_player(ctx, state)
+                .and_then(|id| state.get_player(id))
+                .is_some(),
+            EffectKind::DrawCard { target, .. } | EffectKind::Mill { target, .. } => target
                 .resolve_player(ctx, state)
                 .and_then(|id| state.get_player(id))
                 .map(|player| !player.deck.is_empty())
                 .unwrap_or(false),
+            EffectKind::RevealHand { count, target } => {
+                *count > 0
+                    && target
+                        .resolve_player(ctx, state)
+                        .and_then(|id| state.get_player(id))
+                        .map(|player| {
+                            player
+                                .hand
+                                .iter()
+                                .any(|card| !player.is_revealed_to(card.id, ctx.source_player))
+                        })
+                        .unwrap_or(false)
+            }
+            EffectKind::AddCounters { amount, target, .. } => {
+                *amount > 0 && !target.candidate_cards(ctx, state).is_empty()
+            }
+            EffectKind::RemoveCounters {
+                counter, target, ..
+            } => target
+                .candidate_cards(ctx, state)
+                .into_iter()
+                .any(|(owner, card_id)| {
+                    state
+                        .get_player(owner)
+                        .and_then(|player| player.find_card_on_board(card_id))
+                        .map(|card| card.counter(counter) > 0)
+                        .unwrap_or(false)
+                }),
+            EffectKind::CostModifier { .. } | EffectKind::StatAura { .. } => false,
             EffectKind::Composite { effects } => {
                 effects.iter().any(|effect| effect.can_trigger(ctx, state))
             }
             EffectKind::Conditional { condition, effect } => {
                 condition.is_satisfied(ctx, state) && effect.can_trigger(ctx, state)
             }
+            EffectKind::Delayed { .. } => true,
+            EffectKind::ChooseOne { options } => !options.is_empty(),
+            EffectKind::Discover { filter } => state
+                .card_pool
+                .collectible()
+                .any(|definition| filter.matches(definition)),
+            EffectKind::SelectTarget { filter, .. } => {
+                !EffectTarget::selectable_enemy_units(ctx, state, filter).is_empty()
+            }
+            EffectKind::Counter => ctx.related_card.or(ctx.target_card).is_some(),
+            EffectKind::Overload { amount } => {
+                *amount > 0 && state.get_player(ctx.source_player).is_some()
+            }
+            EffectKind::AddAlternativeVictory { victory } => state
+                .get_player(ctx.source_player)
+                .map(|player| !player.has_alternative_victory(&victory.id))
+                .unwrap_or(false),
+            EffectKind::Resurrect { count } => {
+                *count > 0
+                    && state
+                        .get_player(ctx.source_player)
+                        .map(|player| {
+                            (player.board.len() as u8) < state.config.max_board_size
+                                && player
+                                    .graveyard
+                                    .iter()
+                                    .any(|card| card.card_type == CardType::Unit)
+                        })
+                        .unwrap_or(false)
+            }
         }
     }
 
     pub fn apply(&self, ctx: &EffectContext, state: &mut GameState) -> EffectResolution {
         match self {
-            EffectKind::DirectDamage { amount, target } => {
+            EffectKind::DirectDamage {
+                amount,
+                target,
+                scaling,
+            } => {
+                let amount = AmountScaling::apply_to(*amount, scaling, ctx, state);
                 let mut events = Vec::new();
-                if let Some(card_id) = ctx.target_card {
-                    if let Some(target_owner) = ctx.target_player {
-                        let res = state.damage_card(
+                let targets = target.resolve_cards(ctx, state);
+                for &(target_owner, card_id) in &targets {
+                    let res = state.damage_card(
+                        ctx.source_player,
+                        ctx.source_card,
+                        target_owner,
+                        card_id,
+                        amount,
+                    );
+                    events.extend(res);
+                }
+                if targets.is_empty() && target.falls_back_to_players(ctx) {
+                    for target_player in target.resolve_players(ctx, state) {
+                        events.extend(state.damage_player(
                             ctx.source_player,
                             ctx.source_card,
-                            target_owner,
-                            card_id,
-                            *amount,
-                        );
-                        events.extend(res);
+                            target_player,
+                            amount,
+                        ));
                     }
-                } else if let Some(target_player) = target.resolve_player(ctx, state) {
-                    if let Some(event) = state.damage_player(
-                        ctx.source_player,
-                        ctx.source_card,
-                        target_player,
-                        *amount,
-                    ) {
+                }
+                EffectResolution { events }
+            }
+            EffectKind::Heal {
+                amount,
+                target,
+                scaling,
+            } => {
+                let amount = AmountScaling::apply_to(*amount, scaling, ctx, state);
+                let mut events = Vec::new();
+                let targets = target.resolve_cards(ctx, state);
+                for &(target_owner, card_id) in &targets {
+                    if let Some(event) = state.heal_card(target_owner, card_id, amount) {
                         events.push(event);
                     }
                 }
+                if targets.is_empty() && target.falls_back_to_players(ctx) {
+                    for target_player in target.resolve_players(ctx, state) {
+                        if let Some(event) = state.heal_player(target_player, amount) {
+                            events.push(event);
+                        }
+                    }
+                }
                 EffectResolution { events }
             }
-            EffectKind::Heal { amount, target } => {
+            EffectKind::DrawCard {
+                count,
+                target,
+                scaling,
+            } => {
+                let count = AmountScaling::apply_to(*count as i16, scaling, ctx, state)
+                    .clamp(0, u8::MAX as i16) as u8;
                 let mut events = Vec::new();
-                if let Some(card_id) = ctx.target_card {
-                    if let Some(target_owner) = ctx.target_player {
-                        if let Some(event) = state.heal_card(target_owner, card_id, *amount) {
+                for target_player in target.resolve_players(ctx, state) {
+                    for _ in 0..count {
+                        if let Some(event) = state.draw_card(target_player) {
                             events.push(event);
                         }
                     }
-                } else if let Some(target_player) = target.resolve_player(ctx, state) {
-                    if let Some(event) = state.heal_player(target_player, *amount) {
+                }
+                EffectResolution { events }
+            }
+            EffectKind::Mill { count, target } => {
+                let mut events = Vec::new();
+                for target_player in target.resolve_players(ctx, state) {
+                    events.extend(state.mill_cards(target_player, *count));
+                }
+                EffectResolution { events }
+            }
+            EffectKind::GainArmor { amount, target } => {
+                let mut events = Vec::new();
+                for target_player in target.resolve_players(ctx, state) {
+                    if let Some(event) = state.gain_armor(target_player, *amount) {
                         events.push(event);
                     }
                 }
                 EffectResolution { events }
             }
-            EffectKind::DrawCard { count, target } => {
+            EffectKind::EnchantPlayer {
+                modifier,
+                target,
+                duration,
+            } => {
+                let enchantment = PlayerEnchantment {
+                    source_player: Some(ctx.source_player),
+                    source_card: ctx.source_card,
+                    modifier: *modifier,
+                    expires: duration.expiry(ctx),
+                };
+                EffectResolution {
+                    events: target
+                        .resolve_players(ctx, state)
+                        .into_iter()
+                        .filter_map(|player_id| state.enchant_player(player_id, enchantment))
+                        .collect(),
+                }
+            }
+            EffectKind::PreventDamage { amount, target } => {
                 let mut events = Vec::new();
-                if let Some(target_player) = target.resolve_player(ctx, state) {
-                    for _ in 0..*count {
-                        if let Some(event) = state.draw_card(target_player) {
-                            events.push(event);
-                        }
+                let targets = target.resolve_cards(ctx, state);
+                for &(owner, card_id) in &targets {
+                    events.extend(state.grant_damage_prevention(owner, Some(card_id), *amount));
+                }
+                if targets.is_empty() {
+                    for player_id in target.resolve_players(ctx, state) {
+                        events.extend(state.grant_damage_prevention(player_id, None, *amount));
+                    }
+                }
+                EffectResolution { events }
+            }
+            EffectKind::ModifyStats {
+                attack,
+                health,
+                target,
+                duration,
+            } => {
+                let mut events = Vec::new();
+                for (target_owner, card_id) in target.resolve_cards(ctx, state) {
+                    let enchantment = Enchantment {
+                        source_player: Some(ctx.source_player),
+                        source_card: ctx.source_card,
+                        attack: *attack,
+                        health: *health,
+                        duration: duration
+                            .expiry(ctx)
+                            .map_or(EnchantmentDuration::Permanent, |expires| {
+                                EnchantmentDuration::Until { expires }
+                            }),
+                    };
+                    events.extend(state.modify_card_stats(target_owner, card_id, enchantment));
+                }
+                EffectResolution { events }
+            }
+            EffectKind::Freeze { target, duration } => {
+                let mut events = Vec::new();
+                for (target_owner, card_id) in target.resolve_cards(ctx, state) {
+                    if let Some(event) =
+                        state.freeze_card(target_owner, card_id, duration.expiry(ctx))
+                    {
+                        events.push(event);
+                    }
+                }
+                EffectResolution { events }
+            }
+            EffectKind::GainMana { amount, target } => {
+                let mut events = Vec::new();
+                for target_player in target.resolve_players(ctx, state) {
+                    if let Some(event) = state.gain_mana(target_player, *amount) {
+                        events.push(event);
+                    }
+                }
+                EffectResolution { events }
+            }
+            EffectKind::GainManaCrystal {
+                amount,
+                target,
+                filled,
+            } => {
+                let mut events = Vec::new();
+                for target_player in target.resolve_players(ctx, state) {
+                    if let Some(event) = state.gain_mana_crystals(target_player, *amount, *filled) {
+                        events.push(event);
                     }
                 }
                 EffectResolution { events }
             }
+            EffectKind::CostModifier { .. } | EffectKind::StatAura { .. } => {
+                EffectResolution::default()
+            }
             EffectKind::Composite { effects } => {
                 let mut resolution = EffectResolution::default();
                 for effect in effects {
@@ -191,6 +912,199 @@ impl EffectKind {
                     EffectResolution::default()
                 }
             }
+            EffectKind::Delayed { trigger, effect } => {
+                let event = state.schedule_effect(trigger.clone(), (**effect).clone(), ctx.clone());
+                EffectResolution {
+                    events: vec![event],
+                }
+            }
+            EffectKind::ChooseOne { options } => {
+                let choice_id = state.push_choice(
+                    ctx.source_player,
+                    ChoiceKind::ChooseOne {
+                        options: options.clone(),
+                        context: ctx.clone(),
+                    },
+                );
+                EffectResolution {
+                    events: vec![GameEvent::ChoicePending {
+                        player_id: ctx.source_player,
+                        choice_id,
+                        options: options
+                            .iter()
+                            .map(|option| option.description.clone())
+                            .collect(),
+                    }],
+                }
+            }
+            EffectKind::Discover { filter } => {
+                let mut cards: Vec<Card> = Vec::new();
+                while cards.len() < DISCOVER_OPTIONS {
+                    let offered = |definition: &CardDefinition| {
+                        cards
+                            .iter()
+                            .any(|card| card.def_id == Some(definition.def_id))
+                    };
+                    let Some(def_id) = state
+                        .card_pool
+                        .random_definition(&mut state.rng, |definition| {
+                            filter.matches(definition) && !offered(definition)
+                        })
+                        .map(|definition| definition.def_id)
+                    else {
+                        break;
+                    };
+                    // 只为实际生成的候选分配 ID，候选不足时不会跳过后续卡牌的编号。
+                    let id = state.allocate_card_id();
+                    cards.extend(state.card_pool.instantiate(def_id, id));
+                }
+                let options = cards.iter().map(|card| card.name.clone()).collect();
+                let choice_id =
+                    state.push_choice(ctx.source_player, ChoiceKind::Discover { cards });
+                EffectResolution {
+                    events: vec![GameEvent::ChoicePending {
+                        player_id: ctx.source_player,
+                        choice_id,
+                        options,
+                    }],
+                }
+            }
+            EffectKind::SelectTarget { filter, effect } => {
+                let targets: Vec<ChoiceTarget> =
+                    EffectTarget::selectable_enemy_units(ctx, state, filter)
+                        .into_iter()
+                        .map(|(player_id, card_id)| ChoiceTarget { player_id, card_id })
+                        .collect();
+                let options = targets
+                    .iter()
+                    .filter_map(|target| {
+                        state
+                            .get_player(target.player_id)
+                            .and_then(|player| player.find_card_on_board(target.card_id))
+                            .map(|card| card.name.clone())
+                    })
+                    .collect();
+                let choice_id = state.push_choice(
+                    ctx.source_player,
+                    ChoiceKind::SelectTarget {
+                        targets,
+                        effect: (**effect).clone(),
+                        context: ctx.clone(),
+                    },
+                );
+                EffectResolution {
+                    events: vec![GameEvent::ChoicePending {
+                        player_id: ctx.source_player,
+                        choice_id,
+                        options,
+                    }],
+                }
+            }
+            // 由 `EffectEngine::resolve_all` 的检查点处理
+            EffectKind::Counter => EffectResolution::default(),
+            EffectKind::Overload { amount } => EffectResolution {
+                events: state
+                    .overload_mana(ctx.source_player, *amount)
+                    .into_iter()
+                    .collect(),
+            },
+            EffectKind::AddAlternativeVictory { victory } => {
+                if let Some(player) = state.get_player_mut(ctx.source_player) {
+                    if !player.has_alternative_victory(&victory.id) {
+                        player.alternative_victories.push(victory.clone());
+                    }
+                }
+                EffectResolution::default()
+            }
+            EffectKind::SetStats {
+                attack,
+                health,
+                target,
+            } => EffectResolution {
+                events: target
+                    .resolve_cards(ctx, state)
+                    .into_iter()
+                    .filter_map(|(owner, card_id)| {
+                        state.set_card_stats(owner, card_id, *attack, *health)
+                    })
+                    .collect(),
+            },
+            EffectKind::Resurrect { count } => EffectResolution {
+                events: state.resurrect_units(ctx.source_player, *count),
+            },
+            EffectKind::GrantKeyword {
+                keyword,
+                target,
+                duration,
+            } => EffectResolution {
+                events: target
+                    .resolve_cards(ctx, state)
+                    .into_iter()
+                    .filter_map(|(owner, card_id)| {
+                        state.grant_keyword(owner, card_id, *keyword, duration.expiry(ctx))
+                    })
+                    .collect(),
+            },
+            EffectKind::TakeControl { target, duration } => {
+                let mut events = Vec::new();
+                for (controller, card_id) in target.resolve_cards(ctx, state) {
+                    if state.are_allies(controller, ctx.source_player) {
+                        continue;
+                    }
+                    events.extend(state.change_control(
+                        controller,
+                        card_id,
+                        ctx.source_player,
+                        duration.expiry(ctx),
+                    ));
+                }
+                EffectResolution { events }
+            }
+            EffectKind::Silence { target } => EffectResolution {
+                events: target
+                    .resolve_cards(ctx, state)
+                    .into_iter()
+                    .flat_map(|(owner, card_id)| state.silence_card(owner, card_id))
+                    .collect(),
+            },
+            EffectKind::RevealHand { count, target } => EffectResolution {
+                events: target
+                    .resolve_player(ctx, state)
+                    .and_then(|player_id| {
+                        state.reveal_hand_cards(ctx.source_player, player_id, *count)
+                    })
+                    .into_iter()
+                    .collect(),
+            },
+            EffectKind::AddCounters {
+                counter,
+                amount,
+                target,
+            } => EffectResolution {
+                events: target
+                    .resolve_cards(ctx, state)
+                    .into_iter()
+                    .filter_map(|(owner, card_id)| {
+                        state.adjust_counter(owner, card_id, counter, *amount as i32)
+                    })
+                    .collect(),
+            },
+            EffectKind::RemoveCounters {
+                counter,
+                amount,
+                target,
+            } => {
+                let delta = amount.map_or(-(u16::MAX as i32), |amount| -(amount as i32));
+                EffectResolution {
+                    events: target
+                        .resolve_cards(ctx, state)
+                        .into_iter()
+                        .filter_map(|(owner, card_id)| {
+                            state.adjust_counter(owner, card_id, counter, delta)
+                        })
+                        .collect(),
+                }
+            }
         }
     }
 }
@@ -203,6 +1117,9 @@ pub struct EffectContext {
     pub target_player: Option<PlayerId>,
     pub target_card: Option<CardId>,
     pub current_player: PlayerId,
+    /// 引发本次触发的卡牌（如 `OnSpellCast` 中被施放的法术）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub related_card: Option<CardId>,
 }
 
 impl EffectContext {
@@ -214,9 +1131,15 @@ impl EffectContext {
             target_player: None,
             target_card: None,
             current_player,
+            related_card: None,
         }
     }
 
+    pub fn with_related_card(mut self, card_id: CardId) -> Self {
+        self.related_card = Some(card_id);
+        self
+    }
+
     pub fn with_source_card(mut self, card_id: CardId) -> Self {
         self.source_card = Some(card_id);
         self
@@ -235,36 +1158,257 @@ impl EffectContext {
 }
 
 impl EffectTarget {
-    fn resolve_player(&self, ctx: &EffectContext, state: &GameState) -> Option<PlayerId> {
+    /// 解析为具体的卡牌目标（所属玩家, 卡牌）；指向玩家的目标返回 `None`。
+    /// 随机目标在此从对局随机源抽取，因此需要可变借用状态。
+    pub fn resolve_card(
+        &self,
+        ctx: &EffectContext,
+        state: &mut GameState,
+    ) -> Option<(PlayerId, CardId)> {
         match self {
-            EffectTarget::ContextTarget => ctx.target_player,
-            EffectTarget::SourcePlayer => Some(ctx.source_player),
-            EffectTarget::TargetPlayer => ctx.target_player,
-            EffectTarget::OpponentOfSource => state
-                .players
-                .iter()
-                .find(|p| p.id != ctx.source_player)
-                .map(|player| player.id),
+            EffectTarget::RandomEnemyUnit { filter } => {
+                let candidates = Self::enemy_units(ctx, state, filter);
+                state.rng.choose(&candidates).copied()
+            }
+            _ => self.candidate_cards(ctx, state).into_iter().next(),
         }
     }
-}
 
-#[derive(Default, Debug, Clone)]
-pub struct EffectResolution {
-    pub events: Vec<GameEvent>,
-}
+    /// 可能被选中的全部卡牌目标，不抽取随机数：随机目标返回所有候选，其余与 `resolve_cards` 相同。
+    /// `can_trigger` 只需判断候选是否存在，随机选取留到结算时进行一次。
+    pub fn candidate_cards(
+        &self,
+        ctx: &EffectContext,
+        state: &GameState,
+    ) -> Vec<(PlayerId, CardId)> {
+        match self {
+            EffectTarget::RandomEnemyUnit { filter } | EffectTarget::AllEnemyUnits { filter } => {
+                Self::enemy_units(ctx, state, filter)
+            }
+            EffectTarget::AllFriendlyUnits { filter } => Self::friendly_units(ctx, state, filter),
+            EffectTarget::AdjacentToSource => {
+                Self::adjacent_units(state, Some(ctx.source_player), ctx.source_card)
+            }
+            EffectTarget::AdjacentToTarget => {
+                Self::adjacent_units(state, ctx.target_player, ctx.target_card)
+            }
+            _ => self.single_card(ctx, state).into_iter().collect(),
+        }
+    }
 
-impl EffectResolution {
+    /// 解析为全部卡牌目标；范围与相邻目标返回多个，其余最多一个。
+    pub fn resolve_cards(
+        &self,
+        ctx: &EffectContext,
+        state: &mut GameState,
+    ) -> Vec<(PlayerId, CardId)> {
+        match self {
+            EffectTarget::RandomEnemyUnit { .. } => {
+                self.resolve_card(ctx, state).into_iter().collect()
+            }
+            _ => self.candidate_cards(ctx, state),
+        }
+    }
+
+    /// 只指向单张卡牌的确定性目标。
+    fn single_card(&self, ctx: &EffectContext, state: &GameState) -> Option<(PlayerId, CardId)> {
+        match self {
+            EffectTarget::ContextTarget => {
+                ctx.target_player
+                    .zip(ctx.target_card)
+                    .filter(|&(owner, card_id)| {
+                        state
+                            .get_player(owner)
+                            .and_then(|player| player.find_card_on_board(card_id))
+                            .is_none_or(Card::is_targetable)
+                    })
+            }
+            EffectTarget::SourceCard => ctx.source_card.map(|card_id| (ctx.source_player, card_id)),
+            EffectTarget::WeakestEnemyUnit => Self::select_enemy_unit(ctx, state, |units| {
+                units.into_iter().min_by_key(|(_, card)| card.health)
+            }),
+            EffectTarget::StrongestEnemyUnit => Self::select_enemy_unit(ctx, state, |units| {
+                units.into_iter().rev().max_by_key(|(_, card)| card.attack)
+            }),
+            _ => None,
+        }
+    }
+
+    fn adjacent_units(
+        state: &GameState,
+        owner: Option<PlayerId>,
+        card_id: Option<CardId>,
+    ) -> Vec<(PlayerId, CardId)> {
+        let (Some(owner), Some(card_id)) = (owner, card_id) else {
+            return Vec::new();
+        };
+        state
+            .get_player(owner)
+            .map(|player| player.adjacent_units(card_id))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|neighbour| (owner, neighbour))
+            .collect()
+    }
+
+    fn enemy_units(
+        ctx: &EffectContext,
+        state: &GameState,
+        filter: &TargetFilter,
+    ) -> Vec<(PlayerId, CardId)> {
+        Self::units_where(state, filter, |player_id| {
+            !state.are_allies(ctx.source_player, player_id)
+        })
+    }
+
+    /// 可被来源玩家选中的敌方单位：满足筛选条件、未潜行且不具有灵巧。
+    fn selectable_enemy_units(
+        ctx: &EffectContext,
+        state: &GameState,
+        filter: &TargetFilter,
+    ) -> Vec<(PlayerId, CardId)> {
+        Self::enemy_units(ctx, state, filter)
+            .into_iter()
+            .filter(|(owner, card_id)| {
+                state
+                    .get_player(*owner)
+                    .and_then(|player| player.find_card_on_board(*card_id))
+                    .is_some_and(|card| !card.has_keyword(Keyword::Stealth) && card.is_targetable())
+            })
+            .collect()
+    }
+
+    fn friendly_units(
+        ctx: &EffectContext,
+        state: &GameState,
+        filter: &TargetFilter,
+    ) -> Vec<(PlayerId, CardId)> {
+        Self::units_where(state, filter, |player_id| {
+            state.are_allies(ctx.source_player, player_id)
+        })
+    }
+
+    fn units_where<F>(state: &GameState, filter: &TargetFilter, owner: F) -> Vec<(PlayerId, CardId)>
+    where
+        F: Fn(PlayerId) -> bool,
+    {
+        state
+            .players
+            .iter()
+            .filter(|player| owner(player.id))
+            .flat_map(|player| {
+                player
+                    .board
+                    .iter()
+                    .filter(|card| filter.matches(card))
+                    .map(move |card| (player.id, card.id))
+            })
+            .collect()
+    }
+
+    fn select_enemy_unit<'a, F>(
+        ctx: &EffectContext,
+        state: &'a GameState,
+        select: F,
+    ) -> Option<(PlayerId, CardId)>
+    where
+        F: FnOnce(Vec<(PlayerId, &'a Card)>) -> Option<(PlayerId, &'a Card)>,
+    {
+        let units = state
+            .players
+            .iter()
+            .filter(|player| !state.are_allies(ctx.source_player, player.id))
+            .flat_map(|player| {
+                player
+                    .board
+                    .iter()
+                    .filter(|card| card.is_targetable())
+                    .map(move |card| (player.id, card))
+            })
+            .collect();
+        select(units).map(|(owner, card)| (owner, card.id))
+    }
+
+    fn resolve_player(&self, ctx: &EffectContext, state: &GameState) -> Option<PlayerId> {
+        match self {
+            EffectTarget::ContextTarget => ctx.target_player,
+            EffectTarget::SourcePlayer | EffectTarget::SourceCard => Some(ctx.source_player),
+            EffectTarget::TargetPlayer => ctx.target_player,
+            EffectTarget::WeakestEnemyUnit
+            | EffectTarget::StrongestEnemyUnit
+            | EffectTarget::RandomEnemyUnit { .. }
+            | EffectTarget::AllEnemyUnits { .. }
+            | EffectTarget::AllFriendlyUnits { .. }
+            | EffectTarget::AdjacentToSource
+            | EffectTarget::AdjacentToTarget => None,
+            EffectTarget::OpponentOfSource | EffectTarget::AllEnemyHeroes => {
+                state.opponent_of(ctx.source_player)
+            }
+            EffectTarget::AllAlliedHeroes => Some(ctx.source_player),
+        }
+    }
+
+    /// 卡牌目标为空时能否改为作用于玩家；已指定卡牌的上下文目标被灵巧阻挡时直接失效，不会转到英雄。
+    fn falls_back_to_players(&self, ctx: &EffectContext) -> bool {
+        !matches!(self, EffectTarget::ContextTarget) || ctx.target_card.is_none()
+    }
+
+    /// 解析为全部玩家目标；英雄范围目标返回多个，其余同 `resolve_player`。
+    fn resolve_players(&self, ctx: &EffectContext, state: &GameState) -> Vec<PlayerId> {
+        let living = |ids: Vec<PlayerId>| {
+            ids.into_iter()
+                .filter(|&id| state.get_player(id).is_some_and(Player::is_alive))
+                .collect()
+        };
+        match self {
+            EffectTarget::AllAlliedHeroes => living(state.allies_of(ctx.source_player)),
+            EffectTarget::AllEnemyHeroes => living(state.enemies_of(ctx.source_player)),
+            _ => self.resolve_player(ctx, state).into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct EffectResolution {
+    pub events: Vec<GameEvent>,
+}
+
+impl EffectResolution {
     pub fn extend(&mut self, mut other: EffectResolution) {
         self.events.append(&mut other.events);
     }
 }
 
-#[derive(Debug, Clone)]
+/// 效果栈条目的唯一标识，按入栈顺序递增。
+pub type StackItemId = u64;
+
+/// 对外暴露的待结算栈条目信息。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StackEntry {
+    pub id: StackItemId,
+    pub effect_id: EffectId,
+    pub priority: i8,
+    pub source_player: PlayerId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_card: Option<CardId>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub trigger: EffectTrigger,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_player: Option<PlayerId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_card: Option<CardId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct StackItem {
     entry_id: EffectId,
     priority: i8,
     order: u64,
+    /// 响应层级：后开启的响应窗口中入栈的效果先结算。
+    #[serde(default)]
+    layer: u32,
     effect: CardEffect,
     context: EffectContext,
 }
@@ -285,77 +1429,441 @@ impl PartialOrd for StackItem {
 
 impl Ord for StackItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.priority
-            .cmp(&other.priority)
+        self.layer
+            .cmp(&other.layer)
+            .then_with(|| self.priority.cmp(&other.priority))
             .then_with(|| other.order.cmp(&self.order))
     }
 }
 
-#[derive(Default)]
+/// 待结算效果栈，可序列化以便前端展示或随存档保存。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EffectStack {
     heap: BinaryHeap<StackItem>,
     order: u64,
+    #[serde(default)]
+    layer: u32,
+}
+
+impl PartialEq for EffectStack {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order && self.layer == other.layer && self.entries() == other.entries()
+    }
 }
 
+impl Eq for EffectStack {}
+
 impl EffectStack {
-    pub fn push(&mut self, effect: CardEffect, context: EffectContext) {
+    pub fn push(&mut self, effect: CardEffect, context: EffectContext) -> StackItemId {
         self.order += 1;
         self.heap.push(StackItem {
             entry_id: effect.id,
             priority: effect.priority,
             order: self.order,
+            layer: self.layer,
             effect,
             context,
         });
+        self.order
     }
 
     fn pop(&mut self) -> Option<StackItem> {
-        self.heap.pop()
+        let item = self.heap.pop();
+        if self.heap.is_empty() {
+            self.layer = 0;
+        }
+        item
+    }
+
+    /// 开启新的响应层，此后入栈的效果先于已有条目结算。
+    pub fn open_response_layer(&mut self) {
+        self.layer += 1;
+    }
+
+    /// 按结算顺序列出尚未结算的条目。
+    pub fn entries(&self) -> Vec<StackEntry> {
+        let mut items: Vec<&StackItem> = self.heap.iter().collect();
+        items.sort_by(|a, b| b.cmp(a));
+        items
+            .into_iter()
+            .map(|item| StackEntry {
+                id: item.order,
+                effect_id: item.entry_id,
+                priority: item.priority,
+                source_player: item.context.source_player,
+                source_card: item.context.source_card,
+                description: item.effect.description.clone(),
+                trigger: item.context.trigger.clone(),
+                target_player: item.context.target_player,
+                target_card: item.context.target_card,
+            })
+            .collect()
+    }
+
+    /// 移除指定条目，返回是否存在。
+    pub fn cancel(&mut self, id: StackItemId) -> bool {
+        let before = self.heap.len();
+        self.heap.retain(|item| item.order != id);
+        self.heap.len() != before
+    }
+
+    fn cancel_where<F>(&mut self, predicate: F) -> Vec<StackItem>
+    where
+        F: Fn(&StackItem) -> bool,
+    {
+        let (cancelled, kept): (Vec<StackItem>, Vec<StackItem>) =
+            self.heap.drain().partition(|item| predicate(item));
+        self.heap.extend(kept);
+        cancelled
     }
 
     pub fn is_empty(&self) -> bool {
         self.heap.is_empty()
     }
+
+    /// 丢弃所有尚未结算的条目，返回丢弃数量。
+    fn clear(&mut self) -> usize {
+        let discarded = self.heap.len();
+        self.heap.clear();
+        self.layer = 0;
+        discarded
+    }
+}
+
+/// 调试模式下记录的效果结算过程。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum EffectTraceEntry {
+    Pushed {
+        stack_item_id: StackItemId,
+        effect_id: EffectId,
+        description: String,
+        trigger: EffectTrigger,
+        source_player: PlayerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_card: Option<CardId>,
+    },
+    ConditionChecked {
+        stack_item_id: StackItemId,
+        effect_id: EffectId,
+        passed: bool,
+    },
+    Applied {
+        stack_item_id: StackItemId,
+        effect_id: EffectId,
+        events: Vec<GameEvent>,
+    },
+    Countered {
+        stack_item_id: StackItemId,
+        effect_id: EffectId,
+        cancelled: Vec<StackItemId>,
+    },
 }
 
-#[derive(Default)]
+/// 单次结算默认允许处理的栈条目上限。
+pub const DEFAULT_RESOLUTION_BUDGET: usize = 100;
+
+#[derive(Debug, Clone)]
 pub struct EffectEngine {
     stack: EffectStack,
+    /// 为 `Some` 时记录每次入栈、条件检查与结算。
+    trace: Option<Vec<EffectTraceEntry>>,
+    /// 单次 `resolve_all` 最多处理的栈条目数，防止亡语等触发互相引发形成死循环。
+    resolution_budget: usize,
+}
+
+impl Default for EffectEngine {
+    fn default() -> Self {
+        Self {
+            stack: EffectStack::default(),
+            trace: None,
+            resolution_budget: DEFAULT_RESOLUTION_BUDGET,
+        }
+    }
 }
 
 impl EffectEngine {
+    pub fn with_resolution_budget(mut self, budget: usize) -> Self {
+        self.set_resolution_budget(budget);
+        self
+    }
+
+    /// 设置单次结算的条目上限，至少为 1。
+    pub fn set_resolution_budget(&mut self, budget: usize) {
+        self.resolution_budget = budget.max(1);
+    }
+
+    pub fn resolution_budget(&self) -> usize {
+        self.resolution_budget
+    }
+
+    /// 开启或关闭结算追踪，关闭时丢弃已记录的内容。
+    pub fn set_trace(&mut self, enabled: bool) {
+        self.trace = enabled.then(|| self.trace.take().unwrap_or_default());
+    }
+
+    /// 取出已记录的追踪条目，追踪保持开启。
+    pub fn take_trace(&mut self) -> Vec<EffectTraceEntry> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn record_trace(&mut self, entry: EffectTraceEntry) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(entry);
+        }
+    }
+
+    fn push(&mut self, effect: CardEffect, context: EffectContext) -> StackItemId {
+        let traced = self.trace.is_some().then(|| {
+            (
+                effect.id,
+                effect.description.clone(),
+                context.trigger.clone(),
+                context.source_player,
+                context.source_card,
+            )
+        });
+        let id = self.stack.push(effect, context);
+        if let Some((effect_id, description, trigger, source_player, source_card)) = traced {
+            self.record_trace(EffectTraceEntry::Pushed {
+                stack_item_id: id,
+                effect_id,
+                description,
+                trigger,
+                source_player,
+                source_card,
+            });
+        }
+        id
+    }
+
     pub fn queue_card_effects(&mut self, card: &Card, base_context: EffectContext) {
         for effect in &card.effects {
-            if effect.trigger == base_context.trigger {
-                self.stack.push(effect.clone(), base_context.clone());
+            if effect.trigger == base_context.trigger && !effect.out_of_charges() {
+                self.push(effect.clone(), base_context.clone());
             }
         }
     }
 
-    pub fn queue_effect(&mut self, effect: CardEffect, context: EffectContext) {
-        self.stack.push(effect, context);
+    pub fn queue_effect(&mut self, effect: CardEffect, context: EffectContext) -> StackItemId {
+        self.push(effect, context)
+    }
+
+    /// 取消一个尚未结算的栈条目。
+    pub fn cancel_stack_item(&mut self, id: StackItemId) -> bool {
+        self.stack.cancel(id)
+    }
+
+    pub fn open_response_layer(&mut self) {
+        self.stack.open_response_layer();
+    }
+
+    /// 揭示 `actor` 对手中响应 `trigger` 的奥秘，并压入其效果。
+    ///
+    /// 奥秘效果的上下文以奥秘持有者为来源，以行动方（或 `target_card` 指向的卡牌）为目标。
+    pub fn trigger_secrets(
+        &mut self,
+        state: &mut GameState,
+        trigger: EffectTrigger,
+        actor: PlayerId,
+        target_card: Option<(PlayerId, CardId)>,
+        related_card: Option<CardId>,
+    ) -> Vec<GameEvent> {
+        let owners: Vec<PlayerId> = state
+            .players
+            .iter()
+            .filter(|player| player.id != actor && !player.secrets.is_empty())
+            .map(|player| player.id)
+            .collect();
+        let mut events = Vec::new();
+        for owner in owners {
+            let revealed = state.reveal_secrets(owner, &trigger);
+            for event in &revealed {
+                let GameEvent::SecretRevealed { card, .. } = event else {
+                    continue;
+                };
+                let mut ctx = EffectContext::new(trigger.clone(), owner, state.current_player)
+                    .with_source_card(card.id);
+                ctx = match target_card {
+                    Some((player_id, card_id)) => ctx.with_target_card(player_id, card_id),
+                    None => ctx.with_target_player(actor),
+                };
+                if let Some(related) = related_card {
+                    ctx = ctx.with_related_card(related);
+                }
+                self.queue_card_effects(card, ctx);
+            }
+            for event in &revealed {
+                state.record_event(event.clone());
+            }
+            events.extend(revealed);
+        }
+        events
+    }
+
+    /// 将到期的延迟效果按登记顺序压入效果栈。
+    pub fn queue_scheduled_effects(
+        &mut self,
+        state: &mut GameState,
+        trigger: EffectTrigger,
+        player_id: PlayerId,
+    ) {
+        for scheduled in state.take_scheduled_effects(&trigger, player_id) {
+            let mut context = scheduled.context;
+            context.trigger = trigger.clone();
+            context.current_player = state.current_player;
+            let effect = CardEffect::new(
+                scheduled.id as EffectId,
+                "Delayed effect",
+                trigger.clone(),
+                0,
+                scheduled.effect,
+            );
+            self.push(effect, context);
+        }
     }
 
     pub fn resolve_all(&mut self, state: &mut GameState) -> Vec<GameEvent> {
         let mut events = Vec::new();
-        let mut depth = 0;
-        const MAX_DEPTH: usize = 100; // 防止无限递归
+        let mut resolved = 0;
 
-        while let Some(item) = self.stack.pop() {
-            if depth >= MAX_DEPTH {
-                eprintln!("Effect stack depth limit reached ({}), stopping resolution to prevent infinite recursion", MAX_DEPTH);
+        loop {
+            if resolved >= self.resolution_budget && !self.stack.is_empty() {
+                // 超出预算时丢弃剩余条目，避免互相触发的效果卡死调用方
+                let event = GameEvent::ResolutionAborted {
+                    budget: self.resolution_budget,
+                    discarded: self.stack.clear(),
+                };
+                state.record_event(event.clone());
+                events.push(event);
+                events.extend(self.refresh_auras(state));
+                break;
+            }
+            let Some(item) = self.stack.pop() else {
+                // 效果栈清空后重算光环；光环消失导致的死亡同样引发亡语并继续结算
+                let aura_events = self.refresh_auras(state);
+                let deaths = aura_events
+                    .iter()
+                    .any(|event| matches!(event, GameEvent::CardDestroyed { .. }));
+                self.queue_reactions(state, &aura_events);
+                events.extend(aura_events);
+                if deaths {
+                    continue;
+                }
                 break;
+            };
+            resolved += 1;
+
+            // 限次效果以场上卡牌的实时剩余次数为准
+            let live_charges = item.context.source_card.and_then(|card_id| {
+                state.effect_charges(item.context.source_player, card_id, item.entry_id)
+            });
+            let passed = live_charges != Some(0) && item.effect.can_trigger(&item.context, state);
+            self.record_trace(EffectTraceEntry::ConditionChecked {
+                stack_item_id: item.order,
+                effect_id: item.entry_id,
+                passed,
+            });
+            if !passed {
+                continue;
+            }
+
+            if let (Some(_), Some(card_id)) = (live_charges, item.context.source_card) {
+                if let Some(event) =
+                    state.spend_effect_charge(item.context.source_player, card_id, item.entry_id)
+                {
+                    state.record_event(event.clone());
+                    events.push(event);
+                }
             }
-            depth += 1;
 
-            if !item.effect.can_trigger(&item.context, state) {
+            // 结算检查点：反制效果直接作用于效果栈
+            if matches!(item.effect.kind, EffectKind::Counter) {
+                let mut counter_events = self.counter_pending(&item.context, state);
+                let cancelled = counter_events
+                    .iter()
+                    .filter_map(|event| match event {
+                        GameEvent::EffectCountered { stack_item_id, .. } => Some(*stack_item_id),
+                        _ => None,
+                    })
+                    .collect();
+                self.record_trace(EffectTraceEntry::Countered {
+                    stack_item_id: item.order,
+                    effect_id: item.entry_id,
+                    cancelled,
+                });
+                events.append(&mut counter_events);
                 continue;
             }
 
             let mut resolution = item.effect.apply(&item.context, state);
+            if self.trace.is_some() {
+                self.record_trace(EffectTraceEntry::Applied {
+                    stack_item_id: item.order,
+                    effect_id: item.entry_id,
+                    events: resolution.events.clone(),
+                });
+            }
             for event in &resolution.events {
                 state.record_event(event.clone());
-                if let GameEvent::CardDestroyed { player_id, card } = event {
+            }
+            self.queue_reactions(state, &resolution.events);
+            events.append(&mut resolution.events);
+        }
+
+        events.extend(state.upgrade_hand_cards());
+        events
+    }
+
+    /// 对效果栈之外直接造成伤害（如战斗），并让伤害、死亡引发的触发进入效果栈。
+    pub fn damage_card(
+        &mut self,
+        state: &mut GameState,
+        source_player: PlayerId,
+        source_card: Option<CardId>,
+        target_player: PlayerId,
+        target_card: CardId,
+        amount: i16,
+    ) -> Vec<GameEvent> {
+        let events = state.damage_card(
+            source_player,
+            source_card,
+            target_player,
+            target_card,
+            amount,
+        );
+        self.observe_events(state, &events);
+        events
+    }
+
+    pub fn damage_player(
+        &mut self,
+        state: &mut GameState,
+        source_player: PlayerId,
+        source_card: Option<CardId>,
+        target_player: PlayerId,
+        amount: i16,
+    ) -> Vec<GameEvent> {
+        let events = state.damage_player(source_player, source_card, target_player, amount);
+        self.observe_events(state, &events);
+        events
+    }
+
+    /// 记录外部产生的事件，并压入这些事件引发的反应效果。
+    pub fn observe_events(&mut self, state: &mut GameState, events: &[GameEvent]) {
+        for event in events {
+            state.record_event(event.clone());
+        }
+        self.queue_reactions(state, events);
+    }
+
+    /// 扫描一批事件，为监听这些事件的卡牌（亡语、受伤触发等）压入效果。
+    ///
+    /// 事件需已记录到日志中；效果栈内产生的事件由 `resolve_all` 自动扫描。
+    pub fn queue_reactions(&mut self, state: &GameState, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::CardDestroyed { player_id, card } => {
                     let death_ctx = EffectContext::new(
                         EffectTrigger::OnDeath,
                         *player_id,
@@ -364,8 +1872,228 @@ impl EffectEngine {
                     .with_source_card(card.id);
                     self.queue_card_effects(card, death_ctx);
                 }
+                GameEvent::DamageResolved {
+                    source_player,
+                    target_player,
+                    target_card: Some(card_id),
+                    ..
+                } => {
+                    // 受伤后立即死亡的单位同样会触发（其数据随死亡事件携带）
+                    let damaged = state
+                        .get_player(*target_player)
+                        .and_then(|player| player.board.iter().find(|card| card.id == *card_id))
+                        .or_else(|| {
+                            events.iter().find_map(|other| match other {
+                                GameEvent::CardDestroyed { card, .. } if card.id == *card_id => {
+                                    Some(card)
+                                }
+                                _ => None,
+                            })
+                        });
+                    if let Some(card) = damaged {
+                        let ctx = EffectContext::new(
+                            EffectTrigger::OnDamage,
+                            *target_player,
+                            state.current_player,
+                        )
+                        .with_source_card(card.id)
+                        .with_target_player(*source_player);
+                        self.queue_card_effects(card, ctx);
+                    }
+                }
+                GameEvent::DamageResolved {
+                    source_player,
+                    target_player,
+                    target_card: None,
+                    ..
+                } => {
+                    if let Some(player) = state.get_player(*target_player) {
+                        for card in &player.board {
+                            let ctx = EffectContext::new(
+                                EffectTrigger::OnHeroDamage,
+                                *target_player,
+                                state.current_player,
+                            )
+                            .with_source_card(card.id)
+                            .with_target_player(*source_player);
+                            self.queue_card_effects(card, ctx);
+                        }
+                    }
+                }
+                GameEvent::CardHealed {
+                    player_id, card_id, ..
+                } => {
+                    self.queue_board_listeners(state, EffectTrigger::OnHeal, *player_id, |ctx| {
+                        match card_id {
+                            Some(card_id) => ctx.with_target_card(*player_id, *card_id),
+                            None => ctx.with_target_player(*player_id),
+                        }
+                    });
+                }
+                GameEvent::CardDrawn { player_id, .. } => {
+                    self.queue_board_listeners(state, EffectTrigger::OnDraw, *player_id, |ctx| {
+                        ctx.with_target_player(*player_id)
+                    });
+                }
+                GameEvent::CardBurned { player_id, card } => {
+                    for listener_owner in &state.players {
+                        self.queue_board_listeners(
+                            state,
+                            EffectTrigger::OnCardBurned,
+                            listener_owner.id,
+                            |ctx| {
+                                ctx.with_target_player(*player_id)
+                                    .with_related_card(card.id)
+                            },
+                        );
+                    }
+                }
+                GameEvent::CardSummoned { player_id, card_id } => {
+                    if let Some(player) = state.get_player(*player_id) {
+                        for card in player.board.iter().filter(|card| card.id != *card_id) {
+                            let ctx = EffectContext::new(
+                                EffectTrigger::OnSummon,
+                                *player_id,
+                                state.current_player,
+                            )
+                            .with_source_card(card.id)
+                            .with_target_card(*player_id, *card_id);
+                            self.queue_card_effects(card, ctx);
+                        }
+                    }
+                }
+                _ => {}
             }
-            events.extend(resolution.events.drain(..));
+        }
+    }
+
+    fn queue_board_listeners<F>(
+        &mut self,
+        state: &GameState,
+        trigger: EffectTrigger,
+        player_id: PlayerId,
+        with_target: F,
+    ) where
+        F: Fn(EffectContext) -> EffectContext,
+    {
+        let Some(player) = state.get_player(player_id) else {
+            return;
+        };
+        for card in &player.board {
+            let ctx = EffectContext::new(trigger.clone(), player_id, state.current_player)
+                .with_source_card(card.id);
+            self.queue_card_effects(card, with_target(ctx));
+        }
+    }
+
+    /// 根据当前场面重新计算所有 `Passive` 属性光环。
+    ///
+    /// 每次都从零计算目标加成并与卡牌上已生效的加成做差，因此可以重复调用；
+    /// 移除生命光环后，所受伤害不低于剩余生命上限的单位会死亡。
+    pub fn refresh_auras(&mut self, state: &mut GameState) -> Vec<GameEvent> {
+        let mut desired: HashMap<(PlayerId, CardId), Vec<Enchantment>> = HashMap::new();
+        for source_owner in &state.players {
+            for source in &source_owner.board {
+                for effect in &source.effects {
+                    if effect.trigger != EffectTrigger::Passive {
+                        continue;
+                    }
+                    let EffectKind::StatAura {
+                        attack,
+                        health,
+                        scope,
+                    } = &effect.kind
+                    else {
+                        continue;
+                    };
+                    if let Some(condition) = &effect.condition {
+                        let ctx = EffectContext::new(
+                            EffectTrigger::Passive,
+                            source_owner.id,
+                            state.current_player,
+                        )
+                        .with_source_card(source.id);
+                        if !condition.is_satisfied(&ctx, state) {
+                            continue;
+                        }
+                    }
+                    for target_owner in &state.players {
+                        for card in &target_owner.board {
+                            if scope.covers(source_owner.id, source.id, target_owner.id, card.id) {
+                                desired.entry((target_owner.id, card.id)).or_default().push(
+                                    Enchantment {
+                                        source_player: Some(source_owner.id),
+                                        source_card: Some(source.id),
+                                        attack: *attack,
+                                        health: *health,
+                                        duration: EnchantmentDuration::Aura,
+                                    },
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut dying = Vec::new();
+        for player in &mut state.players {
+            for card in &mut player.board {
+                let target = desired.remove(&(player.id, card.id)).unwrap_or_default();
+                let current: Vec<Enchantment> = card
+                    .enchantments
+                    .iter()
+                    .filter(|item| item.duration == EnchantmentDuration::Aura)
+                    .copied()
+                    .collect();
+                if target == current {
+                    continue;
+                }
+                let before = card.aura_bonus();
+                card.enchantments
+                    .retain(|item| item.duration != EnchantmentDuration::Aura);
+                card.enchantments.extend(target);
+                let after = card.aura_bonus();
+                card.attack += after.attack - before.attack;
+                card.health += after.health - before.health;
+                events.push(GameEvent::AuraChanged {
+                    player_id: player.id,
+                    card_id: card.id,
+                    attack: after.attack,
+                    health: after.health,
+                });
+                if card.health <= 0 {
+                    dying.push((player.id, card.id));
+                }
+            }
+        }
+        for (player_id, card_id) in dying {
+            events.extend(state.destroy_card(player_id, card_id));
+        }
+        for event in &events {
+            state.record_event(event.clone());
+        }
+        events
+    }
+
+    fn counter_pending(&mut self, ctx: &EffectContext, state: &mut GameState) -> Vec<GameEvent> {
+        let Some(countered_card) = ctx.related_card.or(ctx.target_card) else {
+            return Vec::new();
+        };
+        let cancelled = self
+            .stack
+            .cancel_where(|item| item.context.source_card == Some(countered_card));
+        let mut events = Vec::new();
+        for item in cancelled {
+            let event = GameEvent::EffectCountered {
+                player_id: ctx.source_player,
+                stack_item_id: item.order,
+                effect_id: item.entry_id,
+                source_card: item.context.source_card,
+            };
+            state.record_event(event.clone());
+            events.push(event);
         }
         events
     }
@@ -373,6 +2101,16 @@ impl EffectEngine {
     pub fn stack(&self) -> &EffectStack {
         &self.stack
     }
+
+    /// 取出尚未结算的效果栈，供响应窗口暂存到状态中。
+    pub fn take_stack(&mut self) -> EffectStack {
+        std::mem::take(&mut self.stack)
+    }
+
+    /// 恢复先前取出的效果栈，替换当前的栈。
+    pub fn restore_stack(&mut self, stack: EffectStack) {
+        self.stack = stack;
+    }
 }
 
 impl CardEffect {
@@ -389,3 +2127,1209 @@ impl CardEffect {
         self.kind.apply(ctx, state)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GamePhase, Player, StatModifier, SPECTATOR};
+
+    fn unit(id: CardId, attack: i16, health: i16, effects: Vec<CardEffect>) -> Card {
+        let mut card = Card::new(id, "Unit", 1, attack, health, CardType::Unit, effects);
+        card.refresh();
+        card
+    }
+
+    /// 生命 30、法力 1 的玩家，场上为 `board`；需要手牌或牌库时在开局前再修改。
+    fn hero(id: PlayerId, board: Vec<Card>) -> Player {
+        Player::new(id, 30, 0, 1, Vec::new(), board, Vec::new())
+    }
+
+    /// 处于主阶段、由 `current` 行动的对局。
+    fn game(players: Vec<Player>, current: PlayerId) -> GameState {
+        GameState::new(players, current).with_phase(GamePhase::Main)
+    }
+
+    /// 优先级为 0 的效果。
+    fn effect(
+        id: EffectId,
+        description: &str,
+        trigger: EffectTrigger,
+        kind: EffectKind,
+    ) -> CardEffect {
+        CardEffect::new(id, description, trigger, 0, kind)
+    }
+
+    #[test]
+    fn random_targets_are_drawn_once_when_the_effect_resolves() {
+        let target = EffectTarget::RandomEnemyUnit {
+            filter: TargetFilter::default(),
+        };
+        let weaken = EffectKind::ModifyStats {
+            attack: 0,
+            health: -1,
+            target: target.clone(),
+            duration: EffectDuration::default(),
+        };
+        let enemies = vec![unit(20, 1, 3, Vec::new()), unit(21, 1, 3, Vec::new())];
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, enemies)], 0).with_seed(5);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+
+        let mut before = state.rng.clone();
+        assert!(weaken.can_trigger(&ctx, &state));
+        assert_eq!(state.rng, before, "checking for targets draws nothing");
+
+        let candidates = target.candidate_cards(&ctx, &state);
+        assert_eq!(candidates, vec![(1, 20), (1, 21)]);
+        let (_, expected) = *before.choose(&candidates).unwrap();
+        weaken.apply(&ctx, &mut state);
+        let damaged: Vec<CardId> = state.players[1]
+            .board
+            .iter()
+            .filter(|card| card.health < 3)
+            .map(|card| card.id)
+            .collect();
+        assert_eq!(damaged, vec![expected]);
+    }
+
+    #[test]
+    fn gain_armor_grants_armor_that_absorbs_damage() {
+        let mut player_one = hero(0, Vec::new());
+        player_one.armor = 1;
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+
+        let shield_up = EffectKind::GainArmor {
+            amount: 4,
+            target: EffectTarget::SourcePlayer,
+        };
+        let resolution =
+            shield_up.apply(&EffectContext::new(EffectTrigger::OnPlay, 0, 0), &mut state);
+        assert_eq!(
+            resolution.events,
+            vec![GameEvent::ArmorGained {
+                player_id: 0,
+                amount: 4
+            }]
+        );
+        assert_eq!(state.get_player(0).unwrap().armor, 5);
+
+        state.damage_player(1, None, 0, 7);
+        let player = state.get_player(0).unwrap();
+        assert_eq!((player.armor, player.health), (0, 28));
+    }
+
+    #[test]
+    fn heal_draw_and_summon_listeners_queue_inside_resolution() {
+        let armor = |id, trigger, amount| {
+            effect(
+                id,
+                "Listener: gain armor",
+                trigger,
+                EffectKind::GainArmor {
+                    amount,
+                    target: EffectTarget::SourcePlayer,
+                },
+            )
+        };
+        let listener = unit(
+            130,
+            1,
+            3,
+            vec![
+                armor(15, EffectTrigger::OnHeal, 1),
+                armor(16, EffectTrigger::OnDraw, 2),
+                armor(17, EffectTrigger::OnSummon, 4),
+            ],
+        );
+        let deck = vec![unit(131, 1, 1, Vec::new())];
+        let mut player_one = hero(0, vec![listener]);
+        player_one.deck = deck;
+        player_one.health = 20;
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+        let mut engine = EffectEngine::default();
+
+        let mut cast = |state: &mut GameState, id, kind| {
+            let effect = effect(id, "Spell", EffectTrigger::OnPlay, kind);
+            engine.queue_effect(effect, EffectContext::new(EffectTrigger::OnPlay, 0, 0));
+            engine.resolve_all(state);
+            state.get_player(0).unwrap().armor
+        };
+        let heal = EffectKind::Heal {
+            amount: 3,
+            target: EffectTarget::SourcePlayer,
+            scaling: None,
+        };
+        assert_eq!(cast(&mut state, 18, heal), 1, "healing fires OnHeal");
+        let draw = EffectKind::DrawCard {
+            count: 1,
+            target: EffectTarget::SourcePlayer,
+            scaling: None,
+        };
+        assert_eq!(cast(&mut state, 19, draw), 3, "drawing fires OnDraw");
+
+        state.players[0].board.push(unit(132, 1, 1, Vec::new()));
+        let summoned = GameEvent::CardSummoned {
+            player_id: 0,
+            card_id: 132,
+        };
+        engine.observe_events(&mut state, &[summoned]);
+        engine.resolve_all(&mut state);
+        assert_eq!(
+            state.get_player(0).unwrap().armor,
+            7,
+            "another friendly unit entering play fires OnSummon"
+        );
+    }
+
+    #[test]
+    fn computed_selectors_pick_weakest_and_strongest_enemies() {
+        let enemies = vec![
+            unit(60, 2, 3, Vec::new()),
+            unit(61, 5, 1, Vec::new()),
+            unit(62, 5, 4, Vec::new()),
+            unit(63, 1, 1, Vec::new()),
+        ];
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, enemies)], 0);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+
+        assert_eq!(
+            EffectTarget::WeakestEnemyUnit.resolve_card(&ctx, &mut state),
+            Some((1, 61)),
+            "ties go to the leftmost unit"
+        );
+        assert_eq!(
+            EffectTarget::StrongestEnemyUnit.resolve_card(&ctx, &mut state),
+            Some((1, 61))
+        );
+
+        let snipe = EffectKind::DirectDamage {
+            amount: 3,
+            target: EffectTarget::StrongestEnemyUnit,
+            scaling: None,
+        };
+        snipe.apply(&ctx, &mut state);
+        let health: Vec<_> = state.players[1]
+            .board
+            .iter()
+            .map(|card| (card.id, card.health))
+            .collect();
+        assert_eq!(health, vec![(60, 3), (62, 4), (63, 1)]);
+        assert_eq!(
+            EffectTarget::StrongestEnemyUnit.resolve_card(&ctx, &mut state),
+            Some((1, 62)),
+            "selectors are recomputed at resolution"
+        );
+    }
+
+    #[test]
+    fn computed_selectors_consider_every_enemy_player() {
+        let mut ally = hero(1, vec![unit(70, 1, 1, Vec::new())]);
+        ally.team = Some(0);
+        let mut source = hero(0, Vec::new());
+        source.team = Some(0);
+        let near = hero(2, vec![unit(71, 2, 3, Vec::new())]);
+        let far = hero(3, vec![unit(72, 6, 2, Vec::new())]);
+        let mut state = game(vec![source, ally, near, far], 0);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+
+        assert_eq!(
+            EffectTarget::WeakestEnemyUnit.resolve_card(&ctx, &mut state),
+            Some((3, 72)),
+            "allied units are never selected"
+        );
+        assert_eq!(
+            EffectTarget::StrongestEnemyUnit.resolve_card(&ctx, &mut state),
+            Some((3, 72))
+        );
+    }
+
+    #[test]
+    fn trace_records_pushes_condition_checks_and_applications() {
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, Vec::new())], 0);
+        let draw = CardEffect::new(
+            1,
+            "Draw a card",
+            EffectTrigger::OnPlay,
+            5,
+            EffectKind::DrawCard {
+                count: 1,
+                target: EffectTarget::SourcePlayer,
+                scaling: None,
+            },
+        );
+        let armor = effect(
+            2,
+            "Gain 3 armor",
+            EffectTrigger::OnPlay,
+            EffectKind::GainArmor {
+                amount: 3,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+
+        let mut engine = EffectEngine::default();
+        engine.set_trace(true);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        let drawn = engine.queue_effect(draw, ctx.clone());
+        let armored = engine.queue_effect(armor, ctx);
+        engine.resolve_all(&mut state);
+        let trace = engine.take_trace();
+
+        let pushed: Vec<_> = trace
+            .iter()
+            .filter_map(|entry| match entry {
+                EffectTraceEntry::Pushed { stack_item_id, .. } => Some(*stack_item_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pushed, vec![drawn, armored]);
+        let checks: Vec<_> = trace
+            .iter()
+            .filter_map(|entry| match entry {
+                EffectTraceEntry::ConditionChecked {
+                    stack_item_id,
+                    passed,
+                    ..
+                } => Some((*stack_item_id, *passed)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            checks,
+            vec![(drawn, false), (armored, true)],
+            "drawing from an empty deck does not fire"
+        );
+        assert!(matches!(
+            trace.last(),
+            Some(EffectTraceEntry::Applied { stack_item_id, events, .. })
+                if *stack_item_id == armored
+                    && events == &[GameEvent::ArmorGained { player_id: 0, amount: 3 }]
+        ));
+        assert!(engine.take_trace().is_empty(), "taking the trace drains it");
+
+        engine.set_trace(false);
+        engine.queue_effect(
+            effect(
+                3,
+                "Gain 1 armor",
+                EffectTrigger::OnPlay,
+                EffectKind::GainArmor {
+                    amount: 1,
+                    target: EffectTarget::SourcePlayer,
+                },
+            ),
+            EffectContext::new(EffectTrigger::OnPlay, 0, 0),
+        );
+        engine.resolve_all(&mut state);
+        assert!(engine.take_trace().is_empty());
+    }
+
+    #[test]
+    fn charged_triggers_fire_only_until_spent() {
+        let bracer = effect(
+            4,
+            "The first time this takes damage, gain 2 armor",
+            EffectTrigger::OnDamage,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        )
+        .with_charges(1);
+        let guard = unit(70, 1, 5, vec![bracer]);
+        let mut state = game(vec![hero(0, vec![guard]), hero(1, Vec::new())], 1);
+
+        let mut engine = EffectEngine::default();
+        engine.damage_card(&mut state, 1, None, 0, 70, 1);
+        let events = engine.resolve_all(&mut state);
+        assert!(events.contains(&GameEvent::EffectChargeSpent {
+            player_id: 0,
+            card_id: 70,
+            effect_id: 4,
+            remaining: 0,
+        }));
+        assert_eq!(state.effect_charges(0, 70, 4), Some(0));
+
+        engine.damage_card(&mut state, 1, None, 0, 70, 1);
+        engine.resolve_all(&mut state);
+        assert_eq!(state.get_player(0).unwrap().armor, 2, "fires only once");
+
+        let json = serde_json::to_value(&state.get_player(0).unwrap().board[0]).unwrap();
+        assert_eq!(
+            json["effects"][0]["charges"], 0,
+            "remaining charges are serialized"
+        );
+    }
+
+    #[test]
+    fn revealed_hand_cards_stay_visible_in_the_revealers_view() {
+        let hand = vec![
+            unit(140, 1, 1, Vec::new()),
+            unit(141, 2, 2, Vec::new()),
+            unit(142, 3, 3, Vec::new()),
+        ];
+        let player_one = hero(0, Vec::new());
+        let mut player_two = hero(1, Vec::new());
+        player_two.hand = hand;
+        let mut state = game(vec![player_one, player_two], 0);
+        let peek = EffectKind::RevealHand {
+            count: 2,
+            target: EffectTarget::OpponentOfSource,
+        };
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+
+        let resolution = peek.apply(&ctx, &mut state);
+        let revealed = match resolution.events.as_slice() {
+            [GameEvent::HandCardsRevealed {
+                player_id: 1,
+                viewer: 0,
+                cards,
+            }] => cards.clone(),
+            other => panic!("unexpected events: {other:?}"),
+        };
+        assert_eq!(revealed.len(), 2);
+
+        let view = state.redacted_for(0);
+        let visible: Vec<_> = view.players[1]
+            .hand
+            .iter()
+            .filter(|card| !card.name.is_empty())
+            .map(|card| card.id)
+            .collect();
+        let expected: Vec<_> = revealed.iter().map(|card| card.id).collect();
+        assert_eq!(visible, expected);
+
+        assert!(peek.can_trigger(&ctx, &state));
+        peek.apply(&ctx, &mut state);
+        assert_eq!(state.players[1].revealed_hand.len(), 3);
+        assert!(!peek.can_trigger(&ctx, &state), "nothing is left to reveal");
+    }
+
+    #[test]
+    fn counters_accumulate_and_gate_conditional_effects() {
+        let charge = CardEffect::new(
+            5,
+            "At the start of your turn, add a charge counter",
+            EffectTrigger::OnTurnStart,
+            1,
+            EffectKind::AddCounters {
+                counter: "charge".to_string(),
+                amount: 1,
+                target: EffectTarget::SourceCard,
+            },
+        );
+        let discharge = effect(
+            6,
+            "With 3 charge counters, deal 10 damage and discharge",
+            EffectTrigger::OnTurnStart,
+            EffectKind::Composite {
+                effects: vec![
+                    EffectKind::DirectDamage {
+                        amount: 10,
+                        target: EffectTarget::OpponentOfSource,
+                        scaling: None,
+                    },
+                    EffectKind::RemoveCounters {
+                        counter: "charge".to_string(),
+                        amount: None,
+                        target: EffectTarget::SourceCard,
+                    },
+                ],
+            },
+        )
+        .with_condition(EffectCondition::CounterAtLeast {
+            counter: "charge".to_string(),
+            min: 3,
+        });
+        let battery = unit(80, 0, 3, vec![charge, discharge]);
+        let mut state = game(vec![hero(0, vec![battery]), hero(1, Vec::new())], 0);
+
+        let mut engine = EffectEngine::default();
+        let turn_start = |engine: &mut EffectEngine, state: &mut GameState| {
+            let card = state.players[0].board[0].clone();
+            let ctx = EffectContext::new(EffectTrigger::OnTurnStart, 0, 0).with_source_card(80);
+            engine.queue_card_effects(&card, ctx);
+            engine.resolve_all(state)
+        };
+        turn_start(&mut engine, &mut state);
+        let events = turn_start(&mut engine, &mut state);
+        assert!(events.contains(&GameEvent::CountersChanged {
+            player_id: 0,
+            card_id: 80,
+            counter: "charge".to_string(),
+            total: 2,
+        }));
+        assert_eq!(state.get_player(1).unwrap().health, 30);
+
+        turn_start(&mut engine, &mut state);
+        assert_eq!(state.get_player(1).unwrap().health, 20);
+        assert_eq!(state.players[0].board[0].counter("charge"), 0);
+        assert!(!serde_json::to_string(&state.players[0].board[0])
+            .unwrap()
+            .contains("charge\":"));
+    }
+
+    #[test]
+    fn hand_cards_upgrade_once_their_condition_holds() {
+        let larva = Card::new(90, "Larva", 1, 1, 1, CardType::Unit, Vec::new()).with_upgrade(
+            EffectCondition::PlayerHealthBelow {
+                target: EffectTarget::SourcePlayer,
+                threshold: 25,
+            },
+            Card::new(0, "Moth", 3, 3, 3, CardType::Unit, Vec::new()),
+        );
+        let mut player_one = hero(0, Vec::new());
+        player_one.hand = vec![larva];
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+        let mut engine = EffectEngine::default();
+
+        let hit = || {
+            CardEffect::new(
+                9100,
+                "Backlash",
+                EffectTrigger::OnPlay,
+                1,
+                EffectKind::DirectDamage {
+                    amount: 3,
+                    target: EffectTarget::SourcePlayer,
+                    scaling: None,
+                },
+            )
+        };
+        engine.queue_effect(hit(), EffectContext::new(EffectTrigger::OnPlay, 0, 0));
+        let events = engine.resolve_all(&mut state);
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::CardTransformed { .. })));
+        assert_eq!(state.players[0].hand[0].name, "Larva");
+
+        engine.queue_effect(hit(), EffectContext::new(EffectTrigger::OnPlay, 0, 0));
+        let events = engine.resolve_all(&mut state);
+        let transformed = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::CardTransformed {
+                        player_id: 0,
+                        card_id: 90,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(transformed, 1);
+        let moth = &state.players[0].hand[0];
+        assert_eq!((moth.id, moth.name.as_str(), moth.attack), (90, "Moth", 3));
+        assert!(
+            moth.upgrade.is_none(),
+            "an upgraded card does not upgrade again"
+        );
+
+        let view = state.redacted_for(1);
+        assert!(view.event_log.iter().any(|entry| matches!(
+            &entry.event,
+            GameEvent::CardTransformed { card, .. } if card.name != "Moth"
+        )));
+    }
+
+    #[test]
+    fn stat_aura_follows_board_composition() {
+        let leader_aura = effect(
+            1,
+            "Leader: other friendly units have +1 attack",
+            EffectTrigger::Passive,
+            EffectKind::StatAura {
+                attack: 1,
+                health: 0,
+                scope: AuraScope::OtherFriendlyUnits,
+            },
+        );
+        let leader = unit(10, 2, 2, vec![leader_aura]);
+        let follower = unit(11, 1, 1, Vec::new());
+        let enemy = unit(20, 3, 3, Vec::new());
+
+        let mut state = game(
+            vec![hero(0, vec![leader, follower]), hero(1, vec![enemy])],
+            0,
+        );
+
+        let mut engine = EffectEngine::default();
+        let events = engine.refresh_auras(&mut state);
+        assert_eq!(events.len(), 1, "only the follower is covered");
+        let attack_of = |state: &GameState, owner: PlayerId, id: CardId| {
+            state
+                .get_player(owner)
+                .and_then(|player| player.board.iter().find(|card| card.id == id))
+                .map(|card| card.attack)
+        };
+        assert_eq!(attack_of(&state, 0, 11), Some(2));
+        assert_eq!(
+            attack_of(&state, 0, 10),
+            Some(2),
+            "aura excludes its source"
+        );
+        assert_eq!(attack_of(&state, 1, 20), Some(3));
+
+        assert!(
+            engine.refresh_auras(&mut state).is_empty(),
+            "refresh is stable"
+        );
+
+        state.damage_card(1, None, 0, 10, 5);
+        engine.refresh_auras(&mut state);
+        assert_eq!(
+            attack_of(&state, 0, 11),
+            Some(1),
+            "aura ends with its source"
+        );
+    }
+
+    #[test]
+    fn silencing_a_damaged_buffed_unit_kills_it() {
+        let soldier = unit(113, 2, 2, Vec::new());
+        let mut state = game(vec![hero(0, vec![soldier]), hero(1, Vec::new())], 1);
+        let blessing = EffectKind::ModifyStats {
+            attack: 0,
+            health: 2,
+            target: EffectTarget::SourceCard,
+            duration: EffectDuration::Permanent,
+        };
+        blessing.apply(
+            &EffectContext::new(EffectTrigger::OnPlay, 0, 1).with_source_card(113),
+            &mut state,
+        );
+        let mut engine = EffectEngine::default();
+        engine.damage_card(&mut state, 1, None, 0, 113, 3);
+        engine.resolve_all(&mut state);
+        assert_eq!(state.players[0].board[0].health, 1);
+
+        let hush = effect(
+            14,
+            "Silence a unit",
+            EffectTrigger::OnPlay,
+            EffectKind::Silence {
+                target: EffectTarget::ContextTarget,
+            },
+        );
+        let spell = Card::new(114, "Hush", 1, 0, 0, CardType::Spell, vec![hush]);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 1, 1).with_target_card(0, 113);
+        engine.queue_card_effects(&spell, ctx);
+        let events = engine.resolve_all(&mut state);
+
+        assert!(state.players[0].board.is_empty());
+        assert_eq!(state.players[0].graveyard.len(), 1);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::CardDestroyed { card, .. } if card.id == 113
+        )));
+    }
+
+    #[test]
+    fn enchantments_record_sources_and_revert_on_removal() {
+        let banner = effect(
+            3,
+            "Banner: friendly units have +0/+1",
+            EffectTrigger::Passive,
+            EffectKind::StatAura {
+                attack: 0,
+                health: 1,
+                scope: AuraScope::FriendlyUnits,
+            },
+        );
+        let bearer = unit(30, 1, 1, vec![banner]);
+        let mut state = game(vec![hero(0, vec![bearer]), hero(1, Vec::new())], 0);
+
+        let blessing = EffectKind::ModifyStats {
+            attack: 2,
+            health: 2,
+            target: EffectTarget::SourceCard,
+            duration: EffectDuration::Permanent,
+        };
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_source_card(30);
+        blessing.apply(&ctx, &mut state);
+        let mut engine = EffectEngine::default();
+        engine.refresh_auras(&mut state);
+
+        let card = &mut state.players[0].board[0];
+        assert_eq!((card.attack, card.health), (3, 4));
+        assert_eq!(card.enchantments.len(), 2);
+        assert!(card
+            .enchantments
+            .iter()
+            .all(|item| item.source_card == Some(30)));
+        assert_eq!(
+            card.aura_bonus(),
+            StatModifier {
+                attack: 0,
+                health: 1
+            }
+        );
+
+        let removed =
+            card.remove_enchantments(|item| item.duration == EnchantmentDuration::Permanent);
+        assert_eq!(removed.len(), 1);
+        assert_eq!((card.attack, card.health), (1, 2));
+    }
+
+    #[test]
+    fn resurrected_units_are_restored_and_fire_summon_listeners() {
+        let watcher_effect = effect(
+            4,
+            "Whenever another friendly unit is summoned, gain 1 armor",
+            EffectTrigger::OnSummon,
+            EffectKind::GainArmor {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let watcher = unit(40, 1, 1, vec![watcher_effect]);
+        let mut fallen = unit(41, 5, -1, Vec::new());
+        fallen.damage_taken = 4;
+        fallen.enchantments.push(Enchantment {
+            source_player: Some(0),
+            source_card: None,
+            attack: 2,
+            health: 0,
+            duration: EnchantmentDuration::Permanent,
+        });
+        let mut player_one = hero(0, vec![watcher]);
+        player_one.graveyard.push(fallen);
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+
+        let mut engine = EffectEngine::default();
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        let revive = effect(
+            5,
+            "Resurrect two units",
+            EffectTrigger::OnPlay,
+            EffectKind::Resurrect { count: 2 },
+        );
+        engine.queue_effect(revive, ctx.clone());
+        engine.resolve_all(&mut state);
+
+        let player = state.get_player(0).unwrap();
+        assert!(player.graveyard.is_empty());
+        assert_eq!(player.armor, 1);
+        let revived = player.board.iter().find(|card| card.id == 41).unwrap();
+        assert_eq!((revived.attack, revived.health), (3, 3));
+        assert!(revived.enchantments.is_empty());
+
+        let polymorph = EffectKind::SetStats {
+            attack: 1,
+            health: 1,
+            target: EffectTarget::SourceCard,
+        };
+        polymorph.apply(&ctx.with_source_card(41), &mut state);
+        let revived = state.players[0]
+            .board
+            .iter()
+            .find(|card| card.id == 41)
+            .unwrap();
+        assert_eq!((revived.attack, revived.health), (1, 1));
+    }
+
+    #[test]
+    fn resolution_stops_at_budget_and_discards_the_rest() {
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, Vec::new())], 0);
+
+        let mut engine = EffectEngine::default().with_resolution_budget(3);
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        for id in 0..5 {
+            let armor = effect(
+                id,
+                "Gain 1 armor",
+                EffectTrigger::OnPlay,
+                EffectKind::GainArmor {
+                    amount: 1,
+                    target: EffectTarget::SourcePlayer,
+                },
+            );
+            engine.queue_effect(armor, ctx.clone());
+        }
+        let events = engine.resolve_all(&mut state);
+
+        assert_eq!(state.players[0].armor, 3);
+        assert!(engine.stack().is_empty());
+        assert!(events.contains(&GameEvent::ResolutionAborted {
+            budget: 3,
+            discarded: 2,
+        }));
+    }
+
+    #[test]
+    fn tribal_effects_target_and_check_friendly_tribes() {
+        let wolf = unit(50, 2, 2, Vec::new()).with_tribes([Tribe::Beast]);
+        let golem = unit(51, 2, 2, Vec::new()).with_tribes([Tribe::Mech]);
+        let enemy_boar = unit(52, 1, 1, Vec::new()).with_tribes([Tribe::Beast]);
+        let mut state = game(
+            vec![hero(0, vec![wolf, golem]), hero(1, vec![enemy_boar])],
+            0,
+        );
+
+        let beasts = TargetFilter {
+            tribe: Some(Tribe::Beast),
+            ..TargetFilter::default()
+        };
+        let rally = EffectKind::Conditional {
+            condition: Box::new(EffectCondition::ControlsTribe {
+                tribe: Tribe::Beast,
+            }),
+            effect: Box::new(EffectKind::ModifyStats {
+                attack: 1,
+                health: 0,
+                target: EffectTarget::AllFriendlyUnits { filter: beasts },
+                duration: EffectDuration::Permanent,
+            }),
+        };
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        assert!(rally.can_trigger(&ctx, &state));
+        rally.apply(&ctx, &mut state);
+
+        let attacks: Vec<i16> = state.players[0]
+            .board
+            .iter()
+            .map(|card| card.attack)
+            .collect();
+        assert_eq!(attacks, vec![3, 2]);
+        assert_eq!(state.players[1].board[0].attack, 1);
+
+        let enemy_ctx = EffectContext::new(EffectTrigger::OnPlay, 1, 0).with_source_card(52);
+        let condition = EffectCondition::ControlsTribe {
+            tribe: Tribe::Beast,
+        };
+        assert!(!condition.is_satisfied(&enemy_ctx, &state));
+    }
+
+    #[test]
+    fn player_enchantments_adjust_costs_and_damage_until_expiry() {
+        let spell = Card::new(60, "Bolt", 3, 0, 0, CardType::Spell, Vec::new());
+        let minion = unit(61, 2, 2, Vec::new());
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, Vec::new())], 0);
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        let discount = EffectKind::EnchantPlayer {
+            modifier: PlayerModifier::Cost {
+                delta: -1,
+                card_type: Some(CardType::Spell),
+            },
+            target: EffectTarget::SourcePlayer,
+            duration: EffectDuration::Permanent,
+        };
+        let ward = EffectKind::EnchantPlayer {
+            modifier: PlayerModifier::DamageTaken { delta: -1 },
+            target: EffectTarget::SourcePlayer,
+            duration: EffectDuration::ThisTurn,
+        };
+        discount.apply(&ctx, &mut state);
+        ward.apply(&ctx, &mut state);
+
+        assert_eq!(state.card_cost(0, &spell), 2);
+        assert_eq!(state.card_cost(0, &minion), 1);
+        state.damage_player(1, None, 0, 3);
+        assert_eq!(state.players[0].health, 28);
+
+        let events = state.expire_timed_effects(Expiry::EndOfTurn { player_id: 0 });
+        assert_eq!(events.len(), 1);
+        state.damage_player(1, None, 0, 3);
+        assert_eq!(state.players[0].health, 25);
+        assert_eq!(state.card_cost(0, &spell), 2);
+    }
+
+    #[test]
+    fn game_progress_conditions_track_turns_hands_and_deaths() {
+        let veteran = unit(70, 2, 4, Vec::new());
+        let recruit = unit(71, 1, 1, Vec::new());
+        let filler = Card::new(72, "Filler", 1, 0, 0, CardType::Spell, Vec::new());
+        let mut player_one = hero(0, vec![veteran]);
+        player_one.hand = vec![filler];
+        let player_two = hero(1, vec![recruit]);
+        let mut state = game(vec![player_one, player_two], 0);
+        state.turn = 4;
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_source_card(70);
+        let holds =
+            |condition: EffectCondition, state: &GameState| condition.is_satisfied(&ctx, state);
+        assert!(holds(EffectCondition::TurnAtLeast { turn: 4 }, &state));
+        assert!(!holds(EffectCondition::TurnAtLeast { turn: 5 }, &state));
+        let hand = EffectCondition::HandSizeAtLeast {
+            target: EffectTarget::SourcePlayer,
+            min: 1,
+        };
+        assert!(holds(hand, &state));
+        assert!(!holds(EffectCondition::SourceCardDamaged, &state));
+
+        let mut engine = EffectEngine::default();
+        engine.damage_card(&mut state, 1, None, 0, 70, 1);
+        engine.damage_card(&mut state, 0, None, 1, 71, 1);
+        assert!(holds(EffectCondition::SourceCardDamaged, &state));
+        let enemy_deaths = EffectCondition::UnitsDiedThisGameAtLeast {
+            target: Some(EffectTarget::OpponentOfSource),
+            min: 1,
+        };
+        let own_deaths = EffectCondition::UnitsDiedThisGameAtLeast {
+            target: Some(EffectTarget::SourcePlayer),
+            min: 1,
+        };
+        assert!(holds(enemy_deaths, &state));
+        assert!(!holds(own_deaths, &state));
+    }
+
+    #[test]
+    fn damage_routed_through_engine_fires_on_damage_listeners() {
+        let armor_up = effect(
+            2,
+            "Bracer: whenever this takes damage, gain 2 armor",
+            EffectTrigger::OnDamage,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let bracer = unit(30, 1, 1, vec![armor_up]);
+        let mut state = game(vec![hero(0, vec![bracer]), hero(1, Vec::new())], 1);
+
+        let mut engine = EffectEngine::default();
+        engine.damage_card(&mut state, 1, None, 0, 30, 3);
+        let events = engine.resolve_all(&mut state);
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::ArmorGained {
+                player_id: 0,
+                amount: 2
+            }
+        )));
+        assert!(state
+            .events()
+            .any(|event| matches!(event, GameEvent::CardDestroyed { player_id: 0, .. })));
+    }
+
+    #[test]
+    fn damage_prevention_applies_before_armor() {
+        let minion = unit(40, 1, 2, Vec::new());
+        let mut player_one = hero(0, vec![minion]);
+        player_one.armor = 2;
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 1);
+
+        let shield = effect(
+            3,
+            "Prevent the next 3 damage dealt to your hero",
+            EffectTrigger::OnPlay,
+            EffectKind::PreventDamage {
+                amount: 3,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let mut engine = EffectEngine::default();
+        engine.queue_effect(shield, EffectContext::new(EffectTrigger::OnPlay, 0, 1));
+        engine.resolve_all(&mut state);
+
+        let events = engine.damage_player(&mut state, 1, None, 0, 6);
+        assert!(matches!(
+            events.as_slice(),
+            [
+                GameEvent::DamagePrevented { amount: 3, .. },
+                GameEvent::DamageResolved { amount: 3, .. }
+            ]
+        ));
+        let player = state.get_player(0).unwrap();
+        assert_eq!(
+            (player.damage_prevention, player.armor, player.health),
+            (0, 0, 29)
+        );
+
+        state.grant_damage_prevention(0, Some(40), 5);
+        let events = engine.damage_card(&mut state, 1, None, 0, 40, 4);
+        assert!(matches!(
+            events.as_slice(),
+            [GameEvent::DamagePrevented { amount: 4, .. }]
+        ));
+        let minion = &state.get_player(0).unwrap().board[0];
+        assert_eq!((minion.health, minion.damage_prevention), (2, 1));
+    }
+
+    #[test]
+    fn scaled_amounts_are_evaluated_at_resolution() {
+        let board = vec![unit(50, 1, 1, Vec::new()), unit(51, 1, 1, Vec::new())];
+        let mut player_one = hero(0, board);
+        player_one.health = 24;
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+
+        let volley = EffectKind::DirectDamage {
+            amount: 1,
+            target: EffectTarget::OpponentOfSource,
+            scaling: Some(AmountScaling::PerFriendlyUnit { multiplier: 2 }),
+        };
+        let recover = EffectKind::Heal {
+            amount: 0,
+            target: EffectTarget::SourcePlayer,
+            scaling: Some(AmountScaling::EqualsMissingHealth),
+        };
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        volley.apply(&ctx, &mut state);
+        recover.apply(&ctx, &mut state);
+
+        assert_eq!(state.get_player(1).unwrap().health, 25);
+        assert_eq!(state.get_player(0).unwrap().health, 30);
+    }
+
+    #[test]
+    fn stack_entries_round_trip_in_resolution_order() {
+        let ping = |id, priority| {
+            CardEffect::new(
+                id,
+                format!("Ping {}", id),
+                EffectTrigger::OnPlay,
+                priority,
+                EffectKind::DirectDamage {
+                    amount: 1,
+                    target: EffectTarget::OpponentOfSource,
+                    scaling: None,
+                },
+            )
+        };
+        let mut engine = EffectEngine::default();
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_target_player(1);
+        let low = engine.queue_effect(ping(1, 0), ctx.clone());
+        let high = engine.queue_effect(ping(2, 5), ctx);
+
+        let entries = engine.stack().entries();
+        assert_eq!(
+            entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![high, low]
+        );
+        assert_eq!(entries[0].description, "Ping 2");
+        assert_eq!(entries[0].target_player, Some(1));
+
+        let json = serde_json::to_string(engine.stack()).unwrap();
+        let restored: EffectStack = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.entries(), entries);
+    }
+
+    #[test]
+    fn losing_a_health_aura_kills_units_whose_damage_now_exceeds_their_health() {
+        let banner = effect(
+            11,
+            "Banner: other friendly units have +0/+2",
+            EffectTrigger::Passive,
+            EffectKind::StatAura {
+                attack: 0,
+                health: 2,
+                scope: AuraScope::OtherFriendlyUnits,
+            },
+        );
+        let last_stand = effect(
+            12,
+            "Deathrattle: gain 1 armor",
+            EffectTrigger::OnDeath,
+            EffectKind::GainArmor {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let leader = unit(110, 1, 1, vec![banner]);
+        let soldier = unit(111, 2, 2, vec![last_stand]);
+        let survivor = unit(112, 2, 2, Vec::new());
+        let mut state = game(
+            vec![
+                hero(0, vec![leader, soldier, survivor]),
+                hero(1, Vec::new()),
+            ],
+            1,
+        );
+        let mut engine = EffectEngine::default();
+        engine.refresh_auras(&mut state);
+
+        engine.damage_card(&mut state, 1, None, 0, 111, 3);
+        engine.damage_card(&mut state, 1, None, 0, 112, 1);
+        engine.resolve_all(&mut state);
+        assert_eq!(
+            state.players[0].board[1].health, 1,
+            "the aura keeps it alive"
+        );
+
+        let mut events = engine.damage_card(&mut state, 1, None, 0, 110, 1);
+        events.extend(engine.resolve_all(&mut state));
+
+        let player = state.get_player(0).unwrap();
+        let board: Vec<(CardId, i16)> = player.board.iter().map(|c| (c.id, c.health)).collect();
+        assert_eq!(board, vec![(112, 1)], "undamaged health is kept");
+        assert_eq!(player.armor, 1, "the aura death fires its deathrattle");
+        let deaths: Vec<CardId> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::CardDestroyed { card, .. } => Some(card.id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deaths, vec![110, 111]);
+    }
+
+    #[test]
+    fn stat_penalties_that_drop_health_to_zero_kill_through_the_death_path() {
+        let last_stand = effect(
+            13,
+            "Deathrattle: gain 2 armor",
+            EffectTrigger::OnDeath,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let victim = unit(120, 3, 2, vec![last_stand]);
+        let mut state = game(vec![hero(0, Vec::new()), hero(1, vec![victim])], 0);
+        let mut engine = EffectEngine::default();
+
+        let wither = effect(
+            14,
+            "Wither: give a unit -0/-3",
+            EffectTrigger::OnPlay,
+            EffectKind::ModifyStats {
+                attack: 0,
+                health: -3,
+                target: EffectTarget::ContextTarget,
+                duration: EffectDuration::Permanent,
+            },
+        );
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_target_card(1, 120);
+        engine.queue_effect(wither, ctx);
+        let events = engine.resolve_all(&mut state);
+
+        let order: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::StatsModified { .. } => Some("modified"),
+                GameEvent::CardDestroyed { .. } => Some("destroyed"),
+                GameEvent::ArmorGained { .. } => Some("deathrattle"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(order, vec!["modified", "destroyed", "deathrattle"]);
+
+        let owner = state.get_player(1).unwrap();
+        assert!(owner.board.is_empty());
+        assert_eq!(owner.graveyard.len(), 1);
+        assert_eq!(owner.armor, 2);
+        assert_eq!(owner.units_died, 1);
+        assert_eq!(state.get_player(0).unwrap().stats.units_killed, 1);
+    }
+
+    #[test]
+    fn heal_draw_and_summon_listeners_react_to_their_events() {
+        let armor = |id, trigger, amount| {
+            effect(
+                id,
+                "Listener: gain armor",
+                trigger,
+                EffectKind::GainArmor {
+                    amount,
+                    target: EffectTarget::SourcePlayer,
+                },
+            )
+        };
+        let listener = unit(
+            130,
+            1,
+            3,
+            vec![
+                armor(15, EffectTrigger::OnHeal, 1),
+                armor(16, EffectTrigger::OnDraw, 2),
+                armor(17, EffectTrigger::OnSummon, 4),
+            ],
+        );
+        let deck = vec![unit(131, 1, 1, Vec::new())];
+        let mut player_one = hero(0, vec![listener]);
+        player_one.deck = deck;
+        player_one.health = 20;
+        player_one.graveyard.push(unit(132, 1, 1, Vec::new()));
+        let player_two = hero(1, Vec::new());
+        let mut state = game(vec![player_one, player_two], 0);
+        let mut engine = EffectEngine::default();
+
+        let mut cast = |id, kind| {
+            let effect = effect(id, "Spell", EffectTrigger::OnPlay, kind);
+            engine.queue_effect(effect, EffectContext::new(EffectTrigger::OnPlay, 0, 0));
+            engine.resolve_all(&mut state);
+            state.get_player(0).unwrap().armor
+        };
+
+        let heal = EffectKind::Heal {
+            amount: 3,
+            target: EffectTarget::SourcePlayer,
+            scaling: None,
+        };
+        assert_eq!(cast(18, heal), 1, "healing fires OnHeal");
+        let draw = EffectKind::DrawCard {
+            count: 1,
+            target: EffectTarget::SourcePlayer,
+            scaling: None,
+        };
+        assert_eq!(cast(19, draw), 3, "drawing fires OnDraw");
+        assert_eq!(
+            cast(20, EffectKind::Resurrect { count: 1 }),
+            7,
+            "summoning another unit fires OnSummon"
+        );
+    }
+
+    #[test]
+    fn revealed_hand_cards_are_visible_only_to_the_revealer() {
+        let hand = vec![unit(150, 1, 1, Vec::new()), unit(151, 2, 2, Vec::new())];
+        let mut target = hero(1, Vec::new());
+        target.hand = hand;
+        let mut state = game(vec![hero(0, Vec::new()), target, hero(2, Vec::new())], 0);
+        let mut engine = EffectEngine::default();
+        let peek = || {
+            effect(
+                21,
+                "Reveal a card in the target's hand",
+                EffectTrigger::OnPlay,
+                EffectKind::RevealHand {
+                    count: 1,
+                    target: EffectTarget::TargetPlayer,
+                },
+            )
+        };
+        let cast =
+            |source| EffectContext::new(EffectTrigger::OnPlay, source, 0).with_target_player(1);
+
+        engine.queue_effect(peek(), cast(0));
+        engine.resolve_all(&mut state);
+        let owner = state.get_player(1).unwrap();
+        let seen = owner.revealed_hand[0].card_id;
+        assert!(owner.is_revealed_to(seen, 0));
+        assert!(!owner.is_revealed_to(seen, 2));
+        assert!(owner.is_revealed_to(seen, SPECTATOR));
+
+        let visible = |viewer| -> Vec<CardId> {
+            state.redacted_for(viewer).players[1]
+                .hand
+                .iter()
+                .filter(|card| !card.name.is_empty())
+                .map(|card| card.id)
+                .collect()
+        };
+        assert_eq!(visible(0), vec![seen]);
+        assert!(
+            visible(2).is_empty(),
+            "a third player did not see the reveal"
+        );
+        assert!(state.redacted_for(2).players[1].revealed_hand.is_empty());
+        assert_eq!(visible(SPECTATOR), vec![seen]);
+
+        engine.queue_effect(peek(), cast(0));
+        engine.queue_effect(peek(), cast(0));
+        engine.resolve_all(&mut state);
+        assert_eq!(
+            state.get_player(1).unwrap().revealed_hand.len(),
+            2,
+            "each card is revealed to a viewer at most once"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! 规则引擎的操作拦截器，供游戏模式、教程与反作弊在不改动引擎的前提下介入操作。
+
+use std::fmt;
+use std::rc::Rc;
+
+use super::{
+    rules::{GameAction, RuleError},
+    state::{GameEvent, GameState},
+};
+
+/// 操作执行前调用，返回错误即否决该操作（`validate` 同样会调用）。
+pub type PreActionHook = dyn Fn(&GameState, &GameAction) -> Result<(), RuleError>;
+
+/// 操作成功后调用，可修改状态并返回需要追加的事件。
+pub type PostActionHook = dyn Fn(&mut GameState, &GameAction, &[GameEvent]) -> Vec<GameEvent>;
+
+/// 已注册的拦截器，按注册顺序依次调用；克隆引擎时共享同一批拦截器。
+#[derive(Clone, Default)]
+pub struct ActionHooks {
+    pre: Vec<Rc<PreActionHook>>,
+    post: Vec<Rc<PostActionHook>>,
+}
+
+impl ActionHooks {
+    pub fn add_pre<F>(&mut self, hook: F)
+    where
+        F: Fn(&GameState, &GameAction) -> Result<(), RuleError> + 'static,
+    {
+        self.pre.push(Rc::new(hook));
+    }
+
+    pub fn add_post<F>(&mut self, hook: F)
+    where
+        F: Fn(&mut GameState, &GameAction, &[GameEvent]) -> Vec<GameEvent> + 'static,
+    {
+        self.post.push(Rc::new(hook));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.pre.clear();
+        self.post.clear();
+    }
+
+    /// 依次调用执行前拦截器，返回第一个否决。
+    pub fn check(&self, state: &GameState, action: &GameAction) -> Result<(), RuleError> {
+        self.pre.iter().try_for_each(|hook| hook(state, action))
+    }
+
+    /// 依次调用执行后拦截器，追加的事件写入日志并返回。
+    pub fn after(
+        &self,
+        state: &mut GameState,
+        action: &GameAction,
+        events: &[GameEvent],
+    ) -> Vec<GameEvent> {
+        let mut injected = Vec::new();
+        for hook in &self.post {
+            for event in hook(state, action, events) {
+                state.record_event(event.clone());
+                injected.push(event);
+            }
+        }
+        injected
+    }
+}
+
+impl fmt::Debug for ActionHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActionHooks")
+            .field("pre", &self.pre.len())
+            .field("post", &self.post.len())
+            .finish()
+    }
+}
@@ -0,0 +1,260 @@
+//! 存档格式版本与迁移：载入旧存档时先按版本逐步升级 JSON，再还原为游戏状态。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::state::{GameState, IntegrityError};
+
+/// 当前存档格式版本，新建的状态都以此版本序列化。
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 载入存档的结果：记录起止版本与实际执行的迁移步骤，失败时附带原因。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// 实际改动了存档的迁移步骤说明，按执行顺序排列。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<MigrationError>,
+}
+
+impl MigrationReport {
+    fn failed(mut self, error: MigrationError) -> Self {
+        self.error = Some(error);
+        self
+    }
+}
+
+/// 存档无法载入的原因。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum MigrationError {
+    InvalidJson {
+        message: String,
+    },
+    /// 存档来自更新版本的引擎。
+    UnsupportedVersion {
+        version: u32,
+        latest: u32,
+    },
+    /// 迁移后的数据仍无法还原为游戏状态。
+    InvalidState {
+        message: String,
+    },
+    IntegrityViolation {
+        error: IntegrityError,
+    },
+}
+
+/// 把存档升级一个版本，返回实际执行的步骤说明。
+type Migration = fn(&mut Map<String, Value>) -> Vec<String>;
+
+/// 第 `n` 项把版本 `n` 的存档升级到 `n + 1`。
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0, migrate_v1];
+
+impl GameState {
+    /// 载入任意版本的存档：逐步迁移、还原状态并检查完整性。
+    pub fn load_json(json: &str) -> Result<(Self, MigrationReport), MigrationReport> {
+        let mut report = MigrationReport {
+            from_version: 0,
+            to_version: SCHEMA_VERSION,
+            applied: Vec::new(),
+            error: None,
+        };
+        let mut value: Value = match serde_json::from_str(json) {
+            Ok(value) => value,
+            Err(error) => {
+                return Err(report.failed(MigrationError::InvalidJson {
+                    message: error.to_string(),
+                }))
+            }
+        };
+        let Some(fields) = value.as_object_mut() else {
+            return Err(report.failed(MigrationError::InvalidState {
+                message: "存档必须是 JSON 对象".to_string(),
+            }));
+        };
+
+        let version = fields
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+        report.from_version = version;
+        if version > SCHEMA_VERSION {
+            return Err(report.failed(MigrationError::UnsupportedVersion {
+                version,
+                latest: SCHEMA_VERSION,
+            }));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            report.applied.extend(migration(fields));
+        }
+        fields.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+
+        let mut state: GameState = match serde_json::from_value(value) {
+            Ok(state) => state,
+            Err(error) => {
+                return Err(report.failed(MigrationError::InvalidState {
+                    message: error.to_string(),
+                }))
+            }
+        };
+        state.reconcile_after_load();
+        if let Err(error) = state.integrity_check() {
+            return Err(report.failed(MigrationError::IntegrityViolation { error }));
+        }
+        Ok((state, report))
+    }
+}
+
+/// 版本 0：待弃牌记录与计数器改名为通用的待选择项，事件日志补上序号，补齐法力水晶上限。
+fn migrate_v0(fields: &mut Map<String, Value>) -> Vec<String> {
+    let mut applied = Vec::new();
+    if let Some(next_id) = fields.remove("next_pending_discard_id") {
+        fields.entry("next_pending_choice_id").or_insert(next_id);
+        applied.push("next_pending_discard_id 改名为 next_pending_choice_id".to_string());
+    }
+
+    if let Some(Value::Array(discards)) = fields.remove("pending_discards") {
+        if !discards.is_empty() {
+            let choices = fields
+                .entry("pending_choices")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(choices) = choices {
+                for discard in discards {
+                    choices.push(json!({
+                        "id": discard["id"],
+                        "player_id": discard["player_id"],
+                        "kind": { "type": "Discard", "drawn_card": discard["drawn_card"] },
+                    }));
+                }
+            }
+            applied.push("pending_discards 迁移为 Discard 类型的 pending_choices".to_string());
+        }
+    }
+
+    if let Some(Value::Array(entries)) = fields.get_mut("event_log") {
+        let unnumbered = entries.iter().any(|entry| entry.get("seq").is_none());
+        if unnumbered {
+            for (entry, seq) in entries.iter_mut().zip(1u64..) {
+                if let Some(entry) = entry.as_object_mut() {
+                    entry.insert("seq".to_string(), json!(seq));
+                }
+            }
+            applied.push("事件日志按记录顺序补充序号".to_string());
+        }
+    }
+
+    let mut capped = false;
+    let players = fields.get_mut("players").and_then(Value::as_array_mut);
+    for player in players.into_iter().flatten() {
+        let Some(player) = player.as_object_mut() else {
+            continue;
+        };
+        if !player.contains_key("max_mana") {
+            let mana = player.get("mana").cloned().unwrap_or_else(|| json!(0));
+            player.insert("max_mana".to_string(), mana);
+            capped = true;
+        }
+    }
+    if capped {
+        applied.push("缺少 max_mana 的玩家以当前法力作为水晶上限".to_string());
+    }
+    applied
+}
+
+/// 版本 1：玩家身上的伤害与出牌计数移入统计对象。
+fn migrate_v1(fields: &mut Map<String, Value>) -> Vec<String> {
+    let mut moved = false;
+    let players = fields.get_mut("players").and_then(Value::as_array_mut);
+    for player in players.into_iter().flatten() {
+        let Some(player) = player.as_object_mut() else {
+            continue;
+        };
+        for key in ["damage_dealt", "cards_played"] {
+            let Some(count) = player.remove(key) else {
+                continue;
+            };
+            let stats = player.entry("stats").or_insert_with(|| json!({}));
+            if let Some(stats) = stats.as_object_mut() {
+                stats.entry(key).or_insert(count);
+            }
+            moved = true;
+        }
+    }
+    if moved {
+        vec!["玩家的 damage_dealt 与 cards_played 移入 stats".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ChoiceKind;
+
+    #[test]
+    fn legacy_saves_are_migrated_with_a_report() {
+        let mut legacy = serde_json::to_value(GameState::sample()).unwrap();
+        let fields = legacy.as_object_mut().unwrap();
+        fields.remove("schema_version");
+        fields.remove("next_pending_choice_id");
+        fields.insert("next_pending_discard_id".to_string(), json!(4));
+        fields.insert(
+            "pending_discards".to_string(),
+            json!([{
+                "id": 3,
+                "player_id": 0,
+                "drawn_card": { "id": 99, "name": "Spare", "cost": 1, "attack": 1, "health": 1 },
+            }]),
+        );
+        fields.insert(
+            "event_log".to_string(),
+            json!([{ "type": "TurnEnded", "player_id": 0 }]),
+        );
+        let player = fields["players"][0].as_object_mut().unwrap();
+        player.remove("stats");
+        player.insert("damage_dealt".to_string(), json!(7));
+        player.remove("max_mana");
+        player.insert("mana".to_string(), json!(3));
+        let (state, report) = GameState::load_json(&legacy.to_string()).expect("migrates");
+        assert_eq!(
+            (report.from_version, report.to_version),
+            (0, SCHEMA_VERSION)
+        );
+        assert_eq!(report.applied.len(), 5);
+        assert_eq!(report.error, None);
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert_eq!(state.next_pending_choice_id, 4);
+        assert!(matches!(
+            state.pending_choices[0].kind,
+            ChoiceKind::Discard { ref drawn_card } if drawn_card.id == 99
+        ));
+        assert_eq!(state.event_log[0].seq, 1);
+        assert_eq!(state.players[0].stats.damage_dealt, 7);
+        assert_eq!((state.players[0].mana, state.players[0].max_mana), (3, 3));
+
+        let current = serde_json::to_string(&GameState::sample()).unwrap();
+        let (_, report) = GameState::load_json(&current).expect("current saves load");
+        assert_eq!(report.from_version, SCHEMA_VERSION);
+        assert!(report.applied.is_empty());
+
+        let future = json!({ "schema_version": SCHEMA_VERSION + 1 }).to_string();
+        let report = GameState::load_json(&future).unwrap_err();
+        assert_eq!(
+            report.error,
+            Some(MigrationError::UnsupportedVersion {
+                version: SCHEMA_VERSION + 1,
+                latest: SCHEMA_VERSION,
+            })
+        );
+        let report = GameState::load_json("{ not json").unwrap_err();
+        assert!(matches!(
+            report.error,
+            Some(MigrationError::InvalidJson { .. })
+        ));
+    }
+}
@@ -1,39 +1,50 @@
 //! 游戏核心逻辑模块（状态机、规则引擎等）。
 
+pub mod config;
 pub mod effects;
+pub mod hooks;
+pub mod migration;
+pub mod narration;
+pub mod patch;
+pub mod registry;
+pub mod replay;
+pub mod rng;
 pub mod rules;
+pub mod spectator;
 pub mod state;
+pub mod validation;
 
+pub use config::{DeckOutRule, RuleConfig, Tiebreaker, DEFAULT_STARTING_HEALTH};
 pub use effects::{
-    EffectCondition,
-    EffectContext,
-    EffectEngine,
-    EffectKind,
-    EffectResolution,
-    EffectStack,
-    EffectTarget,
-    EffectTrigger,
+    AmountScaling, AuraScope, ChoiceOption, EffectCondition, EffectContext, EffectDuration,
+    EffectEngine, EffectKind, EffectResolution, EffectStack, EffectTarget, EffectTraceEntry,
+    EffectTrigger, FilterFailure, StackEntry, StackItemId, TargetFilter,
 };
-pub use state::{
-    Card,
-    CardEffect,
-    CardId,
-    CardType,
-    GameEvent,
-    GamePhase,
-    GameState,
-    IntegrityError,
-    Player,
-    PlayerId,
-    VictoryReason,
-    VictoryState,
+pub use hooks::{ActionHooks, PostActionHook, PreActionHook};
+pub use migration::{MigrationError, MigrationReport, SCHEMA_VERSION};
+pub use narration::{Locale, NarratedEvent, Narrator};
+pub use patch::{PatchError, PatchOp, StatePatch};
+pub use registry::{CardDefinition, CardFilter, CardRegistry, DefId, Rarity};
+pub use replay::{
+    Replay, ReplayFailure, ReplayPlayback, ReplayStart, ReplayStep, REPLAY_FORMAT_VERSION,
 };
+pub use rng::GameRng;
 pub use rules::{
-    AttackAction,
-    DiscardCardAction,
-    MulliganAction,
-    PlayCardAction,
-    RuleEngine,
-    RuleError,
-    RuleResolution,
+    AttackAction, AttackTargetOption, DiscardCardAction, GameAction, HeroAttackAction,
+    MulliganAction, MulliganPolicy, PassPriorityAction, PlayCardAction, ReplayError,
+    ResolveChoiceAction, ResolveCombatAction, RuleEngine, RuleError, RuleErrorReport,
+    RuleResolution, UseHeroPowerAction,
+};
+pub use spectator::{SpectatorFeed, SpectatorFrame};
+pub use state::{
+    ActionKind, AdditionalCost, AlternativeVictory, Card, CardEffect, CardId, CardStat, CardType,
+    CardUpgrade, ChoiceKind, ChoiceTarget, DrawReason, Enchantment, EnchantmentDuration, Expiry,
+    GameEvent, GameOutcome, GamePhase, GameSetup, GameState, Hero, HeroPower, IntegrityError,
+    Keyword, LoggedEvent, PendingAttack, PendingChoice, Player, PlayerEnchantment, PlayerId,
+    PlayerModifier, PlayerStats, PlayerSummary, PriorityWindow, RevealedCard, StatModifier, TeamId,
+    TimedEffect, TimedEffectKind, Tribe, VictoryReason, VictoryState, SPECTATOR,
+};
+pub use validation::{
+    validate_card, validate_deck, validate_hero_deck, CardDefect, DeckError, DeckFormat,
+    MAX_EFFECT_DEPTH,
 };
@@ -0,0 +1,734 @@
+//! 对局记录叙述：把事件日志转换为本地化的文字描述，供对局历史面板展示。
+//!
+//! 抽牌、置入手牌等涉及隐藏信息的事件只描述动作，不暴露卡牌名称。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::registry::CardRegistry;
+use super::state::{
+    Card, CardId, ChoiceKind, GameEvent, GameState, Keyword, LoggedEvent, PlayerId,
+};
+
+/// 叙述文本使用的语言。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    ZhCn,
+    EnUs,
+}
+
+impl Locale {
+    /// 按语言标签（如 `en-US`、`zh-CN`）选择语言，无法识别时使用中文。
+    pub fn parse(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("en") {
+            Locale::EnUs
+        } else {
+            Locale::ZhCn
+        }
+    }
+}
+
+/// 一条叙述文本及其对应的日志序号与回合。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NarratedEvent {
+    pub seq: u64,
+    pub turn: u32,
+    pub text: String,
+}
+
+/// 按语言选择格式串，格式串中直接引用已绑定的局部变量。
+macro_rules! say {
+    ($narrator:expr, $zh:literal, $en:literal) => {
+        match $narrator.locale {
+            Locale::ZhCn => format!($zh),
+            Locale::EnUs => format!($en),
+        }
+    };
+}
+
+/// 事件叙述器：卡牌名称依次从当前状态、日志中携带的卡牌与卡牌定义中解析。
+pub struct Narrator<'a> {
+    locale: Locale,
+    cards: HashMap<CardId, &'a Card>,
+    registry: Option<&'a CardRegistry>,
+}
+
+impl<'a> Narrator<'a> {
+    pub fn new(state: &'a GameState, locale: Locale) -> Self {
+        let mut cards = HashMap::new();
+        // 已离场的卡牌只保留在事件中，先登记日志里的卡牌，再以状态中的现值覆盖
+        for entry in &state.event_log {
+            for card in event_cards(&entry.event) {
+                cards.insert(card.id, card);
+            }
+        }
+        for pending in &state.pending_choices {
+            let choice_cards = match &pending.kind {
+                ChoiceKind::Discard { drawn_card } => std::slice::from_ref(drawn_card),
+                ChoiceKind::Discover { cards } => cards.as_slice(),
+                ChoiceKind::ChooseOne { .. } | ChoiceKind::SelectTarget { .. } => &[],
+            };
+            cards.extend(choice_cards.iter().map(|card| (card.id, card)));
+        }
+        for player in &state.players {
+            let zones = player
+                .deck
+                .iter()
+                .chain(player.graveyard.iter())
+                .chain(player.hand.iter())
+                .chain(player.secrets.iter())
+                .chain(player.weapon.iter())
+                .chain(player.board.iter());
+            cards.extend(zones.map(|card| (card.id, card)));
+        }
+        Self {
+            locale,
+            cards,
+            registry: None,
+        }
+    }
+
+    /// 优先使用卡牌定义中的名称。
+    pub fn with_registry(mut self, registry: &'a CardRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// 叙述整段日志，跳过没有对应文本的内部事件。
+    pub fn render(&self, entries: &[LoggedEvent]) -> Vec<NarratedEvent> {
+        entries
+            .iter()
+            .filter_map(|entry| {
+                Some(NarratedEvent {
+                    seq: entry.seq,
+                    turn: entry.turn,
+                    text: self.narrate(&entry.event)?,
+                })
+            })
+            .collect()
+    }
+
+    /// 叙述单个事件；优先级、光环重算等界面无需展示的事件返回 `None`。
+    pub fn narrate(&self, event: &GameEvent) -> Option<String> {
+        let text = match event {
+            GameEvent::CardDrawn { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 抽了一张牌", "{player} draws a card")
+            }
+            GameEvent::CardPlayed {
+                player_id,
+                card_id,
+                target_id,
+            } => {
+                let player = self.player(*player_id);
+                let card = self.card(*card_id);
+                match target_id {
+                    Some(target) => {
+                        let target = self.card(*target);
+                        say!(
+                            self,
+                            "{player} 打出了{card}，目标为{target}",
+                            "{player} plays {card} targeting {target}"
+                        )
+                    }
+                    None => say!(self, "{player} 打出了{card}", "{player} plays {card}"),
+                }
+            }
+            GameEvent::CardSummoned { player_id, card_id } => {
+                let player = self.player(*player_id);
+                let card = self.card(*card_id);
+                say!(self, "{player} 召唤了{card}", "{player} summons {card}")
+            }
+            GameEvent::CardResurrected { player_id, card_id } => {
+                let player = self.player(*player_id);
+                let card = self.card(*card_id);
+                say!(self, "{player} 复活了{card}", "{player} resurrects {card}")
+            }
+            GameEvent::KeywordGranted {
+                card_id, keyword, ..
+            } => {
+                let card = self.card(*card_id);
+                let keyword = self.keyword(*keyword);
+                say!(self, "{card}获得了{keyword}", "{card} gains {keyword}")
+            }
+            GameEvent::KeywordRemoved {
+                card_id, keyword, ..
+            } => {
+                let card = self.card(*card_id);
+                let keyword = self.keyword(*keyword);
+                say!(self, "{card}失去了{keyword}", "{card} loses {keyword}")
+            }
+            GameEvent::CardSilenced { card_id, .. } => {
+                let card = self.card(*card_id);
+                say!(self, "{card}被沉默", "{card} is silenced")
+            }
+            GameEvent::ControlChanged { card_id, to, .. } => {
+                let card = self.card(*card_id);
+                let player = self.player(*to);
+                say!(
+                    self,
+                    "{player} 获得了{card}的控制权",
+                    "{player} takes control of {card}"
+                )
+            }
+            GameEvent::HandCardsRevealed {
+                player_id, cards, ..
+            } => {
+                let player = self.player(*player_id);
+                let count = cards.len();
+                let cards = plural(count, "card");
+                say!(
+                    self,
+                    "{player} 的 {count} 张手牌被揭示",
+                    "{cards} in {player}'s hand are revealed"
+                )
+            }
+            GameEvent::CardTransformed { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 的一张手牌发生了进化",
+                    "A card in {player}'s hand evolves"
+                )
+            }
+            GameEvent::FatigueDamage { player_id, amount } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 受到 {amount} 点疲劳伤害",
+                    "{player} takes {amount} fatigue damage"
+                )
+            }
+            GameEvent::CountersChanged {
+                card_id,
+                counter,
+                total,
+                ..
+            } => {
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{card}的{counter}计数变为 {total}",
+                    "{card} now has {total} {counter}"
+                )
+            }
+            GameEvent::StatsSet {
+                card_id,
+                attack,
+                health,
+                ..
+            } => {
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{card}变为 {attack}/{health}",
+                    "{card} becomes {attack}/{health}"
+                )
+            }
+            GameEvent::AttackDeclared {
+                attacker_id,
+                defender_owner,
+                defender_id,
+                ..
+            } => {
+                let attacker = self.card(*attacker_id);
+                let defender = self.target(*defender_owner, *defender_id);
+                say!(
+                    self,
+                    "{attacker}攻击了{defender}",
+                    "{attacker} attacks {defender}"
+                )
+            }
+            GameEvent::DamageResolved {
+                source_player,
+                source_card,
+                target_player,
+                target_card,
+                amount,
+            } => {
+                let source = self.source(*source_player, *source_card);
+                let target = self.target(*target_player, *target_card);
+                say!(
+                    self,
+                    "{source}对{target}造成 {amount} 点伤害",
+                    "{source} deals {amount} damage to {target}"
+                )
+            }
+            GameEvent::CardHealed {
+                player_id,
+                card_id,
+                amount,
+            } => {
+                let target = self.target(*player_id, *card_id);
+                say!(
+                    self,
+                    "{target}恢复了 {amount} 点生命",
+                    "{target} restores {amount} health"
+                )
+            }
+            GameEvent::ArmorGained { player_id, amount } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 获得了 {amount} 点护甲",
+                    "{player} gains {amount} armor"
+                )
+            }
+            GameEvent::DamagePreventionGained {
+                player_id,
+                card_id,
+                amount,
+            } => {
+                let target = self.target(*player_id, *card_id);
+                say!(
+                    self,
+                    "{target}将抵消接下来的 {amount} 点伤害",
+                    "{target} will prevent the next {amount} damage"
+                )
+            }
+            GameEvent::DamagePrevented {
+                target_player,
+                target_card,
+                amount,
+                ..
+            } => {
+                let target = self.target(*target_player, *target_card);
+                say!(
+                    self,
+                    "{target}抵消了 {amount} 点伤害",
+                    "{target} prevents {amount} damage"
+                )
+            }
+            GameEvent::ManaChanged {
+                player_id,
+                mana,
+                max_mana,
+            } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 的法力变为 {mana}/{max_mana}",
+                    "{player} now has {mana}/{max_mana} mana"
+                )
+            }
+            GameEvent::StatsModified {
+                card_id,
+                attack,
+                health,
+                ..
+            } => {
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{card}获得了 {attack:+}/{health:+}",
+                    "{card} gets {attack:+}/{health:+}"
+                )
+            }
+            GameEvent::CardFrozen { card_id, .. } => {
+                let card = self.card(*card_id);
+                say!(self, "{card}被冻结", "{card} is frozen")
+            }
+            GameEvent::TimedEffectExpired { card_id, .. } => {
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{card}身上的临时效果结束了",
+                    "A temporary effect on {card} wears off"
+                )
+            }
+            GameEvent::PlayerEnchanted { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 获得了一个附魔",
+                    "{player} gains an enchantment"
+                )
+            }
+            GameEvent::PlayerEnchantmentExpired { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 的一个附魔到期了",
+                    "An enchantment on {player} expires"
+                )
+            }
+            GameEvent::ResolutionAborted { discarded, .. } => {
+                let effects = plural(*discarded, "effect");
+                say!(
+                    self,
+                    "结算次数超出上限，{discarded} 个效果未能结算",
+                    "The resolution limit was reached and {effects} did not resolve"
+                )
+            }
+            GameEvent::EffectCountered {
+                player_id,
+                source_card,
+                ..
+            } => {
+                let source = self.source(*player_id, *source_card);
+                say!(
+                    self,
+                    "{source}的效果被反制",
+                    "An effect from {source} is countered"
+                )
+            }
+            GameEvent::CardDestroyed { card, .. } => {
+                let card = self.card_name(card);
+                say!(self, "{card}被消灭", "{card} is destroyed")
+            }
+            GameEvent::CardBurned { player_id, card } => {
+                let player = self.player(*player_id);
+                let card = self.card_name(card);
+                say!(
+                    self,
+                    "{player} 的手牌已满，{card}被烧毁",
+                    "{player}'s hand is full and {card} is burned"
+                )
+            }
+            GameEvent::DiscardPending { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 需要选择一张牌弃掉",
+                    "{player} must choose a card to discard"
+                )
+            }
+            GameEvent::CardDiscarded { player_id, card } => {
+                let player = self.player(*player_id);
+                let card = self.card_name(card);
+                say!(self, "{player} 弃掉了{card}", "{player} discards {card}")
+            }
+            GameEvent::OverloadIncurred { player_id, amount } => {
+                let player = self.player(*player_id);
+                let crystals = plural(usize::from(*amount), "mana crystal");
+                say!(
+                    self,
+                    "{player} 过载了 {amount} 个法力水晶",
+                    "{player} overloads {crystals}"
+                )
+            }
+            GameEvent::ManaOverloaded { player_id, locked } => {
+                let player = self.player(*player_id);
+                let crystals = plural(usize::from(*locked), "mana crystal");
+                say!(
+                    self,
+                    "{player} 有 {locked} 个法力水晶被锁定",
+                    "{player} has {crystals} locked"
+                )
+            }
+            GameEvent::HealthPaid {
+                player_id,
+                card_id,
+                amount,
+            } => {
+                let player = self.player(*player_id);
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{player} 为{card}支付了 {amount} 点生命",
+                    "{player} pays {amount} health for {card}"
+                )
+            }
+            GameEvent::SecretPlayed { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 布置了一个奥秘", "{player} sets a secret")
+            }
+            GameEvent::WeaponEquipped { player_id, card_id } => {
+                let player = self.player(*player_id);
+                let card = self.card(*card_id);
+                say!(self, "{player} 装备了{card}", "{player} equips {card}")
+            }
+            GameEvent::WeaponDurabilityChanged {
+                card_id,
+                durability,
+                ..
+            } => {
+                let card = self.card(*card_id);
+                say!(
+                    self,
+                    "{card}的耐久度变为 {durability}",
+                    "{card} has {durability} durability left"
+                )
+            }
+            GameEvent::WeaponDestroyed { card, .. } => {
+                let card = self.card_name(card);
+                say!(self, "{card}被摧毁", "{card} breaks")
+            }
+            GameEvent::HeroPowerUsed {
+                player_id,
+                target_id,
+            } => {
+                let player = self.player(*player_id);
+                match target_id {
+                    Some(target) => {
+                        let target = self.card(*target);
+                        say!(
+                            self,
+                            "{player} 对{target}使用了英雄技能",
+                            "{player} uses their hero power on {target}"
+                        )
+                    }
+                    None => say!(
+                        self,
+                        "{player} 使用了英雄技能",
+                        "{player} uses their hero power"
+                    ),
+                }
+            }
+            GameEvent::HeroAttackDeclared {
+                player_id,
+                defender_owner,
+                defender_id,
+            } => {
+                let player = self.player(*player_id);
+                let defender = self.target(*defender_owner, *defender_id);
+                say!(
+                    self,
+                    "{player} 的英雄攻击了{defender}",
+                    "{player}'s hero attacks {defender}"
+                )
+            }
+            GameEvent::SecretRevealed { player_id, card } => {
+                let player = self.player(*player_id);
+                let card = self.card_name(card);
+                say!(
+                    self,
+                    "{player} 的奥秘{card}被揭示",
+                    "{player}'s secret {card} is revealed"
+                )
+            }
+            GameEvent::ChoicePending { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 正在进行选择", "{player} is making a choice")
+            }
+            GameEvent::ChoiceResolved { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 做出了选择", "{player} makes a choice")
+            }
+            GameEvent::CardAddedToHand { player_id, .. } => {
+                let player = self.player(*player_id);
+                say!(
+                    self,
+                    "{player} 将一张牌置入手牌",
+                    "{player} adds a card to their hand"
+                )
+            }
+            GameEvent::CardMilled { player_id, card } => {
+                let player = self.player(*player_id);
+                let card = self.card_name(card);
+                say!(
+                    self,
+                    "{player} 牌库中的{card}被摧毁",
+                    "{card} is milled from {player}'s deck"
+                )
+            }
+            GameEvent::DeckShuffled { player_id } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 洗混了牌库", "{player} shuffles their deck")
+            }
+            GameEvent::CoinFlipped { first_player } => {
+                let player = self.player(*first_player);
+                say!(self, "{player} 获得先手", "{player} goes first")
+            }
+            GameEvent::MulliganApplied {
+                player_id,
+                replaced,
+            } => {
+                let player = self.player(*player_id);
+                let count = replaced.len();
+                let cards = plural(count, "card");
+                say!(
+                    self,
+                    "{player} 替换了 {count} 张起手牌",
+                    "{player} replaces {cards}"
+                )
+            }
+            GameEvent::TurnEnded { player_id } => {
+                let player = self.player(*player_id);
+                say!(self, "{player} 结束了回合", "{player} ends their turn")
+            }
+            GameEvent::GameWon { winner, .. } => {
+                let player = self.player(*winner);
+                say!(self, "{player} 获得了胜利", "{player} wins the game")
+            }
+            GameEvent::GameDrawn { .. } => {
+                say!(self, "对局以平局结束", "The game ends in a draw")
+            }
+            GameEvent::AuraChanged { .. }
+            | GameEvent::EffectScheduled { .. }
+            | GameEvent::PriorityGranted { .. }
+            | GameEvent::PriorityPassed { .. }
+            | GameEvent::EffectChargeSpent { .. }
+            | GameEvent::PhaseChanged { .. }
+            | GameEvent::GameSummary { .. } => return None,
+        };
+        Some(text)
+    }
+
+    fn player(&self, player_id: PlayerId) -> String {
+        let number = u32::from(player_id) + 1;
+        say!(self, "玩家 {number}", "Player {number}")
+    }
+
+    fn card(&self, card_id: CardId) -> String {
+        match self.cards.get(&card_id) {
+            Some(card) => self.card_name(card),
+            None => say!(self, "卡牌 #{card_id}", "card #{card_id}"),
+        }
+    }
+
+    fn card_name(&self, card: &Card) -> String {
+        let name = card
+            .def_id
+            .and_then(|def_id| self.registry?.get(def_id))
+            .map_or(card.name.as_str(), |definition| definition.name.as_str());
+        say!(self, "【{name}】", "{name}")
+    }
+
+    /// 效果或伤害的来源：来源卡牌写作“某玩家的某卡”，否则为玩家本身。
+    fn source(&self, player_id: PlayerId, card_id: Option<CardId>) -> String {
+        let player = self.player(player_id);
+        match card_id {
+            Some(card_id) => {
+                let card = self.card(card_id);
+                say!(self, "{player} 的{card}", "{player}'s {card}")
+            }
+            None => player,
+        }
+    }
+
+    /// 目标为单位时写作单位名称，否则为该玩家的英雄。
+    fn target(&self, player_id: PlayerId, card_id: Option<CardId>) -> String {
+        match card_id {
+            Some(card_id) => self.card(card_id),
+            None => self.player(player_id),
+        }
+    }
+
+    fn keyword(&self, keyword: Keyword) -> &'static str {
+        match (self.locale, keyword) {
+            (Locale::ZhCn, Keyword::Taunt) => "嘲讽",
+            (Locale::ZhCn, Keyword::Charge) => "冲锋",
+            (Locale::ZhCn, Keyword::Rush) => "突袭",
+            (Locale::ZhCn, Keyword::Lifesteal) => "吸血",
+            (Locale::ZhCn, Keyword::Stealth) => "潜行",
+            (Locale::ZhCn, Keyword::Ranged) => "远程",
+            (Locale::ZhCn, Keyword::Elusive) => "灵巧",
+            (Locale::EnUs, Keyword::Taunt) => "Taunt",
+            (Locale::EnUs, Keyword::Charge) => "Charge",
+            (Locale::EnUs, Keyword::Rush) => "Rush",
+            (Locale::EnUs, Keyword::Lifesteal) => "Lifesteal",
+            (Locale::EnUs, Keyword::Stealth) => "Stealth",
+            (Locale::EnUs, Keyword::Ranged) => "Ranged",
+            (Locale::EnUs, Keyword::Elusive) => "Elusive",
+        }
+    }
+}
+
+impl GameState {
+    /// 以指定语言叙述完整的事件日志。
+    pub fn narrate_log(&self, locale: Locale) -> Vec<NarratedEvent> {
+        Narrator::new(self, locale).render(&self.event_log)
+    }
+}
+
+/// 事件中携带的完整卡牌，用于解析已离场卡牌的名称。
+fn event_cards(event: &GameEvent) -> &[Card] {
+    match event {
+        GameEvent::CardDestroyed { card, .. }
+        | GameEvent::CardBurned { card, .. }
+        | GameEvent::DiscardPending { card, .. }
+        | GameEvent::CardDiscarded { card, .. }
+        | GameEvent::WeaponDestroyed { card, .. }
+        | GameEvent::SecretRevealed { card, .. }
+        | GameEvent::CardTransformed { card, .. }
+        | GameEvent::CardMilled { card, .. } => std::slice::from_ref(card),
+        GameEvent::HandCardsRevealed { cards, .. } => cards,
+        _ => &[],
+    }
+}
+
+/// 英文计数短语，如 `1 card`、`2 cards`。
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        count => format!("{count} {noun}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CardDefinition, CardType, Player};
+
+    #[test]
+    fn log_is_narrated_with_resolved_card_names() {
+        let fireball = Card::new(10, "Fireball", 4, 0, 0, CardType::Spell, Vec::new());
+        let bulwark = Card::new(20, "Steel Bulwark", 3, 2, 6, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 4, Vec::new(), Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0);
+        state.players[0].graveyard.push(fireball);
+        state.record_event(GameEvent::CardDrawn {
+            player_id: 0,
+            card_id: 10,
+        });
+        state.record_event(GameEvent::DamageResolved {
+            source_player: 0,
+            source_card: Some(10),
+            target_player: 1,
+            target_card: Some(20),
+            amount: 6,
+        });
+        state.record_event(GameEvent::CardDestroyed {
+            player_id: 1,
+            card: bulwark,
+        });
+        state.record_event(GameEvent::PriorityPassed { player_id: 1 });
+        state.record_event(GameEvent::DamageResolved {
+            source_player: 0,
+            source_card: Some(99),
+            target_player: 1,
+            target_card: None,
+            amount: 2,
+        });
+
+        let texts = |narrated: Vec<NarratedEvent>| -> Vec<String> {
+            narrated.into_iter().map(|entry| entry.text).collect()
+        };
+        assert_eq!(
+            texts(state.narrate_log(Locale::parse("en-US"))),
+            vec![
+                "Player 1 draws a card",
+                "Player 1's Fireball deals 6 damage to Steel Bulwark",
+                "Steel Bulwark is destroyed",
+                "Player 1's card #99 deals 2 damage to Player 2",
+            ]
+        );
+        let narrated = state.narrate_log(Locale::parse("zh-CN"));
+        assert_eq!(narrated[1].seq, state.event_log[1].seq);
+        assert_eq!(
+            narrated[1].text,
+            "玩家 1 的【Fireball】对【Steel Bulwark】造成 6 点伤害"
+        );
+
+        let mut registry = CardRegistry::new();
+        registry.register(CardDefinition::new(
+            7,
+            "Meteor",
+            4,
+            0,
+            0,
+            CardType::Spell,
+            Vec::new(),
+        ));
+        state.players[0].graveyard[0].def_id = Some(7);
+        let narrator = Narrator::new(&state, Locale::EnUs).with_registry(&registry);
+        assert_eq!(
+            narrator.narrate(&state.event_log[1].event).as_deref(),
+            Some("Player 1's Meteor deals 6 damage to Steel Bulwark")
+        );
+    }
+}
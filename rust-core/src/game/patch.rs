@@ -0,0 +1,258 @@
+//! 状态差量：以类似 JSON Patch 的操作列表描述两个局面之间的变化，供前端增量更新。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::state::GameState;
+
+/// 单个差量操作，`path` 为 JSON Pointer（空串表示整个状态）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// 按顺序应用的差量操作列表，序列化后即为 JSON Patch 数组。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct StatePatch {
+    pub ops: Vec<PatchOp>,
+}
+
+impl StatePatch {
+    /// 以 `value` 整体替换状态的差量，用于首次同步。
+    pub fn replace_all(value: Value) -> Self {
+        Self {
+            ops: vec![PatchOp::Replace {
+                path: String::new(),
+                value,
+            }],
+        }
+    }
+
+    /// 计算从 `from` 变为 `to` 所需的操作。
+    pub fn between(from: &Value, to: &Value) -> Self {
+        let mut ops = Vec::new();
+        diff_values(from, to, &mut String::new(), &mut ops);
+        Self { ops }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// 依次应用到 `target`，遇到无效路径时停止并返回错误。
+    pub fn apply_to(&self, target: &mut Value) -> Result<(), PatchError> {
+        self.ops.iter().try_for_each(|op| apply_op(target, op))
+    }
+}
+
+/// 应用差量失败的原因。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum PatchError {
+    PathNotFound {
+        path: String,
+    },
+    /// 应用后的数据无法还原为合法的游戏状态。
+    InvalidState {
+        message: String,
+    },
+}
+
+impl GameState {
+    /// 计算从当前状态变为 `other` 的差量。
+    pub fn diff(&self, other: &GameState) -> StatePatch {
+        StatePatch::between(&to_json(self), &to_json(other))
+    }
+
+    /// 应用差量；失败时状态保持不变。
+    pub fn apply_patch(&mut self, patch: &StatePatch) -> Result<(), PatchError> {
+        let mut value = to_json(self);
+        patch.apply_to(&mut value)?;
+        *self = serde_json::from_value(value).map_err(|error| PatchError::InvalidState {
+            message: error.to_string(),
+        })?;
+        Ok(())
+    }
+}
+
+fn to_json(state: &GameState) -> Value {
+    serde_json::to_value(state).expect("game state is always serializable")
+}
+
+fn diff_values(from: &Value, to: &Value, path: &mut String, ops: &mut Vec<PatchOp>) {
+    if from == to {
+        return;
+    }
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) => {
+            for key in old.keys().filter(|key| !new.contains_key(*key)) {
+                ops.push(PatchOp::Remove {
+                    path: child_path(path, key),
+                });
+            }
+            for (key, value) in new {
+                let len = path.len();
+                path.push_str(&child_path("", key));
+                match old.get(key) {
+                    Some(previous) => diff_values(previous, value, path, ops),
+                    None => ops.push(PatchOp::Add {
+                        path: path.clone(),
+                        value: value.clone(),
+                    }),
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => diff_arrays(old, new, path, ops),
+        _ => ops.push(PatchOp::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+/// 跳过相同的首尾元素，中间部分逐个比较，多出的元素再删除或插入。
+fn diff_arrays(old: &[Value], new: &[Value], path: &mut String, ops: &mut Vec<PatchOp>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let shortest = old.len().min(new.len()) - prefix;
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take(shortest)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    let paired = old_middle.len().min(new_middle.len());
+
+    for index in 0..paired {
+        let len = path.len();
+        path.push_str(&format!("/{}", prefix + index));
+        diff_values(&old_middle[index], &new_middle[index], path, ops);
+        path.truncate(len);
+    }
+    let at = prefix + paired;
+    for _ in paired..old_middle.len() {
+        ops.push(PatchOp::Remove {
+            path: format!("{path}/{at}"),
+        });
+    }
+    for (offset, value) in new_middle[paired..].iter().enumerate() {
+        ops.push(PatchOp::Add {
+            path: format!("{path}/{}", at + offset),
+            value: value.clone(),
+        });
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{parent}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn apply_op(target: &mut Value, op: &PatchOp) -> Result<(), PatchError> {
+    let path = match op {
+        PatchOp::Add { path, .. } | PatchOp::Remove { path } | PatchOp::Replace { path, .. } => {
+            path
+        }
+    };
+    let not_found = || PatchError::PathNotFound { path: path.clone() };
+
+    if let PatchOp::Replace { value, .. } = op {
+        *target.pointer_mut(path).ok_or_else(not_found)? = value.clone();
+        return Ok(());
+    }
+    let (parent, key) = path.rsplit_once('/').ok_or_else(not_found)?;
+    let key = key.replace("~1", "/").replace("~0", "~");
+    match (target.pointer_mut(parent).ok_or_else(not_found)?, op) {
+        (Value::Object(fields), PatchOp::Add { value, .. }) => {
+            fields.insert(key, value.clone());
+        }
+        (Value::Object(fields), _) => {
+            fields.remove(&key).ok_or_else(not_found)?;
+        }
+        (Value::Array(items), PatchOp::Add { value, .. }) => {
+            let index = match key.as_str() {
+                "-" => items.len(),
+                _ => key.parse().map_err(|_| not_found())?,
+            };
+            if index > items.len() {
+                return Err(not_found());
+            }
+            items.insert(index, value.clone());
+        }
+        (Value::Array(items), _) => {
+            let index: usize = key.parse().map_err(|_| not_found())?;
+            if index >= items.len() {
+                return Err(not_found());
+            }
+            items.remove(index);
+        }
+        _ => return Err(not_found()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::RuleEngine;
+    use serde_json::json;
+
+    #[test]
+    fn patches_reproduce_the_target_state() {
+        let mut engine = RuleEngine::new();
+        let before = GameState::sample();
+        let mut after = before.clone();
+        engine.end_turn(&mut after).expect("end turn");
+        after.players[0].hand.remove(0);
+        after.players[1].board.clear();
+
+        let patch = before.diff(&after);
+        assert!(!patch.is_empty());
+        assert!(patch.ops.iter().all(|op| !matches!(
+            op,
+            PatchOp::Replace { path, .. } if path.is_empty()
+        )));
+        let mut synced = before.clone();
+        synced.apply_patch(&patch).expect("patch applies");
+        assert_eq!(synced, after);
+        assert!(after.diff(&synced).is_empty());
+
+        let mut value = json!({ "a/b": [1, 2, 3, 4], "c": { "d": 1 } });
+        let target = json!({ "a/b": [1, 5, 4], "c": {}, "e": true });
+        let patch = StatePatch::between(&value, &target);
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            json!([
+                { "op": "replace", "path": "/a~1b/1", "value": 5 },
+                { "op": "remove", "path": "/a~1b/2" },
+                { "op": "remove", "path": "/c/d" },
+                { "op": "add", "path": "/e", "value": true },
+            ])
+        );
+        patch.apply_to(&mut value).expect("apply");
+        assert_eq!(value, target);
+
+        let bad = StatePatch {
+            ops: vec![PatchOp::Remove {
+                path: "/players/9".to_string(),
+            }],
+        };
+        assert_eq!(
+            synced.apply_patch(&bad),
+            Err(PatchError::PathNotFound {
+                path: "/players/9".to_string()
+            })
+        );
+        assert_eq!(synced, after);
+    }
+}
@@ -0,0 +1,512 @@
+//! 卡牌定义注册表：卡牌的印刷数据只加载一次，对局中的卡牌实例通过 `def_id` 引用定义。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    rng::GameRng,
+    state::{AdditionalCost, Card, CardEffect, CardId, CardType, GameState, Keyword, Tribe},
+};
+
+/// 卡牌定义的标识，同一定义的多个实例共享。
+pub type DefId = u32;
+
+/// 卡牌稀有度，影响构筑时的携带数量与轮抽卡池。
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+/// 卡牌的印刷数据，与实例状态（受伤、附魔、计数器等）分离。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CardDefinition {
+    pub def_id: DefId,
+    pub name: String,
+    pub cost: u8,
+    pub attack: i16,
+    pub health: i16,
+    #[serde(default)]
+    pub card_type: CardType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<CardEffect>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tribes: Vec<Tribe>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<Keyword>,
+    #[serde(default, skip_serializing_if = "AdditionalCost::is_free")]
+    pub additional_cost: AdditionalCost,
+    /// 卡牌描述文字，仅供展示。
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// 所属职业，`None` 为中立卡牌。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// 所属扩展包，`None` 为不属于任何扩展包的基础卡牌。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    #[serde(default)]
+    pub rarity: Rarity,
+    /// 可收集的卡牌才能放入牌组或出现在发现、轮抽卡池中；衍生物为 `false`。
+    #[serde(default = "default_collectible")]
+    pub collectible: bool,
+    /// 供检索与筛选的关键字标签（如“亡语”“法术伤害”）。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 背景描述文字，仅供展示。
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub flavor: String,
+}
+
+fn default_collectible() -> bool {
+    true
+}
+
+impl CardDefinition {
+    pub fn new(
+        def_id: DefId,
+        name: impl Into<String>,
+        cost: u8,
+        attack: i16,
+        health: i16,
+        card_type: CardType,
+        effects: Vec<CardEffect>,
+    ) -> Self {
+        Self {
+            def_id,
+            name: name.into(),
+            cost,
+            attack,
+            health,
+            card_type,
+            effects,
+            tribes: Vec::new(),
+            keywords: Vec::new(),
+            additional_cost: AdditionalCost::default(),
+            text: String::new(),
+            class: None,
+            set: None,
+            rarity: Rarity::default(),
+            collectible: true,
+            tags: Vec::new(),
+            flavor: String::new(),
+        }
+    }
+
+    pub fn with_tribes(mut self, tribes: impl IntoIterator<Item = Tribe>) -> Self {
+        self.tribes = tribes.into_iter().collect();
+        self
+    }
+
+    pub fn with_keywords(mut self, keywords: impl IntoIterator<Item = Keyword>) -> Self {
+        self.keywords = keywords.into_iter().collect();
+        self
+    }
+
+    pub fn with_additional_cost(mut self, additional_cost: AdditionalCost) -> Self {
+        self.additional_cost = additional_cost;
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    pub fn with_set(mut self, set: impl Into<String>) -> Self {
+        self.set = Some(set.into());
+        self
+    }
+
+    pub fn with_rarity(mut self, rarity: Rarity) -> Self {
+        self.rarity = rarity;
+        self
+    }
+
+    /// 标记为不可收集的衍生卡牌。
+    pub fn uncollectible(mut self) -> Self {
+        self.collectible = false;
+        self
+    }
+
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_flavor(mut self, flavor: impl Into<String>) -> Self {
+        self.flavor = flavor.into();
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|item| item == tag)
+    }
+
+    /// 以 `id` 生成该定义的一张新卡牌实例。
+    pub fn instantiate(&self, id: CardId) -> Card {
+        let mut card = Card::new(
+            id,
+            self.name.clone(),
+            self.cost,
+            self.attack,
+            self.health,
+            self.card_type,
+            self.effects.clone(),
+        )
+        .with_tribes(self.tribes.iter().copied())
+        .with_keywords(self.keywords.iter().copied())
+        .with_additional_cost(self.additional_cost);
+        card.def_id = Some(self.def_id);
+        card
+    }
+}
+
+/// 从卡牌定义中选取候选时的筛选条件，如发现效果的候选范围；未设置的条件不作限制。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct CardFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_type: Option<CardType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tribe: Option<Tribe>,
+    /// 职业，只匹配该职业的卡牌（不含中立卡牌）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<u8>,
+}
+
+impl CardFilter {
+    pub fn matches(&self, definition: &CardDefinition) -> bool {
+        self.card_type
+            .is_none_or(|card_type| definition.card_type == card_type)
+            && self
+                .tribe
+                .is_none_or(|tribe| definition.tribes.contains(&tribe))
+            && self
+                .class
+                .as_ref()
+                .is_none_or(|class| definition.class.as_ref() == Some(class))
+            && self
+                .set
+                .as_ref()
+                .is_none_or(|set| definition.set.as_ref() == Some(set))
+            && self.max_cost.is_none_or(|cost| definition.cost <= cost)
+    }
+}
+
+/// 按 `def_id` 索引的卡牌定义集合，以定义列表的形式序列化。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "Vec<CardDefinition>", into = "Vec<CardDefinition>")]
+pub struct CardRegistry {
+    definitions: BTreeMap<DefId, CardDefinition>,
+}
+
+impl From<Vec<CardDefinition>> for CardRegistry {
+    fn from(definitions: Vec<CardDefinition>) -> Self {
+        let mut registry = Self::new();
+        for definition in definitions {
+            registry.register(definition);
+        }
+        registry
+    }
+}
+
+impl From<CardRegistry> for Vec<CardDefinition> {
+    fn from(registry: CardRegistry) -> Self {
+        registry.definitions.into_values().collect()
+    }
+}
+
+impl CardRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册定义，返回被替换的同 `def_id` 旧定义。
+    pub fn register(&mut self, definition: CardDefinition) -> Option<CardDefinition> {
+        self.definitions.insert(definition.def_id, definition)
+    }
+
+    pub fn get(&self, def_id: DefId) -> Option<&CardDefinition> {
+        self.definitions.get(&def_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// 按 `def_id` 升序遍历所有定义。
+    pub fn definitions(&self) -> impl Iterator<Item = &CardDefinition> {
+        self.definitions.values()
+    }
+
+    /// 可收集的定义，发现与轮抽卡池只从中选取。
+    pub fn collectible(&self) -> impl Iterator<Item = &CardDefinition> {
+        self.definitions()
+            .filter(|definition| definition.collectible)
+    }
+
+    /// 轮抽：从指定稀有度的可收集定义中不重复地随机选出至多 `count` 个候选。
+    pub fn draft_options(
+        &self,
+        rng: &mut GameRng,
+        rarity: Rarity,
+        count: usize,
+    ) -> Vec<&CardDefinition> {
+        let pool: Vec<&CardDefinition> = self
+            .collectible()
+            .filter(|definition| definition.rarity == rarity)
+            .collect();
+        rng.choose_multiple(&pool, count)
+            .into_iter()
+            .copied()
+            .collect()
+    }
+
+    pub fn instantiate(&self, def_id: DefId, id: CardId) -> Option<Card> {
+        self.get(def_id)
+            .map(|definition| definition.instantiate(id))
+    }
+
+    /// 从满足 `filter` 的可收集定义中随机选取一个，用于随机生成与发现类效果。
+    pub fn random_definition<F>(&self, rng: &mut GameRng, filter: F) -> Option<&CardDefinition>
+    where
+        F: Fn(&CardDefinition) -> bool,
+    {
+        let pool: Vec<&CardDefinition> = self.collectible().filter(|def| filter(def)).collect();
+        rng.choose(&pool).copied()
+    }
+
+    /// 返回用于存档或传输的状态副本：效果与定义一致的卡牌省略效果数据。
+    pub fn compact(&self, state: &GameState) -> GameState {
+        let mut compacted = state.clone();
+        for card in cards_mut(&mut compacted) {
+            let shared =
+                card.def_id
+                    .and_then(|def_id| self.get(def_id))
+                    .is_some_and(|definition| {
+                        !definition.effects.is_empty() && definition.effects == card.effects
+                    });
+            if shared {
+                card.effects.clear();
+                card.shared_effects = true;
+            }
+        }
+        compacted
+    }
+
+    /// 供 AI 与联机同步使用的精简状态：在 `compact` 的基础上省略事件日志，
+    /// 并清空与定义一致的卡牌名称和效果描述，`hydrate` 可恢复除事件日志外的全部数据。
+    pub fn lightweight(&self, state: &GameState) -> GameState {
+        let mut light = self.compact(state);
+        light.event_log.clear();
+        for card in cards_mut(&mut light) {
+            let Some(definition) = card.def_id.and_then(|def_id| self.get(def_id)) else {
+                continue;
+            };
+            if card.name == definition.name {
+                card.name.clear();
+            }
+            for effect in &mut card.effects {
+                let printed = definition.effects.iter().any(|printed| {
+                    printed.id == effect.id && printed.description == effect.description
+                });
+                if printed {
+                    effect.description.clear();
+                }
+            }
+        }
+        light
+    }
+
+    /// 从定义中恢复 `compact` 省略的效果数据与 `lightweight` 清空的名称和描述，
+    /// 找不到定义的卡牌保持原样。
+    pub fn hydrate(&self, state: &mut GameState) {
+        for card in cards_mut(state) {
+            let Some(definition) = card.def_id.and_then(|def_id| self.get(def_id)) else {
+                continue;
+            };
+            if card.shared_effects {
+                card.effects = definition.effects.clone();
+                card.shared_effects = false;
+            }
+            if card.name.is_empty() {
+                card.name = definition.name.clone();
+            }
+            for effect in card.effects.iter_mut() {
+                if !effect.description.is_empty() {
+                    continue;
+                }
+                let printed = definition
+                    .effects
+                    .iter()
+                    .find(|printed| printed.id == effect.id);
+                if let Some(printed) = printed {
+                    effect.description = printed.description.clone();
+                }
+            }
+        }
+    }
+}
+
+/// 遍历所有玩家各区域中的卡牌，以及开局参数中保存的牌组。
+fn cards_mut(state: &mut GameState) -> impl Iterator<Item = &mut Card> {
+    let GameState { players, setup, .. } = state;
+    let setup_decks = setup
+        .iter_mut()
+        .flat_map(|setup| setup.decks.iter_mut().flatten());
+    players
+        .iter_mut()
+        .flat_map(|player| {
+            player
+                .hand
+                .iter_mut()
+                .chain(player.board.iter_mut())
+                .chain(player.deck.iter_mut())
+                .chain(player.graveyard.iter_mut())
+                .chain(player.secrets.iter_mut())
+                .chain(player.weapon.iter_mut())
+        })
+        .chain(setup_decks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CardEffect, EffectTarget, EffectTrigger, GameEvent, Player};
+
+    fn registry() -> CardRegistry {
+        let bolt = CardEffect::direct_damage(
+            1,
+            "造成 3 点伤害",
+            EffectTrigger::OnPlay,
+            0,
+            3,
+            EffectTarget::OpponentOfSource,
+        );
+        CardRegistry::from(vec![
+            CardDefinition::new(10, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt])
+                .with_text("对敌方英雄造成 3 点伤害"),
+            CardDefinition::new(20, "Guard", 2, 1, 3, CardType::Unit, Vec::new())
+                .with_keywords([Keyword::Taunt]),
+        ])
+    }
+
+    #[test]
+    fn compacted_states_drop_shared_effects_and_hydrate_back() {
+        let registry = registry();
+        let json = serde_json::to_string(&registry).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CardRegistry>(&json).unwrap(),
+            registry
+        );
+
+        let guard = registry.instantiate(20, 2).expect("guard");
+        assert_eq!(guard.def_id, Some(20));
+        assert!(guard.has_keyword(Keyword::Taunt));
+        assert!(registry.instantiate(99, 3).is_none());
+
+        let mut silenced = registry.instantiate(10, 4).unwrap();
+        silenced.effects.clear();
+        let hand = vec![registry.instantiate(10, 1).unwrap(), silenced];
+        let player_one = Player::new(0, 30, 0, 0, hand, vec![guard], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0);
+
+        let compacted = registry.compact(&state);
+        assert!(compacted.players[0].hand[0].effects.is_empty());
+        assert!(compacted.players[0].hand[0].shared_effects);
+        assert!(!compacted.players[0].hand[1].shared_effects);
+        let saved = serde_json::to_string(&compacted).unwrap();
+        assert!(saved.len() < serde_json::to_string(&state).unwrap().len());
+
+        let mut restored: GameState = serde_json::from_str(&saved).unwrap();
+        registry.hydrate(&mut restored);
+        assert_eq!(restored, state);
+    }
+
+    #[test]
+    fn lightweight_states_drop_display_data_and_hydrate_back() {
+        let registry = registry();
+        let mut renamed = registry.instantiate(20, 3).unwrap();
+        renamed.name = "Captain".to_string();
+        let mut charged = registry.instantiate(10, 4).unwrap();
+        charged.effects[0].charges = Some(1);
+        let hand = vec![registry.instantiate(10, 1).unwrap(), renamed, charged];
+        let player_one = Player::new(0, 30, 0, 0, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0);
+        state.record_event(GameEvent::TurnEnded { player_id: 0 });
+
+        let light = registry.lightweight(&state);
+        assert!(light.event_log.is_empty());
+        assert!(light.players[0].hand[0].name.is_empty());
+        assert_eq!(light.players[0].hand[1].name, "Captain");
+        assert!(light.players[0].hand[2].effects[0].description.is_empty());
+        let payload = serde_json::to_string(&light).unwrap();
+        let compacted = serde_json::to_string(&registry.compact(&state)).unwrap();
+        assert!(payload.len() < compacted.len());
+
+        let mut restored: GameState = serde_json::from_str(&payload).unwrap();
+        registry.hydrate(&mut restored);
+        state.event_log.clear();
+        assert_eq!(restored, state);
+    }
+
+    #[test]
+    fn collectible_pools_respect_metadata() {
+        let mut registry = registry();
+        registry.register(
+            CardDefinition::new(30, "Ancient", 8, 8, 8, CardType::Unit, Vec::new())
+                .with_rarity(Rarity::Legendary)
+                .with_tags(["巨型"])
+                .with_flavor("它比大陆还要古老。"),
+        );
+        registry.register(
+            CardDefinition::new(31, "Sapling", 1, 1, 1, CardType::Unit, Vec::new())
+                .with_rarity(Rarity::Legendary)
+                .uncollectible(),
+        );
+        assert!(registry.get(30).unwrap().has_tag("巨型"));
+
+        let json = serde_json::to_value(registry.get(31).unwrap()).unwrap();
+        assert_eq!(json["rarity"], "Legendary");
+        assert_eq!(json["collectible"], false);
+        let legacy: CardDefinition =
+            serde_json::from_str(r#"{"def_id":1,"name":"Old","cost":1,"attack":1,"health":1}"#)
+                .unwrap();
+        assert!(legacy.collectible);
+        assert_eq!(legacy.rarity, Rarity::Common);
+
+        let mut rng = GameRng::new(3);
+        let options = registry.draft_options(&mut rng, Rarity::Legendary, 3);
+        assert_eq!(options.len(), 1);
+        assert_eq!(
+            options[0].def_id, 30,
+            "uncollectible cards are never offered"
+        );
+    }
+}
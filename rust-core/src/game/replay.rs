@@ -0,0 +1,338 @@
+//! 对局回放：记录初始局面与有序的操作步骤，可逐步重现整局并以最终哈希校验一致性。
+//!
+//! 规则引擎与随机源都是确定性的，因此同样的起点与步骤总能得到同样的事件序列。
+
+use serde::{Deserialize, Serialize};
+
+use super::config::RuleConfig;
+use super::rules::{GameAction, RuleEngine, RuleError};
+use super::state::{Card, GameEvent, GameState, PlayerId};
+
+/// 当前回放文件格式版本。
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+/// 回放文件：起点、有序步骤与记录结束时的状态哈希。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Replay {
+    #[serde(default)]
+    pub format_version: u32,
+    pub start: ReplayStart,
+    /// 记录时是否开启了交互结算模式，回放时沿用。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interactive_priority: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<ReplayStep>,
+    /// 记录结束时的状态哈希（16 位十六进制），缺省时回放不做校验。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_hash: Option<String>,
+}
+
+/// 回放的起点：完整的初始状态，或以牌组、规则配置与种子重新开局。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum ReplayStart {
+    State {
+        state: Box<GameState>,
+    },
+    NewGame {
+        deck_a: Vec<Card>,
+        deck_b: Vec<Card>,
+        #[serde(default)]
+        config: Box<RuleConfig>,
+        seed: u64,
+    },
+}
+
+impl ReplayStart {
+    pub fn initial_state(&self) -> GameState {
+        match self {
+            ReplayStart::State { state } => (**state).clone(),
+            ReplayStart::NewGame {
+                deck_a,
+                deck_b,
+                config,
+                seed,
+            } => GameState::new_game(deck_a.clone(), deck_b.clone(), (**config).clone(), *seed),
+        }
+    }
+}
+
+/// 回放中的一步：规则操作，或不属于 `GameAction` 的回合开始。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum ReplayStep {
+    Action { action: GameAction },
+    StartTurn { player_id: PlayerId },
+}
+
+impl ReplayStep {
+    pub fn apply(
+        &self,
+        rules: &mut RuleEngine,
+        state: &mut GameState,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        match self {
+            ReplayStep::Action { action } => rules.apply_action(state, action),
+            ReplayStep::StartTurn { player_id } => rules.start_turn(state, *player_id),
+        }
+    }
+}
+
+/// 回放失败的原因。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum ReplayFailure {
+    InvalidFile {
+        message: String,
+    },
+    /// 回放文件来自更新版本的引擎。
+    UnsupportedVersion {
+        version: u32,
+        latest: u32,
+    },
+    /// 第 `index` 步无法在回放状态上执行。
+    StepFailed {
+        index: usize,
+        step: ReplayStep,
+        error: RuleError,
+    },
+    /// 全部步骤执行完毕，但最终状态与记录不一致。
+    HashMismatch {
+        expected: String,
+        actual: String,
+    },
+}
+
+impl Replay {
+    /// 以当前状态为起点开始记录。
+    pub fn from_state(state: &GameState) -> Self {
+        Self::starting_at(ReplayStart::State {
+            state: Box::new(state.clone()),
+        })
+    }
+
+    /// 以 `GameState::new_game` 的参数为起点开始记录，文件只需保存牌组与种子。
+    pub fn new_game(deck_a: Vec<Card>, deck_b: Vec<Card>, config: RuleConfig, seed: u64) -> Self {
+        Self::starting_at(ReplayStart::NewGame {
+            deck_a,
+            deck_b,
+            config: Box::new(config),
+            seed,
+        })
+    }
+
+    fn starting_at(start: ReplayStart) -> Self {
+        Self {
+            format_version: REPLAY_FORMAT_VERSION,
+            start,
+            interactive_priority: false,
+            steps: Vec::new(),
+            final_hash: None,
+        }
+    }
+
+    pub fn with_interactive_priority(mut self, enabled: bool) -> Self {
+        self.interactive_priority = enabled;
+        self
+    }
+
+    /// 追加一个已成功执行的步骤。
+    pub fn record(&mut self, step: ReplayStep) {
+        self.steps.push(step);
+    }
+
+    /// 记下结束时的状态哈希，供回放校验。
+    pub fn finish(&mut self, state: &GameState) {
+        self.final_hash = Some(hash_hex(state));
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("replay is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ReplayFailure> {
+        let replay: Replay =
+            serde_json::from_str(json).map_err(|error| ReplayFailure::InvalidFile {
+                message: error.to_string(),
+            })?;
+        if replay.format_version > REPLAY_FORMAT_VERSION {
+            return Err(ReplayFailure::UnsupportedVersion {
+                version: replay.format_version,
+                latest: REPLAY_FORMAT_VERSION,
+            });
+        }
+        Ok(replay)
+    }
+
+    /// 从起点开始逐步回放。
+    pub fn playback(&self) -> ReplayPlayback<'_> {
+        ReplayPlayback {
+            replay: self,
+            rules: RuleEngine::new().with_interactive_priority(self.interactive_priority),
+            state: self.start.initial_state(),
+            position: 0,
+        }
+    }
+
+    /// 回放全部步骤并校验最终哈希，返回最终状态与按顺序产生的全部事件。
+    pub fn verify(&self) -> Result<(GameState, Vec<GameEvent>), ReplayFailure> {
+        let mut playback = self.playback();
+        let events = playback.run()?;
+        Ok((playback.state, events))
+    }
+}
+
+/// 进行中的回放，每次 `step` 重现一个记录的步骤及其事件。
+pub struct ReplayPlayback<'a> {
+    replay: &'a Replay,
+    rules: RuleEngine,
+    state: GameState,
+    position: usize,
+}
+
+impl ReplayPlayback<'_> {
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// 已回放的步骤数。
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.steps.len()
+    }
+
+    /// 回放下一步，全部回放完毕时返回 `None`。
+    pub fn step(&mut self) -> Option<Result<Vec<GameEvent>, ReplayFailure>> {
+        let step = self.replay.steps.get(self.position)?;
+        let result = step
+            .apply(&mut self.rules, &mut self.state)
+            .map_err(|error| ReplayFailure::StepFailed {
+                index: self.position,
+                step: step.clone(),
+                error,
+            });
+        if result.is_ok() {
+            self.position += 1;
+        }
+        Some(result)
+    }
+
+    /// 回放剩余的全部步骤，结束后校验最终哈希。
+    pub fn run(&mut self) -> Result<Vec<GameEvent>, ReplayFailure> {
+        let mut events = Vec::new();
+        while let Some(result) = self.step() {
+            events.extend(result?);
+        }
+        if let Some(expected) = &self.replay.final_hash {
+            let actual = hash_hex(&self.state);
+            if *expected != actual {
+                return Err(ReplayFailure::HashMismatch {
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(events)
+    }
+}
+
+fn hash_hex(state: &GameState) -> String {
+    format!("{:016x}", state.hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CardType, GamePhase, MulliganAction};
+
+    #[test]
+    fn replays_reproduce_games_event_for_event() {
+        let deck = |base: u32| -> Vec<Card> {
+            (0..12)
+                .map(|offset| {
+                    Card::new(
+                        base + offset,
+                        "Recruit",
+                        1,
+                        1,
+                        1,
+                        CardType::Unit,
+                        Vec::new(),
+                    )
+                })
+                .collect()
+        };
+        let mut replay = Replay::new_game(deck(100), deck(200), RuleConfig::default(), 42);
+        let mut rules = RuleEngine::new();
+        let mut state = replay.start.initial_state();
+        let mut recorded = Vec::new();
+        let steps = vec![
+            ReplayStep::Action {
+                action: GameAction::Mulligan {
+                    action: MulliganAction {
+                        player_id: 0,
+                        replacements: Vec::new(),
+                    },
+                },
+            },
+            ReplayStep::Action {
+                action: GameAction::Mulligan {
+                    action: MulliganAction {
+                        player_id: 1,
+                        replacements: Vec::new(),
+                    },
+                },
+            },
+            ReplayStep::Action {
+                action: GameAction::AdvancePhase,
+            },
+            ReplayStep::Action {
+                action: GameAction::EndTurn,
+            },
+        ];
+        for step in steps {
+            recorded.extend(step.apply(&mut rules, &mut state).expect("step applies"));
+            replay.record(step);
+        }
+        assert_eq!(state.phase, GamePhase::Main);
+        replay.finish(&state);
+
+        let loaded = Replay::from_json(&replay.to_json()).expect("round trips");
+        assert_eq!(loaded, replay);
+        let mut playback = loaded.playback();
+        let first = playback.step().expect("has steps").expect("applies");
+        assert_eq!(first[..], recorded[..first.len()]);
+        assert_eq!(playback.position(), 1);
+        let (final_state, events) = loaded.verify().expect("replay matches");
+        assert_eq!(final_state, state);
+        assert_eq!(events, recorded);
+
+        let mut tampered = replay.clone();
+        tampered.steps.pop();
+        assert!(matches!(
+            tampered.verify(),
+            Err(ReplayFailure::HashMismatch { .. })
+        ));
+        tampered
+            .steps
+            .insert(0, ReplayStep::StartTurn { player_id: 9 });
+        assert!(matches!(
+            tampered.verify(),
+            Err(ReplayFailure::StepFailed { index: 0, .. })
+        ));
+        let future = Replay {
+            format_version: REPLAY_FORMAT_VERSION + 1,
+            ..replay
+        };
+        assert_eq!(
+            Replay::from_json(&future.to_json()),
+            Err(ReplayFailure::UnsupportedVersion {
+                version: REPLAY_FORMAT_VERSION + 1,
+                latest: REPLAY_FORMAT_VERSION,
+            })
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! 对局内共用的确定性随机数生成器。
+
+use rand::seq::SliceRandom;
+use rand::{Error, Rng, RngCore};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 以种子初始化的 SplitMix64 随机源，随 `GameState` 一起序列化。
+///
+/// 相同种子与相同操作序列总能得到相同结果，供回放与联机锁步使用。
+/// 抽取随机数需要可变借用，只读借用 `GameState` 的路径（哈希、脱敏、合法性查询等）无法推进随机源。
+/// 种子与内部状态以十进制字符串序列化，经过 JavaScript 往返也不会丢失精度。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "RngRepr", into = "RngRepr")]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RngRepr {
+    #[serde(with = "decimal")]
+    seed: u64,
+    #[serde(with = "decimal")]
+    state: u64,
+}
+
+impl From<RngRepr> for GameRng {
+    fn from(repr: RngRepr) -> Self {
+        Self {
+            seed: repr.seed,
+            state: repr.state,
+        }
+    }
+}
+
+impl From<GameRng> for RngRepr {
+    fn from(rng: GameRng) -> Self {
+        Self {
+            seed: rng.seed,
+            state: rng.state,
+        }
+    }
+}
+
+/// 64 位整数写为十进制字符串；读取时兼容旧存档中的数字。
+mod decimal {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(u64),
+            Text(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(value),
+            Repr::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// 从系统熵源取一个随机种子，供未指定种子的新对局使用；种子随状态保存，回放仍可复现。
+    pub fn entropy_seed() -> u64 {
+        rand::random()
+    }
+
+    /// 初始化时使用的种子。
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// 返回 `0..len` 内的随机下标，`len` 为 0 时返回 `None`。
+    pub fn index(&mut self, len: usize) -> Option<usize> {
+        (len > 0).then(|| self.gen_range(0..len))
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.choose(self)
+    }
+
+    /// 不重复地随机选取至多 `amount` 个元素。
+    pub fn choose_multiple<'a, T>(&mut self, items: &'a [T], amount: usize) -> Vec<&'a T> {
+        items.choose_multiple(self, amount).collect()
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        items.shuffle(self);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (GameRng::next_u64(self) >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        GameRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = GameRng::next_u64(self).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    #[test]
+    fn same_seed_replays_after_serialization() {
+        let mut rng = GameRng::new(42);
+        rng.next_u64();
+        let saved = serde_json::to_string(&rng).unwrap();
+        let mut restored: GameRng = serde_json::from_str(&saved).unwrap();
+
+        let mut first: Vec<u32> = (0..10).collect();
+        let mut second = first.clone();
+        rng.shuffle(&mut first);
+        restored.shuffle(&mut second);
+        assert_eq!(first, second);
+        assert_eq!(rng.index(6), restored.index(6));
+        assert_eq!(restored.seed(), 42);
+        assert_ne!(GameRng::new(7).next_u64(), GameRng::new(42).next_u64());
+
+        let large = GameRng::new(u64::MAX);
+        let json = serde_json::to_value(&large).unwrap();
+        assert_eq!(json["seed"], u64::MAX.to_string());
+        let legacy: GameRng = serde_json::from_str(r#"{"seed":42,"state":42}"#).unwrap();
+        assert_eq!(legacy, GameRng::new(42));
+    }
+
+    #[test]
+    fn seeded_games_resume_identically_after_a_json_round_trip() {
+        let mut state = GameState::sample().with_seed(u64::MAX - 1);
+        state.rng.next_u64();
+        let json = serde_json::to_value(&state).unwrap();
+        let saved = json["rng"]["state"]
+            .as_str()
+            .expect("state is a decimal string");
+        assert!(
+            saved.parse::<u64>().unwrap() > 1 << 53,
+            "beyond JS-safe integers"
+        );
+
+        let mut restored: GameState = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.rng, state.rng);
+        let draws: Vec<u64> = (0..4).map(|_| state.rng.next_u64()).collect();
+        let replayed: Vec<u64> = (0..4).map(|_| restored.rng.next_u64()).collect();
+        assert_eq!(draws, replayed);
+    }
+
+    #[test]
+    fn entropy_seeds_vary_and_are_recorded_on_the_rng() {
+        let seeds: std::collections::HashSet<u64> =
+            (0..8).map(|_| GameRng::entropy_seed()).collect();
+        assert!(seeds.len() > 1, "unseeded games do not share a seed");
+
+        let seed = GameRng::entropy_seed();
+        let rng = GameRng::new(seed);
+        let restored: GameRng =
+            serde_json::from_str(&serde_json::to_string(&rng).unwrap()).unwrap();
+        assert_eq!(restored.seed(), seed, "the seed is saved for replays");
+    }
+}
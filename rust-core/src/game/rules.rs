@@ -1,14 +1,21 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use super::{
-    effects::{EffectContext, EffectEngine, EffectKind, EffectTarget, EffectTrigger},
+    effects::{
+        EffectContext, EffectEngine, EffectKind, EffectStack, EffectTarget, EffectTraceEntry,
+        EffectTrigger, FilterFailure,
+    },
+    hooks::ActionHooks,
     state::{
-        Card, CardId, CardType, GameEvent, GamePhase, GameState, IntegrityError, PlayerId,
-        VictoryState,
+        ActionKind, AdditionalCost, Card, CardId, CardType, ChoiceKind, DrawReason, Expiry,
+        GameEvent, GameOutcome, GamePhase, GameState, IntegrityError, Keyword, PendingAttack,
+        PendingChoice, Player, PlayerId, PriorityWindow, VictoryState,
     },
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct PlayCardAction {
     pub player_id: PlayerId,
     pub card_id: CardId,
@@ -16,9 +23,12 @@ pub struct PlayCardAction {
     pub target_player: Option<PlayerId>,
     #[serde(default)]
     pub target_card: Option<CardId>,
+    /// 单位的插入位置（0 为最左侧），缺省放在最右侧。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct AttackAction {
     pub attacker_owner: PlayerId,
     pub attacker_id: CardId,
@@ -27,20 +37,186 @@ pub struct AttackAction {
     pub defender_card: Option<CardId>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct MulliganAction {
     pub player_id: PlayerId,
     #[serde(default)]
     pub replacements: Vec<CardId>,
 }
 
+/// 自动调度的取舍策略：按费用曲线保留低费卡牌，换掉高费与多余的重复卡牌。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct MulliganPolicy {
+    /// 费用高于此值的卡牌会被换掉。
+    pub max_cost: u8,
+    /// 同名卡牌最多保留的张数。
+    pub max_copies: u8,
+}
+
+impl Default for MulliganPolicy {
+    fn default() -> Self {
+        Self {
+            max_cost: 3,
+            max_copies: 1,
+        }
+    }
+}
+
+impl MulliganPolicy {
+    /// 按策略挑选要换掉的手牌；超过规则的调度上限时优先换掉费用最高的卡牌。
+    pub fn replacements(&self, state: &GameState, player_id: PlayerId) -> Vec<CardId> {
+        let Some(player) = state.get_player(player_id) else {
+            return Vec::new();
+        };
+        let mut kept: HashMap<&str, u8> = HashMap::new();
+        let mut tossed: Vec<(u8, CardId)> = Vec::new();
+        for card in &player.hand {
+            let cost = state.card_cost(player_id, card);
+            if cost > self.max_cost {
+                tossed.push((cost, card.id));
+                continue;
+            }
+            let copies = kept.entry(card.name.as_str()).or_default();
+            if *copies >= self.max_copies {
+                tossed.push((cost, card.id));
+            } else {
+                *copies += 1;
+            }
+        }
+        tossed.sort_by_key(|&(cost, _)| std::cmp::Reverse(cost));
+        if let Some(limit) = state.config.mulligan_limit {
+            tossed.truncate(limit as usize);
+        }
+        tossed.into_iter().map(|(_, card_id)| card_id).collect()
+    }
+
+    /// 按策略替玩家生成的调度操作。
+    pub fn action(&self, state: &GameState, player_id: PlayerId) -> GameAction {
+        GameAction::Mulligan {
+            action: MulliganAction {
+                player_id,
+                replacements: self.replacements(state, player_id),
+            },
+        }
+    }
+}
+
+/// 英雄使用已装备的武器发起攻击。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct HeroAttackAction {
+    pub player_id: PlayerId,
+    pub defender_owner: PlayerId,
+    #[serde(default)]
+    pub defender_card: Option<CardId>,
+}
+
+/// 使用英雄技能，目标规则与打出卡牌相同。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct UseHeroPowerAction {
+    pub player_id: PlayerId,
+    #[serde(default)]
+    pub target_player: Option<PlayerId>,
+    #[serde(default)]
+    pub target_card: Option<CardId>,
+}
+
+/// 攻击可选择的目标：对方英雄或对方场上的单位。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum AttackTargetOption {
+    Hero {
+        player_id: PlayerId,
+    },
+    Unit {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct DiscardCardAction {
     pub player_id: PlayerId,
     pub pending_id: u64,
     pub discard_card_id: CardId,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ResolveChoiceAction {
+    pub player_id: PlayerId,
+    pub choice_id: u64,
+    /// 所选模式在选项列表中的下标。
+    pub option: usize,
+}
+
+/// 持有优先权的玩家放弃响应。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PassPriorityAction {
+    pub player_id: PlayerId,
+}
+
+/// 攻击方结算两段式战斗中已宣告的攻击。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ResolveCombatAction {
+    pub player_id: PlayerId,
+}
+
+/// 玩家的一步操作，供 AI 搜索与合法操作枚举使用。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "type")]
+pub enum GameAction {
+    PlayCard { action: PlayCardAction },
+    Mulligan { action: MulliganAction },
+    Attack { action: AttackAction },
+    HeroAttack { action: HeroAttackAction },
+    UseHeroPower { action: UseHeroPowerAction },
+    ResolveChoice { action: ResolveChoiceAction },
+    DiscardCard { action: DiscardCardAction },
+    PassPriority { action: PassPriorityAction },
+    ResolveCombat { action: ResolveCombatAction },
+    AdvancePhase,
+    EndTurn,
+}
+
+impl GameAction {
+    /// 执行该操作的玩家；推进阶段与结束回合由当前玩家执行，返回 `None`。
+    pub fn player_id(&self) -> Option<PlayerId> {
+        match self {
+            GameAction::PlayCard { action } => Some(action.player_id),
+            GameAction::Mulligan { action } => Some(action.player_id),
+            GameAction::Attack { action } => Some(action.attacker_owner),
+            GameAction::HeroAttack { action } => Some(action.player_id),
+            GameAction::UseHeroPower { action } => Some(action.player_id),
+            GameAction::ResolveChoice { action } => Some(action.player_id),
+            GameAction::DiscardCard { action } => Some(action.player_id),
+            GameAction::PassPriority { action } => Some(action.player_id),
+            GameAction::ResolveCombat { action } => Some(action.player_id),
+            GameAction::AdvancePhase | GameAction::EndTurn => None,
+        }
+    }
+
+    /// 两个操作是否针对同一对象（同一张卡牌、同一攻击者或同一选择），仅目标不同。
+    fn same_subject(&self, other: &GameAction) -> bool {
+        match (self, other) {
+            (GameAction::PlayCard { action: a }, GameAction::PlayCard { action: b }) => {
+                a.card_id == b.card_id
+            }
+            (GameAction::Attack { action: a }, GameAction::Attack { action: b }) => {
+                a.attacker_id == b.attacker_id
+            }
+            (GameAction::ResolveChoice { action: a }, GameAction::ResolveChoice { action: b }) => {
+                a.choice_id == b.choice_id
+            }
+            (GameAction::DiscardCard { action: a }, GameAction::DiscardCard { action: b }) => {
+                a.pending_id == b.pending_id
+            }
+            (GameAction::HeroAttack { .. }, GameAction::HeroAttack { .. })
+            | (GameAction::UseHeroPower { .. }, GameAction::UseHeroPower { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum RuleError {
@@ -57,10 +233,22 @@ pub enum RuleError {
         card_id: CardId,
     },
     InvalidTarget,
+    /// 目标存在，但未通过卡牌的目标筛选条件。
+    TargetFilterFailed {
+        filter: FilterFailure,
+    },
     InsufficientMana {
         required: u8,
         available: u8,
     },
+    InsufficientHealth {
+        required: u8,
+        available: i16,
+    },
+    InsufficientCardsToDiscard {
+        required: u8,
+        available: u8,
+    },
     CardTypeMismatch {
         expected: CardType,
         actual: CardType,
@@ -68,25 +256,194 @@ pub enum RuleError {
     UnitExhausted {
         card_id: CardId,
     },
+    /// 单位本回合刚进场，尚不能攻击（突袭单位不能攻击英雄）。
+    SummoningSick {
+        card_id: CardId,
+    },
     InvalidAttackTarget,
+    /// 组队模式下不能攻击队友及其单位。
+    CannotAttackAlly {
+        player_id: PlayerId,
+    },
+    /// 对手场上有嘲讽单位时只能攻击嘲讽单位。
+    MustAttackTaunt,
+    /// 具有灵巧的单位不能被指定为目标。
+    TargetElusive {
+        card_id: CardId,
+    },
+    /// 潜行单位不能被攻击。
+    TargetStealthed {
+        card_id: CardId,
+    },
     AttackerNotFound {
         card_id: CardId,
     },
     ZeroAttackUnit {
         card_id: CardId,
     },
+    UnitFrozen {
+        card_id: CardId,
+    },
+    NoWeaponEquipped {
+        player_id: PlayerId,
+    },
+    HeroAlreadyAttacked {
+        player_id: PlayerId,
+    },
+    NoHeroPower {
+        player_id: PlayerId,
+    },
+    HeroPowerAlreadyUsed {
+        player_id: PlayerId,
+    },
     BoardFull,
+    /// 本回合打出的卡牌数已达到规则允许的上限。
+    CardsPerTurnLimitReached {
+        limit: u8,
+    },
+    /// 本回合施放的法术数已达到规则允许的上限。
+    SpellsPerTurnLimitReached {
+        limit: u8,
+    },
+    InvalidBoardPosition {
+        position: usize,
+        board_size: usize,
+    },
     MulliganPhaseOnly,
+    /// 双方调度完毕前不能离开调度阶段。
+    MulliganIncomplete,
     MulliganAlreadyCompleted {
         player_id: PlayerId,
     },
+    /// 替换的手牌数超过规则允许的上限。
+    MulliganLimitExceeded {
+        limit: u8,
+        requested: usize,
+    },
     PendingDiscardNotFound {
         player_id: PlayerId,
         pending_id: u64,
     },
+    PendingChoiceNotFound {
+        player_id: PlayerId,
+        choice_id: u64,
+    },
+    /// 有尚未完成的选择时只能先结算该选择。
+    ChoicePending {
+        player_id: PlayerId,
+        choice_id: u64,
+    },
+    InvalidChoiceOption {
+        choice_id: u64,
+        option: usize,
+    },
+    /// 需等待该玩家响应或放弃优先权。
+    AwaitingPriority {
+        player_id: PlayerId,
+    },
+    NoPriorityWindow,
+    /// 两段式战斗中已宣告的攻击需先结算。
+    CombatPending {
+        attacker_id: CardId,
+    },
+    NoPendingAttack,
     IntegrityViolation {
         error: IntegrityError,
     },
+    /// 操作被注册的拦截器否决。
+    ActionVetoed {
+        reason: String,
+    },
+    /// 批量操作中第 `index` 个操作失败，整批已回滚。
+    BatchActionFailed {
+        index: usize,
+        error: Box<RuleError>,
+    },
+}
+
+impl RuleError {
+    /// 稳定的错误码，供前端按错误码本地化提示，不随枚举重构而改变。
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuleError::GameFinished => "game_finished",
+            RuleError::NotPlayerTurn => "not_player_turn",
+            RuleError::PlayerNotFound { .. } => "player_not_found",
+            RuleError::InvalidPhase { .. } => "invalid_phase",
+            RuleError::CardNotFound { .. } => "card_not_found",
+            RuleError::InvalidTarget => "invalid_target",
+            RuleError::TargetFilterFailed { .. } => "target_filter_failed",
+            RuleError::InsufficientMana { .. } => "insufficient_mana",
+            RuleError::InsufficientHealth { .. } => "insufficient_health",
+            RuleError::InsufficientCardsToDiscard { .. } => "insufficient_cards_to_discard",
+            RuleError::CardTypeMismatch { .. } => "card_type_mismatch",
+            RuleError::UnitExhausted { .. } => "unit_exhausted",
+            RuleError::SummoningSick { .. } => "summoning_sick",
+            RuleError::InvalidAttackTarget => "invalid_attack_target",
+            RuleError::CannotAttackAlly { .. } => "cannot_attack_ally",
+            RuleError::MustAttackTaunt => "must_attack_taunt",
+            RuleError::TargetElusive { .. } => "target_elusive",
+            RuleError::TargetStealthed { .. } => "target_stealthed",
+            RuleError::AttackerNotFound { .. } => "attacker_not_found",
+            RuleError::ZeroAttackUnit { .. } => "zero_attack_unit",
+            RuleError::UnitFrozen { .. } => "unit_frozen",
+            RuleError::NoWeaponEquipped { .. } => "no_weapon_equipped",
+            RuleError::HeroAlreadyAttacked { .. } => "hero_already_attacked",
+            RuleError::NoHeroPower { .. } => "no_hero_power",
+            RuleError::HeroPowerAlreadyUsed { .. } => "hero_power_already_used",
+            RuleError::BoardFull => "board_full",
+            RuleError::CardsPerTurnLimitReached { .. } => "cards_per_turn_limit_reached",
+            RuleError::SpellsPerTurnLimitReached { .. } => "spells_per_turn_limit_reached",
+            RuleError::InvalidBoardPosition { .. } => "invalid_board_position",
+            RuleError::MulliganPhaseOnly => "mulligan_phase_only",
+            RuleError::MulliganIncomplete => "mulligan_incomplete",
+            RuleError::MulliganAlreadyCompleted { .. } => "mulligan_already_completed",
+            RuleError::MulliganLimitExceeded { .. } => "mulligan_limit_exceeded",
+            RuleError::PendingDiscardNotFound { .. } => "pending_discard_not_found",
+            RuleError::PendingChoiceNotFound { .. } => "pending_choice_not_found",
+            RuleError::ChoicePending { .. } => "choice_pending",
+            RuleError::InvalidChoiceOption { .. } => "invalid_choice_option",
+            RuleError::AwaitingPriority { .. } => "awaiting_priority",
+            RuleError::NoPriorityWindow => "no_priority_window",
+            RuleError::CombatPending { .. } => "combat_pending",
+            RuleError::NoPendingAttack => "no_pending_attack",
+            RuleError::IntegrityViolation { .. } => "integrity_violation",
+            RuleError::ActionVetoed { .. } => "action_vetoed",
+            RuleError::BatchActionFailed { .. } => "batch_action_failed",
+        }
+    }
+}
+
+/// 序列化给前端的错误报告：错误码、错误详情（平铺）、出错的操作以及可替代的合法操作。
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct RuleErrorReport {
+    pub code: &'static str,
+    #[serde(flatten)]
+    pub error: RuleError,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<GameAction>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<GameAction>,
+}
+
+impl RuleErrorReport {
+    pub fn new(error: RuleError) -> Self {
+        Self {
+            code: error.code(),
+            error,
+            action: None,
+            alternatives: Vec::new(),
+        }
+    }
+}
+
+/// 重放操作序列时的首个分歧点：第 `index` 个操作无法在重放状态上执行。
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ReplayError {
+    pub index: usize,
+    pub action: GameAction,
+    pub error: RuleError,
+    /// 分歧发生时的重放状态，便于与记录方的状态比对。
+    pub state: Box<GameState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +452,11 @@ pub struct RuleResolution {
     pub events: Vec<GameEvent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub victory: Option<VictoryState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draw: Option<DrawReason>,
+    /// 开启结算追踪时附带的效果执行记录。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<EffectTraceEntry>,
 }
 
 impl RuleResolution {
@@ -111,52 +473,248 @@ impl RuleResolution {
                 });
             }
         }
+        let draw = state.draw.clone();
+        if let Some(reason) = &draw {
+            let has_event = events
+                .iter()
+                .any(|event| matches!(event, GameEvent::GameDrawn { .. }));
+            if !has_event {
+                events.push(GameEvent::GameDrawn {
+                    reason: reason.clone(),
+                });
+            }
+        }
+        let has_summary = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameSummary { .. }));
+        if state.is_finished() && !has_summary {
+            events.push(state.summary());
+        }
 
         Self {
             state,
             events,
             victory,
+            draw,
+            trace: Vec::new(),
         }
     }
+
+    pub fn with_trace(mut self, trace: Vec<EffectTraceEntry>) -> Self {
+        self.trace = trace;
+        self
+    }
+}
+
+/// 出牌校验通过后执行所需的信息。
+struct PlayPlan {
+    player_index: usize,
+    hand_index: usize,
+    cost: u8,
+    additional_cost: AdditionalCost,
+    /// 以优先权响应时被响应的卡牌。
+    response_to: Option<Option<CardId>>,
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct RuleEngine {
     effect_engine: EffectEngine,
+    /// 开启后，打出卡牌的效果入栈后先等待对手响应再结算。
+    interactive_priority: bool,
+    hooks: ActionHooks,
 }
 
 impl RuleEngine {
     pub fn new() -> Self {
         Self {
             effect_engine: EffectEngine::default(),
+            interactive_priority: false,
+            hooks: ActionHooks::default(),
         }
     }
 
-    fn ensure_play_phase(state: &GameState) -> Result<(), RuleError> {
-        if state.phase != GamePhase::Main {
-            return Err(RuleError::InvalidPhase {
-                expected: GamePhase::Main,
-                actual: state.phase.clone(),
-            });
+    /// 注册执行前拦截器：`apply_action` 与 `validate` 都会先调用它，返回错误即否决操作。
+    pub fn add_pre_action_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&GameState, &GameAction) -> Result<(), RuleError> + 'static,
+    {
+        self.hooks.add_pre(hook);
+    }
+
+    /// 注册执行后拦截器：操作成功后调用，返回的事件追加到结果与日志中。
+    pub fn add_post_action_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&mut GameState, &GameAction, &[GameEvent]) -> Vec<GameEvent> + 'static,
+    {
+        self.hooks.add_post(hook);
+    }
+
+    pub fn with_hooks(mut self, hooks: ActionHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn hooks(&self) -> &ActionHooks {
+        &self.hooks
+    }
+
+    pub fn clear_hooks(&mut self) {
+        self.hooks.clear();
+    }
+
+    pub fn with_interactive_priority(mut self, enabled: bool) -> Self {
+        self.interactive_priority = enabled;
+        self
+    }
+
+    pub fn set_interactive_priority(&mut self, enabled: bool) {
+        self.interactive_priority = enabled;
+    }
+
+    pub fn interactive_priority(&self) -> bool {
+        self.interactive_priority
+    }
+
+    pub fn with_effect_trace(mut self, enabled: bool) -> Self {
+        self.effect_engine.set_trace(enabled);
+        self
+    }
+
+    pub fn set_effect_trace(&mut self, enabled: bool) {
+        self.effect_engine.set_trace(enabled);
+    }
+
+    pub fn with_resolution_budget(mut self, budget: usize) -> Self {
+        self.effect_engine.set_resolution_budget(budget);
+        self
+    }
+
+    /// 设置单次结算最多处理的栈条目数。
+    pub fn set_resolution_budget(&mut self, budget: usize) {
+        self.effect_engine.set_resolution_budget(budget);
+    }
+
+    pub fn resolution_budget(&self) -> usize {
+        self.effect_engine.resolution_budget()
+    }
+
+    /// 取出自上次调用以来记录的效果追踪。
+    pub fn take_effect_trace(&mut self) -> Vec<EffectTraceEntry> {
+        self.effect_engine.take_trace()
+    }
+
+    /// 当前尚未结算的效果栈。
+    pub fn effect_stack(&self) -> &EffectStack {
+        self.effect_engine.stack()
+    }
+
+    fn ensure_no_priority_window(state: &GameState) -> Result<(), RuleError> {
+        match &state.priority_window {
+            Some(window) => Err(RuleError::AwaitingPriority {
+                player_id: window.player_id,
+            }),
+            None => Ok(()),
         }
-        Ok(())
     }
 
-    fn ensure_combat_phase(state: &GameState) -> Result<(), RuleError> {
-        if state.phase != GamePhase::Combat {
-            return Err(RuleError::InvalidPhase {
-                expected: GamePhase::Combat,
-                actual: state.phase.clone(),
+    /// 交互模式下若效果栈非空，则把优先权交给 `actor` 的对手并暂停结算。
+    fn open_priority_window(
+        &mut self,
+        state: &mut GameState,
+        actor: PlayerId,
+        source_card: Option<CardId>,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
+        if !self.interactive_priority || self.effect_engine.stack().is_empty() {
+            return false;
+        }
+        let Some(responder) = state.opponent_of(actor) else {
+            return false;
+        };
+        self.effect_engine.open_response_layer();
+        self.grant_priority(state, responder, source_card, events);
+        true
+    }
+
+    fn grant_priority(
+        &mut self,
+        state: &mut GameState,
+        responder: PlayerId,
+        source_card: Option<CardId>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        state.priority_window = Some(PriorityWindow {
+            player_id: responder,
+            source_card,
+            stack: self.effect_engine.take_stack(),
+        });
+        let event = GameEvent::PriorityGranted {
+            player_id: responder,
+            source_card,
+        };
+        state.record_event(event.clone());
+        events.push(event);
+    }
+
+    /// 响应方放弃优先权，关闭窗口并结算整个效果栈。
+    pub fn pass_priority(
+        &mut self,
+        state: &mut GameState,
+        action: PassPriorityAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        Self::check_pass_priority(state, &action)?;
+        if let Some(window) = state.priority_window.take() {
+            self.effect_engine.restore_stack(window.stack);
+        }
+
+        let mut events = Vec::new();
+        let pass_event = GameEvent::PriorityPassed {
+            player_id: action.player_id,
+        };
+        state.record_event(pass_event.clone());
+        events.push(pass_event);
+
+        let mut effect_events = self.effect_engine.resolve_all(state);
+        events.append(&mut effect_events);
+
+        self.settle_victory(state, &mut events);
+
+        Ok(events)
+    }
+
+    fn check_pass_priority(
+        state: &GameState,
+        action: &PassPriorityAction,
+    ) -> Result<(), RuleError> {
+        if state.is_finished() {
+            return Err(RuleError::GameFinished);
+        }
+        let window = state
+            .priority_window
+            .as_ref()
+            .ok_or(RuleError::NoPriorityWindow)?;
+        if window.player_id != action.player_id {
+            return Err(RuleError::AwaitingPriority {
+                player_id: window.player_id,
             });
         }
         Ok(())
     }
 
-    fn ensure_mulligan_phase(state: &GameState) -> Result<(), RuleError> {
-        if state.phase != GamePhase::Mulligan {
-            return Err(RuleError::MulliganPhaseOnly);
+    /// 校验当前阶段允许该类操作，否则报告该操作所属的阶段。
+    fn ensure_phase_allows(state: &GameState, kind: ActionKind) -> Result<(), RuleError> {
+        if state.phase.allows(kind) {
+            return Ok(());
         }
-        Ok(())
+        let expected = match kind {
+            ActionKind::Mulligan => return Err(RuleError::MulliganPhaseOnly),
+            ActionKind::Attack => GamePhase::Combat,
+            _ => GamePhase::Main,
+        };
+        Err(RuleError::InvalidPhase {
+            expected,
+            actual: state.phase.clone(),
+        })
     }
 
     fn ensure_turn_owner(state: &GameState, player_id: PlayerId) -> Result<(), RuleError> {
@@ -166,6 +724,81 @@ impl RuleEngine {
         Ok(())
     }
 
+    fn check_taunt(
+        state: &GameState,
+        defender_owner: PlayerId,
+        defender_card: Option<CardId>,
+    ) -> Result<(), RuleError> {
+        let Some(defender) = state.get_player(defender_owner) else {
+            return Ok(());
+        };
+        // 潜行的嘲讽单位不强制对手攻击它
+        let guards =
+            |card: &Card| card.has_keyword(Keyword::Taunt) && !card.has_keyword(Keyword::Stealth);
+        let is_taunt = |card_id: CardId| {
+            defender
+                .board
+                .iter()
+                .any(|card| card.id == card_id && guards(card))
+        };
+        let taunt_present = defender.board.iter().any(guards);
+        if taunt_present && !defender_card.is_some_and(is_taunt) {
+            return Err(RuleError::MustAttackTaunt);
+        }
+        Ok(())
+    }
+
+    /// 校验额外费用；弃牌数量不含正在打出的这张牌。
+    fn check_additional_cost(player: &Player, cost: AdditionalCost) -> Result<(), RuleError> {
+        if cost.health > 0 && player.health <= cost.health as i16 {
+            return Err(RuleError::InsufficientHealth {
+                required: cost.health,
+                available: player.health,
+            });
+        }
+        let other_cards = player.hand.len().saturating_sub(1) as u8;
+        if other_cards < cost.discard {
+            return Err(RuleError::InsufficientCardsToDiscard {
+                required: cost.discard,
+                available: other_cards,
+            });
+        }
+        Ok(())
+    }
+
+    fn pay_additional_cost(
+        state: &mut GameState,
+        player_index: usize,
+        card_id: CardId,
+        cost: AdditionalCost,
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let player = &mut state.players[player_index];
+        if cost.health > 0 {
+            player.health -= cost.health as i16;
+            events.push(GameEvent::HealthPaid {
+                player_id: player.id,
+                card_id,
+                amount: cost.health as i16,
+            });
+        }
+        for _ in 0..cost.discard {
+            let Some(index) = state.rng.index(player.hand.len()) else {
+                break;
+            };
+            let card = player.hand.remove(index);
+            player.graveyard.push(card.clone());
+            events.push(GameEvent::CardDiscarded {
+                player_id: player.id,
+                card,
+            });
+        }
+        for event in &events {
+            state.record_event(event.clone());
+        }
+        events
+    }
+
     fn ensure_integrity(state: &GameState) -> Result<(), RuleError> {
         state
             .integrity_check()
@@ -173,22 +806,108 @@ impl RuleEngine {
     }
 
     fn requires_target(card: &Card) -> bool {
-        card.effects.iter().any(|effect| match &effect.kind {
-            EffectKind::DirectDamage { target, .. }
-            | EffectKind::Heal { target, .. }
-            | EffectKind::DrawCard { target, .. } => matches!(target, EffectTarget::ContextTarget),
-            EffectKind::Composite { effects } => effects.iter().any(Self::requires_target_kind),
-            EffectKind::Conditional { effect, .. } => Self::requires_target_kind(effect),
-        })
+        // 奥秘的目标由触发它的行动决定
+        if card.card_type == CardType::Secret {
+            return false;
+        }
+        card.effects
+            .iter()
+            .any(|effect| Self::requires_target_kind(&effect.kind))
+    }
+
+    /// 校验玩家所选的目标玩家与场上单位存在。
+    fn check_chosen_target(
+        state: &GameState,
+        target_player: Option<PlayerId>,
+        target_card: Option<CardId>,
+    ) -> Result<(), RuleError> {
+        if target_card.is_some() && target_player.is_none() {
+            return Err(RuleError::InvalidTarget);
+        }
+        if let Some(target_player) = target_player {
+            let player = state
+                .get_player(target_player)
+                .ok_or(RuleError::InvalidTarget)?;
+            if let Some(card_id) = target_card {
+                let card = player
+                    .find_card_on_board(card_id)
+                    .ok_or(RuleError::InvalidTarget)?;
+                if !card.is_targetable() {
+                    return Err(RuleError::TargetElusive { card_id });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验玩家所选目标是否满足卡牌各效果的目标筛选。
+    fn check_target_filters(
+        card: &Card,
+        action: &PlayCardAction,
+        state: &GameState,
+    ) -> Result<(), RuleError> {
+        let Some(target_player) = action.target_player else {
+            return Ok(());
+        };
+        let target_card = action.target_card.and_then(|card_id| {
+            state
+                .get_player(target_player)
+                .and_then(|player| player.board.iter().find(|card| card.id == card_id))
+        });
+        for effect in &card.effects {
+            let Some(filter) = &effect.target_filter else {
+                continue;
+            };
+            if !Self::requires_target_kind(&effect.kind) {
+                continue;
+            }
+            filter
+                .check(target_card)
+                .map_err(|failure| RuleError::TargetFilterFailed { filter: failure })?;
+        }
+        Ok(())
     }
 
-    fn requires_target_kind(kind: &EffectKind) -> bool {
+    pub(crate) fn requires_target_kind(kind: &EffectKind) -> bool {
         match kind {
             EffectKind::DirectDamage { target, .. }
             | EffectKind::Heal { target, .. }
-            | EffectKind::DrawCard { target, .. } => matches!(target, EffectTarget::ContextTarget),
+            | EffectKind::DrawCard { target, .. }
+            | EffectKind::Mill { target, .. }
+            | EffectKind::GainArmor { target, .. }
+            | EffectKind::PreventDamage { target, .. }
+            | EffectKind::ModifyStats { target, .. }
+            | EffectKind::Freeze { target, .. }
+            | EffectKind::SetStats { target, .. }
+            | EffectKind::GrantKeyword { target, .. }
+            | EffectKind::Silence { target }
+            | EffectKind::TakeControl { target, .. }
+            | EffectKind::RevealHand { target, .. }
+            | EffectKind::EnchantPlayer { target, .. }
+            | EffectKind::AddCounters { target, .. }
+            | EffectKind::RemoveCounters { target, .. }
+            | EffectKind::GainMana { target, .. }
+            | EffectKind::GainManaCrystal { target, .. } => {
+                matches!(
+                    target,
+                    EffectTarget::ContextTarget | EffectTarget::AdjacentToTarget
+                )
+            }
+            EffectKind::CostModifier { .. }
+            | EffectKind::StatAura { .. }
+            | EffectKind::Discover { .. }
+            | EffectKind::SelectTarget { .. }
+            | EffectKind::Counter
+            | EffectKind::Overload { .. }
+            | EffectKind::Resurrect { .. }
+            | EffectKind::AddAlternativeVictory { .. } => false,
             EffectKind::Composite { effects } => effects.iter().any(Self::requires_target_kind),
-            EffectKind::Conditional { effect, .. } => Self::requires_target_kind(effect),
+            EffectKind::ChooseOne { options } => options
+                .iter()
+                .any(|option| Self::requires_target_kind(&option.effect)),
+            EffectKind::Conditional { effect, .. } | EffectKind::Delayed { effect, .. } => {
+                Self::requires_target_kind(effect)
+            }
         }
     }
 
@@ -221,14 +940,22 @@ impl RuleEngine {
 
         let mut events = Vec::new();
 
+        // 限时加成到期导致的死亡与回合开始效果一同结算亡语
+        let expired = state.expire_timed_effects(Expiry::StartOfTurn { player_id });
+        self.effect_engine.observe_events(state, &expired);
+        events.extend(expired);
+
         if let Some(index) = state.player_index(player_id) {
             let board_snapshot: Vec<Card> = state.players[index].board.clone();
             for card in &board_snapshot {
-                let ctx = EffectContext::new(EffectTrigger::OnTurnStart, player_id, state.current_player)
-                    .with_source_card(card.id);
+                let ctx =
+                    EffectContext::new(EffectTrigger::OnTurnStart, player_id, state.current_player)
+                        .with_source_card(card.id);
                 self.effect_engine.queue_card_effects(card, ctx);
             }
         }
+        self.effect_engine
+            .queue_scheduled_effects(state, EffectTrigger::OnTurnStart, player_id);
 
         let mut trigger_events = self.effect_engine.resolve_all(state);
         events.append(&mut trigger_events);
@@ -237,48 +964,64 @@ impl RuleEngine {
             return Ok(events);
         }
 
-        state.ready_player(player_id);
+        let ready_events = state.ready_player(player_id);
+        self.effect_engine.queue_reactions(state, &ready_events);
+        events.extend(ready_events);
+        let mut reaction_events = self.effect_engine.resolve_all(state);
+        events.append(&mut reaction_events);
 
-        if let Some(outcome) = state.evaluate_victory() {
-            events.push(GameEvent::GameWon {
-                winner: outcome.winner,
-                reason: outcome.reason.clone(),
-            });
-        }
+        self.settle_victory(state, &mut events);
 
         Ok(events)
     }
 
-    pub fn play_card(
-        &mut self,
-        state: &mut GameState,
-        action: PlayCardAction,
-    ) -> Result<Vec<GameEvent>, RuleError> {
+    fn ensure_active(state: &GameState) -> Result<(), RuleError> {
         if state.is_finished() {
             return Err(RuleError::GameFinished);
         }
+        Self::ensure_integrity(state)
+    }
 
-        Self::ensure_integrity(state)?;
-        Self::ensure_turn_owner(state, action.player_id)?;
-        Self::ensure_play_phase(state)?;
+    fn ensure_no_pending_attack(state: &GameState) -> Result<(), RuleError> {
+        match &state.pending_attack {
+            Some(pending) => Err(RuleError::CombatPending {
+                attacker_id: pending.attacker_id,
+            }),
+            None => Ok(()),
+        }
+    }
 
-        if action.target_card.is_some() && action.target_player.is_none() {
-            return Err(RuleError::InvalidTarget);
+    fn ensure_no_pending_choice(state: &GameState) -> Result<(), RuleError> {
+        match state.pending_choices.first() {
+            Some(pending) => Err(RuleError::ChoicePending {
+                player_id: pending.player_id,
+                choice_id: pending.id,
+            }),
+            None => Ok(()),
         }
-        if let Some(target_player) = action.target_player {
-            state
-                .get_player(target_player)
-                .ok_or(RuleError::InvalidTarget)?;
-            if let Some(target_card) = action.target_card {
-                let target_exists = state
-                    .get_player(target_player)
-                    .and_then(|player| player.board.iter().find(|card| card.id == target_card))
-                    .is_some();
-                if !target_exists {
-                    return Err(RuleError::InvalidTarget);
-                }
-            }
+    }
+
+    fn check_play_card(
+        &self,
+        state: &GameState,
+        action: &PlayCardAction,
+    ) -> Result<PlayPlan, RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        // 持有优先权的玩家可以在对手回合打出法术作为响应
+        let response_to = state
+            .priority_window
+            .as_ref()
+            .filter(|window| window.player_id == action.player_id)
+            .map(|window| window.source_card);
+        if response_to.is_none() {
+            Self::ensure_no_priority_window(state)?;
+            Self::ensure_no_pending_attack(state)?;
+            Self::ensure_turn_owner(state, action.player_id)?;
         }
+        Self::ensure_phase_allows(state, ActionKind::Play)?;
+
+        Self::check_chosen_target(state, action.target_player, action.target_card)?;
 
         let player_index = state
             .player_index(action.player_id)
@@ -293,7 +1036,10 @@ impl RuleEngine {
                 card_id: action.card_id,
             })?;
 
-        let cost = state.players[player_index].hand[hand_index].cost;
+        let cost = state.card_cost(
+            action.player_id,
+            &state.players[player_index].hand[hand_index],
+        );
         if available_mana < cost {
             return Err(RuleError::InsufficientMana {
                 required: cost,
@@ -301,26 +1047,91 @@ impl RuleEngine {
             });
         }
 
-        let pending_card_type = state.players[player_index].hand[hand_index]
-            .card_type
-            .clone();
+        let additional_cost = state.players[player_index].hand[hand_index].additional_cost;
+        Self::check_additional_cost(&state.players[player_index], additional_cost)?;
+
+        let pending_card_type = state.players[player_index].hand[hand_index].card_type;
+        if response_to.is_some() && pending_card_type != CardType::Spell {
+            return Err(RuleError::CardTypeMismatch {
+                expected: CardType::Spell,
+                actual: pending_card_type,
+            });
+        }
         if pending_card_type == CardType::Unit
-            && state.players[player_index].board.len() as u8 >= state.max_board_size
+            && state.players[player_index].board.len() as u8 >= state.config.max_board_size
         {
             return Err(RuleError::BoardFull);
         }
+        Self::check_turn_play_limits(state, &state.players[player_index], pending_card_type)?;
+        let board_size = state.players[player_index].board.len();
+        if let Some(position) = action.position.filter(|position| *position > board_size) {
+            return Err(RuleError::InvalidBoardPosition {
+                position,
+                board_size,
+            });
+        }
 
-        let mut card = state.players[player_index].hand.remove(hand_index);
-
-        if Self::requires_target(&card)
+        let card = &state.players[player_index].hand[hand_index];
+        Self::check_target_filters(card, action, state)?;
+        if Self::requires_target(card)
             && action.target_player.is_none()
             && action.target_card.is_none()
         {
             return Err(RuleError::InvalidTarget);
         }
+
+        Ok(PlayPlan {
+            player_index,
+            hand_index,
+            cost,
+            additional_cost,
+            response_to,
+        })
+    }
+
+    /// 检查规则配置中的每回合出牌限制。
+    fn check_turn_play_limits(
+        state: &GameState,
+        player: &Player,
+        card_type: CardType,
+    ) -> Result<(), RuleError> {
+        if let Some(limit) = state.config.max_cards_per_turn {
+            if player.cards_played_this_turn.len() >= limit as usize {
+                return Err(RuleError::CardsPerTurnLimitReached { limit });
+            }
+        }
+        if let Some(limit) = state.config.max_spells_per_turn {
+            if card_type == CardType::Spell && player.spells_played_this_turn >= limit {
+                return Err(RuleError::SpellsPerTurnLimitReached { limit });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn play_card(
+        &mut self,
+        state: &mut GameState,
+        action: PlayCardAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let PlayPlan {
+            player_index,
+            hand_index,
+            cost,
+            additional_cost,
+            response_to,
+        } = self.check_play_card(state, &action)?;
+
+        // 响应期间效果栈暂存在窗口中，先取回以便响应效果入栈
+        let mut parked = response_to.and_then(|_| state.priority_window.take());
+        if let Some(window) = parked.as_mut() {
+            self.effect_engine
+                .restore_stack(std::mem::take(&mut window.stack));
+        }
+
+        let mut card = state.players[player_index].hand.remove(hand_index);
         state.players[player_index].mana -= cost;
 
-        let mut events = Vec::new();
+        let mut events = Self::pay_additional_cost(state, player_index, card.id, additional_cost);
         let play_event = GameEvent::CardPlayed {
             player_id: action.player_id,
             card_id: card.id,
@@ -329,46 +1140,116 @@ impl RuleEngine {
         state.record_event(play_event.clone());
         events.push(play_event);
 
-        let context = Self::build_context(&action, state);
+        let mut context = Self::build_context(&action, state);
+        if let Some(Some(responded_card)) = response_to {
+            context.related_card = Some(responded_card);
+        }
+        let played_card_id = card.id;
+
+        let player = &mut state.players[player_index];
+        if !player.cards_played_this_turn.is_empty() && card.card_type != CardType::Secret {
+            let mut combo_context = context.clone();
+            combo_context.trigger = EffectTrigger::Combo;
+            self.effect_engine.queue_card_effects(&card, combo_context);
+        }
+        player.cards_played_this_turn.push(played_card_id);
+        player.stats.cards_played = player.stats.cards_played.saturating_add(1);
+        if card.card_type == CardType::Spell {
+            player.spells_played_this_turn += 1;
+        }
 
         match card.card_type {
             CardType::Unit => {
-                card.exhausted = true;
-                state.players[player_index].board.push(card);
-                if let Some(board_card) = state.players[player_index].board.last() {
-                    self.effect_engine.queue_card_effects(board_card, context);
-                }
+                card.exhausted = false;
+                card.summoning_sick = true;
+                let card_id = card.id;
+                let board_len = state.players[player_index].board.len();
+                let position = action.position.unwrap_or(board_len).min(board_len);
+                let summon_event = state.summon_unit(action.player_id, card, Some(position));
+                self.effect_engine
+                    .queue_card_effects(&state.players[player_index].board[position], context);
+                self.effect_engine
+                    .observe_events(state, summon_event.as_slice());
+                events.extend(summon_event);
+                let mut secret_events = self.effect_engine.trigger_secrets(
+                    state,
+                    EffectTrigger::OnSummon,
+                    action.player_id,
+                    Some((action.player_id, card_id)),
+                    None,
+                );
+                events.append(&mut secret_events);
             }
             CardType::Spell => {
                 self.effect_engine.queue_card_effects(&card, context);
+                let board_snapshot = state.players[player_index].board.clone();
+                for listener in &board_snapshot {
+                    let ctx = EffectContext::new(
+                        EffectTrigger::OnSpellCast,
+                        action.player_id,
+                        state.current_player,
+                    )
+                    .with_source_card(listener.id)
+                    .with_related_card(card.id);
+                    self.effect_engine.queue_card_effects(listener, ctx);
+                }
+                let mut secret_events = self.effect_engine.trigger_secrets(
+                    state,
+                    EffectTrigger::OnSpellCast,
+                    action.player_id,
+                    None,
+                    Some(card.id),
+                );
+                events.append(&mut secret_events);
+            }
+            CardType::Weapon => {
+                self.effect_engine.queue_card_effects(&card, context);
+                let equip_events = state.equip_weapon(action.player_id, card);
+                self.effect_engine.observe_events(state, &equip_events);
+                events.extend(equip_events);
             }
+            CardType::Secret => {
+                let secret_event = GameEvent::SecretPlayed {
+                    player_id: action.player_id,
+                    card_id: card.id,
+                };
+                state.players[player_index].secrets.push(card);
+                state.record_event(secret_event.clone());
+                events.push(secret_event);
+            }
+        }
+
+        if let Some(window) = parked {
+            state.priority_window = Some(PriorityWindow {
+                stack: self.effect_engine.take_stack(),
+                ..window
+            });
+            return Ok(events);
+        }
+        if self.open_priority_window(state, action.player_id, Some(played_card_id), &mut events) {
+            return Ok(events);
         }
 
         let mut effect_events = self.effect_engine.resolve_all(state);
         events.append(&mut effect_events);
 
-        if let Some(outcome) = state.evaluate_victory() {
-            events.push(GameEvent::GameWon {
-                winner: outcome.winner,
-                reason: outcome.reason.clone(),
-            });
-        }
+        self.settle_victory(state, &mut events);
 
         Ok(events)
     }
 
-    pub fn attack(
-        &mut self,
-        state: &mut GameState,
-        action: AttackAction,
-    ) -> Result<Vec<GameEvent>, RuleError> {
-        if state.is_finished() {
-            return Err(RuleError::GameFinished);
-        }
-
-        Self::ensure_integrity(state)?;
+    /// 返回攻击者所属玩家下标及其在战场上的位置。
+    fn check_attack(
+        &self,
+        state: &GameState,
+        action: &AttackAction,
+    ) -> Result<(usize, usize), RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        Self::ensure_no_pending_attack(state)?;
+        Self::ensure_no_priority_window(state)?;
         Self::ensure_turn_owner(state, action.attacker_owner)?;
-        Self::ensure_combat_phase(state)?;
+        Self::ensure_phase_allows(state, ActionKind::Attack)?;
 
         if state.player_index(action.defender_owner).is_none() {
             return Err(RuleError::InvalidTarget);
@@ -376,6 +1257,17 @@ impl RuleEngine {
         if action.defender_owner == action.attacker_owner {
             return Err(RuleError::InvalidAttackTarget);
         }
+        if state.are_allies(action.attacker_owner, action.defender_owner) {
+            return Err(RuleError::CannotAttackAlly {
+                player_id: action.defender_owner,
+            });
+        }
+        let defender_alive = state
+            .get_player(action.defender_owner)
+            .is_some_and(Player::is_alive);
+        if action.defender_card.is_none() && !defender_alive {
+            return Err(RuleError::InvalidAttackTarget);
+        }
 
         let attacker_index =
             state
@@ -392,188 +1284,656 @@ impl RuleEngine {
                 card_id: action.attacker_id,
             })?;
 
-        // 先获取攻击者卡牌的信息
-        let attacker_card_info = state.players[attacker_index].board[attacker_pos].clone();
-        if attacker_card_info.card_type != CardType::Unit {
+        let attacker = &state.players[attacker_index].board[attacker_pos];
+        if attacker.card_type != CardType::Unit {
             return Err(RuleError::CardTypeMismatch {
                 expected: CardType::Unit,
-                actual: attacker_card_info.card_type.clone(),
+                actual: attacker.card_type,
             });
         }
-        if attacker_card_info.exhausted {
+        if attacker.exhausted {
             return Err(RuleError::UnitExhausted {
-                card_id: attacker_card_info.id,
+                card_id: attacker.id,
+            });
+        }
+        if !attacker.ready_to_attack(action.defender_card.is_none()) {
+            return Err(RuleError::SummoningSick {
+                card_id: attacker.id,
             });
         }
-        if attacker_card_info.attack <= 0 {
+        if attacker.frozen {
+            return Err(RuleError::UnitFrozen {
+                card_id: attacker.id,
+            });
+        }
+        if attacker.attack <= 0 {
             return Err(RuleError::ZeroAttackUnit {
-                card_id: attacker_card_info.id,
+                card_id: attacker.id,
+            });
+        }
+        Self::check_defender(state, action.defender_owner, action.defender_card)?;
+
+        Ok((attacker_index, attacker_pos))
+    }
+
+    /// 校验被攻击的单位存在、未潜行，并遵守嘲讽。
+    fn check_defender(
+        state: &GameState,
+        defender_owner: PlayerId,
+        defender_card: Option<CardId>,
+    ) -> Result<(), RuleError> {
+        if let Some(card_id) = defender_card {
+            let defender = state
+                .get_player(defender_owner)
+                .and_then(|defender| defender.find_card_on_board(card_id))
+                .ok_or(RuleError::InvalidTarget)?;
+            if defender.has_keyword(Keyword::Stealth) {
+                return Err(RuleError::TargetStealthed { card_id });
+            }
+        }
+        Self::check_taunt(state, defender_owner, defender_card)
+    }
+
+    fn check_hero_attack(
+        &self,
+        state: &GameState,
+        action: &HeroAttackAction,
+    ) -> Result<usize, RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        Self::ensure_no_pending_attack(state)?;
+        Self::ensure_no_priority_window(state)?;
+        Self::ensure_turn_owner(state, action.player_id)?;
+        Self::ensure_phase_allows(state, ActionKind::Attack)?;
+
+        if state.player_index(action.defender_owner).is_none() {
+            return Err(RuleError::InvalidTarget);
+        }
+        if action.defender_owner == action.player_id {
+            return Err(RuleError::InvalidAttackTarget);
+        }
+        if state.are_allies(action.player_id, action.defender_owner) {
+            return Err(RuleError::CannotAttackAlly {
+                player_id: action.defender_owner,
+            });
+        }
+        let defender_alive = state
+            .get_player(action.defender_owner)
+            .is_some_and(Player::is_alive);
+        if action.defender_card.is_none() && !defender_alive {
+            return Err(RuleError::InvalidAttackTarget);
+        }
+        let player_index =
+            state
+                .player_index(action.player_id)
+                .ok_or(RuleError::PlayerNotFound {
+                    player_id: action.player_id,
+                })?;
+        let player = &state.players[player_index];
+        if player.hero_attacked {
+            return Err(RuleError::HeroAlreadyAttacked {
+                player_id: action.player_id,
+            });
+        }
+        if player
+            .weapon
+            .as_ref()
+            .is_none_or(|weapon| weapon.attack <= 0)
+        {
+            return Err(RuleError::NoWeaponEquipped {
+                player_id: action.player_id,
+            });
+        }
+        Self::check_defender(state, action.defender_owner, action.defender_card)?;
+
+        Ok(player_index)
+    }
+
+    fn check_hero_power(
+        &self,
+        state: &GameState,
+        action: &UseHeroPowerAction,
+    ) -> Result<usize, RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        Self::ensure_no_pending_attack(state)?;
+        Self::ensure_no_priority_window(state)?;
+        Self::ensure_turn_owner(state, action.player_id)?;
+        Self::ensure_phase_allows(state, ActionKind::Play)?;
+        Self::check_chosen_target(state, action.target_player, action.target_card)?;
+
+        let player_index =
+            state
+                .player_index(action.player_id)
+                .ok_or(RuleError::PlayerNotFound {
+                    player_id: action.player_id,
+                })?;
+        let player = &state.players[player_index];
+        let hero_power = player.hero_power.as_ref().ok_or(RuleError::NoHeroPower {
+            player_id: action.player_id,
+        })?;
+        if hero_power.used {
+            return Err(RuleError::HeroPowerAlreadyUsed {
+                player_id: action.player_id,
+            });
+        }
+        if player.mana < hero_power.cost {
+            return Err(RuleError::InsufficientMana {
+                required: hero_power.cost,
+                available: player.mana,
             });
         }
 
+        if Self::requires_target_kind(&hero_power.effect.kind) {
+            let Some(target_player) = action.target_player else {
+                return Err(RuleError::InvalidTarget);
+            };
+            if let Some(filter) = &hero_power.effect.target_filter {
+                let target_card = action.target_card.and_then(|card_id| {
+                    state
+                        .get_player(target_player)
+                        .and_then(|player| player.find_card_on_board(card_id))
+                });
+                filter
+                    .check(target_card)
+                    .map_err(|failure| RuleError::TargetFilterFailed { filter: failure })?;
+            }
+        }
+
+        Ok(player_index)
+    }
+
+    /// 使用英雄技能：支付法力并立即结算其效果，每回合限一次。
+    pub fn use_hero_power(
+        &mut self,
+        state: &mut GameState,
+        action: UseHeroPowerAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let player_index = self.check_hero_power(state, &action)?;
+        let player = &mut state.players[player_index];
+        let Some(hero_power) = player.hero_power.as_mut() else {
+            return Err(RuleError::NoHeroPower {
+                player_id: action.player_id,
+            });
+        };
+        hero_power.used = true;
+        player.mana -= hero_power.cost;
+        let effect = hero_power.effect.clone();
+
         let mut events = Vec::new();
-        let mut attack_ctx = EffectContext::new(
-            EffectTrigger::OnAttack,
-            action.attacker_owner,
+        let used_event = GameEvent::HeroPowerUsed {
+            player_id: action.player_id,
+            target_id: action.target_card,
+        };
+        state.record_event(used_event.clone());
+        events.push(used_event);
+
+        let mut context = EffectContext::new(
+            EffectTrigger::OnPlay,
+            action.player_id,
             state.current_player,
-        )
-        .with_source_card(attacker_card_info.id);
-        if let Some(defender_card_id) = action.defender_card {
-            attack_ctx = attack_ctx.with_target_card(action.defender_owner, defender_card_id);
-        } else {
-            attack_ctx = attack_ctx.with_target_player(action.defender_owner);
+        );
+        if let Some(target_player) = action.target_player {
+            context = match action.target_card {
+                Some(target_card) => context.with_target_card(target_player, target_card),
+                None => context.with_target_player(target_player),
+            };
         }
-        self.effect_engine
-            .queue_card_effects(&attacker_card_info, attack_ctx);
-        let attack_event = GameEvent::AttackDeclared {
-            attacker_owner: action.attacker_owner,
-            attacker_id: action.attacker_id,
+        self.effect_engine.queue_effect(effect, context);
+        events.extend(self.effect_engine.resolve_all(state));
+
+        self.settle_victory(state, &mut events);
+
+        Ok(events)
+    }
+
+    /// 英雄以武器攻击：武器失去一点耐久，攻击单位时英雄受到反击。
+    pub fn hero_attack(
+        &mut self,
+        state: &mut GameState,
+        action: HeroAttackAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let player_index = self.check_hero_attack(state, &action)?;
+        let player = &mut state.players[player_index];
+        player.hero_attacked = true;
+        let (weapon_id, weapon_attack) = player
+            .weapon
+            .as_ref()
+            .map(|weapon| (weapon.id, weapon.attack))
+            .ok_or(RuleError::NoWeaponEquipped {
+                player_id: action.player_id,
+            })?;
+
+        let mut events = Vec::new();
+        let attack_event = GameEvent::HeroAttackDeclared {
+            player_id: action.player_id,
             defender_owner: action.defender_owner,
             defender_id: action.defender_card,
         };
         state.record_event(attack_event.clone());
         events.push(attack_event);
 
-        let attacker_attack = attacker_card_info.attack;
-        // 现在设置攻击者卡牌为疲惫状态
-        state.players[attacker_index].board[attacker_pos].exhausted = true;
-
         if let Some(defender_card_id) = action.defender_card {
-            let defender_index = state
-                .player_index(action.defender_owner)
-                .ok_or(RuleError::InvalidTarget)?;
-            let defender_card_opt = state.players[defender_index]
-                .board
-                .iter()
-                .find(|card| card.id == defender_card_id)
-                .cloned();
-            let defender_card = defender_card_opt.ok_or(RuleError::InvalidTarget)?;
-
-            let mut dmg_events = state.damage_card(
-                action.attacker_owner,
-                Some(attacker_card_info.id),
+            let retaliation = state
+                .get_player(action.defender_owner)
+                .and_then(|defender| defender.find_card_on_board(defender_card_id))
+                .map_or(0, |card| card.attack);
+            let mut dmg_events = self.effect_engine.damage_card(
+                state,
+                action.player_id,
+                Some(weapon_id),
                 action.defender_owner,
                 defender_card_id,
-                attacker_attack,
+                weapon_attack,
             );
-            for event in &dmg_events {
-                state.record_event(event.clone());
-            }
             events.append(&mut dmg_events);
-
-            if defender_card.card_type == CardType::Unit && defender_card.attack > 0 {
-                let mut retaliate_events = state.damage_card(
+            if state.config.retaliation && retaliation > 0 {
+                let mut retaliate_events = self.effect_engine.damage_player(
+                    state,
                     action.defender_owner,
-                    Some(defender_card.id),
-                    action.attacker_owner,
-                    action.attacker_id,
-                    defender_card.attack,
+                    Some(defender_card_id),
+                    action.player_id,
+                    retaliation,
                 );
-                for event in &retaliate_events {
-                    state.record_event(event.clone());
-                }
                 events.append(&mut retaliate_events);
             }
         } else {
-            let damage_event = state.damage_player(
-                action.attacker_owner,
-                Some(action.attacker_id),
+            let mut damage_events = self.effect_engine.damage_player(
+                state,
+                action.player_id,
+                Some(weapon_id),
                 action.defender_owner,
-                attacker_attack,
+                weapon_attack,
             );
-            if let Some(event) = damage_event {
-                state.record_event(event.clone());
-                events.push(event);
-            }
+            events.append(&mut damage_events);
+        }
+
+        if let Some(wear_event) = state.wear_weapon(action.player_id) {
+            self.effect_engine
+                .observe_events(state, std::slice::from_ref(&wear_event));
+            events.push(wear_event);
         }
 
         let mut effect_events = self.effect_engine.resolve_all(state);
         events.append(&mut effect_events);
 
-        if let Some(outcome) = state.evaluate_victory() {
-            events.push(GameEvent::GameWon {
-                winner: outcome.winner,
-                reason: outcome.reason.clone(),
-            });
-        }
+        self.settle_victory(state, &mut events);
 
         Ok(events)
     }
 
-    pub fn resolve_pending_discard(
+    /// 等同于 `declare_attack`。
+    pub fn attack(
         &mut self,
         state: &mut GameState,
-        action: DiscardCardAction,
+        action: AttackAction,
     ) -> Result<Vec<GameEvent>, RuleError> {
-        if state.is_finished() {
-            return Err(RuleError::GameFinished);
-        }
-
-        Self::ensure_integrity(state)?;
-
-        let player_index = state
-            .player_index(action.player_id)
-            .ok_or(RuleError::PlayerNotFound {
-                player_id: action.player_id,
-            })?;
+        self.declare_attack(state, action)
+    }
 
-        let pending = state
-            .take_pending_discard(action.player_id, action.pending_id)
-            .ok_or(RuleError::PendingDiscardNotFound {
-                player_id: action.player_id,
-                pending_id: action.pending_id,
-            })?;
+    /// 宣告攻击并结算攻击时的效果与奥秘。
+    ///
+    /// 默认立即结算伤害；开启 `two_step_combat` 时把优先权交给防守方，
+    /// 由 `resolve_combat` 结算伤害。
+    pub fn declare_attack(
+        &mut self,
+        state: &mut GameState,
+        action: AttackAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let (attacker_index, attacker_pos) = self.check_attack(state, &action)?;
 
+        // 先获取攻击者卡牌的信息
+        let attacker_card_info = state.players[attacker_index].board[attacker_pos].clone();
         let mut events = Vec::new();
-
-        if action.discard_card_id == pending.drawn_card.id {
-            let discard_event = GameEvent::CardDiscarded {
-                player_id: action.player_id,
-                card: pending.drawn_card,
-            };
-            state.record_event(discard_event.clone());
-            events.push(discard_event);
-            return Ok(events);
-        }
-
-        let player = &mut state.players[player_index];
-        if let Some(pos) = player.find_card_in_hand_index(action.discard_card_id) {
-            let discarded_card = player.hand.remove(pos);
-            let discard_event = GameEvent::CardDiscarded {
-                player_id: action.player_id,
-                card: discarded_card,
-            };
-            state.record_event(discard_event.clone());
-            events.push(discard_event);
-
-            player.hand.push(pending.drawn_card.clone());
-            let draw_event = GameEvent::CardDrawn {
-                player_id: action.player_id,
-                card_id: pending.drawn_card.id,
-            };
-            state.record_event(draw_event.clone());
-            events.push(draw_event);
-
-            Ok(events)
+        let mut attack_ctx = EffectContext::new(
+            EffectTrigger::OnAttack,
+            action.attacker_owner,
+            state.current_player,
+        )
+        .with_source_card(attacker_card_info.id);
+        if let Some(defender_card_id) = action.defender_card {
+            attack_ctx = attack_ctx.with_target_card(action.defender_owner, defender_card_id);
         } else {
-            state.pending_discards.push(pending);
-            Err(RuleError::CardNotFound {
-                card_id: action.discard_card_id,
+            attack_ctx = attack_ctx.with_target_player(action.defender_owner);
+        }
+        self.effect_engine
+            .queue_card_effects(&attacker_card_info, attack_ctx);
+        let attack_event = GameEvent::AttackDeclared {
+            attacker_owner: action.attacker_owner,
+            attacker_id: action.attacker_id,
+            defender_owner: action.defender_owner,
+            defender_id: action.defender_card,
+        };
+        state.record_event(attack_event.clone());
+        events.push(attack_event);
+
+        // 现在设置攻击者卡牌为疲惫状态
+        state.players[attacker_index].board[attacker_pos].exhausted = true;
+        if let Some(reveal_event) =
+            state.remove_keyword(action.attacker_owner, action.attacker_id, Keyword::Stealth)
+        {
+            state.record_event(reveal_event.clone());
+            events.push(reveal_event);
+        }
+
+        // 对手的奥秘在伤害结算前响应攻击宣言
+        let mut secret_events = self.effect_engine.trigger_secrets(
+            state,
+            EffectTrigger::OnAttack,
+            action.attacker_owner,
+            Some((action.attacker_owner, action.attacker_id)),
+            None,
+        );
+        if !secret_events.is_empty() {
+            events.append(&mut secret_events);
+            let mut effect_events = self.effect_engine.resolve_all(state);
+            events.append(&mut effect_events);
+        }
+
+        let pending = PendingAttack {
+            attacker_owner: action.attacker_owner,
+            attacker_id: action.attacker_id,
+            defender_owner: action.defender_owner,
+            defender_card: action.defender_card,
+        };
+        if state.config.two_step_combat && !self.settle_victory(state, &mut events) {
+            state.pending_attack = Some(pending);
+            self.grant_priority(
+                state,
+                action.defender_owner,
+                Some(action.attacker_id),
+                &mut events,
+            );
+            return Ok(events);
+        }
+
+        events.extend(self.apply_combat_damage(state, &pending));
+        let mut effect_events = self.effect_engine.resolve_all(state);
+        events.append(&mut effect_events);
+
+        self.settle_victory(state, &mut events);
+
+        Ok(events)
+    }
+
+    /// 两段式战斗：防守方放弃优先权后，由攻击方结算已宣告攻击的伤害。
+    pub fn resolve_combat(
+        &mut self,
+        state: &mut GameState,
+        action: ResolveCombatAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        Self::check_resolve_combat(state, &action)?;
+        let pending = state
+            .pending_attack
+            .take()
+            .ok_or(RuleError::NoPendingAttack)?;
+
+        let mut events = self.apply_combat_damage(state, &pending);
+        let mut effect_events = self.effect_engine.resolve_all(state);
+        events.append(&mut effect_events);
+
+        self.settle_victory(state, &mut events);
+
+        Ok(events)
+    }
+
+    fn check_resolve_combat(
+        state: &GameState,
+        action: &ResolveCombatAction,
+    ) -> Result<(), RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_priority_window(state)?;
+        let pending = state
+            .pending_attack
+            .as_ref()
+            .ok_or(RuleError::NoPendingAttack)?;
+        // 只有宣告攻击的玩家能推进结算，其他客户端不能代为结算
+        Self::ensure_turn_owner(state, action.player_id)?;
+        if pending.attacker_owner != action.player_id {
+            return Err(RuleError::NotPlayerTurn);
+        }
+        Ok(())
+    }
+
+    /// 按攻防双方当前的属性互相造成战斗伤害；任一方已离场则攻击落空，远程攻击者不受反击。
+    fn apply_combat_damage(
+        &mut self,
+        state: &mut GameState,
+        attack: &PendingAttack,
+    ) -> Vec<GameEvent> {
+        let find = |state: &GameState, owner: PlayerId, card_id: CardId| {
+            state
+                .get_player(owner)
+                .and_then(|player| player.find_card_on_board(card_id))
+                .cloned()
+        };
+        let Some(attacker) = find(state, attack.attacker_owner, attack.attacker_id) else {
+            return Vec::new();
+        };
+
+        let Some(defender_card_id) = attack.defender_card else {
+            return self.effect_engine.damage_player(
+                state,
+                attack.attacker_owner,
+                Some(attack.attacker_id),
+                attack.defender_owner,
+                attacker.attack,
+            );
+        };
+        let Some(defender) = find(state, attack.defender_owner, defender_card_id) else {
+            return Vec::new();
+        };
+
+        let mut events = self.effect_engine.damage_card(
+            state,
+            attack.attacker_owner,
+            Some(attacker.id),
+            attack.defender_owner,
+            defender_card_id,
+            attacker.attack,
+        );
+        let retaliates = state.config.retaliation
+            && defender.card_type == CardType::Unit
+            && defender.attack > 0
+            && !attacker.has_keyword(Keyword::Ranged);
+        if retaliates {
+            events.extend(self.effect_engine.damage_card(
+                state,
+                attack.defender_owner,
+                Some(defender.id),
+                attack.attacker_owner,
+                attack.attacker_id,
+                defender.attack,
+            ));
+        }
+        events
+    }
+
+    /// 弃牌接口：将要弃掉的卡牌换算为选项后交给 `resolve_choice`。
+    pub fn resolve_pending_discard(
+        &mut self,
+        state: &mut GameState,
+        action: DiscardCardAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let choice = Self::discard_choice(state, &action)?;
+        self.resolve_choice(state, choice)
+    }
+
+    /// 把弃牌操作换算为对应的选择：弃掉新抽到的牌对应最后一个选项。
+    fn discard_choice(
+        state: &GameState,
+        action: &DiscardCardAction,
+    ) -> Result<ResolveChoiceAction, RuleError> {
+        let not_found = RuleError::PendingDiscardNotFound {
+            player_id: action.player_id,
+            pending_id: action.pending_id,
+        };
+        let drawn_card = state
+            .pending_choices
+            .iter()
+            .find(|pending| {
+                pending.id == action.pending_id && pending.player_id == action.player_id
             })
+            .and_then(PendingChoice::drawn_card)
+            .ok_or(not_found)?;
+        let hand = &state
+            .get_player(action.player_id)
+            .ok_or(RuleError::PlayerNotFound {
+                player_id: action.player_id,
+            })?
+            .hand;
+        let option = if action.discard_card_id == drawn_card.id {
+            hand.len()
+        } else {
+            hand.iter()
+                .position(|card| card.id == action.discard_card_id)
+                .ok_or(RuleError::CardNotFound {
+                    card_id: action.discard_card_id,
+                })?
+        };
+
+        Ok(ResolveChoiceAction {
+            player_id: action.player_id,
+            choice_id: action.pending_id,
+            option,
+        })
+    }
+
+    fn check_resolve_choice(
+        state: &GameState,
+        action: &ResolveChoiceAction,
+    ) -> Result<(), RuleError> {
+        Self::ensure_active(state)?;
+
+        let option_count = state
+            .pending_choices
+            .iter()
+            .find(|pending| pending.id == action.choice_id && pending.player_id == action.player_id)
+            .map(|pending| state.choice_option_count(pending))
+            .ok_or(Self::choice_not_found(action))?;
+        if action.option >= option_count {
+            return Err(RuleError::InvalidChoiceOption {
+                choice_id: action.choice_id,
+                option: action.option,
+            });
         }
+        Ok(())
     }
 
-    pub fn mulligan(
+    fn choice_not_found(action: &ResolveChoiceAction) -> RuleError {
+        RuleError::PendingChoiceNotFound {
+            player_id: action.player_id,
+            choice_id: action.choice_id,
+        }
+    }
+
+    /// 结算一个待处理的选择（弃牌、抉择、发现或选择目标），是所有选择的统一入口。
+    pub fn resolve_choice(
         &mut self,
         state: &mut GameState,
-        action: MulliganAction,
+        action: ResolveChoiceAction,
     ) -> Result<Vec<GameEvent>, RuleError> {
-        if state.is_finished() {
-            return Err(RuleError::GameFinished);
+        Self::check_resolve_choice(state, &action)?;
+        let pending = state
+            .take_pending_choice(action.player_id, action.choice_id)
+            .ok_or(Self::choice_not_found(&action))?;
+
+        let mut events = Vec::new();
+        let chosen_event = GameEvent::ChoiceResolved {
+            player_id: action.player_id,
+            choice_id: action.choice_id,
+            option: action.option,
+        };
+        state.record_event(chosen_event.clone());
+        events.push(chosen_event);
+
+        let mut resolved = Vec::new();
+        match pending.kind {
+            ChoiceKind::ChooseOne { options, context } => {
+                let effect = &options[action.option].effect;
+                if effect.can_trigger(&context, state) {
+                    resolved = effect.apply(&context, state).events;
+                }
+            }
+            ChoiceKind::Discover { cards } => {
+                if let Some(card) = cards.into_iter().nth(action.option) {
+                    resolved.extend(state.add_card_to_hand(action.player_id, card));
+                }
+            }
+            ChoiceKind::SelectTarget {
+                targets,
+                effect,
+                context,
+            } => {
+                let target = &targets[action.option];
+                let ctx = context.with_target_card(target.player_id, target.card_id);
+                if effect.can_trigger(&ctx, state) {
+                    resolved = effect.apply(&ctx, state).events;
+                }
+            }
+            ChoiceKind::Discard { drawn_card } => {
+                resolved =
+                    Self::resolve_discard(state, action.player_id, drawn_card, action.option);
+            }
         }
+        self.effect_engine.observe_events(state, &resolved);
+        events.append(&mut resolved);
+        events.extend(self.effect_engine.resolve_all(state));
 
-        Self::ensure_integrity(state)?;
-        Self::ensure_mulligan_phase(state)?;
+        self.settle_victory(state, &mut events);
+
+        Ok(events)
+    }
+
+    /// 弃掉手牌中第 `option` 张并收下新抽到的牌；`option` 等于手牌数时弃掉新抽到的牌。
+    ///
+    /// 返回的事件尚未记录，由调用方交给效果引擎统一记录并触发。
+    fn resolve_discard(
+        state: &mut GameState,
+        player_id: PlayerId,
+        drawn_card: Card,
+        option: usize,
+    ) -> Vec<GameEvent> {
+        let Some(player) = state.get_player_mut(player_id) else {
+            return Vec::new();
+        };
+        if option >= player.hand.len() {
+            // 玩家主动弃掉新牌，不同于手牌已满时的爆牌
+            player.graveyard.push(drawn_card.clone());
+            return vec![GameEvent::CardDiscarded {
+                player_id,
+                card: drawn_card,
+            }];
+        }
+
+        let discarded = player.hand.remove(option);
+        player.graveyard.push(discarded.clone());
+        let card_id = drawn_card.id;
+        player.hand.push(drawn_card);
+        vec![
+            GameEvent::CardDiscarded {
+                player_id,
+                card: discarded,
+            },
+            GameEvent::CardDrawn { player_id, card_id },
+        ]
+    }
+
+    /// 按 `policy` 替玩家完成调度，供单人模式处理电脑对手的调度。
+    pub fn auto_mulligan(
+        &mut self,
+        state: &mut GameState,
+        player_id: PlayerId,
+        policy: MulliganPolicy,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let action = policy.action(state, player_id);
+        self.apply_action(state, &action)
+    }
+
+    /// 返回换牌玩家的下标；所有待换的牌都必须在手牌中。
+    fn check_mulligan(state: &GameState, action: &MulliganAction) -> Result<usize, RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_phase_allows(state, ActionKind::Mulligan)?;
 
         let player_index =
             state
@@ -587,8 +1947,38 @@ impl RuleEngine {
                 player_id: action.player_id,
             });
         }
+        let hand = &state.players[player_index].hand;
+        if let Some(&card_id) = action
+            .replacements
+            .iter()
+            .find(|&&card_id| hand.iter().all(|card| card.id != card_id))
+        {
+            return Err(RuleError::CardNotFound { card_id });
+        }
+        if let Some(limit) = state.config.mulligan_limit {
+            let mut unique_replacements = action.replacements.clone();
+            unique_replacements.sort_unstable();
+            unique_replacements.dedup();
+            if unique_replacements.len() > limit as usize {
+                return Err(RuleError::MulliganLimitExceeded {
+                    limit,
+                    requested: unique_replacements.len(),
+                });
+            }
+        }
+
+        Ok(player_index)
+    }
+
+    pub fn mulligan(
+        &mut self,
+        state: &mut GameState,
+        action: MulliganAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let player_index = Self::check_mulligan(state, &action)?;
 
         let mut replaced_ids = Vec::new();
+        let mut returned = Vec::new();
         {
             let player = &mut state.players[player_index];
             let mut unique_replacements = action.replacements.clone();
@@ -597,11 +1987,8 @@ impl RuleEngine {
 
             for card_id in unique_replacements {
                 if let Some(pos) = player.hand.iter().position(|card| card.id == card_id) {
-                    let card = player.hand.remove(pos);
-                    player.deck.insert(0, card);
+                    returned.push(player.hand.remove(pos));
                     replaced_ids.push(card_id);
-                } else {
-                    return Err(RuleError::CardNotFound { card_id });
                 }
             }
         }
@@ -615,6 +2002,11 @@ impl RuleEngine {
             }
         }
 
+        // 换掉的牌在补抽之后洗回牌库，保证不会立刻抽回
+        let player = &mut state.players[player_index];
+        player.deck.extend(returned);
+        state.rng.shuffle(&mut player.deck);
+
         let mulligan_event = GameEvent::MulliganApplied {
             player_id: action.player_id,
             replaced: replaced_ids,
@@ -624,11 +2016,30 @@ impl RuleEngine {
         events.push(mulligan_event);
 
         if state.all_mulligans_completed() {
-            if state.turn == 0 {
-                state.turn = 1;
+            if let Some(bonus) = state.config.second_player_bonus.clone() {
+                let first = state.current_player;
+                let others: Vec<PlayerId> = state
+                    .players
+                    .iter()
+                    .map(|player| player.id)
+                    .filter(|id| *id != first)
+                    .collect();
+                for player_id in others {
+                    if let Some(event) = state.add_card_to_hand(player_id, bonus.clone()) {
+                        state.record_event(event.clone());
+                        events.push(event);
+                    }
+                }
             }
-            // 不要直接跳到Main阶段，让正常的阶段流程处理
-            // 这样确保OnTurnStart效果能正确触发
+
+            // 双方调度完毕即视为对局开始
+            events.extend(self.begin_game(state));
+        }
+
+        // 不要直接跳到Main阶段，让正常的阶段流程处理
+        // 这样确保OnTurnStart效果能正确触发
+        if state.all_mulligans_completed() && state.turn == 0 {
+            state.turn = 1;
         }
 
         Ok(events)
@@ -650,12 +2061,28 @@ impl RuleEngine {
         self.process_turn_start(state, player_id)
     }
 
+    fn check_end_turn(&self, state: &GameState) -> Result<(), RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        Self::ensure_no_pending_attack(state)?;
+        Self::ensure_no_priority_window(state)?;
+        Self::ensure_phase_allows(state, ActionKind::EndTurn)
+    }
+
+    /// 从主阶段或战斗阶段直接结束回合：先进入结束阶段，再结算回合结束效果。
     pub fn end_turn(&mut self, state: &mut GameState) -> Result<Vec<GameEvent>, RuleError> {
-        if state.is_finished() {
-            return Err(RuleError::GameFinished);
+        self.check_end_turn(state)?;
+
+        let mut events = Vec::new();
+        if self.change_phase(state, GamePhase::End, &mut events) {
+            return Ok(events);
         }
-        Self::ensure_integrity(state)?;
+        events.extend(self.finish_turn(state)?);
+        Ok(events)
+    }
 
+    /// 结束阶段的自动处理：结算回合结束效果并将回合交给对手。
+    fn finish_turn(&mut self, state: &mut GameState) -> Result<Vec<GameEvent>, RuleError> {
         let current = state.current_player;
         let mut events = Vec::new();
 
@@ -668,26 +2095,30 @@ impl RuleEngine {
                 self.effect_engine.queue_card_effects(card, ctx);
             }
         }
+        self.effect_engine
+            .queue_scheduled_effects(state, EffectTrigger::OnTurnEnd, current);
 
         let mut trigger_events = self.effect_engine.resolve_all(state);
         events.append(&mut trigger_events);
 
+        let expired = state.expire_timed_effects(Expiry::EndOfTurn { player_id: current });
+        self.effect_engine.observe_events(state, &expired);
+        events.extend(expired);
+        events.extend(self.effect_engine.resolve_all(state));
+
         let end_event = GameEvent::TurnEnded { player_id: current };
         state.record_event(end_event.clone());
         events.push(end_event);
 
-        if let Some(outcome) = state.evaluate_victory() {
-            events.push(GameEvent::GameWon {
-                winner: outcome.winner,
-                reason: outcome.reason.clone(),
-            });
+        if self.settle_victory(state, &mut events) {
             return Ok(events);
         }
 
-        let next_player = state.opponent_of(current);
+        let next_player = state.next_player_after(current);
         state.end_turn();
+        state.enforce_turn_limit();
 
-        if state.is_finished() {
+        if self.settle_victory(state, &mut events) {
             return Ok(events);
         }
 
@@ -701,62 +2132,963 @@ impl RuleEngine {
         Ok(events)
     }
 
-    pub fn check_victory(state: &mut GameState) -> Option<VictoryState> {
-        state.evaluate_victory()
-    }
-
-    pub fn advance_phase(state: &mut GameState) -> Result<GamePhase, RuleError> {
-        if state.is_finished() {
-            return Err(RuleError::GameFinished);
+    /// 离开当前阶段并进入 `phase`，依次结算离开与进入阶段的触发；对局结束时返回 true。
+    fn change_phase(
+        &mut self,
+        state: &mut GameState,
+        phase: GamePhase,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
+        let exit = EffectTrigger::OnPhaseExit {
+            phase: state.phase.clone(),
+        };
+        self.resolve_phase_trigger(state, exit, events);
+        if self.settle_victory(state, events) {
+            return true;
         }
-        Self::ensure_integrity(state)?;
-        state.advance_phase();
-        Ok(state.phase.clone())
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        state.phase = phase.clone();
+        let phase_event = GameEvent::PhaseChanged {
+            player_id: state.current_player,
+            phase: phase.clone(),
+        };
+        state.record_event(phase_event.clone());
+        events.push(phase_event);
 
-    fn setup_state() -> GameState {
-        let mut state = GameState::sample();
-        state.phase = GamePhase::Combat;
-        state
+        self.resolve_phase_trigger(state, EffectTrigger::OnPhaseEnter { phase }, events);
+        self.settle_victory(state, events)
     }
 
-    #[test]
-    fn unit_attack_reduces_hero_health() {
-        let mut engine = RuleEngine::new();
-        let mut state = setup_state();
-
-        let initial_health = state.get_player(1).expect("defender should exist").health;
+    fn resolve_phase_trigger(
+        &mut self,
+        state: &mut GameState,
+        trigger: EffectTrigger,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let current = state.current_player;
+        if let Some(player) = state.get_player(current) {
+            for card in &player.board {
+                let ctx =
+                    EffectContext::new(trigger.clone(), current, current).with_source_card(card.id);
+                self.effect_engine.queue_card_effects(card, ctx);
+            }
+        }
+        events.extend(self.effect_engine.resolve_all(state));
+    }
 
-        let action = AttackAction {
-            attacker_owner: 0,
-            attacker_id: 2,
-            defender_owner: 1,
-            defender_card: None,
+    /// 检查胜负并追加 `GameWon`/`GameDrawn` 事件；首次决出胜者时结算 `OnVictory` 效果。
+    fn settle_victory(&mut self, state: &mut GameState, events: &mut Vec<GameEvent>) -> bool {
+        let (winner, reason) = match state.evaluate_outcome() {
+            None => return false,
+            Some(GameOutcome::Draw { reason }) => {
+                events.push(GameEvent::GameDrawn { reason });
+                events.push(state.summary());
+                return true;
+            }
+            Some(GameOutcome::Victory { winner, reason }) => (winner, reason),
         };
+        events.push(GameEvent::GameWon { winner, reason });
+        events.push(state.summary());
+        if !state.victory_effects_resolved {
+            state.victory_effects_resolved = true;
+            self.queue_game_effects(state, EffectTrigger::OnVictory);
+            events.extend(self.effect_engine.resolve_all(state));
+        }
+        true
+    }
 
-        let _events = engine
-            .attack(&mut state, action)
-            .expect("attack should succeed");
+    /// 结算所有卡牌的 `OnGameStart` 效果，每局只生效一次。
+    ///
+    /// 带调度的对局在双方调度完毕后自动调用；跳过调度直接开局时由搭建对局的一方调用。
+    pub fn begin_game(&mut self, state: &mut GameState) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if state.game_start_resolved || state.is_finished() {
+            return events;
+        }
+        state.game_start_resolved = true;
+        self.queue_game_effects(state, EffectTrigger::OnGameStart);
+        events.extend(self.effect_engine.resolve_all(state));
+        self.settle_victory(state, &mut events);
+        events
+    }
 
-        let updated_health = state.get_player(1).expect("defender should exist").health;
+    /// 压入所有玩家牌库、手牌与战场上响应对局级 `trigger` 的效果。
+    fn queue_game_effects(&mut self, state: &GameState, trigger: EffectTrigger) {
+        for player in &state.players {
+            for card in player.deck.iter().chain(&player.hand).chain(&player.board) {
+                let ctx = EffectContext::new(trigger.clone(), player.id, state.current_player)
+                    .with_source_card(card.id);
+                self.effect_engine.queue_card_effects(card, ctx);
+            }
+        }
+    }
 
-        assert!(
-            updated_health < initial_health,
-            "defender health should be reduced"
-        );
+    /// 执行一个操作，前后依次调用已注册的拦截器。
+    pub fn apply_action(
+        &mut self,
+        state: &mut GameState,
+        action: &GameAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        self.hooks.check(state, action)?;
+        let mut events = self.dispatch_action(state, action)?;
+        let injected = self.hooks.after(state, action, &events);
+        events.extend(injected);
+        Ok(events)
     }
 
-    #[test]
-    fn unit_attack_trades_with_opponent_unit() {
-        let mut engine = RuleEngine::new();
-        let mut state = setup_state();
+    fn dispatch_action(
+        &mut self,
+        state: &mut GameState,
+        action: &GameAction,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        match action {
+            GameAction::PlayCard { action } => self.play_card(state, action.clone()),
+            GameAction::Mulligan { action } => self.mulligan(state, action.clone()),
+            GameAction::Attack { action } => self.attack(state, action.clone()),
+            GameAction::HeroAttack { action } => self.hero_attack(state, action.clone()),
+            GameAction::UseHeroPower { action } => self.use_hero_power(state, action.clone()),
+            GameAction::ResolveChoice { action } => self.resolve_choice(state, action.clone()),
+            GameAction::DiscardCard { action } => {
+                self.resolve_pending_discard(state, action.clone())
+            }
+            GameAction::PassPriority { action } => self.pass_priority(state, action.clone()),
+            GameAction::ResolveCombat { action } => self.resolve_combat(state, action.clone()),
+            GameAction::AdvancePhase => self.advance_phase(state),
+            GameAction::EndTurn => self.end_turn(state),
+        }
+    }
 
-        // Ensure defender unit exists on player 1 board (id 8 in sample state)
+    /// 原子地依次执行一组操作：任一操作失败时，状态与引擎都回滚到执行前。
+    pub fn apply_all(
+        &mut self,
+        state: &mut GameState,
+        actions: Vec<GameAction>,
+    ) -> Result<Vec<GameEvent>, RuleError> {
+        let state_snapshot = state.clone();
+        let engine_snapshot = self.clone();
+        let mut events = Vec::new();
+        for (index, action) in actions.iter().enumerate() {
+            match self.apply_action(state, action) {
+                Ok(action_events) => events.extend(action_events),
+                Err(error) => {
+                    *state = state_snapshot;
+                    *self = engine_snapshot;
+                    return Err(RuleError::BatchActionFailed {
+                        index,
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// 从 `initial_state` 起依次重放记录的操作并返回最终状态，用于存档回放与联机不同步排查。
+    ///
+    /// 重放在沿用本引擎交互优先权与拦截器设置的新引擎上进行，不影响本引擎。
+    pub fn replay(
+        &self,
+        initial_state: &GameState,
+        actions: &[GameAction],
+    ) -> Result<GameState, ReplayError> {
+        let mut engine = RuleEngine::new()
+            .with_interactive_priority(self.interactive_priority)
+            .with_hooks(self.hooks.clone());
+        let mut state = initial_state.clone();
+        for (index, action) in actions.iter().enumerate() {
+            if let Err(error) = engine.apply_action(&mut state, action) {
+                return Err(ReplayError {
+                    index,
+                    action: action.clone(),
+                    error,
+                    state: Box::new(state),
+                });
+            }
+        }
+        Ok(state)
+    }
+
+    /// 为失败的操作生成错误报告，附带同一卡牌或攻击者当前可执行的其他操作。
+    pub fn explain(
+        &self,
+        state: &GameState,
+        action: &GameAction,
+        error: RuleError,
+    ) -> RuleErrorReport {
+        let player_id = action.player_id().unwrap_or(state.current_player);
+        let alternatives = self
+            .legal_actions(state, player_id)
+            .into_iter()
+            .filter(|candidate| candidate.same_subject(action))
+            .collect();
+        RuleErrorReport {
+            action: Some(action.clone()),
+            alternatives,
+            ..RuleErrorReport::new(error)
+        }
+    }
+
+    /// 不修改状态地校验操作，返回与实际执行时相同的错误。
+    pub fn validate(&self, state: &GameState, action: &GameAction) -> Result<(), RuleError> {
+        self.hooks.check(state, action)?;
+        match action {
+            GameAction::PlayCard { action } => self.check_play_card(state, action).map(|_| ()),
+            GameAction::Mulligan { action } => Self::check_mulligan(state, action).map(|_| ()),
+            GameAction::Attack { action } => self.check_attack(state, action).map(|_| ()),
+            GameAction::HeroAttack { action } => self.check_hero_attack(state, action).map(|_| ()),
+            GameAction::UseHeroPower { action } => self.check_hero_power(state, action).map(|_| ()),
+            GameAction::ResolveChoice { action } => Self::check_resolve_choice(state, action),
+            GameAction::DiscardCard { action } => Self::discard_choice(state, action)
+                .and_then(|choice| Self::check_resolve_choice(state, &choice)),
+            GameAction::PassPriority { action } => Self::check_pass_priority(state, action),
+            GameAction::ResolveCombat { action } => Self::check_resolve_combat(state, action),
+            GameAction::AdvancePhase => self.check_advance_phase(state),
+            GameAction::EndTurn => self.check_end_turn(state),
+        }
+    }
+
+    /// 枚举玩家当前可执行的出牌、单位与英雄攻击、推进阶段与结束回合操作（含具体目标）。
+    ///
+    /// 有待结算的抉择时只返回各个选项；响应窗口打开时只有持有优先权的玩家可以放弃或响应；
+    /// 调度阶段的换牌组合不在枚举范围内。
+    pub fn legal_actions(&self, state: &GameState, player_id: PlayerId) -> Vec<GameAction> {
+        Self::candidate_actions(state, player_id)
+            .into_iter()
+            .filter(|action| self.validate(state, action).is_ok())
+            .collect()
+    }
+
+    /// 同 `legal_actions`，并附带执行每个操作后的状态。
+    pub fn legal_transitions(
+        &self,
+        state: &GameState,
+        player_id: PlayerId,
+    ) -> Vec<(GameAction, GameState)> {
+        self.legal_actions(state, player_id)
+            .into_iter()
+            .filter_map(|action| {
+                let mut next_state = state.clone();
+                let mut engine = self.clone();
+                engine
+                    .apply_action(&mut next_state, &action)
+                    .ok()
+                    .map(|_| (action, next_state))
+            })
+            .collect()
+    }
+
+    /// 待处理选择的各个选项；弃牌选择以要弃掉的卡牌表示。
+    fn choice_actions(state: &GameState, pending: &PendingChoice) -> Vec<GameAction> {
+        let player_id = pending.player_id;
+        if let Some(drawn_card) = pending.drawn_card() {
+            let hand = state
+                .get_player(player_id)
+                .map(|player| player.hand.as_slice())
+                .unwrap_or_default();
+            return hand
+                .iter()
+                .chain(std::iter::once(drawn_card))
+                .map(|card| GameAction::DiscardCard {
+                    action: DiscardCardAction {
+                        player_id,
+                        pending_id: pending.id,
+                        discard_card_id: card.id,
+                    },
+                })
+                .collect();
+        }
+        (0..state.choice_option_count(pending))
+            .map(|option| GameAction::ResolveChoice {
+                action: ResolveChoiceAction {
+                    player_id,
+                    choice_id: pending.id,
+                    option,
+                },
+            })
+            .collect()
+    }
+
+    /// 列出可能合法的操作，最终是否合法由 `validate` 决定。
+    fn candidate_actions(state: &GameState, player_id: PlayerId) -> Vec<GameAction> {
+        let choices: Vec<GameAction> = state
+            .pending_choices
+            .iter()
+            .filter(|pending| pending.player_id == player_id)
+            .flat_map(|pending| Self::choice_actions(state, pending))
+            .collect();
+        if !choices.is_empty() {
+            return choices;
+        }
+        // 响应窗口打开时只有持有优先权的玩家可以行动：放弃优先权或打出卡牌响应
+        let responding = state.priority_window.is_some();
+        if state.acting_player() != player_id {
+            return Vec::new();
+        }
+        if !responding && state.pending_attack.is_some() {
+            return vec![GameAction::ResolveCombat {
+                action: ResolveCombatAction { player_id },
+            }];
+        }
+        let Some(player) = state.get_player(player_id) else {
+            return Vec::new();
+        };
+
+        let mut actions = Vec::new();
+        if responding {
+            actions.push(GameAction::PassPriority {
+                action: PassPriorityAction { player_id },
+            });
+        } else if state.phase == GamePhase::Main {
+            actions.push(GameAction::AdvancePhase);
+        }
+
+        // 组队时敌方可能有多名存活玩家，逐一列出
+        let enemies: Vec<PlayerId> = state
+            .enemies_of(player_id)
+            .into_iter()
+            .filter(|&id| state.get_player(id).is_some_and(Player::is_alive))
+            .collect();
+        let sides = std::iter::once(player_id)
+            .chain(
+                state
+                    .allies_of(player_id)
+                    .into_iter()
+                    .filter(|&id| id != player_id),
+            )
+            .chain(enemies.iter().copied());
+        let mut targets: Vec<(PlayerId, Option<CardId>)> = Vec::new();
+        for side in sides {
+            targets.push((side, None));
+            if let Some(side_player) = state.get_player(side) {
+                targets.extend(
+                    side_player
+                        .board
+                        .iter()
+                        .filter(|card| card.is_targetable())
+                        .map(|card| (side, Some(card.id))),
+                );
+            }
+        }
+        for card in &player.hand {
+            let play = |target_player, target_card| GameAction::PlayCard {
+                action: PlayCardAction {
+                    player_id,
+                    card_id: card.id,
+                    target_player,
+                    target_card,
+                    position: None,
+                },
+            };
+            if Self::requires_target(card) {
+                for &(target_player, target_card) in &targets {
+                    actions.push(play(Some(target_player), target_card));
+                }
+            } else {
+                actions.push(play(None, None));
+            }
+        }
+        if responding {
+            return actions;
+        }
+        if let Some(hero_power) = &player.hero_power {
+            let use_power = |target_player, target_card| GameAction::UseHeroPower {
+                action: UseHeroPowerAction {
+                    player_id,
+                    target_player,
+                    target_card,
+                },
+            };
+            if Self::requires_target_kind(&hero_power.effect.kind) {
+                for &(target_player, target_card) in &targets {
+                    actions.push(use_power(Some(target_player), target_card));
+                }
+            } else {
+                actions.push(use_power(None, None));
+            }
+        }
+
+        if state.phase == GamePhase::Combat {
+            for &opponent in &enemies {
+                let defenders: Vec<Option<CardId>> = std::iter::once(None)
+                    .chain(
+                        state
+                            .get_player(opponent)
+                            .into_iter()
+                            .flat_map(|defender| defender.board.iter().map(|card| Some(card.id))),
+                    )
+                    .collect();
+                for attacker in &player.board {
+                    for &defender_card in &defenders {
+                        actions.push(GameAction::Attack {
+                            action: AttackAction {
+                                attacker_owner: player_id,
+                                attacker_id: attacker.id,
+                                defender_owner: opponent,
+                                defender_card,
+                            },
+                        });
+                    }
+                }
+                if player.weapon.is_some() {
+                    for &defender_card in &defenders {
+                        actions.push(GameAction::HeroAttack {
+                            action: HeroAttackAction {
+                                player_id,
+                                defender_owner: opponent,
+                                defender_card,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        actions.push(GameAction::EndTurn);
+        actions
+    }
+
+    /// 列出单位当前可攻击的目标，已考虑阶段、疲惫、冻结、嘲讽与潜行。
+    pub fn legal_defenders(
+        &self,
+        state: &GameState,
+        attacker_owner: PlayerId,
+        attacker_id: CardId,
+    ) -> Vec<AttackTargetOption> {
+        state
+            .enemies_of(attacker_owner)
+            .into_iter()
+            .filter_map(|id| state.get_player(id))
+            .flat_map(|defender| {
+                std::iter::once(None)
+                    .chain(defender.board.iter().map(|card| Some(card.id)))
+                    .map(move |defender_card| (defender.id, defender_card))
+            })
+            .filter(|&(defender_owner, defender_card)| {
+                let action = AttackAction {
+                    attacker_owner,
+                    attacker_id,
+                    defender_owner,
+                    defender_card,
+                };
+                self.check_attack(state, &action).is_ok()
+            })
+            .map(|(defender_owner, defender_card)| match defender_card {
+                Some(card_id) => AttackTargetOption::Unit {
+                    player_id: defender_owner,
+                    card_id,
+                },
+                None => AttackTargetOption::Hero {
+                    player_id: defender_owner,
+                },
+            })
+            .collect()
+    }
+
+    pub fn check_victory(state: &mut GameState) -> Option<VictoryState> {
+        state.evaluate_victory()
+    }
+
+    pub fn check_outcome(state: &mut GameState) -> Option<GameOutcome> {
+        state.evaluate_outcome()
+    }
+
+    fn check_advance_phase(&self, state: &GameState) -> Result<(), RuleError> {
+        Self::ensure_active(state)?;
+        Self::ensure_no_pending_choice(state)?;
+        Self::ensure_no_pending_attack(state)?;
+        Self::ensure_no_priority_window(state)?;
+        Self::ensure_phase_allows(state, ActionKind::AdvancePhase)?;
+        if state.phase == GamePhase::Mulligan && !state.all_mulligans_completed() {
+            return Err(RuleError::MulliganIncomplete);
+        }
+        Ok(())
+    }
+
+    /// 推进到下一阶段并结算阶段触发；进入结束阶段后自动结束回合。
+    pub fn advance_phase(&mut self, state: &mut GameState) -> Result<Vec<GameEvent>, RuleError> {
+        self.check_advance_phase(state)?;
+
+        let next = match state.phase {
+            GamePhase::Mulligan => GamePhase::Main,
+            GamePhase::Main => GamePhase::Combat,
+            GamePhase::Combat | GamePhase::End => GamePhase::End,
+        };
+        let mut events = Vec::new();
+        if self.change_phase(state, next, &mut events) {
+            return Ok(events);
+        }
+        if state.phase == GamePhase::End {
+            events.extend(self.finish_turn(state)?);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{MAX_CARD_STAT, SPECTATOR};
+    use crate::game::{
+        validate_hero_deck, AlternativeVictory, CardDefinition, CardEffect, CardFilter,
+        CardRegistry, CardStat, ChoiceOption, DeckError, DeckFormat, EffectCondition,
+        EffectDuration, FilterFailure, GameRng, Hero, HeroPower, Player, PlayerStats, RuleConfig,
+        TargetFilter, Tiebreaker, VictoryReason,
+    };
+
+    fn setup_state() -> GameState {
+        let mut state = GameState::sample();
+        state.phase = GamePhase::Combat;
+        state
+    }
+
+    #[test]
+    fn unit_attack_reduces_hero_health() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state();
+
+        let initial_health = state.get_player(1).expect("defender should exist").health;
+
+        let action = AttackAction {
+            attacker_owner: 0,
+            attacker_id: 2,
+            defender_owner: 1,
+            defender_card: None,
+        };
+
+        let _events = engine
+            .attack(&mut state, action)
+            .expect("attack should succeed");
+
+        let updated_health = state.get_player(1).expect("defender should exist").health;
+
+        assert!(
+            updated_health < initial_health,
+            "defender health should be reduced"
+        );
+    }
+
+    #[test]
+    fn game_start_and_victory_effects_fire_once() {
+        let armor = |trigger, amount| {
+            CardEffect::new(
+                1,
+                "Gain armor",
+                trigger,
+                0,
+                EffectKind::GainArmor {
+                    amount,
+                    target: EffectTarget::SourcePlayer,
+                },
+            )
+        };
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state();
+        state.phase = GamePhase::Mulligan;
+        state.players[0].deck.push(Card::new(
+            90,
+            "Bulwark",
+            1,
+            1,
+            1,
+            CardType::Unit,
+            vec![armor(EffectTrigger::OnGameStart, 2)],
+        ));
+        for player_id in [0, 1] {
+            assert_eq!(state.players[0].armor, 0);
+            engine
+                .mulligan(
+                    &mut state,
+                    MulliganAction {
+                        player_id,
+                        replacements: Vec::new(),
+                    },
+                )
+                .expect("mulligan");
+        }
+        assert_eq!(state.players[0].armor, 2, "deck cards answer OnGameStart");
+
+        state.phase = GamePhase::Combat;
+        state.players[1].health = 1;
+        let attacker = state.players[0]
+            .board
+            .iter_mut()
+            .find(|card| card.id == 2)
+            .expect("attacker");
+        attacker.effects.push(armor(EffectTrigger::OnVictory, 3));
+        let events = engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 2,
+                    defender_owner: 1,
+                    defender_card: None,
+                },
+            )
+            .expect("attack");
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameWon { winner: 0, .. })));
+        assert_eq!(state.players[0].armor, 5);
+        assert!(state.victory_effects_resolved);
+
+        let mut repeat = Vec::new();
+        assert!(engine.settle_victory(&mut state, &mut repeat));
+        assert_eq!(state.players[0].armor, 5, "OnVictory only resolves once");
+    }
+
+    #[test]
+    fn rule_config_disables_retaliation_and_caps_mulligans() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state().with_config(RuleConfig::default().with_retaliation(false));
+        let attacker_health = state.players[0].board[0].health;
+        engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 2,
+                    defender_owner: 1,
+                    defender_card: Some(8),
+                },
+            )
+            .expect("attack");
+        let attacker = state.players[0].board.iter().find(|card| card.id == 2);
+        assert_eq!(attacker.map(|card| card.health), Some(attacker_health));
+
+        let mut state =
+            setup_state().with_config(RuleConfig::default().with_mulligan_limit(Some(1)));
+        state.phase = GamePhase::Mulligan;
+        let replacements: Vec<CardId> = state.players[0]
+            .hand
+            .iter()
+            .take(2)
+            .map(|card| card.id)
+            .collect();
+        assert_eq!(
+            engine.mulligan(
+                &mut state,
+                MulliganAction {
+                    player_id: 0,
+                    replacements,
+                },
+            ),
+            Err(RuleError::MulliganLimitExceeded {
+                limit: 1,
+                requested: 2
+            })
+        );
+    }
+
+    #[test]
+    fn equipped_weapons_let_heroes_attack_once_per_turn_until_broken() {
+        let mut engine = RuleEngine::new();
+        let axe = Card::new(300, "War Axe", 2, 3, 2, CardType::Weapon, Vec::new());
+        let raptor = Card::new(301, "Raptor", 2, 3, 2, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 3, vec![axe], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![raptor], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let strike_hero = HeroAttackAction {
+            player_id: 0,
+            defender_owner: 1,
+            defender_card: None,
+        };
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 300,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("weapon should be equipped");
+        assert!(events.contains(&GameEvent::WeaponEquipped {
+            player_id: 0,
+            card_id: 300,
+        }));
+        assert_eq!(state.get_player(0).unwrap().mana, 1);
+        assert_eq!(
+            engine.hero_attack(&mut state, strike_hero.clone()),
+            Err(RuleError::InvalidPhase {
+                expected: GamePhase::Combat,
+                actual: GamePhase::Main,
+            })
+        );
+
+        engine.advance_phase(&mut state).unwrap();
+        let events = engine
+            .hero_attack(&mut state, strike_hero.clone())
+            .expect("hero should attack with the weapon");
+        assert!(events.contains(&GameEvent::WeaponDurabilityChanged {
+            player_id: 0,
+            card_id: 300,
+            durability: 1,
+        }));
+        assert_eq!(state.get_player(1).unwrap().health, 27);
+        assert_eq!(
+            engine.hero_attack(&mut state, strike_hero.clone()),
+            Err(RuleError::HeroAlreadyAttacked { player_id: 0 })
+        );
+
+        state.players[0].ready_board();
+        engine
+            .hero_attack(
+                &mut state,
+                HeroAttackAction {
+                    player_id: 0,
+                    defender_owner: 1,
+                    defender_card: Some(301),
+                },
+            )
+            .expect("hero should attack the unit");
+        let attacker = state.get_player(0).unwrap();
+        assert_eq!(attacker.health, 27, "the unit strikes back");
+        assert!(attacker.weapon.is_none());
+        assert!(attacker.graveyard.iter().any(|card| card.id == 300));
+        assert!(state.get_player(1).unwrap().board.is_empty());
+
+        state.players[0].ready_board();
+        assert_eq!(
+            engine.hero_attack(&mut state, strike_hero),
+            Err(RuleError::NoWeaponEquipped { player_id: 0 })
+        );
+    }
+
+    #[test]
+    fn simultaneous_defeat_is_a_draw() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state();
+        for player in &mut state.players {
+            player.health = 0;
+        }
+
+        assert_eq!(
+            RuleEngine::check_outcome(&mut state),
+            Some(GameOutcome::Draw {
+                reason: DrawReason::SimultaneousDefeat
+            })
+        );
+        assert!(state.is_finished());
+        assert!(state.outcome.is_none(), "nobody is crowned the winner");
+        assert!(state
+            .event_log
+            .iter()
+            .any(|entry| matches!(entry.event, GameEvent::GameDrawn { .. })));
+
+        let resolution = RuleResolution::new(state.clone(), Vec::new());
+        assert_eq!(resolution.draw, Some(DrawReason::SimultaneousDefeat));
+        assert!(resolution.victory.is_none());
+        assert_eq!(engine.end_turn(&mut state), Err(RuleError::GameFinished));
+    }
+
+    #[test]
+    fn phases_gate_actions_and_leaving_combat_ends_the_turn() {
+        let mut engine = RuleEngine::new();
+        let war_drum = CardEffect::direct_damage(
+            9072,
+            "Deal 1 damage when combat begins",
+            EffectTrigger::OnPhaseEnter {
+                phase: GamePhase::Combat,
+            },
+            0,
+            1,
+            EffectTarget::OpponentOfSource,
+        );
+        let mut drummer = Card::new(110, "War Drummer", 2, 2, 3, CardType::Unit, vec![war_drum]);
+        drummer.refresh();
+        let spark = Card::new(111, "Spark", 1, 0, 0, CardType::Spell, Vec::new());
+        let player_one = Player::new(0, 30, 0, 3, vec![spark], vec![drummer], Vec::new());
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let turn = state.turn;
+        let swing = AttackAction {
+            attacker_owner: 0,
+            attacker_id: 110,
+            defender_owner: 1,
+            defender_card: None,
+        };
+        assert_eq!(
+            engine.attack(&mut state, swing.clone()),
+            Err(RuleError::InvalidPhase {
+                expected: GamePhase::Combat,
+                actual: GamePhase::Main,
+            })
+        );
+
+        let events = engine.advance_phase(&mut state).unwrap();
+        assert_eq!(state.phase, GamePhase::Combat);
+        assert!(events.contains(&GameEvent::PhaseChanged {
+            player_id: 0,
+            phase: GamePhase::Combat,
+        }));
+        assert_eq!(
+            state.get_player(1).unwrap().health,
+            29,
+            "combat trigger fired"
+        );
+        assert_eq!(
+            engine.play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 111,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            ),
+            Err(RuleError::InvalidPhase {
+                expected: GamePhase::Main,
+                actual: GamePhase::Combat,
+            })
+        );
+        engine.attack(&mut state, swing).unwrap();
+        assert_eq!(state.get_player(1).unwrap().health, 27);
+
+        let events = engine.advance_phase(&mut state).unwrap();
+        assert!(events.contains(&GameEvent::PhaseChanged {
+            player_id: 0,
+            phase: GamePhase::End,
+        }));
+        assert!(events.contains(&GameEvent::TurnEnded { player_id: 0 }));
+        assert_eq!(state.current_player, 1);
+        assert_eq!(state.turn, turn + 1);
+        assert_eq!(state.phase, GamePhase::Main);
+    }
+
+    #[test]
+    fn two_step_combat_waits_for_the_defender_before_dealing_damage() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state().with_config(RuleConfig::default().with_two_step_combat(true));
+        let health = state.players[1].health;
+        let attack = AttackAction {
+            attacker_owner: 0,
+            attacker_id: 2,
+            defender_owner: 1,
+            defender_card: None,
+        };
+
+        let events = engine.declare_attack(&mut state, attack).expect("declare");
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::PriorityGranted { player_id: 1, .. })));
+        assert_eq!(
+            state.players[1].health, health,
+            "no damage before resolution"
+        );
+        assert!(state.pending_attack.is_some());
+        assert!(engine.end_turn(&mut state).is_err());
+
+        engine
+            .pass_priority(&mut state, PassPriorityAction { player_id: 1 })
+            .expect("pass");
+        assert_eq!(
+            engine.end_turn(&mut state),
+            Err(RuleError::CombatPending { attacker_id: 2 })
+        );
+        assert_eq!(
+            engine.resolve_combat(&mut state, ResolveCombatAction { player_id: 1 }),
+            Err(RuleError::NotPlayerTurn),
+            "only the attacker resolves its combat"
+        );
+        let resolve = ResolveCombatAction { player_id: 0 };
+        engine
+            .resolve_combat(&mut state, resolve.clone())
+            .expect("resolve");
+        assert!(state.pending_attack.is_none());
+        assert!(state.players[1].health < health);
+        assert_eq!(
+            engine.resolve_combat(&mut state, resolve),
+            Err(RuleError::NoPendingAttack)
+        );
+    }
+
+    #[test]
+    fn apply_all_rolls_back_the_whole_batch_when_an_action_fails() {
+        let squire = Card::new(218, "Squire", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![squire], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let initial = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 218,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+
+        let mut engine = RuleEngine::new();
+        let mut state = initial.clone();
+        let error = engine
+            .apply_all(&mut state, vec![play.clone(), play.clone()])
+            .expect_err("the card was already played");
+        let RuleError::BatchActionFailed { index, error } = error else {
+            panic!("expected a batch failure, got {error:?}");
+        };
+        assert_eq!(index, 1);
+        assert_eq!(*error, RuleError::CardNotFound { card_id: 218 });
+        assert_eq!(state, initial, "the first play is rolled back");
+
+        let events = engine
+            .apply_all(&mut state, vec![play, GameAction::EndTurn])
+            .expect("the batch should apply");
+        assert!(events.contains(&GameEvent::TurnEnded { player_id: 0 }));
+        assert_eq!(state.players[0].board.len(), 1);
+        assert_eq!(state.current_player, 1);
+    }
+
+    #[test]
+    fn two_step_attack_can_be_played_through_legal_actions() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state().with_config(RuleConfig::default().with_two_step_combat(true));
+        let initial_health = state.get_player(1).unwrap().health;
+
+        let declare = GameAction::Attack {
+            action: AttackAction {
+                attacker_owner: 0,
+                attacker_id: 2,
+                defender_owner: 1,
+                defender_card: None,
+            },
+        };
+        assert!(engine.legal_actions(&state, 0).contains(&declare));
+        engine.apply_action(&mut state, &declare).expect("declare");
+        assert_eq!(state.get_player(1).unwrap().health, initial_health);
+        assert!(engine.legal_actions(&state, 0).is_empty());
+
+        let pass = GameAction::PassPriority {
+            action: PassPriorityAction { player_id: 1 },
+        };
+        let responses = engine.legal_actions(&state, 1);
+        assert!(responses.contains(&pass));
+        assert!(!responses.contains(&GameAction::EndTurn));
+        engine.apply_action(&mut state, &pass).expect("pass");
+
+        let resolve = GameAction::ResolveCombat {
+            action: ResolveCombatAction { player_id: 0 },
+        };
+        assert_eq!(engine.legal_actions(&state, 0), vec![resolve.clone()]);
+        assert!(engine.legal_actions(&state, 1).is_empty());
+        engine.apply_action(&mut state, &resolve).expect("resolve");
+        assert!(state.get_player(1).unwrap().health < initial_health);
+        assert!(state.pending_attack.is_none());
+        assert!(engine
+            .legal_actions(&state, 0)
+            .contains(&GameAction::EndTurn));
+    }
+
+    #[test]
+    fn unit_attack_trades_with_opponent_unit() {
+        let mut engine = RuleEngine::new();
+        let mut state = setup_state();
+
+        // Ensure defender unit exists on player 1 board (id 8 in sample state)
         let defender_before = state
             .get_player(1)
             .and_then(|player| player.board.iter().find(|card| card.id == 8))
@@ -798,11 +3130,44 @@ mod tests {
     }
 
     #[test]
-    fn end_turn_triggers_next_player_start_effects() {
+    fn ranged_attacker_takes_no_retaliation() {
         let mut engine = RuleEngine::new();
+        let mut state = setup_state();
+        let attacker = state.players[0]
+            .find_card_on_board_mut(2)
+            .expect("attacker unit should exist");
+        attacker.keywords.push(Keyword::Ranged);
+        let attacker_before = attacker.health;
 
-        let healer_effect = CardEffect::heal(
-            9001,
+        let action = AttackAction {
+            attacker_owner: 0,
+            attacker_id: 2,
+            defender_owner: 1,
+            defender_card: Some(8),
+        };
+        engine
+            .attack(&mut state, action)
+            .expect("attack should succeed");
+
+        let attacker_after = state.players[0]
+            .find_card_on_board(2)
+            .map(|card| card.health);
+        assert_eq!(attacker_after, Some(attacker_before));
+        assert!(!state.events().any(|event| matches!(
+            event,
+            GameEvent::DamageResolved {
+                target_card: Some(2),
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn end_turn_triggers_next_player_start_effects() {
+        let mut engine = RuleEngine::new();
+
+        let healer_effect = CardEffect::heal(
+            9001,
             "Healer",
             EffectTrigger::OnTurnStart,
             1,
@@ -810,14 +3175,32 @@ mod tests {
             EffectTarget::SourcePlayer,
         );
 
-        let mut healer = Card::new(100, "Turn Healer", 2, 2, 3, CardType::Unit, vec![healer_effect]);
+        let mut healer = Card::new(
+            100,
+            "Turn Healer",
+            2,
+            2,
+            3,
+            CardType::Unit,
+            vec![healer_effect],
+        );
         healer.exhausted = true;
+        healer.summoning_sick = true;
 
         let deck_card_one = Card::new(101, "Deck Filler A", 1, 1, 1, CardType::Unit, Vec::new());
         let deck_card_two = Card::new(102, "Deck Filler B", 1, 1, 1, CardType::Unit, Vec::new());
 
         let player_one = Player::new(0, 30, 0, 3, Vec::new(), Vec::new(), vec![deck_card_one]);
-        let player_two = Player::new(1, 25, 0, 3, Vec::new(), vec![healer.clone()], vec![deck_card_two]);
+        let mut player_two = Player::new(
+            1,
+            30,
+            0,
+            3,
+            Vec::new(),
+            vec![healer.clone()],
+            vec![deck_card_two],
+        );
+        player_two.health = 25;
 
         let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
 
@@ -843,7 +3226,7 @@ mod tests {
             player_two_state
                 .board
                 .iter()
-                .all(|card| !card.exhausted),
+                .all(|card| !card.exhausted && !card.summoning_sick),
             "board units should be refreshed"
         );
         assert_eq!(
@@ -852,4 +3235,3199 @@ mod tests {
             "next player should draw a card on turn start"
         );
     }
+
+    #[test]
+    fn lethal_damage_to_both_heroes_ends_in_a_draw() {
+        let mut engine = RuleEngine::new();
+        let blast = |id: u32, target: EffectTarget| {
+            CardEffect::new(
+                id,
+                "Cataclysm: deal 5 damage to a hero",
+                EffectTrigger::OnPlay,
+                1,
+                EffectKind::DirectDamage {
+                    amount: 5,
+                    target,
+                    scaling: None,
+                },
+            )
+        };
+        let spell = Card::new(
+            120,
+            "Cataclysm",
+            1,
+            0,
+            0,
+            CardType::Spell,
+            vec![
+                blast(9010, EffectTarget::OpponentOfSource),
+                blast(9011, EffectTarget::SourcePlayer),
+            ],
+        );
+        let player_one = Player::new(0, 5, 0, 3, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 5, 0, 3, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 120,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("play");
+
+        assert!(state.outcome.is_none());
+        assert_eq!(
+            state.game_outcome(),
+            Some(GameOutcome::Draw {
+                reason: DrawReason::SimultaneousDefeat
+            })
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::GameDrawn {
+                reason: DrawReason::SimultaneousDefeat
+            }
+        )));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameWon { .. })));
+    }
+
+    #[test]
+    fn drawing_from_an_empty_deck_loses_at_the_next_victory_check() {
+        let mut state = GameState::new(
+            vec![
+                Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), Vec::new()),
+                Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new()),
+            ],
+            0,
+        );
+
+        assert!(state.draw_card(1).is_none());
+        assert!(state.outcome.is_none());
+        assert_eq!(
+            RuleEngine::check_victory(&mut state),
+            Some(VictoryState {
+                winner: 0,
+                reason: VictoryReason::DeckOut { loser: 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn mill_moves_top_of_deck_into_graveyard() {
+        let mut engine = RuleEngine::new();
+
+        let mill_effect = CardEffect::new(
+            9002,
+            "Drain: mill two cards from the opponent",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::Mill {
+                count: 2,
+                target: EffectTarget::OpponentOfSource,
+            },
+        );
+        let spell = Card::new(
+            110,
+            "Mind Drain",
+            1,
+            0,
+            0,
+            CardType::Spell,
+            vec![mill_effect],
+        );
+        let deck: Vec<Card> = (111..114)
+            .map(|id| Card::new(id, "Deck Filler", 1, 1, 1, CardType::Unit, Vec::new()))
+            .collect();
+
+        let player_one = Player::new(0, 30, 0, 3, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), Vec::new(), deck);
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 110,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("mill spell should resolve");
+
+        let milled: Vec<CardId> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::CardMilled { player_id: 1, card } => Some(card.id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            milled,
+            vec![113, 112],
+            "cards leave from the top of the deck"
+        );
+
+        let opponent = state.get_player(1).expect("opponent should exist");
+        assert_eq!(opponent.deck.len(), 1);
+        assert_eq!(opponent.graveyard.len(), 2);
+        assert!(opponent.hand.is_empty(), "milled cards are not drawn");
+    }
+
+    #[test]
+    fn destroyed_units_enter_the_graveyard() {
+        let mut engine = RuleEngine::new();
+        let mut raider = Card::new(115, "Raider", 1, 3, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let scout = Card::new(116, "Scout", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![scout], Vec::new());
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+        let fallen = EffectCondition::GraveyardSizeAtLeast {
+            target: EffectTarget::SourcePlayer,
+            min: 1,
+            card_type: Some(CardType::Unit),
+        };
+        let ctx = EffectContext::new(EffectTrigger::Passive, 1, 0);
+        assert!(!fallen.is_satisfied(&ctx, &state));
+
+        engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 115,
+                    defender_owner: 1,
+                    defender_card: Some(116),
+                },
+            )
+            .expect("raider attacks the scout");
+
+        let graveyard: Vec<CardId> = state.players[1]
+            .graveyard
+            .iter()
+            .map(|card| card.id)
+            .collect();
+        assert_eq!(graveyard, vec![116]);
+        assert!(fallen.is_satisfied(&ctx, &state));
+        assert!(state.integrity_check().is_ok());
+
+        let mut copy = state.players[1].graveyard[0].clone();
+        copy.health = 1;
+        state.players[1].hand.push(copy);
+        assert_eq!(
+            state.integrity_check(),
+            Err(IntegrityError::DuplicateCardId { card_id: 116 })
+        );
+    }
+
+    #[test]
+    fn temporary_mana_expires_while_empty_crystal_persists() {
+        let mut engine = RuleEngine::new();
+
+        let coin_effect = CardEffect::new(
+            9003,
+            "Coin: gain 1 mana this turn",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::GainMana {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let growth_effect = CardEffect::new(
+            9004,
+            "Growth: gain an empty mana crystal",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::GainManaCrystal {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+                filled: false,
+            },
+        );
+        let coin = Card::new(120, "The Coin", 0, 0, 0, CardType::Spell, vec![coin_effect]);
+        let growth = Card::new(
+            121,
+            "Wild Growth",
+            2,
+            0,
+            0,
+            CardType::Spell,
+            vec![growth_effect],
+        );
+
+        let player_one = Player::new(0, 30, 0, 2, vec![coin, growth], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 2, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for card_id in [120, 121] {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: None,
+                        target_card: None,
+                        position: None,
+                    },
+                )
+                .expect("mana spells should resolve");
+        }
+
+        let player = state.get_player(0).expect("player should exist");
+        assert_eq!(player.mana, 1, "coin mana is spendable this turn");
+        assert_eq!(player.max_mana, 3, "empty crystal raises the cap");
+        assert!(state.integrity_check().is_ok());
+
+        state.ready_player(0);
+        let player = state.get_player(0).expect("player should exist");
+        assert_eq!((player.mana, player.max_mana), (4, 4));
+    }
+
+    #[test]
+    fn passive_cost_modifier_discounts_matching_cards_in_hand() {
+        let mut engine = RuleEngine::new();
+
+        let discount = CardEffect::new(
+            9005,
+            "Spellweaver: your spells cost 2 less",
+            EffectTrigger::Passive,
+            0,
+            EffectKind::CostModifier {
+                delta: -2,
+                card_type: Some(CardType::Spell),
+            },
+        );
+        let mut weaver = Card::new(130, "Spellweaver", 3, 2, 2, CardType::Unit, vec![discount]);
+        weaver.refresh();
+        let bolt = Card::new(131, "Bolt", 3, 0, 0, CardType::Spell, Vec::new());
+        let golem = Card::new(132, "Golem", 3, 3, 3, CardType::Unit, Vec::new());
+
+        let player_one = Player::new(0, 30, 0, 1, vec![bolt, golem], vec![weaver], Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        assert_eq!(state.effective_cost(0, 131), Some(1));
+        assert_eq!(
+            state.effective_cost(0, 132),
+            Some(3),
+            "units are not discounted"
+        );
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 131,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("discounted spell should be affordable");
+        assert_eq!(state.get_player(0).map(|player| player.mana), Some(0));
+    }
+
+    #[test]
+    fn timed_effects_expire_on_turn_boundaries() {
+        let mut engine = RuleEngine::new();
+
+        let rage = CardEffect::new(
+            9006,
+            "Rage: give a unit +2 attack this turn",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::ModifyStats {
+                attack: 2,
+                health: 0,
+                target: EffectTarget::ContextTarget,
+                duration: EffectDuration::ThisTurn,
+            },
+        );
+        let chill = CardEffect::new(
+            9007,
+            "Chill: freeze a unit until your next turn",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::Freeze {
+                target: EffectTarget::ContextTarget,
+                duration: EffectDuration::UntilNextTurn,
+            },
+        );
+        let rage_card = Card::new(140, "Rage", 1, 0, 0, CardType::Spell, vec![rage]);
+        let chill_card = Card::new(141, "Chill", 1, 0, 0, CardType::Spell, vec![chill]);
+        let mut ally = Card::new(142, "Ally", 1, 1, 3, CardType::Unit, Vec::new());
+        ally.refresh();
+        let mut foe = Card::new(143, "Foe", 1, 2, 3, CardType::Unit, Vec::new());
+        foe.refresh();
+
+        let player_one = Player::new(
+            0,
+            30,
+            0,
+            2,
+            vec![rage_card, chill_card],
+            vec![ally],
+            Vec::new(),
+        );
+        let player_two = Player::new(1, 30, 0, 2, Vec::new(), vec![foe], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for (card_id, owner, target) in [(140, 0, 142), (141, 1, 143)] {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: Some(owner),
+                        target_card: Some(target),
+                        position: None,
+                    },
+                )
+                .expect("targeted spell should resolve");
+        }
+        let board_card = |state: &GameState, owner: PlayerId, id: CardId| {
+            state
+                .get_player(owner)
+                .and_then(|player| player.board.iter().find(|card| card.id == id))
+                .cloned()
+                .expect("unit should be on board")
+        };
+        assert_eq!(board_card(&state, 0, 142).attack, 3);
+
+        engine.end_turn(&mut state).expect("turn should pass");
+        assert_eq!(
+            board_card(&state, 0, 142).attack,
+            1,
+            "buff ends with the turn"
+        );
+        assert!(
+            board_card(&state, 1, 143).frozen,
+            "freeze lasts through the enemy turn"
+        );
+
+        state.phase = GamePhase::Combat;
+        let blocked = engine.attack(
+            &mut state,
+            AttackAction {
+                attacker_owner: 1,
+                attacker_id: 143,
+                defender_owner: 0,
+                defender_card: None,
+            },
+        );
+        assert_eq!(blocked, Err(RuleError::UnitFrozen { card_id: 143 }));
+
+        engine.end_turn(&mut state).expect("turn should pass back");
+        assert!(
+            !board_card(&state, 1, 143).frozen,
+            "thaws at the caster's next turn"
+        );
+    }
+
+    #[test]
+    fn expiring_health_buff_kills_a_unit_whose_damage_outlasts_it() {
+        let mut engine = RuleEngine::new();
+        let fortify = CardEffect::new(
+            9008,
+            "Fortify: give a unit +3 health this turn",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::ModifyStats {
+                attack: 0,
+                health: 3,
+                target: EffectTarget::ContextTarget,
+                duration: EffectDuration::ThisTurn,
+            },
+        );
+        let last_stand = CardEffect::new(
+            9009,
+            "Deathrattle: gain 2 armor",
+            EffectTrigger::OnDeath,
+            0,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let spell = |id| {
+            Card::new(
+                id,
+                "Fortify",
+                1,
+                0,
+                0,
+                CardType::Spell,
+                vec![fortify.clone()],
+            )
+        };
+        let mut guard = Card::new(145, "Guard", 1, 1, 2, CardType::Unit, vec![last_stand]);
+        guard.refresh();
+        let mut squire = Card::new(146, "Squire", 1, 1, 2, CardType::Unit, Vec::new());
+        squire.refresh();
+        let player_one = Player::new(
+            0,
+            30,
+            0,
+            2,
+            vec![spell(144), spell(147)],
+            vec![guard, squire],
+            Vec::new(),
+        );
+        let player_two = Player::new(1, 30, 0, 2, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for (card_id, target) in [(144, 145), (147, 146)] {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: Some(0),
+                        target_card: Some(target),
+                        position: None,
+                    },
+                )
+                .expect("buff should resolve");
+        }
+        state.damage_card(1, None, 0, 145, 4);
+        state.damage_card(1, None, 0, 146, 1);
+        assert_eq!(state.players[0].board[0].health, 1);
+
+        let events = engine.end_turn(&mut state).expect("turn should pass");
+
+        let player = state.get_player(0).unwrap();
+        let board: Vec<(CardId, i16)> = player.board.iter().map(|c| (c.id, c.health)).collect();
+        assert_eq!(board, vec![(146, 1)], "damage is kept when the buff ends");
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::CardDestroyed { card, .. } if card.id == 145
+        )));
+        assert_eq!(player.armor, 2, "the deathrattle resolves");
+    }
+
+    #[test]
+    fn delayed_effect_fires_on_owners_next_turn_start() {
+        let mut engine = RuleEngine::new();
+
+        let foresight = CardEffect::new(
+            9008,
+            "Foresight: at the start of your next turn, gain 3 armor",
+            EffectTrigger::OnPlay,
+            1,
+            EffectKind::Delayed {
+                trigger: EffectTrigger::OnTurnStart,
+                effect: Box::new(EffectKind::GainArmor {
+                    amount: 3,
+                    target: EffectTarget::SourcePlayer,
+                }),
+            },
+        );
+        let spell = Card::new(150, "Foresight", 1, 0, 0, CardType::Spell, vec![foresight]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 150,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("delayed spell should resolve");
+        assert_eq!(state.scheduled_effects.len(), 1);
+
+        engine.end_turn(&mut state).expect("turn should pass");
+        assert_eq!(state.get_player(0).map(|player| player.armor), Some(0));
+        assert_eq!(
+            state.scheduled_effects.len(),
+            1,
+            "opponent's turn does not fire it"
+        );
+
+        engine.end_turn(&mut state).expect("turn should pass back");
+        assert_eq!(state.get_player(0).map(|player| player.armor), Some(3));
+        assert!(state.scheduled_effects.is_empty());
+    }
+
+    #[test]
+    fn spell_cast_listeners_buff_themselves() {
+        let mut engine = RuleEngine::new();
+
+        let growth = CardEffect::new(
+            9009,
+            "Wyrm: whenever you cast a spell, gain +1/+1",
+            EffectTrigger::OnSpellCast,
+            0,
+            EffectKind::ModifyStats {
+                attack: 1,
+                health: 1,
+                target: EffectTarget::SourceCard,
+                duration: EffectDuration::Permanent,
+            },
+        );
+        let mut wyrm = Card::new(160, "Mana Wyrm", 1, 1, 3, CardType::Unit, vec![growth]);
+        wyrm.refresh();
+        let spell = Card::new(161, "Spark", 0, 0, 0, CardType::Spell, Vec::new());
+        let unit = Card::new(162, "Recruit", 0, 1, 1, CardType::Unit, Vec::new());
+
+        let player_one = Player::new(0, 30, 0, 1, vec![spell, unit], vec![wyrm], Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for card_id in [161, 162] {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: None,
+                        target_card: None,
+                        position: None,
+                    },
+                )
+                .expect("card should be playable");
+        }
+
+        let wyrm = state
+            .get_player(0)
+            .and_then(|player| player.board.iter().find(|card| card.id == 160))
+            .expect("wyrm should stay on board");
+        assert_eq!(
+            (wyrm.attack, wyrm.health),
+            (2, 4),
+            "only the spell triggers it"
+        );
+    }
+
+    #[test]
+    fn overdraw_burns_card_and_fires_burn_listeners() {
+        let mut engine = RuleEngine::new();
+
+        let draw_three = CardEffect::draw_card(
+            9010,
+            "Draw three cards",
+            EffectTrigger::OnPlay,
+            0,
+            3,
+            EffectTarget::SourcePlayer,
+        );
+        let spell = Card::new(170, "Overflow", 0, 0, 0, CardType::Spell, vec![draw_three]);
+        let deck = vec![
+            Card::new(171, "Deck A", 1, 1, 1, CardType::Unit, Vec::new()),
+            Card::new(172, "Deck B", 1, 1, 1, CardType::Unit, Vec::new()),
+            Card::new(173, "Deck C", 1, 1, 1, CardType::Unit, Vec::new()),
+        ];
+        let punisher = CardEffect::new(
+            9011,
+            "Whenever a card is burned, deal 2 damage to its owner",
+            EffectTrigger::OnCardBurned,
+            0,
+            EffectKind::DirectDamage {
+                amount: 2,
+                target: EffectTarget::ContextTarget,
+                scaling: None,
+            },
+        );
+        let watcher = Card::new(174, "Watcher", 1, 1, 3, CardType::Unit, vec![punisher]);
+
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), deck);
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), vec![watcher], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        state.config.max_hand_size = 1;
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 170,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("spell should be playable");
+
+        assert_eq!(
+            state.pending_choices.len(),
+            1,
+            "only one pending choice is kept"
+        );
+        assert!(state
+            .events()
+            .any(|event| matches!(event, GameEvent::CardBurned { player_id: 0, .. })));
+        let player = state.get_player(0).unwrap();
+        assert_eq!(player.graveyard.len(), 1);
+        assert_eq!(player.health, 28);
+    }
+
+    #[test]
+    fn secret_reveals_on_opponent_attack_and_is_hidden_from_opponent() {
+        let mut engine = RuleEngine::new();
+
+        let trap = CardEffect::new(
+            9012,
+            "Secret: when an enemy attacks, deal 3 damage to it",
+            EffectTrigger::OnAttack,
+            0,
+            EffectKind::DirectDamage {
+                amount: 3,
+                target: EffectTarget::ContextTarget,
+                scaling: None,
+            },
+        );
+        let secret = Card::new(180, "Snare", 1, 0, 0, CardType::Secret, vec![trap]);
+        let player_one = Player::new(0, 30, 0, 1, vec![secret], Vec::new(), Vec::new());
+        let mut raider = Card::new(181, "Raider", 2, 4, 2, CardType::Unit, Vec::new());
+        raider.refresh();
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 180,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("secret should be playable without a target");
+        assert_eq!(state.get_player(0).unwrap().secrets.len(), 1);
+        let view = state.redacted_for(1);
+        let hidden = &view.get_player(0).unwrap().secrets[0];
+        assert!(hidden.name.is_empty() && hidden.effects.is_empty());
+
+        state.current_player = 1;
+        state.phase = GamePhase::Combat;
+        let events = engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 1,
+                    attacker_id: 181,
+                    defender_owner: 0,
+                    defender_card: None,
+                },
+            )
+            .expect("attack should be declared");
+
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::SecretRevealed { player_id: 0, .. })));
+        let owner = state.get_player(0).unwrap();
+        assert!(owner.secrets.is_empty());
+        assert_eq!(owner.health, 30, "destroyed attacker deals no damage");
+        assert!(state.get_player(1).unwrap().board.is_empty());
+    }
+
+    #[test]
+    fn redacted_views_do_not_leak_the_rng() {
+        let mut state = GameState::sample().with_seed(0x1234_5678_9abc_def0);
+        state.rng.next_u64();
+        let seed = state.rng.seed().to_string();
+        let rng_state = serde_json::to_value(&state.rng).unwrap()["state"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        for viewer in [0, 1, SPECTATOR] {
+            let view = state.redacted_for(viewer);
+            assert_eq!(view.rng, GameRng::default());
+            let json = serde_json::to_string(&view).unwrap();
+            assert!(!json.contains(&seed), "seed leaked to viewer {viewer}");
+            assert!(
+                !json.contains(&rng_state),
+                "rng state leaked to viewer {viewer}"
+            );
+        }
+    }
+
+    #[test]
+    fn counter_secret_cancels_pending_spell_effects() {
+        let mut engine = RuleEngine::new();
+
+        let counter = CardEffect::new(
+            9013,
+            "Secret: counter the next spell your opponent casts",
+            EffectTrigger::OnSpellCast,
+            10,
+            EffectKind::Counter,
+        );
+        let secret = Card::new(190, "Denial", 3, 0, 0, CardType::Secret, vec![counter]);
+        let mut player_one = Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        player_one.secrets.push(secret);
+
+        let bolt = CardEffect::direct_damage(
+            9014,
+            "Deal 5 damage",
+            EffectTrigger::OnPlay,
+            0,
+            5,
+            EffectTarget::OpponentOfSource,
+        );
+        let spell = Card::new(191, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt]);
+        let player_two = Player::new(1, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 1).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 1,
+                    card_id: 191,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("spell should be playable");
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::EffectCountered {
+                effect_id: 9014,
+                ..
+            }
+        )));
+        assert_eq!(state.get_player(0).unwrap().health, 30);
+        assert!(engine.effect_engine.stack().is_empty());
+    }
+
+    #[test]
+    fn legal_actions_enumerate_affordable_plays_with_valid_targets() {
+        let engine = RuleEngine::new();
+        let zap = CardEffect::direct_damage(
+            9040,
+            "Deal 1 damage",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::ContextTarget,
+        );
+        let hand = vec![
+            Card::new(210, "Zap", 1, 0, 0, CardType::Spell, vec![zap]),
+            Card::new(211, "Squire", 1, 1, 1, CardType::Unit, Vec::new()),
+            Card::new(212, "Giant", 8, 8, 8, CardType::Unit, Vec::new()),
+        ];
+        let player_one = Player::new(0, 30, 0, 1, hand, Vec::new(), Vec::new());
+        let enemy = Card::new(213, "Guard", 1, 1, 2, CardType::Unit, Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), vec![enemy], Vec::new());
+        let state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let actions = engine.legal_actions(&state, 0);
+        let plays: Vec<(CardId, Option<PlayerId>, Option<CardId>)> = actions
+            .iter()
+            .filter_map(|action| match action {
+                GameAction::PlayCard { action } => {
+                    Some((action.card_id, action.target_player, action.target_card))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            plays,
+            vec![
+                (210, Some(0), None),
+                (210, Some(1), None),
+                (210, Some(1), Some(213)),
+                (211, None, None),
+            ]
+        );
+        assert!(actions.contains(&GameAction::AdvancePhase));
+        assert_eq!(actions.last(), Some(&GameAction::EndTurn));
+        assert!(engine.legal_actions(&state, 1).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_untargeted_play_without_losing_the_card() {
+        let mut engine = RuleEngine::new();
+        let zap = CardEffect::direct_damage(
+            9041,
+            "Deal 1 damage",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::ContextTarget,
+        );
+        let hand = vec![Card::new(214, "Zap", 1, 0, 0, CardType::Spell, vec![zap])];
+        let player_one = Player::new(0, 30, 0, 1, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let untargeted = PlayCardAction {
+            player_id: 0,
+            card_id: 214,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+        let action = GameAction::PlayCard {
+            action: untargeted.clone(),
+        };
+        assert_eq!(
+            engine.validate(&state, &action),
+            Err(RuleError::InvalidTarget)
+        );
+        assert_eq!(
+            engine.play_card(&mut state, untargeted),
+            Err(RuleError::InvalidTarget)
+        );
+        assert_eq!(state.players[0].hand.len(), 1);
+        assert_eq!(state.players[0].mana, 1);
+
+        let attack = GameAction::Attack {
+            action: AttackAction {
+                attacker_owner: 0,
+                attacker_id: 214,
+                defender_owner: 1,
+                defender_card: None,
+            },
+        };
+        assert_eq!(
+            engine.validate(&state, &attack),
+            Err(RuleError::InvalidPhase {
+                expected: GamePhase::Combat,
+                actual: GamePhase::Main,
+            })
+        );
+        assert_eq!(engine.validate(&state, &GameAction::EndTurn), Ok(()));
+    }
+
+    #[test]
+    fn explain_reports_code_and_targeted_alternatives() {
+        let mut engine = RuleEngine::new();
+        let zap = CardEffect::direct_damage(
+            9042,
+            "Deal 1 damage",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::ContextTarget,
+        );
+        let hand = vec![
+            Card::new(215, "Zap", 1, 0, 0, CardType::Spell, vec![zap]),
+            Card::new(216, "Squire", 1, 1, 1, CardType::Unit, Vec::new()),
+        ];
+        let player_one = Player::new(0, 30, 0, 1, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let action = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 215,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+        let error = engine
+            .apply_action(&mut state, &action)
+            .expect_err("zap needs a target");
+        let report = engine.explain(&state, &action, error);
+        assert_eq!(report.code, "invalid_target");
+        assert_eq!(report.action, Some(action));
+        assert_eq!(report.alternatives.len(), 2, "one alternative per hero");
+        assert!(report.alternatives.iter().all(|alternative| matches!(
+            alternative,
+            GameAction::PlayCard { action } if action.card_id == 215
+        )));
+
+        let json = serde_json::to_value(&report).expect("report serializes");
+        assert_eq!(json["code"], "invalid_target");
+        assert_eq!(json["type"], "InvalidTarget");
+        assert_eq!(json["action"]["type"], "PlayCard");
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_games_and_reports_divergence() {
+        let squire = Card::new(217, "Squire", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![squire], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let initial = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 217,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+        let actions = vec![play.clone(), GameAction::EndTurn];
+
+        let mut engine = RuleEngine::new();
+        let mut recorded = initial.clone();
+        for action in &actions {
+            engine.apply_action(&mut recorded, action).unwrap();
+        }
+        assert_eq!(engine.replay(&initial, &actions), Ok(recorded));
+
+        let diverged = [play.clone(), play];
+        let error = engine
+            .replay(&initial, &diverged)
+            .expect_err("the card was already played");
+        assert_eq!(error.index, 1);
+        assert_eq!(error.error.code(), "card_not_found");
+        assert_eq!(error.state.players[0].board.len(), 1);
+    }
+
+    #[test]
+    fn action_hooks_veto_actions_and_inject_events() {
+        let mut engine = RuleEngine::new();
+        engine.add_pre_action_hook(|state, action| match action {
+            GameAction::EndTurn if state.turn < 2 => Err(RuleError::ActionVetoed {
+                reason: "tutorial: attack first".into(),
+            }),
+            _ => Ok(()),
+        });
+        engine.add_post_action_hook(|state, action, _| match action {
+            GameAction::AdvancePhase => state
+                .gain_armor(state.current_player, 1)
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        });
+        let mut state = GameState::sample();
+        state.phase = GamePhase::Main;
+
+        let vetoed = RuleError::ActionVetoed {
+            reason: "tutorial: attack first".into(),
+        };
+        assert_eq!(
+            engine.validate(&state, &GameAction::EndTurn),
+            Err(vetoed.clone())
+        );
+        assert_eq!(
+            engine.apply_action(&mut state, &GameAction::EndTurn),
+            Err(vetoed)
+        );
+        assert!(!engine
+            .legal_actions(&state, state.current_player)
+            .contains(&GameAction::EndTurn));
+
+        let armor = state.players[0].armor;
+        let events = engine
+            .apply_action(&mut state, &GameAction::AdvancePhase)
+            .expect("advance to combat");
+        assert_eq!(state.players[0].armor, armor + 1);
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::ArmorGained { amount: 1, .. })
+        ));
+        assert_eq!(state.events().last(), events.last());
+    }
+
+    #[test]
+    fn interactive_priority_lets_opponent_counter_before_resolution() {
+        let mut engine = RuleEngine::new().with_interactive_priority(true);
+
+        let bolt = CardEffect::direct_damage(
+            9030,
+            "Deal 5 damage",
+            EffectTrigger::OnPlay,
+            0,
+            5,
+            EffectTarget::OpponentOfSource,
+        );
+        let spell = Card::new(195, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let negate = CardEffect::new(
+            9031,
+            "Counter the spell being responded to",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::Counter,
+        );
+        let response = Card::new(196, "Negate", 1, 0, 0, CardType::Spell, vec![negate]);
+        let player_two = Player::new(1, 30, 0, 1, vec![response], Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 195,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("spell should be playable");
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::PriorityGranted {
+                player_id: 1,
+                source_card: Some(195)
+            }
+        )));
+        assert_eq!(state.get_player(1).unwrap().health, 30);
+        let window = state.priority_window.as_ref().expect("window is open");
+        assert_eq!(window.stack.entries().len(), 1);
+        assert_eq!(
+            engine.end_turn(&mut state),
+            Err(RuleError::AwaitingPriority { player_id: 1 })
+        );
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 1,
+                    card_id: 196,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("priority holder may respond with a spell");
+        let events = engine
+            .pass_priority(&mut state, PassPriorityAction { player_id: 1 })
+            .expect("holder may pass");
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::EffectCountered {
+                effect_id: 9030,
+                ..
+            }
+        )));
+        assert_eq!(state.get_player(1).unwrap().health, 30);
+        assert!(state.priority_window.is_none());
+        assert!(engine.effect_stack().is_empty());
+    }
+
+    #[test]
+    fn passing_priority_is_a_replayable_game_action() {
+        let engine = RuleEngine::new().with_interactive_priority(true);
+        let bolt = CardEffect::direct_damage(
+            9032,
+            "Deal 5 damage",
+            EffectTrigger::OnPlay,
+            0,
+            5,
+            EffectTarget::OpponentOfSource,
+        );
+        let spell = Card::new(197, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let initial = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 197,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+        let pass = |player_id| GameAction::PassPriority {
+            action: PassPriorityAction { player_id },
+        };
+
+        let mut state = initial.clone();
+        engine
+            .clone()
+            .apply_action(&mut state, &play)
+            .expect("play");
+        assert_eq!(
+            engine.validate(&state, &pass(0)),
+            Err(RuleError::AwaitingPriority { player_id: 1 })
+        );
+
+        let replayed = engine
+            .replay(&initial, &[play, pass(1)])
+            .expect("replay should pass priority");
+        assert!(replayed.priority_window.is_none());
+        assert_eq!(replayed.get_player(1).unwrap().health, 25);
+    }
+
+    #[test]
+    fn additional_costs_are_validated_and_paid() {
+        let mut engine = RuleEngine::new();
+
+        let pact = Card::new(210, "Blood Pact", 0, 0, 0, CardType::Spell, Vec::new())
+            .with_additional_cost(AdditionalCost {
+                health: 3,
+                discard: 1,
+            });
+        let filler = Card::new(211, "Filler", 1, 0, 0, CardType::Spell, Vec::new());
+        let player_one = Player::new(0, 3, 0, 0, vec![pact, filler], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = PlayCardAction {
+            player_id: 0,
+            card_id: 210,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+
+        assert_eq!(
+            engine.play_card(&mut state, play.clone()),
+            Err(RuleError::InsufficientHealth {
+                required: 3,
+                available: 3
+            })
+        );
+        state.players[0].health = 10;
+        let events = engine
+            .play_card(&mut state, play)
+            .expect("costs can be paid");
+
+        assert_eq!(state.get_player(0).unwrap().health, 7);
+        assert!(state.get_player(0).unwrap().hand.is_empty());
+        assert_eq!(state.get_player(0).unwrap().graveyard[0].id, 211);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::CardDiscarded { card, .. } if card.id == 211
+        )));
+    }
+
+    #[test]
+    fn combo_effects_need_a_prior_card_this_turn() {
+        let mut engine = RuleEngine::new();
+
+        let strike = |id: CardId| {
+            let bonus = CardEffect::direct_damage(
+                9040 + id,
+                "Combo: deal 2 damage",
+                EffectTrigger::Combo,
+                0,
+                2,
+                EffectTarget::OpponentOfSource,
+            );
+            Card::new(id, "Strike", 0, 0, 0, CardType::Spell, vec![bonus])
+        };
+        let hand = vec![strike(220), strike(221)];
+        let player_one = Player::new(0, 30, 0, 0, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for card_id in [220, 221] {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: None,
+                        target_card: None,
+                        position: None,
+                    },
+                )
+                .expect("free spell should be playable");
+        }
+
+        assert_eq!(
+            state.get_player(1).unwrap().health,
+            28,
+            "only the second strike combos"
+        );
+        assert_eq!(
+            state.get_player(0).unwrap().cards_played_this_turn,
+            vec![220, 221]
+        );
+        engine.end_turn(&mut state).expect("turn should pass");
+        assert!(state
+            .get_player(0)
+            .unwrap()
+            .cards_played_this_turn
+            .is_empty());
+    }
+
+    #[test]
+    fn per_turn_play_limits_are_enforced() {
+        let spell = |id| Card::new(id, "Spark", 0, 0, 0, CardType::Spell, Vec::new());
+        let unit = |id| Card::new(id, "Squire", 0, 1, 1, CardType::Unit, Vec::new());
+        let hand = vec![spell(222), spell(223), unit(224), unit(225)];
+        let player_one = Player::new(0, 30, 0, 0, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let config = RuleConfig::default()
+            .with_max_cards_per_turn(Some(3))
+            .with_max_spells_per_turn(Some(1));
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_phase(GamePhase::Main)
+            .with_config(config);
+        let play = |card_id| PlayCardAction {
+            player_id: 0,
+            card_id,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+
+        let mut engine = RuleEngine::new();
+        engine.play_card(&mut state, play(222)).unwrap();
+        assert_eq!(
+            engine.play_card(&mut state, play(223)),
+            Err(RuleError::SpellsPerTurnLimitReached { limit: 1 })
+        );
+        engine.play_card(&mut state, play(224)).unwrap();
+        engine.play_card(&mut state, play(225)).unwrap();
+        assert!(engine
+            .legal_actions(&state, 0)
+            .iter()
+            .all(|action| !matches!(action, GameAction::PlayCard { .. })));
+
+        state.players[0].hand.push(unit(226));
+        assert_eq!(
+            engine.play_card(&mut state, play(226)),
+            Err(RuleError::CardsPerTurnLimitReached { limit: 3 })
+        );
+        engine.end_turn(&mut state).unwrap();
+        assert_eq!(state.players[0].spells_played_this_turn, 0);
+    }
+
+    #[test]
+    fn keywords_gate_attacks_and_silence_removes_them() {
+        let mut engine = RuleEngine::new();
+
+        let mut raider = Card::new(230, "Raider", 1, 3, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let guard = Card::new(231, "Guard", 1, 1, 5, CardType::Unit, Vec::new())
+            .with_keywords([Keyword::Taunt]);
+        let scout = Card::new(232, "Scout", 1, 1, 1, CardType::Unit, Vec::new());
+        let mut player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        player_one.health = 20;
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![guard, scout], Vec::new());
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let attack_at = |defender_card| AttackAction {
+            attacker_owner: 0,
+            attacker_id: 230,
+            defender_owner: 1,
+            defender_card,
+        };
+        assert_eq!(
+            engine.attack(&mut state, attack_at(None)),
+            Err(RuleError::MustAttackTaunt)
+        );
+        assert_eq!(
+            engine.attack(&mut state, attack_at(Some(232))),
+            Err(RuleError::MustAttackTaunt)
+        );
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0)
+            .with_source_card(230)
+            .with_target_card(1, 231);
+        EffectKind::GrantKeyword {
+            keyword: Keyword::Lifesteal,
+            target: EffectTarget::SourceCard,
+            duration: EffectDuration::Permanent,
+        }
+        .apply(&ctx, &mut state);
+        EffectKind::Silence {
+            target: EffectTarget::ContextTarget,
+        }
+        .apply(&ctx, &mut state);
+
+        engine
+            .attack(&mut state, attack_at(None))
+            .expect("silenced guard no longer taunts");
+        assert_eq!(state.get_player(1).unwrap().health, 27);
+        assert_eq!(
+            state.get_player(0).unwrap().health,
+            23,
+            "lifesteal heals the owner"
+        );
+    }
+
+    #[test]
+    fn summoning_sickness_is_waived_by_charge_and_rush_against_units() {
+        let mut engine = RuleEngine::new();
+        let hand = vec![
+            Card::new(240, "Recruit", 0, 2, 2, CardType::Unit, Vec::new()),
+            Card::new(241, "Charger", 0, 2, 2, CardType::Unit, Vec::new())
+                .with_keywords([Keyword::Charge]),
+            Card::new(242, "Rusher", 0, 2, 2, CardType::Unit, Vec::new())
+                .with_keywords([Keyword::Rush]),
+        ];
+        let mut target = Card::new(243, "Target", 1, 0, 9, CardType::Unit, Vec::new());
+        target.refresh();
+        let player_one = Player::new(0, 30, 0, 0, hand, Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![target], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        for card_id in 240..243 {
+            engine
+                .play_card(
+                    &mut state,
+                    PlayCardAction {
+                        player_id: 0,
+                        card_id,
+                        target_player: None,
+                        target_card: None,
+                        position: None,
+                    },
+                )
+                .expect("free unit");
+        }
+        assert!(state.players[0]
+            .board
+            .iter()
+            .all(|card| card.summoning_sick));
+        engine.advance_phase(&mut state).expect("enter combat");
+
+        let attack = |attacker_id, defender_card| AttackAction {
+            attacker_owner: 0,
+            attacker_id,
+            defender_owner: 1,
+            defender_card,
+        };
+        assert_eq!(
+            engine.attack(&mut state, attack(240, Some(243))),
+            Err(RuleError::SummoningSick { card_id: 240 })
+        );
+        assert_eq!(
+            engine.attack(&mut state, attack(242, None)),
+            Err(RuleError::SummoningSick { card_id: 242 })
+        );
+        engine
+            .attack(&mut state, attack(242, Some(243)))
+            .expect("rush attacks units");
+        engine
+            .attack(&mut state, attack(241, None))
+            .expect("charge attacks heroes");
+        assert_eq!(
+            engine.attack(&mut state, attack(241, None)),
+            Err(RuleError::UnitExhausted { card_id: 241 })
+        );
+
+        state.ready_player(0);
+        assert!(state.players[0]
+            .board
+            .iter()
+            .all(|card| card.ready_to_attack(true)));
+    }
+
+    #[test]
+    fn elusive_units_cannot_be_targeted_but_can_be_attacked() {
+        let mut engine = RuleEngine::new();
+        let zap = CardEffect::direct_damage(
+            9044,
+            "Deal 1 damage",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::ContextTarget,
+        );
+        let smite = CardEffect::direct_damage(
+            9045,
+            "Deal 2 damage to the strongest enemy",
+            EffectTrigger::OnPlay,
+            0,
+            2,
+            EffectTarget::StrongestEnemyUnit,
+        );
+        let hand = vec![
+            Card::new(236, "Zap", 0, 0, 0, CardType::Spell, vec![zap]),
+            Card::new(237, "Smite", 0, 0, 0, CardType::Spell, vec![smite]),
+        ];
+        let mut raider = Card::new(238, "Raider", 1, 2, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let sprite = Card::new(239, "Sprite", 1, 5, 5, CardType::Unit, Vec::new())
+            .with_keywords([Keyword::Elusive]);
+        let scout = Card::new(240, "Scout", 1, 1, 4, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 0, hand, vec![raider], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![sprite, scout], Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = |card_id, target_card| PlayCardAction {
+            player_id: 0,
+            card_id,
+            target_player: Some(1),
+            target_card,
+            position: None,
+        };
+
+        assert_eq!(
+            engine.play_card(&mut state, play(236, Some(239))),
+            Err(RuleError::TargetElusive { card_id: 239 })
+        );
+        assert!(!engine
+            .legal_actions(&state, 0)
+            .iter()
+            .any(|action| matches!(
+                action,
+                GameAction::PlayCard { action } if action.target_card == Some(239)
+            )));
+
+        engine
+            .play_card(&mut state, play(237, None))
+            .expect("untargeted spell resolves");
+        let health = |state: &GameState, card_id| {
+            state.players[1]
+                .find_card_on_board(card_id)
+                .map(|card| card.health)
+        };
+        assert_eq!(health(&state, 239), Some(5));
+        assert_eq!(health(&state, 240), Some(2));
+
+        state.phase = GamePhase::Combat;
+        let attack = AttackAction {
+            attacker_owner: 0,
+            attacker_id: 238,
+            defender_owner: 1,
+            defender_card: Some(239),
+        };
+        engine
+            .attack(&mut state, attack)
+            .expect("elusive units can still be attacked");
+        assert_eq!(health(&state, 239), Some(3));
+    }
+
+    #[test]
+    fn effects_aimed_at_elusive_units_fizzle_instead_of_hitting_the_hero() {
+        let mut engine = RuleEngine::new();
+        let strike = CardEffect::direct_damage(
+            9046,
+            "Deal 2 damage to the defender",
+            EffectTrigger::OnAttack,
+            0,
+            2,
+            EffectTarget::ContextTarget,
+        );
+        let mut raider = Card::new(241, "Raider", 1, 1, 3, CardType::Unit, vec![strike]);
+        raider.refresh();
+        let sprite = Card::new(242, "Sprite", 1, 1, 5, CardType::Unit, Vec::new())
+            .with_keywords([Keyword::Elusive]);
+        let scout = Card::new(243, "Scout", 1, 1, 4, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let mut player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![sprite, scout], Vec::new());
+        player_two.health = 25;
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+        let health = |state: &GameState, card_id| {
+            state.players[1]
+                .find_card_on_board(card_id)
+                .map(|card| card.health)
+        };
+
+        engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 241,
+                    defender_owner: 1,
+                    defender_card: Some(242),
+                },
+            )
+            .expect("elusive units can still be attacked");
+        assert_eq!(health(&state, 242), Some(4), "only combat damage lands");
+        assert_eq!(state.players[1].health, 25);
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_target_card(1, 243);
+        EffectKind::GrantKeyword {
+            keyword: Keyword::Elusive,
+            target: EffectTarget::ContextTarget,
+            duration: EffectDuration::Permanent,
+        }
+        .apply(&ctx, &mut state);
+        for effect in [
+            EffectKind::DirectDamage {
+                amount: 2,
+                target: EffectTarget::ContextTarget,
+                scaling: None,
+            },
+            EffectKind::Heal {
+                amount: 2,
+                target: EffectTarget::ContextTarget,
+                scaling: None,
+            },
+        ] {
+            assert!(effect.apply(&ctx, &mut state).events.is_empty());
+        }
+        assert_eq!(health(&state, 243), Some(4));
+        assert_eq!(state.players[1].health, 25);
+    }
+
+    #[test]
+    fn legal_defenders_skip_stealthed_units_until_they_attack() {
+        let mut engine = RuleEngine::new();
+
+        let mut raider = Card::new(233, "Raider", 1, 2, 3, CardType::Unit, Vec::new());
+        raider.refresh();
+        let mut lurker = Card::new(234, "Lurker", 1, 1, 4, CardType::Unit, Vec::new())
+            .with_keywords([Keyword::Taunt, Keyword::Stealth]);
+        lurker.refresh();
+        let scout = Card::new(235, "Scout", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![raider], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![lurker, scout], Vec::new());
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        assert_eq!(
+            engine.legal_defenders(&state, 0, 233),
+            vec![
+                AttackTargetOption::Hero { player_id: 1 },
+                AttackTargetOption::Unit {
+                    player_id: 1,
+                    card_id: 235,
+                },
+            ],
+            "a stealthed taunt neither guards nor can be attacked"
+        );
+        assert_eq!(
+            engine.validate(
+                &state,
+                &GameAction::Attack {
+                    action: AttackAction {
+                        attacker_owner: 0,
+                        attacker_id: 233,
+                        defender_owner: 1,
+                        defender_card: Some(234),
+                    },
+                },
+            ),
+            Err(RuleError::TargetStealthed { card_id: 234 })
+        );
+
+        state.end_turn();
+        state.phase = GamePhase::Combat;
+        let events = engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 1,
+                    attacker_id: 234,
+                    defender_owner: 0,
+                    defender_card: None,
+                },
+            )
+            .expect("the lurker attacks from stealth");
+        assert!(events.contains(&GameEvent::KeywordRemoved {
+            player_id: 1,
+            card_id: 234,
+            keyword: Keyword::Stealth,
+        }));
+
+        state.end_turn();
+        state.phase = GamePhase::Combat;
+        state.players[0].board[0].refresh();
+        assert_eq!(
+            engine.legal_defenders(&state, 0, 233),
+            vec![AttackTargetOption::Unit {
+                player_id: 1,
+                card_id: 234,
+            }]
+        );
+        assert!(engine.legal_defenders(&state, 0, 999).is_empty());
+    }
+
+    #[test]
+    fn hero_power_costs_mana_and_is_usable_once_per_turn() {
+        let mut engine = RuleEngine::new();
+        let ping = CardEffect::direct_damage(
+            9042,
+            "Deal 1 damage",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::ContextTarget,
+        );
+        let player_one = Player::new(0, 30, 0, 3, Vec::new(), Vec::new(), Vec::new())
+            .with_hero_power(HeroPower::new("Fireblast", ping));
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let ping_hero = UseHeroPowerAction {
+            player_id: 0,
+            target_player: Some(1),
+            target_card: None,
+        };
+        assert!(engine
+            .legal_actions(&state, 0)
+            .contains(&GameAction::UseHeroPower {
+                action: ping_hero.clone(),
+            }));
+        assert_eq!(
+            engine.use_hero_power(
+                &mut state,
+                UseHeroPowerAction {
+                    target_player: None,
+                    ..ping_hero.clone()
+                },
+            ),
+            Err(RuleError::InvalidTarget)
+        );
+
+        let events = engine
+            .use_hero_power(&mut state, ping_hero.clone())
+            .expect("hero power should resolve");
+        assert!(events.contains(&GameEvent::HeroPowerUsed {
+            player_id: 0,
+            target_id: None,
+        }));
+        assert_eq!(state.get_player(1).unwrap().health, 29);
+        assert_eq!(state.get_player(0).unwrap().mana, 1);
+        assert_eq!(
+            engine.use_hero_power(&mut state, ping_hero.clone()),
+            Err(RuleError::HeroPowerAlreadyUsed { player_id: 0 })
+        );
+
+        state.players[0].ready_board();
+        assert_eq!(
+            engine.use_hero_power(&mut state, ping_hero),
+            Err(RuleError::InsufficientMana {
+                required: 2,
+                available: 1,
+            })
+        );
+        assert!(engine
+            .legal_actions(&state, 0)
+            .iter()
+            .all(|action| !matches!(action, GameAction::UseHeroPower { .. })));
+    }
+
+    #[test]
+    fn turn_limit_ends_the_game_by_tiebreaker() {
+        let build = |tiebreaker, second_health| {
+            let player_one = Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+            let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+            let mut state = GameState::new(vec![player_one, player_two], 0)
+                .with_phase(GamePhase::Main)
+                .with_config(RuleConfig::default().with_turn_limit(Some(2), tiebreaker));
+            state.players[1].health = second_health;
+            state
+        };
+
+        let mut engine = RuleEngine::new();
+        let mut state = build(Tiebreaker::HighestHealth, 25);
+        engine
+            .end_turn(&mut state)
+            .expect("first turn ends normally");
+        assert!(!state.is_finished());
+        let events = engine
+            .end_turn(&mut state)
+            .expect("second turn hits the cap");
+        let reason = VictoryReason::TurnLimitReached { turn_limit: 2 };
+        assert_eq!(
+            state.game_outcome(),
+            Some(GameOutcome::Victory {
+                winner: 0,
+                reason: reason.clone(),
+            })
+        );
+        assert!(events.contains(&GameEvent::GameWon { winner: 0, reason }));
+        assert_eq!(engine.end_turn(&mut state), Err(RuleError::GameFinished));
+
+        let draws = [(Tiebreaker::HighestHealth, 30), (Tiebreaker::Draw, 5)];
+        for (tiebreaker, second_health) in draws {
+            let mut engine = RuleEngine::new();
+            let mut state = build(tiebreaker, second_health);
+            engine.end_turn(&mut state).unwrap();
+            let events = engine.end_turn(&mut state).unwrap();
+            let reason = DrawReason::TurnLimitReached { turn_limit: 2 };
+            assert_eq!(state.draw, Some(reason.clone()));
+            assert!(events.contains(&GameEvent::GameDrawn { reason }));
+            assert_eq!(state.outcome, None);
+        }
+    }
+
+    #[test]
+    fn game_end_emits_summary_with_player_statistics() {
+        let bolt = CardEffect::direct_damage(
+            9043,
+            "Deal 3 damage",
+            EffectTrigger::OnPlay,
+            0,
+            3,
+            EffectTarget::OpponentOfSource,
+        );
+        let hand = vec![Card::new(218, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt])];
+        let squire = Card::new(219, "Squire", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 2, hand, Vec::new(), vec![squire]);
+        let player_two = Player::new(1, 2, 1, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = PlayCardAction {
+            player_id: 0,
+            card_id: 218,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+
+        let mut engine = RuleEngine::new();
+        let events = engine.play_card(&mut state, play).expect("bolt is lethal");
+        let summary = events
+            .iter()
+            .find(|event| matches!(event, GameEvent::GameSummary { .. }))
+            .expect("finished games carry a summary");
+        let GameEvent::GameSummary { turns, players } = summary else {
+            unreachable!();
+        };
+        assert_eq!(*turns, state.turn);
+        assert_eq!(players[0].stats.damage_dealt, 3);
+        assert_eq!(players[0].stats.cards_played, 1);
+        assert_eq!((players[0].mana, players[0].deck_size), (1, 1));
+        assert_eq!((players[1].health, players[1].armor), (0, 0));
+        assert_eq!(players[1].stats.damage_dealt, 0);
+
+        let log: Vec<&GameEvent> = state.events().collect();
+        let logged = log
+            .iter()
+            .position(|event| matches!(event, GameEvent::GameSummary { .. }))
+            .expect("summary is logged");
+        assert!(matches!(
+            log[logged - 1],
+            GameEvent::GameWon { winner: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn alternative_victories_from_config_and_card_effects() {
+        let swarm = AlternativeVictory::new(
+            "swarm",
+            EffectCondition::BoardCountAtLeast {
+                target: EffectTarget::SourcePlayer,
+                min: 2,
+            },
+        );
+        let mut unit = Card::new(1, "Recruit", 0, 1, 1, CardType::Unit, Vec::new());
+        unit.refresh();
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), vec![unit], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_phase(GamePhase::Main)
+            .with_config(RuleConfig::default().with_alternative_victory(swarm));
+        let mut engine = RuleEngine::new();
+        assert_eq!(engine.end_turn(&mut state).map(|_| ()), Ok(()));
+        assert!(!state.is_finished());
+
+        let mut second = Card::new(2, "Recruit", 0, 1, 1, CardType::Unit, Vec::new());
+        second.refresh();
+        state.players[0].board.push(second);
+        let reason = VictoryReason::Alternative { id: "swarm".into() };
+        assert_eq!(
+            RuleEngine::check_victory(&mut state),
+            Some(VictoryState {
+                winner: 0,
+                reason: reason.clone(),
+            })
+        );
+
+        let tome_effect = CardEffect::new(
+            1,
+            "登记牌库耗尽胜利",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::AddAlternativeVictory {
+                victory: AlternativeVictory::new(
+                    "empty_deck",
+                    EffectCondition::DeckSizeAtMost {
+                        target: EffectTarget::SourcePlayer,
+                        max: 0,
+                    },
+                ),
+            },
+        );
+        let tome = Card::new(3, "Empty Tome", 0, 0, 0, CardType::Spell, vec![tome_effect]);
+        let filler = Card::new(4, "Filler", 9, 0, 0, CardType::Spell, Vec::new());
+        let player_one = Player::new(0, 30, 0, 0, vec![tome], Vec::new(), vec![filler]);
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = PlayCardAction {
+            player_id: 0,
+            card_id: 3,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+        engine.play_card(&mut state, play).unwrap();
+        assert!(state.players[0].has_alternative_victory("empty_deck"));
+        assert!(!state.is_finished());
+
+        state.players[0].deck.clear();
+        let reason = VictoryReason::Alternative {
+            id: "empty_deck".into(),
+        };
+        assert_eq!(
+            RuleEngine::check_victory(&mut state),
+            Some(VictoryState { winner: 0, reason })
+        );
+    }
+
+    #[test]
+    fn second_player_receives_bonus_card_after_mulligan() {
+        let mut engine = RuleEngine::new();
+        let opening = |base: CardId| -> Vec<Card> {
+            (base..base + 3)
+                .map(|id| Card::new(id, "Recruit", 1, 1, 1, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let deck = |base: CardId| -> Vec<Card> {
+            (base..base + 5)
+                .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let player_one = Player::new(0, 30, 0, 0, opening(1), Vec::new(), deck(10));
+        let player_two = Player::new(1, 30, 0, 0, opening(20), Vec::new(), deck(30));
+        let coin = Card::new(0, "The Coin", 0, 0, 0, CardType::Spell, Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_config(RuleConfig::default().with_second_player_bonus(Some(coin)));
+        state.reset_for_mulligan();
+
+        engine
+            .mulligan(
+                &mut state,
+                MulliganAction {
+                    player_id: 0,
+                    replacements: vec![1, 2],
+                },
+            )
+            .expect("first mulligan");
+        assert_eq!(
+            state.players[1].hand.len(),
+            3,
+            "bonus waits for both mulligans"
+        );
+
+        let events = engine
+            .mulligan(
+                &mut state,
+                MulliganAction {
+                    player_id: 1,
+                    replacements: Vec::new(),
+                },
+            )
+            .expect("second mulligan");
+        assert_eq!(state.players[0].hand.len(), 3);
+        assert_eq!(state.players[1].hand.len(), 4);
+        let coin = state.players[1].hand.last().expect("bonus card");
+        assert_eq!(coin.name, "The Coin");
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::CardAddedToHand { player_id: 1, card_id } if *card_id == coin.id
+        )));
+        let replaced: Vec<CardId> = state.players[0].hand.iter().map(|card| card.id).collect();
+        assert!(!replaced.contains(&1) && !replaced.contains(&2));
+    }
+
+    #[test]
+    fn auto_mulligan_tosses_expensive_cards_and_duplicates() {
+        let card =
+            |id, name: &str, cost| Card::new(id, name, cost, 1, 1, CardType::Unit, Vec::new());
+        let hand = vec![
+            card(1, "Recruit", 1),
+            card(2, "Recruit", 1),
+            card(3, "Giant", 8),
+            card(4, "Knight", 3),
+            card(5, "Dragon", 6),
+        ];
+        let deck = (10..16).map(|id| card(id, "Reserve", 2)).collect();
+        let player_one = Player::new(0, 30, 0, 0, hand, Vec::new(), deck);
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0);
+        state.reset_for_mulligan();
+
+        let policy = MulliganPolicy::default();
+        assert_eq!(policy.replacements(&state, 0), vec![3, 5, 2]);
+        state.config.mulligan_limit = Some(2);
+        assert_eq!(policy.replacements(&state, 0), vec![3, 5]);
+
+        let mut engine = RuleEngine::new();
+        engine
+            .auto_mulligan(&mut state, 0, policy)
+            .expect("auto mulligan applies");
+        assert!(state.mulligan_completed(0));
+        let hand: Vec<CardId> = state.players[0].hand.iter().map(|card| card.id).collect();
+        assert_eq!(hand.len(), 5);
+        assert!(!hand.contains(&3) && !hand.contains(&5));
+    }
+
+    #[test]
+    fn second_player_draws_extra_card_and_coin_grants_mana() {
+        let mut engine = RuleEngine::new();
+        let deck = |base: CardId| -> Vec<Card> {
+            (base..base + 6)
+                .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), deck(1));
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), deck(10));
+        let config = RuleConfig::default()
+            .with_coin()
+            .with_second_player_extra_cards(1);
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_config(config);
+        state.reset_for_mulligan();
+        state.draw_initial_hand(3);
+        assert_eq!(state.players[0].hand.len(), 3);
+        assert_eq!(state.players[1].hand.len(), 4);
+
+        for player_id in [0, 1] {
+            engine
+                .mulligan(
+                    &mut state,
+                    MulliganAction {
+                        player_id,
+                        replacements: Vec::new(),
+                    },
+                )
+                .expect("keep opening hand");
+        }
+        let coin = state.players[1].hand.last().expect("coin").clone();
+        assert_eq!(coin.name, Card::coin().name);
+
+        state.current_player = 1;
+        state.phase = GamePhase::Main;
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 1,
+                    card_id: coin.id,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("coin is free to play");
+        assert_eq!(state.players[1].mana, 1);
+        assert_eq!(state.players[1].max_mana, 0);
+    }
+
+    #[test]
+    fn new_game_shuffles_flips_and_deals_opening_hands() {
+        let deck = |base: CardId| -> Vec<Card> {
+            (base..base + 10)
+                .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let config = RuleConfig::default().with_starting_health(20);
+        let state = GameState::new_game(deck(1), deck(20), config.clone(), 7);
+        let again = GameState::new_game(deck(1), deck(20), config, 7);
+        assert_eq!(state, again, "same seed yields the same setup");
+
+        let first = state.current_player;
+        let second = 1 - first;
+        assert_eq!(state.phase, GamePhase::Mulligan);
+        assert_eq!(state.players[first as usize].hand.len(), 3);
+        assert_eq!(state.players[second as usize].hand.len(), 4);
+        assert_eq!(
+            state.players[0].deck.len() + state.players[0].hand.len(),
+            10
+        );
+        assert!(state.players.iter().all(|player| player.health == 20));
+        assert!(state.events().any(|event| *event
+            == GameEvent::CoinFlipped {
+                first_player: first
+            }));
+        let shuffled = state
+            .events()
+            .filter(|event| matches!(event, GameEvent::DeckShuffled { .. }))
+            .count();
+        assert_eq!(shuffled, 2);
+        assert!(state.integrity_check().is_ok());
+
+        let firsts: Vec<PlayerId> = (0..16)
+            .map(|seed| GameState::new_game(deck(1), deck(20), RuleConfig::default(), seed))
+            .map(|state| state.current_player)
+            .collect();
+        assert!(
+            firsts.contains(&0) && firsts.contains(&1),
+            "coin flip varies by seed"
+        );
+    }
+
+    #[test]
+    fn every_seat_after_the_first_draws_the_extra_opening_card() {
+        let deck = |base: CardId| -> Vec<Card> {
+            (base..base + 10)
+                .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let mut state = GameState::new_game(deck(1), deck(20), RuleConfig::default(), 7);
+        let setup = state.setup.as_mut().expect("created from decks");
+        setup.decks.extend([deck(40), deck(60)]);
+        setup.first_player = 0;
+
+        let table = state.rematch(3).expect("created from decks");
+        assert_eq!(table.players.len(), 4);
+        assert_eq!(table.current_player, 1);
+        let hands: Vec<usize> = table.players.iter().map(|p| p.hand.len()).collect();
+        assert_eq!(hands, vec![4, 3, 4, 4]);
+    }
+
+    #[test]
+    fn state_hash_tracks_game_state_but_not_the_event_log() {
+        let mut engine = RuleEngine::new();
+        let mut state = GameState::sample().with_seed(9);
+        let mut replica: GameState =
+            serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(state.hash(), replica.hash());
+
+        engine.end_turn(&mut state).expect("end turn");
+        assert_ne!(state.hash(), replica.hash());
+        RuleEngine::new()
+            .end_turn(&mut replica)
+            .expect("replica ends turn");
+        assert_eq!(state.hash(), replica.hash());
+
+        replica.event_log.clear();
+        assert_eq!(state.hash(), replica.hash());
+        replica.card_pool.register(CardDefinition::new(
+            1,
+            "Wisp",
+            0,
+            1,
+            1,
+            CardType::Unit,
+            Vec::new(),
+        ));
+        assert_eq!(state.hash(), replica.hash());
+        replica.players[0].health -= 1;
+        assert_ne!(state.hash(), replica.hash());
+    }
+
+    #[test]
+    fn event_log_is_sequenced_and_pruned_to_the_configured_cap() {
+        let mut engine = RuleEngine::new();
+        let mut state = GameState::sample()
+            .with_config(RuleConfig::default().with_max_event_log(Some(4)))
+            .with_phase(GamePhase::Main);
+        for _ in 0..4 {
+            engine.end_turn(&mut state).expect("end turn");
+        }
+
+        assert_eq!(state.event_log.len(), 4);
+        let seqs: Vec<u64> = state.event_log.iter().map(|entry| entry.seq).collect();
+        assert!(seqs.windows(2).all(|pair| pair[1] == pair[0] + 1));
+        assert_eq!(state.next_event_seq, seqs[3] + 1);
+        assert!(seqs[0] > 1, "older entries were pruned");
+        let last = state.event_log.last().unwrap();
+        assert!(
+            last.turn > 1 && last.turn <= state.turn,
+            "entries carry the turn"
+        );
+
+        assert_eq!(state.events_since(seqs[1]).len(), 2);
+        assert_eq!(state.events_since(0).len(), 4);
+        assert!(state.events_since(seqs[3]).is_empty());
+
+        let json = serde_json::to_value(last).unwrap();
+        assert_eq!(json["seq"], seqs[3]);
+        assert!(json["type"].is_string(), "event fields are flattened");
+
+        let mut legacy: GameState = serde_json::from_str(
+            r#"{"players":[],"current_player":0,"turn":1,"phase":"Main",
+                "event_log":[{"type":"TurnEnded","player_id":0},{"type":"TurnEnded","player_id":1}]}"#,
+        )
+        .unwrap();
+        legacy.reconcile_after_load();
+        let seqs: Vec<u64> = legacy.event_log.iter().map(|entry| entry.seq).collect();
+        assert_eq!(seqs, vec![1, 2]);
+        assert_eq!(legacy.next_event_seq, 3);
+    }
+
+    #[test]
+    fn pending_discard_blocks_actions_until_resolved_as_a_choice() {
+        let mut engine = RuleEngine::new();
+        let unit = |id: CardId| Card::new(id, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![unit(1)], Vec::new(), vec![unit(2)]);
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_config(RuleConfig::default().with_max_hand_size(1))
+            .with_phase(GamePhase::Main);
+
+        state.draw_card(0);
+        let choice_id = state.pending_choices[0].id;
+        assert_eq!(state.choice_option_count(&state.pending_choices[0]), 2);
+        let discards: Vec<CardId> = engine
+            .legal_actions(&state, 0)
+            .into_iter()
+            .filter_map(|action| match action {
+                GameAction::DiscardCard { action } => Some(action.discard_card_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(discards, vec![1, 2]);
+        assert_eq!(
+            engine.end_turn(&mut state),
+            Err(RuleError::ChoicePending {
+                player_id: 0,
+                choice_id
+            })
+        );
+
+        engine
+            .resolve_choice(
+                &mut state,
+                ResolveChoiceAction {
+                    player_id: 0,
+                    choice_id,
+                    option: 0,
+                },
+            )
+            .expect("discarding the old card resolves the choice");
+        let hand: Vec<CardId> = state.players[0].hand.iter().map(|card| card.id).collect();
+        assert_eq!(hand, vec![2]);
+        assert!(engine.end_turn(&mut state).is_ok());
+    }
+
+    #[test]
+    fn units_enter_at_chosen_position_and_buff_their_neighbours() {
+        let mut engine = RuleEngine::new();
+        let unit = |id: CardId| Card::new(id, "Squire", 1, 1, 1, CardType::Unit, Vec::new());
+        let banner = CardEffect::new(
+            9030,
+            "Give adjacent units +1 attack",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::ModifyStats {
+                attack: 1,
+                health: 0,
+                target: EffectTarget::AdjacentToSource,
+                duration: EffectDuration::Permanent,
+            },
+        );
+        let bannerman = Card::new(4, "Bannerman", 1, 1, 1, CardType::Unit, vec![banner]);
+        let board = vec![unit(1), unit(2), unit(3)];
+        let player_one = Player::new(0, 30, 0, 1, vec![bannerman], board, Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        let play = |position| PlayCardAction {
+            player_id: 0,
+            card_id: 4,
+            target_player: None,
+            target_card: None,
+            position: Some(position),
+        };
+
+        assert_eq!(
+            engine.validate(&state, &GameAction::PlayCard { action: play(4) }),
+            Err(RuleError::InvalidBoardPosition {
+                position: 4,
+                board_size: 3
+            })
+        );
+        engine
+            .play_card(&mut state, play(1))
+            .expect("insert between units");
+
+        let board: Vec<(CardId, i16)> = state.players[0]
+            .board
+            .iter()
+            .map(|card| (card.id, card.attack))
+            .collect();
+        assert_eq!(board, vec![(1, 2), (4, 1), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn target_filters_reject_targets_and_narrow_area_effects() {
+        let mut engine = RuleEngine::new();
+
+        let finisher = CardEffect::direct_damage(
+            9015,
+            "Deal 4 damage to a damaged unit",
+            EffectTrigger::OnPlay,
+            0,
+            4,
+            EffectTarget::ContextTarget,
+        )
+        .with_target_filter(TargetFilter {
+            damaged_only: true,
+            ..TargetFilter::default()
+        });
+        let execute = Card::new(200, "Execute", 1, 0, 0, CardType::Spell, vec![finisher]);
+        let cull = CardEffect::direct_damage(
+            9016,
+            "Deal 1 damage to all enemy units costing 2 or less",
+            EffectTrigger::OnPlay,
+            0,
+            1,
+            EffectTarget::AllEnemyUnits {
+                filter: TargetFilter {
+                    max_cost: Some(2),
+                    ..TargetFilter::default()
+                },
+            },
+        );
+        let sweep = Card::new(201, "Cull", 1, 0, 0, CardType::Spell, vec![cull]);
+
+        let player_one = Player::new(0, 30, 0, 2, vec![execute, sweep], Vec::new(), Vec::new());
+        let enemy_board = vec![
+            Card::new(202, "Cheap", 1, 1, 3, CardType::Unit, Vec::new()),
+            Card::new(203, "Pricey", 5, 1, 3, CardType::Unit, Vec::new()),
+        ];
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), enemy_board, Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let execute_on = |card_id| PlayCardAction {
+            player_id: 0,
+            card_id: 200,
+            target_player: Some(1),
+            target_card: Some(card_id),
+            position: None,
+        };
+        let err = engine
+            .play_card(&mut state, execute_on(202))
+            .expect_err("undamaged target should be rejected");
+        assert_eq!(
+            err,
+            RuleError::TargetFilterFailed {
+                filter: FilterFailure::NotDamaged
+            }
+        );
+        assert_eq!(err.code(), "target_filter_failed");
+        assert_eq!(
+            serde_json::to_value(&RuleError::InvalidTarget).unwrap(),
+            serde_json::json!({"type": "InvalidTarget"}),
+            "a missing target keeps its original shape"
+        );
+        assert_eq!(
+            state.get_player(0).unwrap().hand.len(),
+            2,
+            "card stays in hand"
+        );
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 201,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("area spell should resolve");
+        let health_of = |state: &GameState, id| {
+            state
+                .get_player(1)
+                .unwrap()
+                .board
+                .iter()
+                .find(|card| card.id == id)
+                .unwrap()
+                .health
+        };
+        assert_eq!((health_of(&state, 202), health_of(&state, 203)), (2, 3));
+
+        engine
+            .play_card(&mut state, execute_on(202))
+            .expect("damaged target passes the filter");
+        assert!(state
+            .get_player(1)
+            .unwrap()
+            .board
+            .iter()
+            .all(|card| card.id != 202));
+    }
+
+    #[test]
+    fn choose_one_waits_for_resolve_choice() {
+        let mut engine = RuleEngine::new();
+
+        let modes = CardEffect::new(
+            9017,
+            "Choose one: deal 2 damage to the enemy hero; or gain 3 armor",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::ChooseOne {
+                options: vec![
+                    ChoiceOption::new(
+                        "Deal 2 damage",
+                        EffectKind::DirectDamage {
+                            amount: 2,
+                            target: EffectTarget::OpponentOfSource,
+                            scaling: None,
+                        },
+                    ),
+                    ChoiceOption::new(
+                        "Gain 3 armor",
+                        EffectKind::GainArmor {
+                            amount: 3,
+                            target: EffectTarget::SourcePlayer,
+                        },
+                    ),
+                ],
+            },
+        );
+        let spell = Card::new(210, "Wrath", 1, 0, 0, CardType::Spell, vec![modes]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 210,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("choose-one spell should be playable");
+        let choice_id = state.pending_choices[0].id;
+        assert_eq!(
+            state.get_player(0).unwrap().armor,
+            0,
+            "nothing resolves yet"
+        );
+
+        let err = engine
+            .resolve_choice(
+                &mut state,
+                ResolveChoiceAction {
+                    player_id: 0,
+                    choice_id,
+                    option: 2,
+                },
+            )
+            .expect_err("out-of-range option");
+        assert_eq!(
+            err,
+            RuleError::InvalidChoiceOption {
+                choice_id,
+                option: 2
+            }
+        );
+
+        engine
+            .resolve_choice(
+                &mut state,
+                ResolveChoiceAction {
+                    player_id: 0,
+                    choice_id,
+                    option: 1,
+                },
+            )
+            .expect("valid option resolves");
+        assert!(state.pending_choices.is_empty());
+        assert_eq!(state.get_player(0).unwrap().armor, 3);
+        assert_eq!(state.get_player(1).unwrap().health, 30);
+    }
+
+    fn discover_state(pool: Vec<CardDefinition>) -> GameState {
+        let discover = CardEffect::new(
+            9018,
+            "Discover a unit",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::Discover {
+                filter: CardFilter {
+                    card_type: Some(CardType::Unit),
+                    ..CardFilter::default()
+                },
+            },
+        );
+        let spell = Card::new(220, "Scout", 1, 0, 0, CardType::Spell, vec![discover]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_phase(GamePhase::Main)
+            .with_seed(11);
+        state.card_pool = CardRegistry::from(pool);
+        state
+    }
+
+    fn play_discover(engine: &mut RuleEngine, state: &mut GameState) {
+        engine
+            .play_card(
+                state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 220,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("discover spell should be playable");
+    }
+
+    #[test]
+    fn discovered_card_joins_hand_with_fresh_id() {
+        let mut engine = RuleEngine::new();
+        let mut state = discover_state(vec![
+            CardDefinition::new(1, "Wisp", 0, 1, 1, CardType::Unit, Vec::new()),
+            CardDefinition::new(2, "Ogre", 6, 6, 7, CardType::Unit, Vec::new()),
+            CardDefinition::new(3, "Bolt", 1, 0, 0, CardType::Spell, Vec::new()),
+            CardDefinition::new(4, "Imp", 1, 1, 1, CardType::Unit, Vec::new()).uncollectible(),
+        ]);
+
+        play_discover(&mut engine, &mut state);
+        let pending = &state.pending_choices[0];
+        assert_eq!(
+            pending.option_count(),
+            Some(2),
+            "only two collectible units match"
+        );
+        let choice_id = pending.id;
+
+        engine
+            .resolve_choice(
+                &mut state,
+                ResolveChoiceAction {
+                    player_id: 0,
+                    choice_id,
+                    option: 0,
+                },
+            )
+            .expect("discover choice resolves");
+        let hand = &state.get_player(0).unwrap().hand;
+        assert_eq!(hand.len(), 1);
+        assert!(["Wisp", "Ogre"].contains(&hand[0].name.as_str()));
+        assert_eq!(state.next_card_instance_id, hand[0].id + 1);
+        assert!(state.integrity_check().is_ok());
+
+        let mut empty = discover_state(Vec::new());
+        let next_id = empty.next_card_instance_id;
+        play_discover(&mut engine, &mut empty);
+        assert!(empty.pending_choices.is_empty(), "nothing to discover");
+        assert_eq!(
+            empty.next_card_instance_id, next_id,
+            "an empty discover allocates no ids"
+        );
+    }
+
+    #[test]
+    fn discover_offers_registry_cards_and_replays_under_the_same_seed() {
+        let pool: Vec<CardDefinition> = (1..=8)
+            .map(|def_id| {
+                let name = format!("Unit {def_id}");
+                CardDefinition::new(def_id, name, 2, 2, 2, CardType::Unit, Vec::new())
+            })
+            .collect();
+        let offered = |state: &GameState| match &state.pending_choices[0].kind {
+            ChoiceKind::Discover { cards } => cards.clone(),
+            other => panic!("expected a discover choice, got {other:?}"),
+        };
+
+        let mut state = discover_state(pool.clone());
+        play_discover(&mut RuleEngine::new(), &mut state);
+        let cards = offered(&state);
+        assert_eq!(cards.len(), 3);
+        for card in &cards {
+            let def_id = card.def_id.expect("generated from a definition");
+            assert_eq!(card, &state.card_pool.instantiate(def_id, card.id).unwrap());
+        }
+        let mut def_ids: Vec<_> = cards.iter().filter_map(|card| card.def_id).collect();
+        def_ids.sort_unstable();
+        def_ids.dedup();
+        assert_eq!(def_ids.len(), 3, "candidates are distinct");
+        let mut ids: Vec<_> = cards.iter().map(|card| card.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 3, "each candidate gets a fresh id");
+        assert!(ids.iter().all(|id| *id != 220));
+
+        let mut replayed = discover_state(pool);
+        play_discover(&mut RuleEngine::new(), &mut replayed);
+        assert_eq!(offered(&replayed), cards);
+        assert_eq!(replayed.hash(), state.hash());
+    }
+
+    #[test]
+    fn overload_locks_crystals_on_the_owners_next_turn() {
+        let mut engine = RuleEngine::new();
+        let overload = CardEffect::new(
+            1,
+            "Overload: (2)",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::Overload { amount: 2 },
+        );
+        let storm = Card::new(1, "Storm", 1, 0, 0, CardType::Spell, vec![overload]);
+        let player_one = Player::new(0, 30, 0, 3, vec![storm], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 1,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("play");
+        assert!(events.contains(&GameEvent::OverloadIncurred {
+            player_id: 0,
+            amount: 2
+        }));
+        assert_eq!(state.players[0].overloaded_mana, 2);
+        assert_eq!(state.players[0].mana, 2, "overload does not lock this turn");
+
+        engine.end_turn(&mut state).expect("end turn");
+        let events = engine.end_turn(&mut state).expect("end turn");
+        assert!(events.contains(&GameEvent::ManaOverloaded {
+            player_id: 0,
+            locked: 2
+        }));
+        let player = &state.players[0];
+        assert_eq!(
+            (player.mana, player.max_mana, player.overloaded_mana),
+            (2, 4, 0)
+        );
+
+        engine.end_turn(&mut state).expect("end turn");
+        engine.end_turn(&mut state).expect("end turn");
+        assert_eq!(state.players[0].mana, 5, "the lock lasts a single turn");
+    }
+
+    #[test]
+    fn played_units_are_summoned_at_their_position_and_fire_summon_listeners() {
+        let mut engine = RuleEngine::new();
+        let herald = CardEffect::new(
+            9101,
+            "Whenever you summon a unit, gain 1 armor",
+            EffectTrigger::OnSummon,
+            0,
+            EffectKind::GainArmor {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let listener = Card::new(140, "Herald", 1, 1, 1, CardType::Unit, vec![herald]);
+        let flank = Card::new(141, "Flank", 1, 1, 1, CardType::Unit, Vec::new());
+        let recruit = Card::new(142, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let board = vec![listener, flank];
+        let player_one = Player::new(0, 30, 0, 3, vec![recruit], board, Vec::new());
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 142,
+                    target_player: None,
+                    target_card: None,
+                    position: Some(1),
+                },
+            )
+            .expect("recruit should be summoned");
+
+        let summoned = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::CardSummoned {
+                        player_id: 0,
+                        card_id: 142
+                    }
+                )
+            })
+            .count();
+        assert_eq!(summoned, 1);
+        let player = state.get_player(0).unwrap();
+        let order: Vec<CardId> = player.board.iter().map(|card| card.id).collect();
+        assert_eq!(order, vec![140, 142, 141]);
+        assert_eq!(player.armor, 1, "the herald reacts to the summon");
+    }
+
+    #[test]
+    fn teams_share_victory_and_cannot_attack_each_other() {
+        let mut engine = RuleEngine::new();
+        let unit = |id| {
+            let mut card = Card::new(id, "Footman", 1, 2, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let players = (0..4)
+            .map(|id| {
+                let board = vec![unit(300 + id as CardId)];
+                Player::new(id, 30, 0, 1, Vec::new(), board, Vec::new()).with_team(id % 2)
+            })
+            .collect();
+        let mut state = GameState::new(players, 0).with_phase(GamePhase::Combat);
+        assert!(state.are_allies(0, 2) && !state.are_allies(0, 1));
+
+        let attack = |defender_owner| AttackAction {
+            attacker_owner: 0,
+            attacker_id: 300,
+            defender_owner,
+            defender_card: None,
+        };
+        assert_eq!(
+            engine.attack(&mut state, attack(2)),
+            Err(RuleError::CannotAttackAlly { player_id: 2 })
+        );
+        let defenders: Vec<PlayerId> = engine
+            .legal_actions(&state, 0)
+            .iter()
+            .filter_map(|action| match action {
+                GameAction::Attack { action } => Some(action.defender_owner),
+                _ => None,
+            })
+            .collect();
+        assert!(defenders.contains(&1) && defenders.contains(&3));
+        assert!(!defenders.contains(&2));
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        EffectKind::GainArmor {
+            amount: 3,
+            target: EffectTarget::AllAlliedHeroes,
+        }
+        .apply(&ctx, &mut state);
+        let armor: Vec<u8> = state.players.iter().map(|player| player.armor).collect();
+        assert_eq!(armor, vec![3, 0, 3, 0]);
+        let enemy_units = EffectTarget::AllEnemyUnits {
+            filter: TargetFilter::default(),
+        }
+        .candidate_cards(&ctx, &state);
+        assert_eq!(enemy_units, vec![(1, 301), (3, 303)]);
+
+        state.damage_player(0, None, 1, 30);
+        assert!(!state.is_finished(), "one enemy still stands");
+        assert_eq!(state.evaluate_victory(), None);
+        engine.end_turn(&mut state).expect("turn passes");
+        assert_eq!(state.current_player, 2, "defeated players are skipped");
+
+        state.players[3].health = 0;
+        let victory = state.evaluate_victory().expect("whole team defeated");
+        assert!(state.are_allies(victory.winner, 0));
+    }
+
+    #[test]
+    fn heroes_set_class_power_and_armor() {
+        let armor_up = CardEffect::new(
+            9043,
+            "Gain 2 armor",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let warrior = Hero::new("warrior")
+            .with_hero_power(HeroPower::new("Armor Up", armor_up))
+            .with_portrait("garrosh")
+            .with_starting_armor(5);
+        let player_one =
+            Player::new(0, 30, 0, 3, Vec::new(), Vec::new(), Vec::new()).with_hero(warrior.clone());
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), Vec::new(), Vec::new())
+            .with_hero(Hero::new("mage"));
+        assert_eq!(player_one.armor, 5);
+        assert_eq!(player_one.class(), Some("warrior"));
+        let hero_power = player_one.hero_power.as_ref().expect("hero power assigned");
+        assert_eq!(hero_power.name, "Armor Up");
+        let state = GameState::new(vec![player_one, player_two], 0);
+
+        let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0);
+        let class_is = |target, class: &str| EffectCondition::HeroClass {
+            target,
+            class: class.to_string(),
+        };
+        assert!(class_is(EffectTarget::SourcePlayer, "warrior").is_satisfied(&ctx, &state));
+        assert!(class_is(EffectTarget::OpponentOfSource, "mage").is_satisfied(&ctx, &state));
+        assert!(!class_is(EffectTarget::OpponentOfSource, "warrior").is_satisfied(&ctx, &state));
+
+        let card = |def_id, class: Option<&str>| {
+            let definition =
+                CardDefinition::new(def_id, "Card", 1, 1, 1, CardType::Unit, Vec::new());
+            match class {
+                Some(class) => definition.with_class(class),
+                None => definition,
+            }
+        };
+        let format = DeckFormat::default().with_deck_size(0, 30);
+        let deck = vec![card(1, None), card(2, Some("warrior"))];
+        assert_eq!(validate_hero_deck(&deck, &format, &warrior), Ok(()));
+        let deck = vec![card(1, None), card(3, Some("mage")), card(3, Some("mage"))];
+        assert_eq!(
+            validate_hero_deck(&deck, &format, &warrior),
+            Err(vec![DeckError::WrongClass {
+                def_id: 3,
+                class: "mage".to_string(),
+                hero_class: "warrior".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn match_statistics_accumulate_as_actions_resolve() {
+        let mut engine = RuleEngine::new();
+        let mut ogre = Card::new(250, "Ogre", 4, 4, 3, CardType::Unit, Vec::new());
+        ogre.refresh();
+        let wolf = Card::new(251, "Wolf", 2, 2, 2, CardType::Unit, Vec::new());
+        let spare = Card::new(252, "Spare", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 3, Vec::new(), vec![ogre], vec![spare]);
+        let player_two = Player::new(1, 30, 0, 3, Vec::new(), vec![wolf], Vec::new());
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 250,
+                    defender_owner: 1,
+                    defender_card: Some(251),
+                },
+            )
+            .expect("ogre attacks the wolf");
+        state.damage_player(0, None, 1, 6);
+        state.heal_player(1, 3);
+        assert_eq!(
+            state.heal_player(1, 10),
+            Some(GameEvent::CardHealed {
+                player_id: 1,
+                card_id: None,
+                amount: 3,
+            }),
+            "healing stops at the health cap"
+        );
+        assert_eq!(state.get_player(1).unwrap().health, 30);
+        assert_eq!(state.heal_player(1, 5), None);
+        state.draw_card(0);
+
+        let summaries = state.player_summaries();
+        assert_eq!(
+            summaries[0].stats,
+            PlayerStats {
+                damage_dealt: 10,
+                cards_drawn: 1,
+                units_killed: 1,
+                largest_hit: 6,
+                ..PlayerStats::default()
+            }
+        );
+        assert_eq!(
+            summaries[1].stats,
+            PlayerStats {
+                damage_dealt: 2,
+                damage_taken: 6,
+                healing: 6,
+                largest_hit: 2,
+                ..PlayerStats::default()
+            }
+        );
+        let json = serde_json::to_value(&summaries[1]).unwrap();
+        assert_eq!(json["damage_taken"], 6, "stats are flattened");
+    }
+
+    #[test]
+    fn integrity_check_reports_each_inconsistency() {
+        let check = |corrupt: fn(&mut GameState)| {
+            let mut state = GameState::sample();
+            corrupt(&mut state);
+            state.integrity_check()
+        };
+        assert_eq!(check(|_| {}), Ok(()));
+        assert_eq!(
+            check(|state| state.config.max_board_size = 0),
+            Err(IntegrityError::BoardTooLarge {
+                player_id: 0,
+                size: 1,
+                max: 0,
+            })
+        );
+        assert!(matches!(
+            check(|state| state.config.max_hand_size = 0),
+            Err(IntegrityError::HandTooLarge { max: 0, .. })
+        ));
+        assert!(matches!(
+            check(|state| state.players[0].hand[0].exhausted = true),
+            Err(IntegrityError::ExhaustedOutOfPlay { .. })
+        ));
+        assert!(matches!(
+            check(|state| state.players[1].board[0].attack = MAX_CARD_STAT + 1),
+            Err(IntegrityError::CardStatOutOfRange {
+                stat: CardStat::Attack,
+                ..
+            })
+        ));
+        assert_eq!(
+            check(|state| {
+                state.record_event(GameEvent::TurnEnded { player_id: 0 });
+                state.outcome = Some(VictoryState {
+                    winner: 1,
+                    reason: VictoryReason::Special {
+                        reason: "test".to_string(),
+                    },
+                });
+            }),
+            Err(IntegrityError::OutcomeNotLogged { winner: 1 })
+        );
+        assert_eq!(
+            check(|state| {
+                state.declare_victory(0, VictoryReason::HealthDepleted { loser: 1 });
+                state.draw = Some(DrawReason::SimultaneousDefeat);
+            }),
+            Err(IntegrityError::ConflictingOutcome)
+        );
+        assert_eq!(
+            check(|state| {
+                state.push_choice(7, ChoiceKind::Discover { cards: Vec::new() });
+            }),
+            Err(IntegrityError::PendingChoicePlayerMissing {
+                choice_id: 0,
+                player_id: 7,
+            })
+        );
+    }
+
+    #[test]
+    fn mana_crystals_refill_without_keeping_spent_or_temporary_mana() {
+        let mut engine = RuleEngine::new();
+        let unit = |id: CardId| Card::new(id, "Recruit", 2, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 2, vec![unit(1)], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_config(RuleConfig::default().with_max_mana(3))
+            .with_phase(GamePhase::Main);
+
+        state.gain_mana(1, 1);
+        assert_eq!((state.players[1].mana, state.players[1].max_mana), (1, 0));
+        let play = PlayCardAction {
+            player_id: 0,
+            card_id: 1,
+            target_player: None,
+            target_card: None,
+            position: None,
+        };
+        engine.play_card(&mut state, play).expect("play");
+        assert_eq!((state.players[0].mana, state.players[0].max_mana), (0, 2));
+
+        engine.end_turn(&mut state).expect("end turn");
+        assert_eq!(
+            (state.players[1].mana, state.players[1].max_mana),
+            (1, 1),
+            "temporary mana does not become a crystal"
+        );
+        engine.end_turn(&mut state).expect("end turn");
+        assert_eq!((state.players[0].mana, state.players[0].max_mana), (3, 3));
+        engine.end_turn(&mut state).expect("end turn");
+        engine.end_turn(&mut state).expect("end turn");
+        assert_eq!((state.players[0].mana, state.players[0].max_mana), (3, 3));
+    }
+
+    #[test]
+    fn generated_cards_take_ids_from_the_instance_counter() {
+        let coin = Card::new(0, "Coin", 0, 0, 0, CardType::Spell, Vec::new());
+        let unit = Card::new(5, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, Vec::new(), vec![unit], Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        assert_eq!(state.next_card_instance_id, 6);
+
+        state.add_card_to_hand(1, coin.clone());
+        assert_eq!(state.players[1].hand[0].id, 6);
+        state.players[1].hand.clear();
+        state.add_card_to_hand(1, coin.clone());
+        assert_eq!(
+            state.players[1].hand[0].id, 7,
+            "ids of removed cards are not reused"
+        );
+
+        let placed = Card::new(40, "Placed", 1, 1, 1, CardType::Unit, Vec::new());
+        state.players[0].board.push(placed);
+        state.add_card_to_hand(0, coin);
+        assert_eq!(
+            state.players[0].hand[0].id, 41,
+            "cards placed directly are skipped"
+        );
+        assert!(state.integrity_check().is_ok());
+
+        let mut legacy = serde_json::to_value(&state).unwrap();
+        legacy
+            .as_object_mut()
+            .unwrap()
+            .remove("next_card_instance_id");
+        let mut restored: GameState = serde_json::from_value(legacy).unwrap();
+        restored.reconcile_after_load();
+        assert_eq!(restored.next_card_instance_id, 42);
+    }
+
+    #[test]
+    fn taken_units_change_boards_and_temporary_control_returns() {
+        let mut engine = RuleEngine::new();
+        let unit = |id: CardId| {
+            let mut card = Card::new(id, "Thrall", 1, 2, 2, CardType::Unit, Vec::new());
+            card.refresh();
+            card
+        };
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), vec![unit(1), unit(2)], Vec::new());
+        let mut state =
+            GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Combat);
+
+        let steal = |card_id: CardId, duration: EffectDuration| {
+            let ctx = EffectContext::new(EffectTrigger::OnPlay, 0, 0).with_target_card(1, card_id);
+            let kind = EffectKind::TakeControl {
+                target: EffectTarget::ContextTarget,
+                duration,
+            };
+            (ctx, kind)
+        };
+        let (ctx, kind) = steal(1, EffectDuration::Permanent);
+        let events = kind.apply(&ctx, &mut state).events;
+        assert!(matches!(
+            events[..],
+            [GameEvent::ControlChanged {
+                card_id: 1,
+                from: 1,
+                to: 0,
+                expires: None
+            }]
+        ));
+        let taken = &state.players[0].board[0];
+        assert_eq!((taken.owner, taken.owner_or(0)), (Some(1), 1));
+        assert!(
+            taken.summoning_sick,
+            "permanently taken units must wait a turn"
+        );
+
+        let (ctx, kind) = steal(2, EffectDuration::ThisTurn);
+        kind.apply(&ctx, &mut state);
+        assert_eq!(state.players[0].board.len(), 2);
+        assert!(state.players[1].board.is_empty());
+        engine
+            .attack(
+                &mut state,
+                AttackAction {
+                    attacker_owner: 0,
+                    attacker_id: 2,
+                    defender_owner: 1,
+                    defender_card: None,
+                },
+            )
+            .expect("borrowed units attack right away");
+
+        let events = engine.end_turn(&mut state).expect("end turn");
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::ControlChanged {
+                card_id: 2,
+                from: 0,
+                to: 1,
+                ..
+            }
+        )));
+        assert_eq!(state.players[1].board[0].id, 2);
+        assert_eq!(state.players[1].board[0].owner, None);
+        assert_eq!(state.players[0].board[0].id, 1, "permanent control is kept");
+    }
+
+    #[test]
+    fn rematch_reuses_decks_and_alternates_the_first_player() {
+        let deck = |base: CardId| -> Vec<Card> {
+            (base..base + 10)
+                .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+                .collect()
+        };
+        let config = RuleConfig::default().with_starting_health(20);
+        let mut state = GameState::new_game(deck(1), deck(20), config, 7);
+        let first = state.current_player;
+        state.players[0].health = 3;
+        state.config.starting_health = 25;
+
+        let rematch = state.rematch(8).expect("created from decks");
+        assert_eq!(rematch.current_player, 1 - first);
+        assert_eq!(rematch.phase, GamePhase::Mulligan);
+        assert_eq!(rematch.rng.seed(), 8);
+        assert!(rematch.players.iter().all(|player| player.health == 25));
+        let setup = rematch.setup.as_ref().unwrap();
+        assert_eq!(setup.decks, [deck(1), deck(20)]);
+        assert_eq!(setup.first_player, 1 - first);
+        let again = rematch.rematch(9).unwrap();
+        assert_eq!(again.current_player, first);
+
+        let view = rematch.redacted_for(0);
+        let decks = &view.setup.as_ref().unwrap().decks;
+        assert_eq!(decks[0], deck(1));
+        assert!(decks[1].iter().all(|card| card.name.is_empty()));
+        assert!(GameState::sample().rematch(1).is_none());
+    }
+
+    #[test]
+    fn discarding_the_drawn_card_is_logged_once_as_a_discard() {
+        let mut engine = RuleEngine::new();
+        let unit = |id: CardId| Card::new(id, "Recruit", 1, 1, 1, CardType::Unit, Vec::new());
+        let player_one = Player::new(0, 30, 0, 1, vec![unit(1)], Vec::new(), vec![unit(2)]);
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0)
+            .with_config(RuleConfig::default().with_max_hand_size(1))
+            .with_phase(GamePhase::Main);
+
+        state.draw_card(0);
+        let choice_id = state.pending_choices[0].id;
+        let events = engine
+            .resolve_choice(
+                &mut state,
+                ResolveChoiceAction {
+                    player_id: 0,
+                    choice_id,
+                    option: 1,
+                },
+            )
+            .expect("discarding the drawn card resolves the choice");
+
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::CardBurned { .. })));
+        let discarded: Vec<CardId> = state
+            .event_log
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                GameEvent::CardDiscarded { card, .. } => Some(card.id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(discarded, vec![2], "the discard is recorded exactly once");
+        let player = state.get_player(0).unwrap();
+        assert_eq!(
+            player.hand.iter().map(|card| card.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(player.graveyard.len(), 1);
+    }
+
+    #[test]
+    fn open_priority_windows_survive_a_save_and_reload() {
+        let mut engine = RuleEngine::new().with_interactive_priority(true);
+        let bolt = CardEffect::direct_damage(
+            9032,
+            "Deal 4 damage",
+            EffectTrigger::OnPlay,
+            0,
+            4,
+            EffectTarget::OpponentOfSource,
+        );
+        let spell = Card::new(197, "Bolt", 1, 0, 0, CardType::Spell, vec![bolt]);
+        let player_one = Player::new(0, 30, 0, 1, vec![spell], Vec::new(), Vec::new());
+        let player_two = Player::new(1, 30, 0, 1, Vec::new(), Vec::new(), Vec::new());
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+        engine
+            .play_card(
+                &mut state,
+                PlayCardAction {
+                    player_id: 0,
+                    card_id: 197,
+                    target_player: None,
+                    target_card: None,
+                    position: None,
+                },
+            )
+            .expect("spell should be playable");
+        assert!(
+            engine.effect_stack().is_empty(),
+            "the stack is parked on the state"
+        );
+
+        let saved = serde_json::to_string(&state).expect("state serializes");
+        let mut restored: GameState = serde_json::from_str(&saved).expect("state loads");
+        assert_eq!(restored, state);
+        let mut reloaded = RuleEngine::new().with_interactive_priority(true);
+        assert_eq!(
+            reloaded.end_turn(&mut restored),
+            Err(RuleError::AwaitingPriority { player_id: 1 })
+        );
+        reloaded
+            .pass_priority(&mut restored, PassPriorityAction { player_id: 1 })
+            .expect("the holder passes after the reload");
+        assert_eq!(restored.get_player(1).unwrap().health, 26);
+        assert!(restored.priority_window.is_none());
+    }
+
+    fn armored_deck(base: CardId) -> Vec<Card> {
+        let bulwark = CardEffect::new(
+            1,
+            "开局获得 2 点护甲",
+            EffectTrigger::OnGameStart,
+            0,
+            EffectKind::GainArmor {
+                amount: 2,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        let mut deck: Vec<Card> = (base..base + 9)
+            .map(|id| Card::new(id, "Reserve", 2, 2, 2, CardType::Unit, Vec::new()))
+            .collect();
+        deck.push(Card::new(
+            base + 9,
+            "Bulwark",
+            1,
+            1,
+            1,
+            CardType::Unit,
+            vec![bulwark],
+        ));
+        deck
+    }
+
+    #[test]
+    fn game_start_effects_fire_once_after_both_mulligans() {
+        let mut engine = RuleEngine::new();
+        let mut state =
+            GameState::new_game(armored_deck(1), armored_deck(20), RuleConfig::default(), 3);
+        for player_id in [0, 1] {
+            assert!(state.players.iter().all(|player| player.armor == 0));
+            engine
+                .mulligan(
+                    &mut state,
+                    MulliganAction {
+                        player_id,
+                        replacements: Vec::new(),
+                    },
+                )
+                .expect("mulligan");
+        }
+        assert!(state.game_start_resolved);
+        assert!(state.players.iter().all(|player| player.armor == 2));
+
+        engine.begin_game(&mut state);
+        assert!(
+            state.players.iter().all(|player| player.armor == 2),
+            "OnGameStart only resolves once"
+        );
+    }
+
+    #[test]
+    fn game_start_effects_fire_for_games_set_up_without_a_mulligan() {
+        let mut engine = RuleEngine::new();
+        let player_one = Player::new(0, 30, 0, 0, Vec::new(), Vec::new(), armored_deck(1));
+        let player_two = Player::new(1, 30, 0, 0, Vec::new(), Vec::new(), armored_deck(20));
+        let mut state = GameState::new(vec![player_one, player_two], 0).with_phase(GamePhase::Main);
+
+        let events = engine.begin_game(&mut state);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::ArmorGained { player_id: 1, .. })));
+        assert!(state.players.iter().all(|player| player.armor == 2));
+
+        engine.end_turn(&mut state).expect("end turn");
+        engine.begin_game(&mut state);
+        assert!(state.players.iter().all(|player| player.armor == 2));
+    }
 }
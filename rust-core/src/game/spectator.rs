@@ -0,0 +1,128 @@
+//! 观战：以观战者视角记录每次操作后的局面与事件，并按设定的操作数延迟放出，供观战界面播放。
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::state::{GameState, LoggedEvent, SPECTATOR};
+
+/// 一帧观战画面：某次操作后的观战者视角局面，以及该操作产生的事件。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpectatorFrame {
+    /// 帧序号，开始观战时的局面为 0，之后每次操作加 1。
+    pub index: u64,
+    /// 已隐藏手牌与牌库的局面，事件日志被清空，改由 `events` 增量提供。
+    pub state: GameState,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<LoggedEvent>,
+}
+
+/// 观战流：每次操作后调用 `observe`，帧在之后又发生 `delay` 次操作时才可取出；
+/// 对局结束时剩余的帧全部放出。
+#[derive(Debug, Clone)]
+pub struct SpectatorFeed {
+    delay: usize,
+    last_seq: u64,
+    next_index: u64,
+    pending: VecDeque<SpectatorFrame>,
+    ready: Vec<SpectatorFrame>,
+}
+
+impl SpectatorFeed {
+    /// 以当前局面开始观战，起始帧立即可取出。
+    pub fn new(state: &GameState, delay: usize) -> Self {
+        let mut feed = Self {
+            delay,
+            last_seq: state.event_log.last().map_or(0, |entry| entry.seq),
+            next_index: 0,
+            pending: VecDeque::new(),
+            ready: Vec::new(),
+        };
+        let frame = feed.capture(state);
+        feed.ready.push(frame);
+        feed
+    }
+
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// 记录一次操作后的局面。
+    pub fn observe(&mut self, state: &GameState) {
+        let frame = self.capture(state);
+        self.pending.push_back(frame);
+        let held = if state.is_finished() { 0 } else { self.delay };
+        while self.pending.len() > held {
+            self.ready.extend(self.pending.pop_front());
+        }
+    }
+
+    /// 取出已过延迟的帧，按发生顺序排列。
+    pub fn take_ready(&mut self) -> Vec<SpectatorFrame> {
+        std::mem::take(&mut self.ready)
+    }
+
+    fn capture(&mut self, state: &GameState) -> SpectatorFrame {
+        let mut view = state.redacted_for(SPECTATOR);
+        let events = view.events_since(self.last_seq).to_vec();
+        if let Some(entry) = events.last() {
+            self.last_seq = entry.seq;
+        }
+        view.event_log.clear();
+        let index = self.next_index;
+        self.next_index += 1;
+        SpectatorFrame {
+            index,
+            state: view,
+            events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{RevealedCard, RuleEngine, VictoryReason};
+
+    #[test]
+    fn spectators_see_delayed_redacted_frames() {
+        let mut engine = RuleEngine::new();
+        let mut state = GameState::sample();
+        let hidden_card = state.players[1].hand[0].id;
+        state.players[1].revealed_hand.push(RevealedCard {
+            card_id: hidden_card,
+            viewer: 0,
+        });
+        let mut feed = SpectatorFeed::new(&state, 1);
+
+        let start = feed.take_ready();
+        assert_eq!(start.len(), 1);
+        assert_eq!(start[0].index, 0);
+        for (player, view) in state.players.iter().zip(&start[0].state.players) {
+            for (card, seen) in player.hand.iter().zip(&view.hand) {
+                let revealed = player.is_revealed_to(card.id, SPECTATOR);
+                assert_eq!(seen == card, revealed, "card {}", card.id);
+            }
+            assert!(view.deck.iter().all(|card| card.name.is_empty()));
+        }
+
+        engine.end_turn(&mut state).expect("end turn");
+        feed.observe(&state);
+        assert!(feed.take_ready().is_empty());
+        engine.end_turn(&mut state).expect("end turn");
+        feed.observe(&state);
+        let frames = feed.take_ready();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].index, 1);
+        assert!(!frames[0].events.is_empty());
+        assert!(frames[0].state.event_log.is_empty());
+
+        state.declare_victory(0, VictoryReason::HealthDepleted { loser: 1 });
+        feed.observe(&state);
+        let frames = feed.take_ready();
+        assert_eq!(
+            frames.iter().map(|frame| frame.index).collect::<Vec<_>>(),
+            [2, 3]
+        );
+    }
+}
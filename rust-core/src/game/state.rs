@@ -1,26 +1,74 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-
-const DEFAULT_MAX_HAND_SIZE: u8 = 10;
-const DEFAULT_MAX_BOARD_SIZE: u8 = 7;
-
+use std::collections::{BTreeMap, HashSet};
+
+pub(crate) const DEFAULT_MAX_HAND_SIZE: u8 = 10;
+pub(crate) const DEFAULT_MAX_BOARD_SIZE: u8 = 7;
+/// 法力水晶上限，同时也是临时法力所能达到的上限。
+pub const MAX_MANA: u8 = 10;
+pub const DEFAULT_HERO_POWER_COST: u8 = 2;
+/// 幸运币“获得 1 点法力”效果的标识。
+pub const COIN_EFFECT_ID: EffectId = 9000;
+/// 先手玩家的起手牌数，后手玩家多抽一张。
+pub const OPENING_HAND_SIZE: u8 = 3;
+/// 卡牌费用、攻击力与生命值的合理上限，超出视为状态损坏。
+pub const MAX_CARD_STAT: i16 = 99;
+
+use super::config::{DeckOutRule, RuleConfig, Tiebreaker};
 use super::effects::{
-    EffectCondition, EffectContext, EffectEngine, EffectKind, EffectTarget, EffectTrigger,
+    ChoiceOption, EffectCondition, EffectContext, EffectEngine, EffectKind, EffectStack,
+    EffectTarget, EffectTrigger, TargetFilter,
 };
+use super::migration::SCHEMA_VERSION;
+use super::registry::{CardRegistry, DefId};
+use super::rng::GameRng;
 
 /// 全局唯一的卡牌标识。
 pub type CardId = u32;
 /// 玩家标识。
 pub type PlayerId = u8;
+/// 观战者的视角标识，不对应任何玩家：所有手牌与牌库都被隐藏，仅公开已揭示的手牌。
+pub const SPECTATOR: PlayerId = PlayerId::MAX;
+/// 组队模式下的队伍标识。
+pub type TeamId = u8;
 /// 卡牌效果标识。
 pub type EffectId = u32;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum VictoryReason {
-    HealthDepleted { loser: PlayerId },
-    DeckOut { loser: PlayerId },
-    Special { reason: String },
+    HealthDepleted {
+        loser: PlayerId,
+    },
+    DeckOut {
+        loser: PlayerId,
+    },
+    /// 达到回合上限时英雄剩余生命最高。
+    TurnLimitReached {
+        turn_limit: u32,
+    },
+    /// 满足了由规则配置或卡牌效果登记的额外胜利条件。
+    Alternative {
+        id: String,
+    },
+    Special {
+        reason: String,
+    },
+}
+
+/// 额外胜利条件：以玩家为来源检查 `condition`，满足时该玩家以 `VictoryReason::Alternative` 获胜。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AlternativeVictory {
+    pub id: String,
+    pub condition: EffectCondition,
+}
+
+impl AlternativeVictory {
+    pub fn new(id: impl Into<String>, condition: EffectCondition) -> Self {
+        Self {
+            id: id.into(),
+            condition,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -29,16 +77,42 @@ pub struct VictoryState {
     pub reason: VictoryReason,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum DrawReason {
+    /// 双方英雄同时阵亡。
+    SimultaneousDefeat,
+    /// 达到回合上限且未分出胜负。
+    TurnLimitReached {
+        turn_limit: u32,
+    },
+    Special {
+        reason: String,
+    },
+}
+
+/// 对局的最终结果：一方获胜或平局。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum GameOutcome {
+    Victory {
+        winner: PlayerId,
+        reason: VictoryReason,
+    },
+    Draw {
+        reason: DrawReason,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CardType {
+    #[default]
     Unit,
     Spell,
-}
-
-impl Default for CardType {
-    fn default() -> Self {
-        CardType::Unit
-    }
+    /// 奥秘：面朝下放置，由对手的行动自动触发。
+    Secret,
+    /// 武器：装备给英雄，`attack` 为攻击力，`health` 为耐久度。
+    Weapon,
 }
 
 /// 卡牌附带的效果描述。
@@ -53,6 +127,12 @@ pub struct CardEffect {
     pub kind: EffectKind,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub condition: Option<EffectCondition>,
+    /// 打出时对玩家所选目标的筛选（仅作用于 `ContextTarget`）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_filter: Option<TargetFilter>,
+    /// 剩余触发次数，`None` 为不限次数；归零后不再触发。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charges: Option<u8>,
 }
 
 impl CardEffect {
@@ -70,6 +150,8 @@ impl CardEffect {
             priority,
             kind,
             condition: None,
+            target_filter: None,
+            charges: None,
         }
     }
 
@@ -78,6 +160,20 @@ impl CardEffect {
         self
     }
 
+    pub fn with_target_filter(mut self, filter: TargetFilter) -> Self {
+        self.target_filter = Some(filter);
+        self
+    }
+
+    pub fn with_charges(mut self, charges: u8) -> Self {
+        self.charges = Some(charges);
+        self
+    }
+
+    pub fn out_of_charges(&self) -> bool {
+        self.charges == Some(0)
+    }
+
     pub fn direct_damage(
         id: EffectId,
         description: impl Into<String>,
@@ -91,7 +187,11 @@ impl CardEffect {
             description,
             trigger,
             priority,
-            EffectKind::DirectDamage { amount, target },
+            EffectKind::DirectDamage {
+                amount,
+                target,
+                scaling: None,
+            },
         )
     }
 
@@ -108,7 +208,11 @@ impl CardEffect {
             description,
             trigger,
             priority,
-            EffectKind::Heal { amount, target },
+            EffectKind::Heal {
+                amount,
+                target,
+                scaling: None,
+            },
         )
     }
 
@@ -125,25 +229,348 @@ impl CardEffect {
             description,
             trigger,
             priority,
-            EffectKind::DrawCard { count, target },
+            EffectKind::DrawCard {
+                count,
+                target,
+                scaling: None,
+            },
         )
     }
 }
 
+/// 属性修正量（攻击 / 生命）。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatModifier {
+    #[serde(default)]
+    pub attack: i16,
+    #[serde(default)]
+    pub health: i16,
+}
+
+impl StatModifier {
+    pub fn is_zero(&self) -> bool {
+        self.attack == 0 && self.health == 0
+    }
+}
+
+/// 限时效果的到期时机。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum Expiry {
+    /// 指定玩家的回合结束时到期。
+    EndOfTurn { player_id: PlayerId },
+    /// 指定玩家的下个回合开始时到期。
+    StartOfTurn { player_id: PlayerId },
+}
+
+/// 单位种族，用于种族协同效果与目标筛选。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Tribe {
+    Beast,
+    Demon,
+    Dragon,
+    Elemental,
+    Mech,
+    Murloc,
+    Pirate,
+    Undead,
+}
+
+/// 单位关键字。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Keyword {
+    /// 嘲讽：对手必须先攻击嘲讽单位。
+    Taunt,
+    /// 冲锋：进场当回合即可攻击。
+    Charge,
+    /// 突袭：进场当回合即可攻击单位，但不能攻击英雄。
+    Rush,
+    /// 吸血：该单位造成伤害时为其拥有者恢复等量生命。
+    Lifesteal,
+    /// 潜行：不能被敌方攻击，主动攻击后失去潜行。
+    Stealth,
+    /// 远程：攻击单位时不受反击伤害。
+    Ranged,
+    /// 灵巧：不能被卡牌、英雄技能或效果指定为目标，但可以被攻击；范围与随机效果不受影响。
+    Elusive,
+}
+
+/// 限时效果的内容；`Control` 为限时控制，到期后单位归还原拥有者。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum TimedEffectKind {
+    Stats { attack: i16, health: i16 },
+    Frozen,
+    Keyword { keyword: Keyword },
+    Control,
+}
+
+/// 附着在卡牌上、到期后自动撤销的效果。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimedEffect {
+    pub kind: TimedEffectKind,
+    pub expires: Expiry,
+}
+
+/// 法力之外的额外费用，打出卡牌时一并支付。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdditionalCost {
+    /// 支付的英雄生命值，不允许因此阵亡。
+    #[serde(default)]
+    pub health: u8,
+    /// 随机弃掉的其他手牌数量。
+    #[serde(default)]
+    pub discard: u8,
+}
+
+impl AdditionalCost {
+    pub fn is_free(&self) -> bool {
+        self.health == 0 && self.discard == 0
+    }
+}
+
+/// 英雄技能：每回合可使用一次，支付法力后结算一条效果。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HeroPower {
+    pub name: String,
+    #[serde(default = "default_hero_power_cost")]
+    pub cost: u8,
+    /// 使用时结算的效果，`trigger` 字段被忽略。
+    pub effect: CardEffect,
+    /// 本回合是否已经使用，回合开始时重置。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub used: bool,
+}
+
+fn default_hero_power_cost() -> u8 {
+    DEFAULT_HERO_POWER_COST
+}
+
+impl HeroPower {
+    pub fn new(name: impl Into<String>, effect: CardEffect) -> Self {
+        Self {
+            name: name.into(),
+            cost: DEFAULT_HERO_POWER_COST,
+            effect,
+            used: false,
+        }
+    }
+
+    pub fn with_cost(mut self, cost: u8) -> Self {
+        self.cost = cost;
+        self
+    }
+}
+
+/// 单个玩家本局的统计，随操作结算累积，供结算界面与平衡分析使用。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlayerStats {
+    /// 对英雄与单位造成的总伤害。
+    pub damage_dealt: u32,
+    /// 英雄承受的总伤害（含疲劳）。
+    pub damage_taken: u32,
+    /// 己方英雄与单位恢复的总生命值。
+    pub healing: u32,
+    pub cards_drawn: u32,
+    pub cards_played: u32,
+    /// 以伤害消灭的敌方单位数。
+    pub units_killed: u32,
+    /// 单次造成的最高伤害。
+    pub largest_hit: u32,
+}
+
+impl PlayerStats {
+    pub fn is_empty(&self) -> bool {
+        self == &PlayerStats::default()
+    }
+}
+
+/// 英雄：决定玩家的职业、英雄技能、头像与开局护甲。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hero {
+    pub class: String,
+    /// 该英雄的基础英雄技能，指派英雄时复制到玩家身上。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero_power: Option<HeroPower>,
+    /// 前端用于查找头像资源的标识。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portrait_id: Option<String>,
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub starting_armor: u8,
+}
+
+impl Hero {
+    pub fn new(class: impl Into<String>) -> Self {
+        Self {
+            class: class.into(),
+            hero_power: None,
+            portrait_id: None,
+            starting_armor: 0,
+        }
+    }
+
+    pub fn with_hero_power(mut self, hero_power: HeroPower) -> Self {
+        self.hero_power = Some(hero_power);
+        self
+    }
+
+    pub fn with_portrait(mut self, portrait_id: impl Into<String>) -> Self {
+        self.portrait_id = Some(portrait_id.into());
+        self
+    }
+
+    pub fn with_starting_armor(mut self, armor: u8) -> Self {
+        self.starting_armor = armor;
+        self
+    }
+}
+
+/// 附魔的持续方式。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum EnchantmentDuration {
+    #[default]
+    Permanent,
+    /// 由光环维持，随 `EffectEngine::refresh_auras` 增删。
+    Aura,
+    /// 到期后撤销。
+    Until { expires: Expiry },
+}
+
+/// 卡牌上的一条属性附魔，记录加成来源以便撤销与展示。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Enchantment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_player: Option<PlayerId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_card: Option<CardId>,
+    #[serde(default)]
+    pub attack: i16,
+    #[serde(default)]
+    pub health: i16,
+    #[serde(default)]
+    pub duration: EnchantmentDuration,
+}
+
+/// 玩家级附魔提供的修正。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum PlayerModifier {
+    /// 手牌费用修正，可限定卡牌类型。
+    Cost {
+        delta: i8,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        card_type: Option<CardType>,
+    },
+    /// 英雄受到的伤害修正，负数为减伤。
+    DamageTaken { delta: i8 },
+}
+
+/// 对某位玩家揭示过的一张手牌。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RevealedCard {
+    pub card_id: CardId,
+    /// 看到这张牌的玩家。
+    pub viewer: PlayerId,
+}
+
+/// 作用于玩家本身的持续性附魔（如“你的法术费用减少 1 点”）。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlayerEnchantment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_player: Option<PlayerId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_card: Option<CardId>,
+    pub modifier: PlayerModifier,
+    /// 为 `None` 时永久生效。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Expiry>,
+}
+
+pub(crate) fn is_zero_u8(value: &u8) -> bool {
+    *value == 0
+}
+
+fn is_zero_i16(value: &i16) -> bool {
+    *value == 0
+}
+
+fn is_zero_u32(value: &u32) -> bool {
+    *value == 0
+}
+
+/// 从抵消额度中扣除伤害，返回被抵消的数值。
+fn consume_prevention(pool: &mut u8, amount: i16) -> i16 {
+    let prevented = amount.clamp(0, *pool as i16);
+    *pool -= prevented as u8;
+    prevented
+}
+
 /// 战斗中使用的卡牌数据。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Card {
     pub id: CardId,
+    /// 引用的卡牌定义，临时构造的卡牌为 `None`。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub def_id: Option<DefId>,
+    /// 效果数据已省略，载入后需从卡牌定义中恢复（见 `CardRegistry::hydrate`）。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shared_effects: bool,
     pub name: String,
     pub cost: u8,
     pub attack: i16,
     pub health: i16,
     #[serde(default)]
     pub card_type: CardType,
+    /// 本回合已经攻击过。
     #[serde(default)]
     pub exhausted: bool,
+    /// 本回合刚进场（召唤失调），冲锋单位可无视，突袭单位只能攻击单位。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub summoning_sick: bool,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub effects: Vec<CardEffect>,
+    /// 旧存档中的光环加成，载入时迁移为附魔记录。
+    #[serde(default, skip_serializing)]
+    pub aura: StatModifier,
+    /// 已计入攻击/生命的附魔，按附加顺序排列。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enchantments: Vec<Enchantment>,
+    /// 冻结的单位无法攻击。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timed_effects: Vec<TimedEffect>,
+    /// 接下来可抵消的伤害量，先于生命值扣除。
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub damage_prevention: u8,
+    /// 尚未治疗的累计伤害，大于 0 即为受伤状态。
+    #[serde(default, skip_serializing_if = "is_zero_i16")]
+    pub damage_taken: i16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tribes: Vec<Tribe>,
+    #[serde(default, skip_serializing_if = "AdditionalCost::is_free")]
+    pub additional_cost: AdditionalCost,
+    /// 印刷与运行时获得的关键字，沉默时一并移除。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<Keyword>,
+    /// 具名计数器（如充能层数），数量为 0 的计数器不保留。
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counters: BTreeMap<String, u16>,
+    /// 留在手牌中时，条件满足即进化为另一张卡牌。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade: Option<Box<CardUpgrade>>,
+    /// 被夺取控制权的单位记录原拥有者；为 `None` 时由所在场地的玩家拥有并控制。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<PlayerId>,
+}
+
+/// 手牌进化：以持有者为来源玩家检查 `condition`，满足时卡牌变为 `into`（保留 ID）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CardUpgrade {
+    pub condition: EffectCondition,
+    pub into: Card,
 }
 
 impl Card {
@@ -158,14 +585,219 @@ impl Card {
     ) -> Self {
         Self {
             id,
+            def_id: None,
+            shared_effects: false,
             name: name.into(),
             cost,
             attack,
             health,
             card_type,
-            exhausted: matches!(card_type, CardType::Unit),
+            exhausted: false,
+            summoning_sick: matches!(card_type, CardType::Unit),
             effects,
+            aura: StatModifier::default(),
+            enchantments: Vec::new(),
+            frozen: false,
+            timed_effects: Vec::new(),
+            damage_prevention: 0,
+            damage_taken: 0,
+            tribes: Vec::new(),
+            additional_cost: AdditionalCost::default(),
+            keywords: Vec::new(),
+            counters: BTreeMap::new(),
+            upgrade: None,
+            owner: None,
+        }
+    }
+
+    /// 幸运币：0 费法术，本回合获得 1 点临时法力，作为后手补偿发放。
+    pub fn coin() -> Self {
+        let effect = CardEffect::new(
+            COIN_EFFECT_ID,
+            "获得 1 点法力，仅限本回合",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::GainMana {
+                amount: 1,
+                target: EffectTarget::SourcePlayer,
+            },
+        );
+        Card::new(0, "幸运币", 0, 0, 0, CardType::Spell, vec![effect])
+    }
+
+    pub fn with_tribes<I>(mut self, tribes: I) -> Self
+    where
+        I: IntoIterator<Item = Tribe>,
+    {
+        self.tribes = tribes.into_iter().collect();
+        self
+    }
+
+    pub fn has_tribe(&self, tribe: Tribe) -> bool {
+        self.tribes.contains(&tribe)
+    }
+
+    pub fn with_keywords(mut self, keywords: impl IntoIterator<Item = Keyword>) -> Self {
+        self.keywords = keywords.into_iter().collect();
+        self
+    }
+
+    pub fn with_upgrade(mut self, condition: EffectCondition, into: Card) -> Self {
+        self.upgrade = Some(Box::new(CardUpgrade { condition, into }));
+        self
+    }
+
+    pub fn has_keyword(&self, keyword: Keyword) -> bool {
+        self.keywords.contains(&keyword)
+    }
+
+    /// 单位的拥有者；`controller` 为其当前所在场地的玩家。
+    pub fn owner_or(&self, controller: PlayerId) -> PlayerId {
+        self.owner.unwrap_or(controller)
+    }
+
+    /// 能否被指定为卡牌、英雄技能或效果的目标。
+    pub fn is_targetable(&self) -> bool {
+        !self.has_keyword(Keyword::Elusive)
+    }
+
+    /// 沉默：移除效果、关键字、非光环附魔与限时状态；与 `remove_enchantments` 一样可能使生命归零。
+    pub fn silence(&mut self) {
+        self.remove_enchantments(|item| item.duration != EnchantmentDuration::Aura);
+        for timed in std::mem::take(&mut self.timed_effects) {
+            if let TimedEffectKind::Stats { attack, health } = timed.kind {
+                self.attack = (self.attack - attack).max(0);
+                self.health -= health;
+            }
         }
+        self.effects.clear();
+        self.keywords.clear();
+        self.counters.clear();
+        self.frozen = false;
+        self.damage_prevention = 0;
+    }
+
+    pub fn counter(&self, name: &str) -> u16 {
+        self.counters.get(name).copied().unwrap_or(0)
+    }
+
+    /// 按 `delta` 增减具名计数器（不低于 0），返回调整后的数量。
+    pub fn adjust_counter(&mut self, name: &str, delta: i32) -> u16 {
+        let total = (self.counter(name) as i32 + delta).clamp(0, u16::MAX as i32) as u16;
+        if total == 0 {
+            self.counters.remove(name);
+        } else {
+            self.counters.insert(name.to_string(), total);
+        }
+        total
+    }
+
+    pub fn with_additional_cost(mut self, additional_cost: AdditionalCost) -> Self {
+        self.additional_cost = additional_cost;
+        self
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.damage_taken > 0
+    }
+
+    /// 附加一条附魔并计入当前攻击/生命。
+    pub fn enchant(&mut self, enchantment: Enchantment) {
+        self.attack = (self.attack + enchantment.attack).max(0);
+        self.health += enchantment.health;
+        self.enchantments.push(enchantment);
+    }
+
+    /// 移除满足条件的附魔并撤销其加成。
+    ///
+    /// 已受的伤害保留，因此生命可能归零；场上单位由调用方经 `GameState::destroy_card` 处理死亡。
+    pub fn remove_enchantments<F>(&mut self, predicate: F) -> Vec<Enchantment>
+    where
+        F: Fn(&Enchantment) -> bool,
+    {
+        let (removed, kept): (Vec<Enchantment>, Vec<Enchantment>) = self
+            .enchantments
+            .drain(..)
+            .partition(|item| predicate(item));
+        self.enchantments = kept;
+        for item in &removed {
+            self.attack = (self.attack - item.attack).max(0);
+            self.health -= item.health;
+        }
+        removed
+    }
+
+    /// 检查费用、攻击力与生命值是否处于合理范围。
+    pub fn check_stats(&self) -> Result<(), IntegrityError> {
+        let stats = [
+            (CardStat::Cost, i16::from(self.cost), 0),
+            (CardStat::Attack, self.attack, 0),
+            (CardStat::Health, self.health, -MAX_CARD_STAT),
+        ];
+        for (stat, value, min) in stats {
+            if !(min..=MAX_CARD_STAT).contains(&value) {
+                return Err(IntegrityError::CardStatOutOfRange {
+                    card_id: self.id,
+                    stat,
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 离场卡牌恢复初始状态的副本：治愈伤害、撤销附魔并清除临时状态。
+    pub fn restored(&self) -> Card {
+        let mut card = self.clone();
+        card.health += std::mem::take(&mut card.damage_taken);
+        card.remove_enchantments(|_| true);
+        card.timed_effects.clear();
+        card.counters.clear();
+        card.frozen = false;
+        card.damage_prevention = 0;
+        card.aura = StatModifier::default();
+        card.owner = None;
+        card.exhausted = false;
+        card.summoning_sick = matches!(card.card_type, CardType::Unit);
+        card
+    }
+
+    /// 重置单位：清除召唤失调并恢复本回合的攻击次数。
+    pub fn refresh(&mut self) {
+        self.exhausted = false;
+        self.summoning_sick = false;
+    }
+
+    /// 单位当前能否攻击指定类型的目标（`attacks_hero` 为 true 表示攻击英雄）。
+    pub fn ready_to_attack(&self, attacks_hero: bool) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        !self.summoning_sick
+            || self.has_keyword(Keyword::Charge)
+            || (self.has_keyword(Keyword::Rush) && !attacks_hero)
+    }
+
+    /// 当前由光环提供的属性加成总和。
+    pub fn aura_bonus(&self) -> StatModifier {
+        self.enchantments
+            .iter()
+            .filter(|item| item.duration == EnchantmentDuration::Aura)
+            .fold(StatModifier::default(), |total, item| StatModifier {
+                attack: total.attack + item.attack,
+                health: total.health + item.health,
+            })
+    }
+
+    /// 对其他玩家隐藏的卡牌视图，仅保留 ID（奥秘保留类型）。
+    pub fn concealed(&self) -> Self {
+        let card_type = match self.card_type {
+            CardType::Secret => CardType::Secret,
+            _ => CardType::default(),
+        };
+        let mut card = Card::new(self.id, "", 0, 0, 0, card_type, Vec::new());
+        card.summoning_sick = false;
+        card
     }
 }
 
@@ -174,6 +806,9 @@ impl Card {
 pub struct Player {
     pub id: PlayerId,
     pub health: i16,
+    /// 英雄生命上限，用于计算已损失生命。
+    #[serde(default)]
+    pub max_health: i16,
     #[serde(default)]
     pub armor: u8,
     pub mana: u8,
@@ -185,6 +820,58 @@ pub struct Player {
     pub board: Vec<Card>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub deck: Vec<Card>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graveyard: Vec<Card>,
+    /// 面朝下的奥秘区。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<Card>,
+    /// 英雄接下来可抵消的伤害量，先于护甲结算。
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub damage_prevention: u8,
+    /// 下个回合开始时将被锁定的法力水晶数。
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub overloaded_mana: u8,
+    /// 本回合已打出的卡牌，按打出顺序排列，回合结束时清空。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cards_played_this_turn: Vec<CardId>,
+    /// 本回合已施放的法术数，回合结束时清零。
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub spells_played_this_turn: u8,
+    /// 已被揭示的手牌，只对揭示时登记的玩家（以及观战者）可见。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revealed_hand: Vec<RevealedCard>,
+    /// 作用于玩家本身的附魔。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enchantments: Vec<PlayerEnchantment>,
+    /// 本局已死亡的己方单位数。
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub units_died: u32,
+    /// 本局累积的对局统计。
+    #[serde(default, skip_serializing_if = "PlayerStats::is_empty")]
+    pub stats: PlayerStats,
+    /// 已承受的疲劳次数，下一次疲劳伤害为该值加一。
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub fatigue: u8,
+    /// 是否因牌库抽空而落败；与生命归零一样在下一次胜负检查时结算。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub decked_out: bool,
+    /// 英雄当前装备的武器。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weapon: Option<Card>,
+    /// 英雄本回合是否已经攻击过。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hero_attacked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero_power: Option<HeroPower>,
+    /// 玩家选用的英雄；未指定时没有职业。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero: Option<Hero>,
+    /// 由卡牌效果为该玩家登记的额外胜利条件。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_victories: Vec<AlternativeVictory>,
+    /// 组队模式下所属的队伍；同队玩家共享胜负且不能互相攻击，未分队时各自为战。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<TeamId>,
 }
 
 impl Player {
@@ -200,22 +887,73 @@ impl Player {
         Self {
             id,
             health,
+            max_health: health,
             armor,
             mana,
             max_mana: mana,
             hand,
             board,
             deck,
+            graveyard: Vec::new(),
+            secrets: Vec::new(),
+            damage_prevention: 0,
+            overloaded_mana: 0,
+            cards_played_this_turn: Vec::new(),
+            spells_played_this_turn: 0,
+            revealed_hand: Vec::new(),
+            enchantments: Vec::new(),
+            units_died: 0,
+            stats: PlayerStats::default(),
+            fatigue: 0,
+            decked_out: false,
+            weapon: None,
+            hero_attacked: false,
+            hero_power: None,
+            hero: None,
+            alternative_victories: Vec::new(),
+            team: None,
         }
     }
 
+    /// 把法力与水晶上限限制在 `MAX_MANA` 以内；旧存档缺少的上限由迁移补齐。
     pub fn reconcile_mana_cap(&mut self) {
-        if self.max_mana == 0 {
-            self.max_mana = self.mana;
-        }
-        if self.mana > self.max_mana {
-            self.mana = self.max_mana;
-        }
+        self.max_mana = self.max_mana.min(MAX_MANA);
+        self.mana = self.mana.min(MAX_MANA);
+    }
+
+    /// 回合开始时法力水晶上限加一（不超过 `cap`），再补满扣除过载锁定后的法力；
+    /// 上回合剩余或临时获得的法力不会留存。返回被锁定的水晶数。
+    pub fn refill_mana(&mut self, cap: u8) -> u8 {
+        self.max_mana = self.max_mana.saturating_add(1).min(cap);
+        let locked = std::mem::take(&mut self.overloaded_mana).min(self.max_mana);
+        self.mana = self.max_mana - locked;
+        locked
+    }
+
+    /// 玩家附魔对该卡牌费用的修正总和。
+    pub fn cost_adjustment(&self, card: &Card) -> i16 {
+        self.enchantments
+            .iter()
+            .filter_map(|enchantment| match enchantment.modifier {
+                PlayerModifier::Cost { delta, card_type }
+                    if card_type.is_none_or(|card_type| card_type == card.card_type) =>
+                {
+                    Some(delta as i16)
+                }
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// 玩家附魔对英雄所受伤害的修正总和。
+    pub fn damage_taken_adjustment(&self) -> i16 {
+        self.enchantments
+            .iter()
+            .filter_map(|enchantment| match enchantment.modifier {
+                PlayerModifier::DamageTaken { delta } => Some(delta as i16),
+                _ => None,
+            })
+            .sum()
     }
 
     pub fn find_card_in_hand_index(&self, card_id: CardId) -> Option<usize> {
@@ -227,39 +965,243 @@ impl Player {
         Some(self.hand.remove(idx))
     }
 
-    pub fn find_card_on_board_mut(&mut self, card_id: CardId) -> Option<&mut Card> {
-        self.board.iter_mut().find(|card| card.id == card_id)
+    /// 手牌 `card_id` 是否已对 `viewer` 揭示；观战者可以看到对任何玩家揭示过的手牌。
+    pub fn is_revealed_to(&self, card_id: CardId, viewer: PlayerId) -> bool {
+        self.revealed_hand.iter().any(|revealed| {
+            revealed.card_id == card_id && (revealed.viewer == viewer || viewer == SPECTATOR)
+        })
     }
 
-    pub fn ready_board(&mut self) {
-        for card in &mut self.board {
-            card.exhausted = false;
-        }
+    pub fn find_card_on_board(&self, card_id: CardId) -> Option<&Card> {
+        self.board.iter().find(|card| card.id == card_id)
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub fn find_card_on_board_mut(&mut self, card_id: CardId) -> Option<&mut Card> {
+        self.board.iter_mut().find(|card| card.id == card_id)
+    }
+
+    pub fn has_alternative_victory(&self, id: &str) -> bool {
+        self.alternative_victories
+            .iter()
+            .any(|victory| victory.id == id)
+    }
+
+    /// 单位在战场上从左到右的位置；单位离场时其余单位保持相对顺序。
+    pub fn board_position(&self, card_id: CardId) -> Option<usize> {
+        self.board.iter().position(|card| card.id == card_id)
+    }
+
+    /// 与该单位左右相邻的单位（先左后右）。
+    pub fn adjacent_units(&self, card_id: CardId) -> Vec<CardId> {
+        let Some(pos) = self.board_position(card_id) else {
+            return Vec::new();
+        };
+        let left = pos.checked_sub(1).and_then(|index| self.board.get(index));
+        let right = self.board.get(pos + 1);
+        left.into_iter().chain(right).map(|card| card.id).collect()
+    }
+
+    pub fn ready_board(&mut self) {
+        for card in &mut self.board {
+            card.refresh();
+        }
+        self.hero_attacked = false;
+        if let Some(hero_power) = &mut self.hero_power {
+            hero_power.used = false;
+        }
+    }
+
+    pub fn with_hero_power(mut self, hero_power: HeroPower) -> Self {
+        self.hero_power = Some(hero_power);
+        self
+    }
+
+    /// 指派英雄：获得其英雄技能与开局护甲。
+    pub fn with_hero(mut self, hero: Hero) -> Self {
+        if let Some(hero_power) = &hero.hero_power {
+            self.hero_power = Some(hero_power.clone());
+        }
+        self.armor = self.armor.saturating_add(hero.starting_armor);
+        self.hero = Some(hero);
+        self
+    }
+
+    pub fn class(&self) -> Option<&str> {
+        self.hero.as_ref().map(|hero| hero.class.as_str())
+    }
+
+    pub fn with_team(mut self, team: TeamId) -> Self {
+        self.team = Some(team);
+        self
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.health > 0 && !self.decked_out
+    }
+}
+
+/// 登记在状态中、等待未来触发时机结算的延迟效果。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScheduledEffect {
+    pub id: u64,
+    pub trigger: EffectTrigger,
+    pub player_id: PlayerId,
+    pub effect: EffectKind,
+    pub context: EffectContext,
+}
+
+/// 旧存档中的待弃牌记录，加载后迁移为 `PendingChoice`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PendingDiscard {
     pub id: u64,
     pub player_id: PlayerId,
     pub drawn_card: Card,
 }
 
-/// 游戏阶段。
+/// 两段式战斗中已宣告、尚未结算伤害的攻击。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingAttack {
+    pub attacker_owner: PlayerId,
+    pub attacker_id: CardId,
+    pub defender_owner: PlayerId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defender_card: Option<CardId>,
+}
+
+/// 交互结算模式下等待响应的窗口。
+///
+/// 窗口开启期间尚未结算的效果栈随窗口保存在状态中，存档或重新载入后仍可继续结算。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PriorityWindow {
+    /// 当前可以响应的玩家。
+    pub player_id: PlayerId,
+    /// 开启窗口的卡牌，响应效果以其为关联卡牌。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_card: Option<CardId>,
+    #[serde(default, skip_serializing_if = "EffectStack::is_empty")]
+    pub stack: EffectStack,
+}
+
+/// 等待玩家做出的选择。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingChoice {
+    pub id: u64,
+    pub player_id: PlayerId,
+    pub kind: ChoiceKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum ChoiceKind {
+    /// 手牌已满时抽到的牌：弃掉它或手牌中的一张。
+    Discard { drawn_card: Card },
+    /// 抉择：从若干模式中选择一个，以原效果的上下文结算。
+    ChooseOne {
+        options: Vec<ChoiceOption>,
+        context: EffectContext,
+    },
+    /// 发现：从候选卡牌中选择一张置入手牌。
+    Discover { cards: Vec<Card> },
+    /// 选择目标：以所选单位为上下文目标结算 `effect`。
+    SelectTarget {
+        targets: Vec<ChoiceTarget>,
+        effect: EffectKind,
+        context: EffectContext,
+    },
+}
+
+/// 目标选择中的一个候选单位。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChoiceTarget {
+    pub player_id: PlayerId,
+    pub card_id: CardId,
+}
+
+impl PendingChoice {
+    pub fn drawn_card(&self) -> Option<&Card> {
+        match &self.kind {
+            ChoiceKind::Discard { drawn_card } => Some(drawn_card),
+            _ => None,
+        }
+    }
+
+    /// 与状态无关的选项数；弃牌选择取决于手牌数量，返回 `None`，
+    /// 完整计数见 `GameState::choice_option_count`。
+    pub fn option_count(&self) -> Option<usize> {
+        match &self.kind {
+            ChoiceKind::Discard { .. } => None,
+            ChoiceKind::ChooseOne { options, .. } => Some(options.len()),
+            ChoiceKind::Discover { cards } => Some(cards.len()),
+            ChoiceKind::SelectTarget { targets, .. } => Some(targets.len()),
+        }
+    }
+}
+
+/// 游戏阶段。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GamePhase {
+    #[default]
     Mulligan,
     Main,
     Combat,
     End,
 }
 
-impl Default for GamePhase {
-    fn default() -> Self {
-        Self::Mulligan
+/// 操作类别，各阶段据此声明允许的操作。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ActionKind {
+    Mulligan,
+    /// 打出卡牌或使用英雄技能。
+    Play,
+    /// 单位或英雄攻击。
+    Attack,
+    ResolveChoice,
+    AdvancePhase,
+    EndTurn,
+}
+
+impl GamePhase {
+    /// 该阶段允许的操作；结束阶段自动结算回合结束，不接受玩家操作。
+    pub fn allowed_actions(&self) -> &'static [ActionKind] {
+        match self {
+            GamePhase::Mulligan => &[
+                ActionKind::Mulligan,
+                ActionKind::ResolveChoice,
+                ActionKind::AdvancePhase,
+            ],
+            GamePhase::Main => &[
+                ActionKind::Play,
+                ActionKind::ResolveChoice,
+                ActionKind::AdvancePhase,
+                ActionKind::EndTurn,
+            ],
+            GamePhase::Combat => &[
+                ActionKind::Attack,
+                ActionKind::ResolveChoice,
+                ActionKind::AdvancePhase,
+                ActionKind::EndTurn,
+            ],
+            GamePhase::End => &[ActionKind::ResolveChoice],
+        }
+    }
+
+    pub fn allows(&self, kind: ActionKind) -> bool {
+        self.allowed_actions().contains(&kind)
     }
 }
 
+/// 事件日志中的一条记录，带有整局唯一的序号与发生时的回合数。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoggedEvent {
+    /// 旧存档中缺失时为 0，载入后重新编号。
+    #[serde(default)]
+    pub seq: u64,
+    #[serde(default)]
+    pub turn: u32,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
 /// 游戏事件流。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -274,6 +1216,70 @@ pub enum GameEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         target_id: Option<CardId>,
     },
+    CardSummoned {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    /// 单位从墓地回到战场，随后会有对应的 `CardSummoned`。
+    CardResurrected {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    KeywordGranted {
+        player_id: PlayerId,
+        card_id: CardId,
+        keyword: Keyword,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires: Option<Expiry>,
+    },
+    KeywordRemoved {
+        player_id: PlayerId,
+        card_id: CardId,
+        keyword: Keyword,
+    },
+    CardSilenced {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    /// 单位从 `from` 的场上移到 `to` 的场上，`expires` 为限时控制的归还时机。
+    ControlChanged {
+        card_id: CardId,
+        from: PlayerId,
+        to: PlayerId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires: Option<Expiry>,
+    },
+    /// `player_id` 的部分手牌被揭示给 `viewer`。
+    HandCardsRevealed {
+        player_id: PlayerId,
+        viewer: PlayerId,
+        cards: Vec<Card>,
+    },
+    /// 手牌中的卡牌满足进化条件，变为 `card`。
+    CardTransformed {
+        player_id: PlayerId,
+        card_id: CardId,
+        card: Card,
+    },
+    /// 从空牌库抽牌受到的疲劳伤害。
+    FatigueDamage {
+        player_id: PlayerId,
+        amount: i16,
+    },
+    /// 单位的具名计数器数量发生变化。
+    CountersChanged {
+        player_id: PlayerId,
+        card_id: CardId,
+        counter: String,
+        total: u16,
+    },
+    /// 单位属性被设为固定值。
+    StatsSet {
+        player_id: PlayerId,
+        card_id: CardId,
+        attack: i16,
+        health: i16,
+    },
     AttackDeclared {
         attacker_owner: PlayerId,
         attacker_id: CardId,
@@ -296,6 +1302,92 @@ pub enum GameEvent {
         card_id: Option<CardId>,
         amount: i16,
     },
+    ArmorGained {
+        player_id: PlayerId,
+        amount: u8,
+    },
+    DamagePreventionGained {
+        player_id: PlayerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        card_id: Option<CardId>,
+        amount: u8,
+    },
+    /// 伤害在扣除生命（及护甲）前被抵消。
+    DamagePrevented {
+        source_player: PlayerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_card: Option<CardId>,
+        target_player: PlayerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target_card: Option<CardId>,
+        amount: i16,
+    },
+    ManaChanged {
+        player_id: PlayerId,
+        mana: u8,
+        max_mana: u8,
+    },
+    AuraChanged {
+        player_id: PlayerId,
+        card_id: CardId,
+        attack: i16,
+        health: i16,
+    },
+    StatsModified {
+        player_id: PlayerId,
+        card_id: CardId,
+        attack: i16,
+        health: i16,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires: Option<Expiry>,
+    },
+    CardFrozen {
+        player_id: PlayerId,
+        card_id: CardId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires: Option<Expiry>,
+    },
+    TimedEffectExpired {
+        player_id: PlayerId,
+        card_id: CardId,
+        effect: TimedEffectKind,
+    },
+    PlayerEnchanted {
+        player_id: PlayerId,
+        enchantment: PlayerEnchantment,
+    },
+    PlayerEnchantmentExpired {
+        player_id: PlayerId,
+        enchantment: PlayerEnchantment,
+    },
+    EffectScheduled {
+        player_id: PlayerId,
+        scheduled_id: u64,
+        trigger: EffectTrigger,
+    },
+    /// 效果入栈后，该玩家获得响应机会。
+    PriorityGranted {
+        player_id: PlayerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_card: Option<CardId>,
+    },
+    /// 玩家放弃响应，效果栈开始结算。
+    PriorityPassed {
+        player_id: PlayerId,
+    },
+    /// 单次结算处理的栈条目超出预算，剩余条目被丢弃。
+    ResolutionAborted {
+        budget: usize,
+        discarded: usize,
+    },
+    /// 栈中尚未结算的效果被反制移除。
+    EffectCountered {
+        player_id: PlayerId,
+        stack_item_id: u64,
+        effect_id: EffectId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_card: Option<CardId>,
+    },
     CardDestroyed {
         player_id: PlayerId,
         card: Card,
@@ -313,6 +1405,91 @@ pub enum GameEvent {
         player_id: PlayerId,
         card: Card,
     },
+    /// 过载累积，将在下个回合锁定法力水晶。
+    OverloadIncurred {
+        player_id: PlayerId,
+        amount: u8,
+    },
+    /// 回合开始时因过载锁定的法力水晶。
+    ManaOverloaded {
+        player_id: PlayerId,
+        locked: u8,
+    },
+    /// 打出卡牌时以生命值支付额外费用。
+    HealthPaid {
+        player_id: PlayerId,
+        card_id: CardId,
+        amount: i16,
+    },
+    SecretPlayed {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    WeaponEquipped {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    /// 武器耐久度降低但仍未摧毁。
+    WeaponDurabilityChanged {
+        player_id: PlayerId,
+        card_id: CardId,
+        durability: i16,
+    },
+    /// 武器耐久耗尽或被新武器替换，已移入墓地。
+    WeaponDestroyed {
+        player_id: PlayerId,
+        card: Card,
+    },
+    HeroPowerUsed {
+        player_id: PlayerId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target_id: Option<CardId>,
+    },
+    HeroAttackDeclared {
+        player_id: PlayerId,
+        defender_owner: PlayerId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        defender_id: Option<CardId>,
+    },
+    SecretRevealed {
+        player_id: PlayerId,
+        card: Card,
+    },
+    /// 抉择等待玩家选择，`options` 为各模式的描述。
+    ChoicePending {
+        player_id: PlayerId,
+        choice_id: u64,
+        options: Vec<String>,
+    },
+    /// 生成的卡牌（如发现）置入手牌。
+    CardAddedToHand {
+        player_id: PlayerId,
+        card_id: CardId,
+    },
+    ChoiceResolved {
+        player_id: PlayerId,
+        choice_id: u64,
+        option: usize,
+    },
+    /// 限次效果触发后剩余的次数。
+    EffectChargeSpent {
+        player_id: PlayerId,
+        card_id: CardId,
+        effect_id: EffectId,
+        remaining: u8,
+    },
+    CardMilled {
+        player_id: PlayerId,
+        card: Card,
+    },
+    /// 开局洗牌。
+    DeckShuffled {
+        player_id: PlayerId,
+    },
+    /// 开局掷硬币决定的先手玩家。
+    CoinFlipped {
+        first_player: PlayerId,
+    },
     MulliganApplied {
         player_id: PlayerId,
         replaced: Vec<CardId>,
@@ -320,19 +1497,105 @@ pub enum GameEvent {
     TurnEnded {
         player_id: PlayerId,
     },
+    PhaseChanged {
+        player_id: PlayerId,
+        phase: GamePhase,
+    },
     GameWon {
         winner: PlayerId,
         reason: VictoryReason,
     },
+    GameDrawn {
+        reason: DrawReason,
+    },
+    /// 对局结束时的统计，紧随 `GameWon`/`GameDrawn` 记录。
+    GameSummary {
+        turns: u32,
+        players: Vec<PlayerSummary>,
+    },
+}
+
+/// 对局结束时单个玩家的统计与剩余资源。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlayerSummary {
+    pub player_id: PlayerId,
+    #[serde(flatten)]
+    pub stats: PlayerStats,
+    pub health: i16,
+    pub armor: u8,
+    pub mana: u8,
+    pub max_mana: u8,
+    pub hand_size: usize,
+    pub deck_size: usize,
+    pub board_size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum IntegrityError {
-    InvalidPlayerIndex { player_id: PlayerId },
-    DuplicateCardId { card_id: CardId },
-    NegativeHealth { player_id: PlayerId, value: i16 },
-    ManaOutOfRange { player_id: PlayerId, value: u8 },
+    InvalidPlayerIndex {
+        player_id: PlayerId,
+    },
+    DuplicateCardId {
+        card_id: CardId,
+    },
+    NegativeHealth {
+        player_id: PlayerId,
+        value: i16,
+    },
+    ManaOutOfRange {
+        player_id: PlayerId,
+        value: u8,
+    },
+    HandTooLarge {
+        player_id: PlayerId,
+        size: usize,
+        max: u8,
+    },
+    BoardTooLarge {
+        player_id: PlayerId,
+        size: usize,
+        max: u8,
+    },
+    /// 手牌或牌库中的卡牌、或调度阶段的任意卡牌被标记为已攻击。
+    ExhaustedOutOfPlay {
+        card_id: CardId,
+    },
+    /// 已有胜负结果，但完整的事件日志中没有对应的 `GameWon`。
+    OutcomeNotLogged {
+        winner: PlayerId,
+    },
+    /// 同时记录了胜负与平局。
+    ConflictingOutcome,
+    PendingChoicePlayerMissing {
+        choice_id: u64,
+        player_id: PlayerId,
+    },
+    /// 目标选择引用的单位已不在场上。
+    PendingChoiceCardMissing {
+        choice_id: u64,
+        card_id: CardId,
+    },
+    CardStatOutOfRange {
+        card_id: CardId,
+        stat: CardStat,
+        value: i16,
+    },
+}
+
+/// 参与完整性检查的卡牌数值。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CardStat {
+    Cost,
+    Attack,
+    Health,
+}
+
+/// 开局参数：洗牌前的牌组（按玩家顺序）与先手玩家。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GameSetup {
+    pub decks: Vec<Vec<Card>>,
+    pub first_player: PlayerId,
 }
 
 /// 游戏整体状态。
@@ -343,22 +1606,62 @@ pub struct GameState {
     pub current_player: PlayerId,
     pub turn: u32,
     pub phase: GamePhase,
-    #[serde(default)]
-    pub max_hand_size: u8,
-    #[serde(default)]
-    pub max_board_size: u8,
+    /// 本局的规则参数，序列化时平铺在状态顶层。
+    #[serde(flatten)]
+    pub config: RuleConfig,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mulligan_completed: Vec<PlayerId>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_choices: Vec<PendingChoice>,
+    /// 仅用于读取旧存档，`reconcile_after_load` 会将其迁移到 `pending_choices`。
+    #[serde(default, skip_serializing)]
     pub pending_discards: Vec<PendingDiscard>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub event_log: Vec<GameEvent>,
+    pub scheduled_effects: Vec<ScheduledEffect>,
+    /// 两段式战斗中等待 `resolve_combat` 的攻击。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_attack: Option<PendingAttack>,
+    /// 交互结算模式下尚未关闭的响应窗口。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_window: Option<PriorityWindow>,
+    /// 已记录的事件，超过 `max_event_log` 时裁剪最早的记录。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_log: Vec<LoggedEvent>,
+    /// 下一条事件的序号，裁剪日志后仍保持单调递增。
+    #[serde(default)]
+    pub next_event_seq: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub outcome: Option<VictoryState>,
+    /// 平局结果，与 `outcome` 互斥。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draw: Option<DrawReason>,
+    #[serde(default, alias = "next_pending_discard_id")]
+    pub next_pending_choice_id: u64,
+    #[serde(default)]
+    pub next_scheduled_effect_id: u64,
+    /// 下一张生成卡牌的实例 ID，只增不减，已离场卡牌的 ID 不会被复用。
     #[serde(default)]
-    pub next_pending_discard_id: u64,
+    pub next_card_instance_id: CardId,
     #[serde(default)]
     pub version: u64,
+    /// 存档格式版本，旧存档中缺失时为 0，载入时由迁移层升级。
+    #[serde(default)]
+    pub schema_version: u32,
+    /// 对局内所有随机行为共用的随机源。
+    #[serde(default)]
+    pub rng: GameRng,
+    /// 以 `new_game` 开局时的牌组与先手，供 `rematch` 重开。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<GameSetup>,
+    /// `OnGameStart` 效果是否已经结算，保证只触发一次。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub game_start_resolved: bool,
+    /// `OnVictory` 效果是否已经结算，保证只触发一次。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub victory_effects_resolved: bool,
+    /// 发现等效果生成卡牌时选取的卡牌定义，随状态保存以便回放得到相同的候选。
+    #[serde(default, skip_serializing_if = "CardRegistry::is_empty")]
+    pub card_pool: CardRegistry,
 }
 
 impl GameState {
@@ -368,20 +1671,35 @@ impl GameState {
             player.reconcile_mana_cap();
         }
 
-        Self {
+        let mut state = Self {
             players,
             current_player,
             turn: 1,
             phase: GamePhase::default(),
-            max_hand_size: DEFAULT_MAX_HAND_SIZE,
-            max_board_size: DEFAULT_MAX_BOARD_SIZE,
+            config: RuleConfig::default(),
             mulligan_completed: Vec::new(),
+            pending_choices: Vec::new(),
             pending_discards: Vec::new(),
+            scheduled_effects: Vec::new(),
+            pending_attack: None,
+            priority_window: None,
             event_log: Vec::new(),
+            next_event_seq: 1,
             outcome: None,
-            next_pending_discard_id: 0,
+            draw: None,
+            next_pending_choice_id: 0,
+            next_scheduled_effect_id: 0,
+            next_card_instance_id: 0,
             version: 1,
-        }
+            schema_version: SCHEMA_VERSION,
+            rng: GameRng::default(),
+            setup: None,
+            game_start_resolved: false,
+            victory_effects_resolved: false,
+            card_pool: CardRegistry::new(),
+        };
+        state.next_card_instance_id = state.unused_card_id();
+        state
     }
 
     pub fn with_phase(mut self, phase: GamePhase) -> Self {
@@ -389,21 +1707,182 @@ impl GameState {
         self
     }
 
+    /// 应用规则配置，并将所有英雄的生命重置为配置的开局生命值。
+    pub fn with_config(mut self, config: RuleConfig) -> Self {
+        for player in &mut self.players {
+            player.health = config.starting_health;
+            player.max_health = config.starting_health;
+            player.max_mana = player.max_mana.min(config.max_mana);
+            player.mana = player.mana.min(config.max_mana);
+        }
+        self.config = config;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = GameRng::new(seed);
+        self
+    }
+
+    /// 以两副牌组开局：用种子洗牌、掷硬币决定先手、发放起手牌（先手 3 张、后手 4 张，
+    /// 再加上配置的补偿牌数）并进入调度阶段，各步骤的事件均记录在日志中。
+    pub fn new_game(deck_a: Vec<Card>, deck_b: Vec<Card>, config: RuleConfig, seed: u64) -> Self {
+        Self::start_game(vec![deck_a, deck_b], config, seed, None)
+    }
+
+    /// 以开局时的牌组、当前规则配置与卡池再来一局，由上一局的后手先行；
+    /// 状态不是由 `new_game` 创建时返回 `None`。
+    pub fn rematch(&self, seed: u64) -> Option<Self> {
+        let setup = self.setup.as_ref()?;
+        let first = setup.first_player;
+        let next_first = self
+            .players
+            .iter()
+            .map(|player| player.id)
+            .find(|id| *id != first)
+            .unwrap_or(first);
+        let mut next = Self::start_game(
+            setup.decks.clone(),
+            self.config.clone(),
+            seed,
+            Some(next_first),
+        );
+        next.card_pool = self.card_pool.clone();
+        Some(next)
+    }
+
+    /// 未指定先手时掷硬币决定。
+    fn start_game(
+        decks: Vec<Vec<Card>>,
+        config: RuleConfig,
+        seed: u64,
+        first: Option<PlayerId>,
+    ) -> Self {
+        let players = decks
+            .iter()
+            .cloned()
+            .zip(0..)
+            .map(|(deck, id)| Player::new(id, 0, 0, 0, Vec::new(), Vec::new(), deck))
+            .collect();
+        let mut state = Self::new(players, 0).with_config(config).with_seed(seed);
+        state.reset_for_mulligan();
+
+        for index in 0..state.players.len() {
+            let player = &mut state.players[index];
+            state.rng.shuffle(&mut player.deck);
+            let event = GameEvent::DeckShuffled {
+                player_id: player.id,
+            };
+            state.record_event(event);
+        }
+
+        let seats = state.players.len().max(1) as u64;
+        let first = first.unwrap_or_else(|| (state.rng.next_u64() % seats) as PlayerId);
+        state.current_player = first;
+        state.setup = Some(GameSetup {
+            decks,
+            first_player: first,
+        });
+        state.record_event(GameEvent::CoinFlipped {
+            first_player: first,
+        });
+
+        state.draw_initial_hand(OPENING_HAND_SIZE);
+        // 先手以外的每个座位多抽一张
+        let later_seats: Vec<PlayerId> = state
+            .players
+            .iter()
+            .map(|player| player.id)
+            .filter(|id| *id != first)
+            .collect();
+        for player_id in later_seats {
+            if let Some(event) = state.draw_card(player_id) {
+                state.record_event(event);
+            }
+        }
+        state.record_event(GameEvent::PhaseChanged {
+            player_id: first,
+            phase: GamePhase::Mulligan,
+        });
+        state
+    }
+
     pub fn record_event(&mut self, event: GameEvent) {
-        self.event_log.push(event);
+        if let GameEvent::CardDestroyed { player_id, card } = &event {
+            if card.card_type == CardType::Unit {
+                if let Some(player) = self.get_player_mut(*player_id) {
+                    player.units_died = player.units_died.saturating_add(1);
+                }
+            }
+        }
+        self.event_log.push(LoggedEvent {
+            seq: self.next_event_seq,
+            turn: self.turn,
+            event,
+        });
+        self.next_event_seq = self.next_event_seq.saturating_add(1);
         self.version = self.version.saturating_add(1);
+        if let Some(cap) = self.config.max_event_log {
+            let excess = self.event_log.len().saturating_sub(cap);
+            self.event_log.drain(..excess);
+        }
+    }
+
+    /// 按记录顺序遍历日志中仍保留的事件。
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &GameEvent> {
+        self.event_log.iter().map(|entry| &entry.event)
+    }
+
+    /// 序号大于 `seq` 的日志记录，传入 0 返回全部保留的记录。
+    pub fn events_since(&self, seq: u64) -> &[LoggedEvent] {
+        let start = self.event_log.partition_point(|entry| entry.seq <= seq);
+        &self.event_log[start..]
     }
 
     pub fn reconcile_after_load(&mut self) {
         for player in &mut self.players {
             player.reconcile_mana_cap();
+            player.max_health = player.max_health.max(player.health);
+            for card in &mut player.board {
+                let legacy_aura = std::mem::take(&mut card.aura);
+                if !legacy_aura.is_zero() {
+                    card.enchantments.push(Enchantment {
+                        source_player: None,
+                        source_card: None,
+                        attack: legacy_aura.attack,
+                        health: legacy_aura.health,
+                        duration: EnchantmentDuration::Aura,
+                    });
+                }
+            }
+        }
+        for legacy in std::mem::take(&mut self.pending_discards) {
+            self.pending_choices.push(PendingChoice {
+                id: legacy.id,
+                player_id: legacy.player_id,
+                kind: ChoiceKind::Discard {
+                    drawn_card: legacy.drawn_card,
+                },
+            });
         }
-        if let Some(max_id) = self.pending_discards.iter().map(|pending| pending.id).max() {
-            self.next_pending_discard_id = max_id.saturating_add(1);
+        if let Some(max_id) = self.pending_choices.iter().map(|pending| pending.id).max() {
+            self.next_pending_choice_id = self.next_pending_choice_id.max(max_id + 1);
         }
+        if let Some(max_id) = self.scheduled_effects.iter().map(|item| item.id).max() {
+            self.next_scheduled_effect_id = self.next_scheduled_effect_id.max(max_id + 1);
+        }
+        self.next_card_instance_id = self.next_card_instance_id.max(self.unused_card_id());
         if self.version == 0 {
             self.version = (self.event_log.len() as u64).saturating_add(1);
         }
+        // 旧存档中的事件没有序号，按记录顺序补齐
+        if self.event_log.iter().any(|entry| entry.seq == 0) {
+            for (entry, seq) in self.event_log.iter_mut().zip(1..) {
+                entry.seq = seq;
+            }
+        }
+        let last_seq = self.event_log.last().map_or(0, |entry| entry.seq);
+        self.next_event_seq = self.next_event_seq.max(last_seq + 1);
     }
 
     pub fn reset_for_mulligan(&mut self) {
@@ -440,15 +1919,123 @@ impl GameState {
         self.players.iter().position(|player| player.id == id)
     }
 
-    pub fn opponent_of(&self, player_id: PlayerId) -> Option<PlayerId> {
+    /// 同一玩家或同队玩家互为盟友；未分队的玩家只与自己结盟。
+    pub fn are_allies(&self, a: PlayerId, b: PlayerId) -> bool {
+        if a == b {
+            return true;
+        }
+        let team = |id| self.get_player(id).and_then(|player| player.team);
+        team(a).is_some_and(|team_a| team(b) == Some(team_a))
+    }
+
+    /// 包括自身在内的全部盟友，按座位顺序排列。
+    pub fn allies_of(&self, player_id: PlayerId) -> Vec<PlayerId> {
+        self.players
+            .iter()
+            .filter(|player| self.are_allies(player_id, player.id))
+            .map(|player| player.id)
+            .collect()
+    }
+
+    /// 全部敌对玩家（含已阵亡者），按座位顺序排列。
+    pub fn enemies_of(&self, player_id: PlayerId) -> Vec<PlayerId> {
         self.players
             .iter()
-            .find(|player| player.id != player_id)
+            .filter(|player| !self.are_allies(player_id, player.id))
+            .map(|player| player.id)
+            .collect()
+    }
+
+    /// 首个存活的敌对玩家；敌方全部阵亡时退回首个敌对玩家。
+    pub fn opponent_of(&self, player_id: PlayerId) -> Option<PlayerId> {
+        let enemies = self.enemies_of(player_id);
+        enemies
+            .iter()
+            .copied()
+            .find(|&id| self.get_player(id).is_some_and(Player::is_alive))
+            .or_else(|| enemies.first().copied())
+    }
+
+    /// 当前应当行动的玩家：有响应窗口时为持有优先权的玩家，否则为回合玩家。
+    pub fn acting_player(&self) -> PlayerId {
+        self.priority_window
+            .as_ref()
+            .map_or(self.current_player, |window| window.player_id)
+    }
+
+    /// 按座位顺序排在 `player_id` 之后的下一位存活玩家；其余玩家都阵亡时退回紧邻的下一位。
+    pub fn next_player_after(&self, player_id: PlayerId) -> Option<PlayerId> {
+        let index = self.player_index(player_id)?;
+        let mut others = self
+            .players
+            .iter()
+            .cycle()
+            .skip(index + 1)
+            .take(self.players.len() - 1);
+        let fallback = others.clone().next().map(|player| player.id);
+        others
+            .find(|player| player.is_alive())
             .map(|player| player.id)
+            .or(fallback)
+    }
+
+    /// 计算手牌在当前场面下的实际费用（叠加己方场上的持续性费用修正）。
+    pub fn card_cost(&self, player_id: PlayerId, card: &Card) -> u8 {
+        let Some(player) = self.get_player(player_id) else {
+            return card.cost;
+        };
+        let mut delta = player.cost_adjustment(card);
+        for source in &player.board {
+            for effect in &source.effects {
+                if effect.trigger != EffectTrigger::Passive {
+                    continue;
+                }
+                let EffectKind::CostModifier {
+                    delta: modifier,
+                    card_type,
+                } = &effect.kind
+                else {
+                    continue;
+                };
+                if card_type.is_some_and(|card_type| card_type != card.card_type) {
+                    continue;
+                }
+                if let Some(condition) = &effect.condition {
+                    let ctx =
+                        EffectContext::new(EffectTrigger::Passive, player_id, self.current_player)
+                            .with_source_card(source.id);
+                    if !condition.is_satisfied(&ctx, self) {
+                        continue;
+                    }
+                }
+                delta += *modifier as i16;
+            }
+        }
+        (card.cost as i16 + delta).clamp(0, u8::MAX as i16) as u8
+    }
+
+    /// 查询手牌的实际费用，供界面展示与出牌校验使用。
+    pub fn effective_cost(&self, player_id: PlayerId, card_id: CardId) -> Option<u8> {
+        let player = self.get_player(player_id)?;
+        let card = player.hand.iter().find(|card| card.id == card_id)?;
+        Some(self.card_cost(player_id, card))
     }
 
     pub fn is_finished(&self) -> bool {
-        self.outcome.is_some()
+        self.outcome.is_some() || self.draw.is_some()
+    }
+
+    /// 已决出的对局结果，胜负与平局统一表示。
+    pub fn game_outcome(&self) -> Option<GameOutcome> {
+        if let Some(reason) = &self.draw {
+            return Some(GameOutcome::Draw {
+                reason: reason.clone(),
+            });
+        }
+        self.outcome.as_ref().map(|victory| GameOutcome::Victory {
+            winner: victory.winner,
+            reason: victory.reason.clone(),
+        })
     }
 
     pub fn damage_player(
@@ -457,10 +2044,30 @@ impl GameState {
         source_card: Option<CardId>,
         target_player: PlayerId,
         amount: i16,
-    ) -> Option<GameEvent> {
-        let player = self.get_player_mut(target_player)?;
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let Some(player) = self.get_player_mut(target_player) else {
+            return events;
+        };
+        let amount = amount + player.damage_taken_adjustment();
         if amount <= 0 {
-            return None;
+            return events;
+        }
+
+        // 替换层：抵消先于护甲结算
+        let prevented = consume_prevention(&mut player.damage_prevention, amount);
+        if prevented > 0 {
+            events.push(GameEvent::DamagePrevented {
+                source_player,
+                source_card,
+                target_player,
+                target_card: None,
+                amount: prevented,
+            });
+        }
+        let amount = amount - prevented;
+        if amount == 0 {
+            return events;
         }
 
         let mut remaining = amount;
@@ -474,31 +2081,18 @@ impl GameState {
             player.health -= remaining;
         }
 
-        let event = GameEvent::DamageResolved {
+        events.push(GameEvent::DamageResolved {
             source_player,
             source_card,
             target_player,
             target_card: None,
             amount,
-        };
-
-        if player.health <= 0 {
-            if let Some(winner) = self
-                .players
-                .iter()
-                .find(|p| p.id != target_player)
-                .map(|p| p.id)
-            {
-                self.declare_victory(
-                    winner,
-                    VictoryReason::HealthDepleted {
-                        loser: target_player,
-                    },
-                );
-            }
-        }
+        });
 
-        Some(event)
+        // 胜负留待伤害全部结算后由 `evaluate_victory` 判定，双方同时阵亡时才能判为平局
+        self.record_damage(source_player, &events);
+        events.extend(self.lifesteal_heal(source_player, source_card, &events));
+        events
     }
 
     pub fn damage_card(
@@ -518,7 +2112,22 @@ impl GameState {
             if let Some(pos) = player.board.iter().position(|card| card.id == target_card) {
                 let mut destroyed_card = None;
                 if let Some(card) = player.board.get_mut(pos) {
+                    let prevented = consume_prevention(&mut card.damage_prevention, amount);
+                    if prevented > 0 {
+                        events.push(GameEvent::DamagePrevented {
+                            source_player,
+                            source_card,
+                            target_player,
+                            target_card: Some(target_card),
+                            amount: prevented,
+                        });
+                    }
+                    let amount = amount - prevented;
+                    if amount == 0 {
+                        return events;
+                    }
                     card.health -= amount;
+                    card.damage_taken = card.damage_taken.saturating_add(amount);
                     events.push(GameEvent::DamageResolved {
                         source_player,
                         source_card,
@@ -532,6 +2141,7 @@ impl GameState {
                 }
                 if let Some(dead_card) = destroyed_card {
                     player.board.remove(pos);
+                    player.graveyard.push(dead_card.clone());
                     events.push(GameEvent::CardDestroyed {
                         player_id: target_player,
                         card: dead_card,
@@ -540,23 +2150,683 @@ impl GameState {
             }
         }
 
-        events
+        self.record_damage(source_player, &events);
+        events.extend(self.lifesteal_heal(source_player, source_card, &events));
+        events
+    }
+
+    /// 为场上单位附加属性附魔；`Until` 附魔到期后撤销。
+    pub fn modify_card_stats(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        enchantment: Enchantment,
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let Enchantment { attack, health, .. } = enchantment;
+        if attack == 0 && health == 0 {
+            return events;
+        }
+        let expires = match enchantment.duration {
+            EnchantmentDuration::Until { expires } => Some(expires),
+            _ => None,
+        };
+        let Some(player) = self.get_player_mut(player_id) else {
+            return events;
+        };
+        let Some(pos) = player.board.iter().position(|card| card.id == card_id) else {
+            return events;
+        };
+
+        let card = &mut player.board[pos];
+        card.enchant(enchantment);
+        events.push(GameEvent::StatsModified {
+            player_id,
+            card_id,
+            attack,
+            health,
+            expires,
+        });
+
+        if card.health <= 0 {
+            events.extend(self.destroy_card(player_id, card_id));
+            if let Some(source_player) = enchantment.source_player {
+                self.record_damage(source_player, &events);
+            }
+        }
+        events
+    }
+
+    /// 将生命值归零的场上单位移入墓地；亡语等死亡触发由效果引擎根据返回的事件入栈。
+    pub fn destroy_card(&mut self, player_id: PlayerId, card_id: CardId) -> Option<GameEvent> {
+        let player = self.get_player_mut(player_id)?;
+        let pos = player.board.iter().position(|card| card.id == card_id)?;
+        let dead_card = player.board.remove(pos);
+        player.graveyard.push(dead_card.clone());
+        Some(GameEvent::CardDestroyed {
+            player_id,
+            card: dead_card,
+        })
+    }
+
+    pub fn freeze_card(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        expires: Option<Expiry>,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        card.frozen = true;
+        if let Some(expires) = expires {
+            card.timed_effects.push(TimedEffect {
+                kind: TimedEffectKind::Frozen,
+                expires,
+            });
+        }
+        Some(GameEvent::CardFrozen {
+            player_id,
+            card_id,
+            expires,
+        })
+    }
+
+    /// 赋予场上单位关键字；已拥有该关键字时不重复叠加。
+    pub fn grant_keyword(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        keyword: Keyword,
+        expires: Option<Expiry>,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        if card.has_keyword(keyword) {
+            return None;
+        }
+        card.keywords.push(keyword);
+        if let Some(expires) = expires {
+            card.timed_effects.push(TimedEffect {
+                kind: TimedEffectKind::Keyword { keyword },
+                expires,
+            });
+        }
+        Some(GameEvent::KeywordGranted {
+            player_id,
+            card_id,
+            keyword,
+            expires,
+        })
+    }
+
+    /// 移除场上单位的关键字（连同其限时来源），单位没有该关键字时返回 `None`。
+    pub fn remove_keyword(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        keyword: Keyword,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        if !card.has_keyword(keyword) {
+            return None;
+        }
+        card.keywords.retain(|item| *item != keyword);
+        card.timed_effects
+            .retain(|timed| timed.kind != TimedEffectKind::Keyword { keyword });
+        Some(GameEvent::KeywordRemoved {
+            player_id,
+            card_id,
+            keyword,
+        })
+    }
+
+    /// 把 `from` 场上的单位移到 `to` 场上。限时控制的单位本回合即可攻击，到期后归还原拥有者；
+    /// 永久夺取的单位视为刚进场。目标场地已满时不移动。
+    pub fn change_control(
+        &mut self,
+        from: PlayerId,
+        card_id: CardId,
+        to: PlayerId,
+        expires: Option<Expiry>,
+    ) -> Option<GameEvent> {
+        let max_board_size = self.config.max_board_size as usize;
+        if from == to || self.get_player(to)?.board.len() >= max_board_size {
+            return None;
+        }
+        let source = self.get_player_mut(from)?;
+        let pos = source.board.iter().position(|card| card.id == card_id)?;
+        let mut card = source.board.remove(pos);
+        let owner = card.owner_or(from);
+        card.owner = (owner != to).then_some(owner);
+        card.timed_effects
+            .retain(|timed| timed.kind != TimedEffectKind::Control);
+        if let Some(expires) = expires {
+            card.timed_effects.push(TimedEffect {
+                kind: TimedEffectKind::Control,
+                expires,
+            });
+        }
+        card.summoning_sick = expires.is_none();
+        self.get_player_mut(to)?.board.push(card);
+        Some(GameEvent::ControlChanged {
+            card_id,
+            from,
+            to,
+            expires,
+        })
+    }
+
+    /// 沉默场上单位；失去生命加成后生命归零的单位随即死亡。
+    pub fn silence_card(&mut self, player_id: PlayerId, card_id: CardId) -> Vec<GameEvent> {
+        let Some(card) = self
+            .get_player_mut(player_id)
+            .and_then(|player| player.find_card_on_board_mut(card_id))
+        else {
+            return Vec::new();
+        };
+        card.silence();
+        let dead = card.health <= 0;
+        let mut events = vec![GameEvent::CardSilenced { player_id, card_id }];
+        if dead {
+            events.extend(self.destroy_card(player_id, card_id));
+        }
+        events
+    }
+
+    /// 随机揭示 `player_id` 至多 `count` 张尚未公开的手牌。
+    pub fn reveal_hand_cards(
+        &mut self,
+        viewer: PlayerId,
+        player_id: PlayerId,
+        count: u8,
+    ) -> Option<GameEvent> {
+        let index = self.player_index(player_id)?;
+        let player = &mut self.players[index];
+        let hidden: Vec<usize> = player
+            .hand
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| !player.is_revealed_to(card.id, viewer))
+            .map(|(index, _)| index)
+            .collect();
+        let mut chosen: Vec<usize> = self
+            .rng
+            .choose_multiple(&hidden, count as usize)
+            .into_iter()
+            .copied()
+            .collect();
+        if chosen.is_empty() {
+            return None;
+        }
+        chosen.sort_unstable();
+        let cards: Vec<Card> = chosen
+            .into_iter()
+            .map(|index| player.hand[index].clone())
+            .collect();
+        player
+            .revealed_hand
+            .extend(cards.iter().map(|card| RevealedCard {
+                card_id: card.id,
+                viewer,
+            }));
+        Some(GameEvent::HandCardsRevealed {
+            player_id,
+            viewer,
+            cards,
+        })
+    }
+
+    /// 将本次结算造成的伤害、承受的伤害与消灭的单位计入统计。
+    fn record_damage(&mut self, source_player: PlayerId, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::DamageResolved {
+                    target_player,
+                    target_card,
+                    amount,
+                    ..
+                } => {
+                    let amount = (*amount).max(0) as u32;
+                    if let Some(player) = self.get_player_mut(source_player) {
+                        let stats = &mut player.stats;
+                        stats.damage_dealt = stats.damage_dealt.saturating_add(amount);
+                        stats.largest_hit = stats.largest_hit.max(amount);
+                    }
+                    if target_card.is_none() {
+                        if let Some(player) = self.get_player_mut(*target_player) {
+                            player.stats.damage_taken =
+                                player.stats.damage_taken.saturating_add(amount);
+                        }
+                    }
+                }
+                GameEvent::CardDestroyed { player_id, .. }
+                    if !self.are_allies(source_player, *player_id) =>
+                {
+                    if let Some(player) = self.get_player_mut(source_player) {
+                        player.stats.units_killed = player.stats.units_killed.saturating_add(1);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 伤害来源为场上的吸血单位时，为其拥有者恢复造成的伤害量。
+    fn lifesteal_heal(
+        &mut self,
+        source_player: PlayerId,
+        source_card: Option<CardId>,
+        events: &[GameEvent],
+    ) -> Option<GameEvent> {
+        let card_id = source_card?;
+        let has_lifesteal = self
+            .get_player(source_player)?
+            .board
+            .iter()
+            .any(|card| card.id == card_id && card.has_keyword(Keyword::Lifesteal));
+        if !has_lifesteal {
+            return None;
+        }
+        let dealt: i16 = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::DamageResolved { amount, .. } => Some(*amount),
+                _ => None,
+            })
+            .sum();
+        self.heal_player(source_player, dealt)
+    }
+
+    /// 撤销所有在指定时机到期的限时效果。
+    ///
+    /// 与移除生命光环相同，失去的生命加成从当前生命中扣除而已受的伤害保留，
+    /// 生命因此归零的单位经 `destroy_card` 死亡。
+    pub fn expire_timed_effects(&mut self, moment: Expiry) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let mut returning = Vec::new();
+        let mut dying = Vec::new();
+        for player in &mut self.players {
+            let (expired, remaining): (Vec<PlayerEnchantment>, Vec<PlayerEnchantment>) = player
+                .enchantments
+                .drain(..)
+                .partition(|enchantment| enchantment.expires == Some(moment));
+            player.enchantments = remaining;
+            events.extend(expired.into_iter().map(|enchantment| {
+                GameEvent::PlayerEnchantmentExpired {
+                    player_id: player.id,
+                    enchantment,
+                }
+            }));
+            for card in &mut player.board {
+                let (expired, remaining): (Vec<TimedEffect>, Vec<TimedEffect>) = card
+                    .timed_effects
+                    .drain(..)
+                    .partition(|effect| effect.expires == moment);
+                card.timed_effects = remaining;
+                for effect in expired {
+                    match effect.kind {
+                        TimedEffectKind::Stats { attack, health } => {
+                            card.attack = (card.attack - attack).max(0);
+                            card.health -= health;
+                        }
+                        TimedEffectKind::Frozen => {
+                            card.frozen = card
+                                .timed_effects
+                                .iter()
+                                .any(|other| other.kind == TimedEffectKind::Frozen);
+                        }
+                        TimedEffectKind::Keyword { keyword } => {
+                            if let Some(pos) = card.keywords.iter().position(|k| *k == keyword) {
+                                card.keywords.remove(pos);
+                            }
+                        }
+                        TimedEffectKind::Control => {
+                            returning.push((player.id, card.id, card.owner_or(player.id)));
+                        }
+                    }
+                    events.push(GameEvent::TimedEffectExpired {
+                        player_id: player.id,
+                        card_id: card.id,
+                        effect: effect.kind,
+                    });
+                }
+                let until = EnchantmentDuration::Until { expires: moment };
+                for expired in card.remove_enchantments(|item| item.duration == until) {
+                    events.push(GameEvent::TimedEffectExpired {
+                        player_id: player.id,
+                        card_id: card.id,
+                        effect: TimedEffectKind::Stats {
+                            attack: expired.attack,
+                            health: expired.health,
+                        },
+                    });
+                }
+                if card.health <= 0 {
+                    dying.push((player.id, card.id));
+                }
+            }
+        }
+        for (player_id, card_id) in dying {
+            events.extend(self.destroy_card(player_id, card_id));
+        }
+        // 原拥有者场地已满时，单位留在控制者一方
+        for (controller, card_id, owner) in returning {
+            events.extend(self.change_control(controller, card_id, owner, None));
+        }
+        events
+    }
+
+    pub fn enchant_player(
+        &mut self,
+        player_id: PlayerId,
+        enchantment: PlayerEnchantment,
+    ) -> Option<GameEvent> {
+        self.get_player_mut(player_id)?
+            .enchantments
+            .push(enchantment);
+        Some(GameEvent::PlayerEnchanted {
+            player_id,
+            enchantment,
+        })
+    }
+
+    /// 为英雄恢复生命，不超过生命上限；事件与统计只记录实际恢复量，未恢复任何生命时返回 `None`。
+    pub fn heal_player(&mut self, player_id: PlayerId, amount: i16) -> Option<GameEvent> {
+        if amount <= 0 {
+            return None;
+        }
+        let player = self.get_player_mut(player_id)?;
+        let healed = amount.min(player.max_health - player.health);
+        if healed <= 0 {
+            return None;
+        }
+        player.health += healed;
+        player.stats.healing = player.stats.healing.saturating_add(healed as u32);
+        let event = GameEvent::CardHealed {
+            player_id,
+            card_id: None,
+            amount: healed,
+        };
+        Some(event)
+    }
+
+    pub fn gain_armor(&mut self, player_id: PlayerId, amount: u8) -> Option<GameEvent> {
+        if amount == 0 {
+            return None;
+        }
+        let player = self.get_player_mut(player_id)?;
+        player.armor = player.armor.saturating_add(amount);
+        Some(GameEvent::ArmorGained { player_id, amount })
+    }
+
+    /// 将场上单位的属性设为给定值，移除光环以外的附魔并清除伤害。
+    pub fn set_card_stats(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        attack: i16,
+        health: i16,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        card.enchantments
+            .retain(|item| item.duration == EnchantmentDuration::Aura);
+        card.timed_effects
+            .retain(|effect| !matches!(effect.kind, TimedEffectKind::Stats { .. }));
+        let aura = card.aura_bonus();
+        card.attack = (attack + aura.attack).max(0);
+        card.health = (health + aura.health).max(1);
+        card.damage_taken = 0;
+        Some(GameEvent::StatsSet {
+            player_id,
+            card_id,
+            attack,
+            health,
+        })
+    }
+
+    /// 将满足进化条件的手牌替换为进化后的卡牌。
+    pub fn upgrade_hand_cards(&mut self) -> Vec<GameEvent> {
+        let mut ready = Vec::new();
+        for (player_index, player) in self.players.iter().enumerate() {
+            for (card_index, card) in player.hand.iter().enumerate() {
+                let Some(upgrade) = &card.upgrade else {
+                    continue;
+                };
+                let ctx =
+                    EffectContext::new(EffectTrigger::Passive, player.id, self.current_player)
+                        .with_source_card(card.id);
+                if upgrade.condition.is_satisfied(&ctx, self) {
+                    ready.push((player_index, card_index));
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        for (player_index, card_index) in ready {
+            let player = &mut self.players[player_index];
+            let card = &mut player.hand[card_index];
+            let Some(upgrade) = card.upgrade.take() else {
+                continue;
+            };
+            let mut upgraded = upgrade.into;
+            upgraded.id = card.id;
+            *card = upgraded;
+            events.push(GameEvent::CardTransformed {
+                player_id: player.id,
+                card_id: card.id,
+                card: card.clone(),
+            });
+        }
+        for event in &events {
+            self.record_event(event.clone());
+        }
+        events
+    }
+
+    /// 增减场上单位的具名计数器，数量未变化时返回 `None`。
+    pub fn adjust_counter(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        counter: &str,
+        delta: i32,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        let before = card.counter(counter);
+        let total = card.adjust_counter(counter, delta);
+        (total != before).then(|| GameEvent::CountersChanged {
+            player_id,
+            card_id,
+            counter: counter.to_string(),
+            total,
+        })
+    }
+
+    /// 从墓地随机复活至多 `count` 个单位，受场面上限限制；复活的单位恢复初始状态。
+    pub fn resurrect_units(&mut self, player_id: PlayerId, count: u8) -> Vec<GameEvent> {
+        let max_board_size = self.config.max_board_size as usize;
+        let Some(player_index) = self.player_index(player_id) else {
+            return Vec::new();
+        };
+        let player = &mut self.players[player_index];
+        let space = max_board_size.saturating_sub(player.board.len());
+        let candidates: Vec<usize> = player
+            .graveyard
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| card.card_type == CardType::Unit)
+            .map(|(index, _)| index)
+            .collect();
+        let mut chosen: Vec<usize> = self
+            .rng
+            .choose_multiple(&candidates, space.min(count as usize))
+            .into_iter()
+            .copied()
+            .collect();
+        chosen.sort_unstable();
+
+        let revived: Vec<Card> = chosen
+            .into_iter()
+            .rev()
+            .map(|index| player.graveyard.remove(index).restored())
+            .collect();
+
+        let mut events = Vec::new();
+        for card in revived {
+            events.push(GameEvent::CardResurrected {
+                player_id,
+                card_id: card.id,
+            });
+            events.extend(self.summon_unit(player_id, card, None));
+        }
+        events
+    }
+
+    /// 将单位放上战场（默认放在最右侧）并返回召唤事件，召唤触发由效果引擎根据该事件入栈。
+    pub fn summon_unit(
+        &mut self,
+        player_id: PlayerId,
+        card: Card,
+        position: Option<usize>,
+    ) -> Option<GameEvent> {
+        let board = &mut self.get_player_mut(player_id)?.board;
+        let position = position.unwrap_or(board.len()).min(board.len());
+        let card_id = card.id;
+        board.insert(position, card);
+        Some(GameEvent::CardSummoned { player_id, card_id })
+    }
+
+    /// 场上卡牌某个效果的剩余触发次数；卡牌不在场或效果不限次数时返回 `None`。
+    pub fn effect_charges(
+        &self,
+        player_id: PlayerId,
+        card_id: CardId,
+        effect_id: EffectId,
+    ) -> Option<u8> {
+        self.get_player(player_id)?
+            .board
+            .iter()
+            .find(|card| card.id == card_id)?
+            .effects
+            .iter()
+            .find(|effect| effect.id == effect_id)?
+            .charges
     }
 
-    pub fn heal_player(&mut self, player_id: PlayerId, amount: i16) -> Option<GameEvent> {
-        if amount <= 0 {
+    /// 消耗场上卡牌某个效果的一次触发次数。
+    pub fn spend_effect_charge(
+        &mut self,
+        player_id: PlayerId,
+        card_id: CardId,
+        effect_id: EffectId,
+    ) -> Option<GameEvent> {
+        let card = self
+            .get_player_mut(player_id)?
+            .find_card_on_board_mut(card_id)?;
+        let charges = card
+            .effects
+            .iter_mut()
+            .find(|effect| effect.id == effect_id)?
+            .charges
+            .as_mut()?;
+        *charges = charges.saturating_sub(1);
+        Some(GameEvent::EffectChargeSpent {
+            player_id,
+            card_id,
+            effect_id,
+            remaining: *charges,
+        })
+    }
+
+    /// 为英雄或场上单位增加伤害抵消额度。
+    pub fn grant_damage_prevention(
+        &mut self,
+        player_id: PlayerId,
+        card_id: Option<CardId>,
+        amount: u8,
+    ) -> Option<GameEvent> {
+        if amount == 0 {
             return None;
         }
         let player = self.get_player_mut(player_id)?;
-        player.health = player.health.saturating_add(amount);
-        let event = GameEvent::CardHealed {
+        let pool = match card_id {
+            Some(card_id) => {
+                &mut player
+                    .board
+                    .iter_mut()
+                    .find(|card| card.id == card_id)?
+                    .damage_prevention
+            }
+            None => &mut player.damage_prevention,
+        };
+        *pool = pool.saturating_add(amount);
+        Some(GameEvent::DamagePreventionGained {
             player_id,
-            card_id: None,
+            card_id,
             amount,
-        };
-        Some(event)
+        })
+    }
+
+    /// 本回合获得临时法力，回合开始补满法力时自然失效。
+    pub fn gain_mana(&mut self, player_id: PlayerId, amount: u8) -> Option<GameEvent> {
+        let cap = self.config.max_mana;
+        let player = self.get_player_mut(player_id)?;
+        let mana = player.mana.saturating_add(amount).min(cap);
+        if amount == 0 || mana == player.mana {
+            return None;
+        }
+        player.mana = mana;
+        Some(GameEvent::ManaChanged {
+            player_id,
+            mana: player.mana,
+            max_mana: player.max_mana,
+        })
     }
 
+    /// 累积过载，下个回合开始时锁定对应数量的法力水晶。
+    pub fn overload_mana(&mut self, player_id: PlayerId, amount: u8) -> Option<GameEvent> {
+        if amount == 0 {
+            return None;
+        }
+        let player = self.get_player_mut(player_id)?;
+        player.overloaded_mana = player.overloaded_mana.saturating_add(amount);
+        Some(GameEvent::OverloadIncurred { player_id, amount })
+    }
+
+    /// 获得法力水晶；`filled` 为 false 时获得的是空水晶，本回合不可用。
+    pub fn gain_mana_crystals(
+        &mut self,
+        player_id: PlayerId,
+        amount: u8,
+        filled: bool,
+    ) -> Option<GameEvent> {
+        let cap = self.config.max_mana;
+        let player = self.get_player_mut(player_id)?;
+        let max_mana = player.max_mana.saturating_add(amount).min(cap);
+        let gained = max_mana - player.max_mana;
+        if gained == 0 {
+            return None;
+        }
+        player.max_mana = max_mana;
+        if filled {
+            player.mana = player.mana.saturating_add(gained).min(cap);
+        }
+        Some(GameEvent::ManaChanged {
+            player_id,
+            mana: player.mana,
+            max_mana: player.max_mana,
+        })
+    }
+
+    /// 恢复场上单位受到的伤害，同样只记录实际恢复量。
     pub fn heal_card(
         &mut self,
         player_id: PlayerId,
@@ -568,11 +2838,17 @@ impl GameState {
         }
         let player = self.get_player_mut(player_id)?;
         if let Some(card) = player.find_card_on_board_mut(card_id) {
-            card.health = card.health.saturating_add(amount);
+            let healed = amount.min(card.damage_taken);
+            if healed <= 0 {
+                return None;
+            }
+            card.health += healed;
+            card.damage_taken -= healed;
+            player.stats.healing = player.stats.healing.saturating_add(healed as u32);
             let event = GameEvent::CardHealed {
                 player_id,
                 card_id: Some(card_id),
-                amount,
+                amount: healed,
             };
             return Some(event);
         }
@@ -580,30 +2856,39 @@ impl GameState {
     }
 
     pub fn draw_card(&mut self, player_id: PlayerId) -> Option<GameEvent> {
-        let max_hand_size = self.max_hand_size;
+        let max_hand_size = self.config.max_hand_size;
+        let deck_out = self.config.deck_out;
         let player = self.get_player_mut(player_id)?;
         if player.deck.is_empty() {
-            if let Some(winner) = self.opponent_of(player_id) {
-                self.declare_victory(winner, VictoryReason::DeckOut { loser: player_id });
+            if deck_out == DeckOutRule::Fatigue {
+                // 疲劳伤害无视护甲与抵消，胜负由下一次胜负检查判定
+                player.fatigue = player.fatigue.saturating_add(1);
+                let amount = i16::from(player.fatigue);
+                player.health -= amount;
+                player.stats.damage_taken = player.stats.damage_taken.saturating_add(amount as u32);
+                return Some(GameEvent::FatigueDamage { player_id, amount });
             }
+            player.decked_out = true;
             return None;
         }
 
         let card = player.deck.pop()?;
+        player.stats.cards_drawn = player.stats.cards_drawn.saturating_add(1);
         if player.hand.len() as u8 >= max_hand_size {
-            let pending_id = self.next_pending_discard_id;
-            self.next_pending_discard_id = self.next_pending_discard_id.wrapping_add(1);
-            let pending = PendingDiscard {
-                id: pending_id,
-                player_id,
-                drawn_card: card,
-            };
+            // 已有待处理的弃牌选择时不再叠加，新抽到的牌直接爆掉
+            if self
+                .pending_choices
+                .iter()
+                .any(|pending| pending.player_id == player_id && pending.drawn_card().is_some())
+            {
+                return self.burn_card(player_id, card);
+            }
             let event = GameEvent::DiscardPending {
                 player_id,
-                pending_id,
-                card: pending.drawn_card.clone(),
+                pending_id: self.next_pending_choice_id,
+                card: card.clone(),
             };
-            self.pending_discards.push(pending);
+            self.push_choice(player_id, ChoiceKind::Discard { drawn_card: card });
             Some(event)
         } else {
             let card_id = card.id;
@@ -613,22 +2898,299 @@ impl GameState {
         }
     }
 
-    pub fn take_pending_discard(
+    /// 因手牌已满而爆牌：卡牌不进入手牌，直接置入墓地。
+    pub fn burn_card(&mut self, player_id: PlayerId, card: Card) -> Option<GameEvent> {
+        let player = self.get_player_mut(player_id)?;
+        let event = GameEvent::CardBurned {
+            player_id,
+            card: card.clone(),
+        };
+        player.graveyard.push(card);
+        Some(event)
+    }
+
+    /// 揭示并移除 `owner` 奥秘区中响应 `trigger` 的奥秘，移入墓地。
+    pub fn reveal_secrets(&mut self, owner: PlayerId, trigger: &EffectTrigger) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let Some(player) = self.get_player_mut(owner) else {
+            return events;
+        };
+        let (revealed, hidden): (Vec<Card>, Vec<Card>) = player
+            .secrets
+            .drain(..)
+            .partition(|card| card.effects.iter().any(|effect| &effect.trigger == trigger));
+        player.secrets = hidden;
+        for card in revealed {
+            events.push(GameEvent::SecretRevealed {
+                player_id: owner,
+                card: card.clone(),
+            });
+            player.graveyard.push(card);
+        }
+        events
+    }
+
+    /// 为英雄装备武器，原有武器被摧毁并移入墓地。
+    pub fn equip_weapon(&mut self, player_id: PlayerId, card: Card) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let Some(player) = self.get_player_mut(player_id) else {
+            return events;
+        };
+        let card_id = card.id;
+        if let Some(old) = player.weapon.replace(card) {
+            player.graveyard.push(old.clone());
+            events.push(GameEvent::WeaponDestroyed {
+                player_id,
+                card: old,
+            });
+        }
+        events.push(GameEvent::WeaponEquipped { player_id, card_id });
+        events
+    }
+
+    /// 武器失去一点耐久，耐久耗尽时摧毁并移入墓地。
+    pub fn wear_weapon(&mut self, player_id: PlayerId) -> Option<GameEvent> {
+        let player = self.get_player_mut(player_id)?;
+        let weapon = player.weapon.as_mut()?;
+        weapon.health -= 1;
+        if weapon.health > 0 {
+            return Some(GameEvent::WeaponDurabilityChanged {
+                player_id,
+                card_id: weapon.id,
+                durability: weapon.health,
+            });
+        }
+        let card = player.weapon.take()?;
+        player.graveyard.push(card.clone());
+        Some(GameEvent::WeaponDestroyed { player_id, card })
+    }
+
+    /// 以 `viewer` 的视角生成状态副本：隐藏其他玩家的手牌、牌库、奥秘与待弃牌，
+    /// 并清空随机源（知道种子与内部状态即可推算之后的抽牌与随机结果）。
+    /// 以 `SPECTATOR` 为视角时隐藏所有玩家的信息，但已揭示的手牌对观战者公开。
+    pub fn redacted_for(&self, viewer: PlayerId) -> GameState {
+        let mut view = self.clone();
+        view.rng = GameRng::default();
+        for player in view.players.iter_mut().filter(|player| player.id != viewer) {
+            let revealed: Vec<CardId> = player
+                .hand
+                .iter()
+                .filter(|card| player.is_revealed_to(card.id, viewer))
+                .map(|card| card.id)
+                .collect();
+            // 其他玩家看到了哪些手牌同样属于隐藏信息
+            player
+                .revealed_hand
+                .retain(|entry| entry.viewer == viewer || viewer == SPECTATOR);
+            for card in player
+                .hand
+                .iter_mut()
+                .filter(|card| !revealed.contains(&card.id))
+                .chain(player.deck.iter_mut())
+                .chain(player.secrets.iter_mut())
+            {
+                *card = card.concealed();
+            }
+        }
+        if let Some(setup) = view.setup.as_mut() {
+            for (deck, player) in setup.decks.iter_mut().zip(&view.players) {
+                if player.id != viewer {
+                    for card in deck.iter_mut() {
+                        *card = card.concealed();
+                    }
+                }
+            }
+        }
+        for pending in view
+            .pending_choices
+            .iter_mut()
+            .filter(|pending| pending.player_id != viewer)
+        {
+            match &mut pending.kind {
+                ChoiceKind::Discard { drawn_card } => *drawn_card = drawn_card.concealed(),
+                ChoiceKind::Discover { cards } => {
+                    for card in cards.iter_mut() {
+                        *card = card.concealed();
+                    }
+                }
+                ChoiceKind::ChooseOne { .. } | ChoiceKind::SelectTarget { .. } => {}
+            }
+        }
+        for entry in view.event_log.iter_mut() {
+            match &mut entry.event {
+                GameEvent::DiscardPending {
+                    player_id, card, ..
+                } if *player_id != viewer => *card = card.concealed(),
+                GameEvent::CardTransformed {
+                    player_id, card, ..
+                } if *player_id != viewer => *card = card.concealed(),
+                GameEvent::HandCardsRevealed {
+                    player_id,
+                    viewer: revealed_to,
+                    cards,
+                } if *player_id != viewer && *revealed_to != viewer && viewer != SPECTATOR => {
+                    for card in cards.iter_mut() {
+                        *card = card.concealed();
+                    }
+                }
+                _ => {}
+            }
+        }
+        view
+    }
+
+    /// 将牌库顶的若干张牌直接置入墓地（不抽取）。
+    pub fn mill_cards(&mut self, player_id: PlayerId, count: u8) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let Some(player) = self.get_player_mut(player_id) else {
+            return events;
+        };
+        for _ in 0..count {
+            let Some(card) = player.deck.pop() else {
+                break;
+            };
+            events.push(GameEvent::CardMilled {
+                player_id,
+                card: card.clone(),
+            });
+            player.graveyard.push(card);
+        }
+        events
+    }
+
+    /// 登记延迟效果，在 `player_id` 的下一个 `trigger` 时机结算。
+    pub fn schedule_effect(
+        &mut self,
+        trigger: EffectTrigger,
+        effect: EffectKind,
+        context: EffectContext,
+    ) -> GameEvent {
+        let id = self.next_scheduled_effect_id;
+        self.next_scheduled_effect_id = self.next_scheduled_effect_id.wrapping_add(1);
+        let player_id = context.source_player;
+        self.scheduled_effects.push(ScheduledEffect {
+            id,
+            trigger: trigger.clone(),
+            player_id,
+            effect,
+            context,
+        });
+        GameEvent::EffectScheduled {
+            player_id,
+            scheduled_id: id,
+            trigger,
+        }
+    }
+
+    /// 取出所有登记在指定玩家、指定时机的延迟效果。
+    pub fn take_scheduled_effects(
         &mut self,
+        trigger: &EffectTrigger,
         player_id: PlayerId,
-        pending_id: u64,
-    ) -> Option<PendingDiscard> {
-        if let Some(pos) = self
-            .pending_discards
+    ) -> Vec<ScheduledEffect> {
+        let (due, remaining): (Vec<ScheduledEffect>, Vec<ScheduledEffect>) = self
+            .scheduled_effects
+            .drain(..)
+            .partition(|item| &item.trigger == trigger && item.player_id == player_id);
+        self.scheduled_effects = remaining;
+        due
+    }
+
+    /// 取出指定的待弃牌选择，返回抽到的牌；其他类型的选择保持不变。
+    pub fn take_pending_discard(&mut self, player_id: PlayerId, pending_id: u64) -> Option<Card> {
+        let pos = self.pending_choices.iter().position(|pending| {
+            pending.id == pending_id
+                && pending.player_id == player_id
+                && pending.drawn_card().is_some()
+        })?;
+        match self.pending_choices.remove(pos).kind {
+            ChoiceKind::Discard { drawn_card } => Some(drawn_card),
+            _ => None,
+        }
+    }
+
+    /// 为新生成的卡牌分配实例 ID。计数器保证 ID 不会复用，同时跳过调用方
+    /// 直接放入各区域的卡牌，确保不与现存卡牌冲突。
+    pub fn allocate_card_id(&mut self) -> CardId {
+        let id = self.next_card_instance_id.max(self.unused_card_id());
+        self.next_card_instance_id = id.wrapping_add(1);
+        id
+    }
+
+    /// 比所有区域（含待选择项）中卡牌都大的最小 ID。
+    fn unused_card_id(&self) -> CardId {
+        let in_players = self.players.iter().flat_map(|player| {
+            player
+                .hand
+                .iter()
+                .chain(player.board.iter())
+                .chain(player.deck.iter())
+                .chain(player.graveyard.iter())
+                .chain(player.secrets.iter())
+                .chain(player.weapon.iter())
+        });
+        let in_choices = self
+            .pending_choices
             .iter()
-            .position(|pending| pending.id == pending_id && pending.player_id == player_id)
-        {
-            Some(self.pending_discards.remove(pos))
-        } else {
-            None
+            .flat_map(|pending| match &pending.kind {
+                ChoiceKind::Discard { drawn_card } => std::slice::from_ref(drawn_card),
+                ChoiceKind::Discover { cards } => cards.as_slice(),
+                ChoiceKind::ChooseOne { .. } | ChoiceKind::SelectTarget { .. } => &[],
+            });
+        in_players
+            .chain(in_choices)
+            .map(|card| card.id)
+            .max()
+            .map_or(0, |id| id.wrapping_add(1))
+    }
+
+    /// 将新生成的卡牌（分配新 ID）置入手牌；手牌已满时爆牌。
+    pub fn add_card_to_hand(&mut self, player_id: PlayerId, mut card: Card) -> Option<GameEvent> {
+        card.id = self.allocate_card_id();
+        let max_hand_size = self.config.max_hand_size;
+        let player = self.get_player_mut(player_id)?;
+        if player.hand.len() as u8 >= max_hand_size {
+            return self.burn_card(player_id, card);
+        }
+        let card_id = card.id;
+        player.hand.push(card);
+        Some(GameEvent::CardAddedToHand { player_id, card_id })
+    }
+
+    /// 登记一个待玩家处理的选择，返回其 ID。
+    pub fn push_choice(&mut self, player_id: PlayerId, kind: ChoiceKind) -> u64 {
+        let id = self.next_pending_choice_id;
+        self.next_pending_choice_id = self.next_pending_choice_id.wrapping_add(1);
+        self.pending_choices.push(PendingChoice {
+            id,
+            player_id,
+            kind,
+        });
+        id
+    }
+
+    /// 选择的选项数。弃牌选择中选项 `0..手牌数` 依次对应手牌，最后一项对应新抽到的牌。
+    pub fn choice_option_count(&self, pending: &PendingChoice) -> usize {
+        match &pending.kind {
+            ChoiceKind::Discard { .. } => self
+                .get_player(pending.player_id)
+                .map_or(0, |player| player.hand.len() + 1),
+            _ => pending.option_count().unwrap_or(0),
         }
     }
 
+    pub fn take_pending_choice(
+        &mut self,
+        player_id: PlayerId,
+        choice_id: u64,
+    ) -> Option<PendingChoice> {
+        let pos = self
+            .pending_choices
+            .iter()
+            .position(|pending| pending.id == choice_id && pending.player_id == player_id)?;
+        Some(self.pending_choices.remove(pos))
+    }
+
     pub fn put_card_on_bottom_of_deck(&mut self, player_id: PlayerId, card: Card) {
         if let Some(player) = self.get_player_mut(player_id) {
             player.deck.insert(0, card);
@@ -650,25 +3212,43 @@ impl GameState {
                 }
             }
         }
+        // 后手玩家按配置多抽补偿牌
+        let first = self.current_player;
+        for _ in 0..self.config.second_player_extra_cards {
+            for player_id in player_ids.iter().filter(|id| **id != first) {
+                if let Some(event) = self.draw_card(*player_id) {
+                    self.record_event(event.clone());
+                    events.push(event);
+                }
+            }
+        }
         events
     }
 
-    pub fn ready_player(&mut self, player_id: PlayerId) {
+    /// 回合开始时刷新单位、补满法力并抽牌，返回已记录到日志中的事件。
+    pub fn ready_player(&mut self, player_id: PlayerId) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let cap = self.config.max_mana;
+        let fatigue = self.config.deck_out == DeckOutRule::Fatigue;
         if let Some(player) = self.get_player_mut(player_id) {
             player.ready_board();
-            player.reconcile_mana_cap();
-
-            // 恢复法力上限并填充（每回合+1，最大10）
-            player.max_mana = (player.max_mana + 1).min(10);
-            player.mana = player.max_mana;
+            let locked = player.refill_mana(cap);
+            let has_deck = !player.deck.is_empty() || fatigue;
+            if locked > 0 {
+                let event = GameEvent::ManaOverloaded { player_id, locked };
+                self.record_event(event.clone());
+                events.push(event);
+            }
 
-            // 抽一张牌（只在牌库不为空时）
-            if !player.deck.is_empty() {
+            // 抽一张牌（牌库为空时仅在疲劳规则下抽牌）
+            if has_deck {
                 if let Some(event) = self.draw_card(player_id) {
                     self.record_event(event.clone());
+                    events.push(event);
                 }
             }
         }
+        events
     }
 
     pub fn advance_phase(&mut self) {
@@ -690,53 +3270,228 @@ impl GameState {
     pub fn end_turn(&mut self) {
         // 先推进到End阶段，保持阶段转换的一致性
         self.phase = GamePhase::End;
+        if let Some(player) = self.get_player_mut(self.current_player) {
+            player.cards_played_this_turn.clear();
+            player.spells_played_this_turn = 0;
+        }
+        for player in &mut self.players {
+            let hand = &player.hand;
+            player
+                .revealed_hand
+                .retain(|revealed| hand.iter().any(|card| card.id == revealed.card_id));
+        }
 
-        // 然后切换到下一个玩家
-        if let Some(next_player) = self.opponent_of(self.current_player) {
+        // 然后按座位顺序切换到下一个存活的玩家
+        if let Some(next_player) = self.next_player_after(self.current_player) {
             self.current_player = next_player;
             self.turn += 1; // 增加回合数
             self.phase = GamePhase::Main; // 下一个玩家从Main阶段开始
         }
     }
 
+    /// 检查胜负并返回获胜结果；平局时返回 `None`，结果记录在 `draw` 中。
     pub fn evaluate_victory(&mut self) -> Option<VictoryState> {
-        if let Some(outcome) = &self.outcome {
-            return Some(outcome.clone());
+        if self.is_finished() {
+            return self.outcome.clone();
+        }
+
+        // 队伍全部成员阵亡才算落败；未分队的玩家各自成队
+        let mut sides: Vec<Vec<&Player>> = Vec::new();
+        for player in &self.players {
+            match sides
+                .iter_mut()
+                .find(|side| self.are_allies(side[0].id, player.id))
+            {
+                Some(side) => side.push(player),
+                None => sides.push(vec![player]),
+            }
+        }
+        let (defeated, standing): (Vec<_>, Vec<_>) = sides
+            .into_iter()
+            .partition(|side| side.iter().all(|player| !player.is_alive()));
+
+        if !defeated.is_empty() && standing.len() == 1 {
+            let loser = defeated[0][0].id;
+            let decked_out = defeated[0].iter().any(|player| player.decked_out);
+            let winner = standing[0]
+                .iter()
+                .find(|player| player.is_alive())
+                .map(|player| player.id);
+            if let Some(winner) = winner {
+                let reason = if decked_out {
+                    VictoryReason::DeckOut { loser }
+                } else {
+                    VictoryReason::HealthDepleted { loser }
+                };
+                return Some(self.declare_victory(winner, reason));
+            }
+        } else if !defeated.is_empty() && standing.is_empty() {
+            self.declare_draw(DrawReason::SimultaneousDefeat);
+        } else if defeated.is_empty() {
+            if let Some((winner, id)) = self.alternative_victory() {
+                return Some(self.declare_victory(winner, VictoryReason::Alternative { id }));
+            }
         }
 
-        let defeated: Vec<PlayerId> = self
+        self.outcome.clone()
+    }
+
+    /// 按当前玩家优先的顺序检查规则配置与玩家自身登记的额外胜利条件，返回首个满足者。
+    fn alternative_victory(&self) -> Option<(PlayerId, String)> {
+        let current = self.current_player;
+        let players = self
             .players
             .iter()
-            .filter(|player| player.health <= 0)
-            .map(|player| player.id)
-            .collect();
+            .filter(|player| player.id == current)
+            .chain(self.players.iter().filter(|player| player.id != current));
+        for player in players {
+            let ctx = EffectContext::new(EffectTrigger::Passive, player.id, current);
+            let satisfied = self
+                .config
+                .alternative_victories
+                .iter()
+                .chain(&player.alternative_victories)
+                .find(|victory| victory.condition.is_satisfied(&ctx, self));
+            if let Some(victory) = satisfied {
+                return Some((player.id, victory.id.clone()));
+            }
+        }
+        None
+    }
 
-        if defeated.len() == 1 {
-            let loser = defeated[0];
-            if let Some(winner) = self.opponent_of(loser) {
-                return Some(self.declare_victory(winner, VictoryReason::HealthDepleted { loser }));
-            }
-        } else if defeated.len() > 1 {
-            if let Some(first) = self.players.first() {
-                return Some(self.declare_victory(
-                    first.id,
-                    VictoryReason::Special {
-                        reason: "Simultaneous defeat".into(),
-                    },
-                ));
+    /// 同 `evaluate_victory`，但以 `GameOutcome` 同时返回胜负与平局。
+    pub fn evaluate_outcome(&mut self) -> Option<GameOutcome> {
+        self.evaluate_victory();
+        self.game_outcome()
+    }
+
+    /// 超过回合上限时按配置的判定方式结束对局。
+    pub fn enforce_turn_limit(&mut self) {
+        let Some(turn_limit) = self.config.turn_limit else {
+            return;
+        };
+        if self.is_finished() || self.turn <= turn_limit {
+            return;
+        }
+        if self.config.tiebreaker == Tiebreaker::HighestHealth {
+            let top = self.players.iter().map(|player| player.health).max();
+            let mut leaders = self
+                .players
+                .iter()
+                .filter(|player| Some(player.health) == top);
+            if let (Some(leader), None) = (leaders.next(), leaders.next()) {
+                let winner = leader.id;
+                self.declare_victory(winner, VictoryReason::TurnLimitReached { turn_limit });
+                return;
             }
         }
+        self.declare_draw(DrawReason::TurnLimitReached { turn_limit });
+    }
 
-        self.outcome.clone()
+    /// 局面的稳定哈希：对不含事件日志与卡池的 JSON 编码做 FNV-1a，
+    /// 联机各端在每次操作后比对即可立即发现不同步。
+    pub fn hash(&self) -> u64 {
+        let GameState {
+            players,
+            current_player,
+            turn,
+            phase,
+            config,
+            mulligan_completed,
+            pending_choices,
+            pending_discards: _,
+            scheduled_effects,
+            pending_attack,
+            priority_window,
+            event_log: _,
+            next_event_seq,
+            outcome,
+            draw,
+            next_pending_choice_id,
+            next_scheduled_effect_id,
+            next_card_instance_id,
+            version,
+            schema_version,
+            rng,
+            setup,
+            game_start_resolved,
+            victory_effects_resolved,
+            card_pool: _,
+        } = self;
+        let hashed = HashedState {
+            players,
+            current_player: *current_player,
+            turn: *turn,
+            phase,
+            config,
+            mulligan_completed,
+            pending_choices,
+            scheduled_effects,
+            pending_attack,
+            priority_window,
+            next_event_seq: *next_event_seq,
+            outcome,
+            draw,
+            next_pending_choice_id: *next_pending_choice_id,
+            next_scheduled_effect_id: *next_scheduled_effect_id,
+            next_card_instance_id: *next_card_instance_id,
+            version: *version,
+            schema_version: *schema_version,
+            rng,
+            setup,
+            game_start_resolved: *game_start_resolved,
+            victory_effects_resolved: *victory_effects_resolved,
+        };
+        let mut hasher = Fnv1a::default();
+        // 写入 `Fnv1a` 不会失败，各字段与存档一样总能编码，不存在需要处理的错误
+        let _ = serde_json::to_writer(&mut hasher, &hashed);
+        hasher.0
+    }
+
+    /// 当前对局的统计事件，结束时随胜负事件一同记录。
+    pub fn summary(&self) -> GameEvent {
+        GameEvent::GameSummary {
+            turns: self.turn,
+            players: self.player_summaries(),
+        }
+    }
+
+    /// 各玩家截至目前的统计与剩余资源，按座位顺序排列。
+    pub fn player_summaries(&self) -> Vec<PlayerSummary> {
+        self.players
+            .iter()
+            .map(|player| PlayerSummary {
+                player_id: player.id,
+                stats: player.stats,
+                health: player.health,
+                armor: player.armor,
+                mana: player.mana,
+                max_mana: player.max_mana,
+                hand_size: player.hand.len(),
+                deck_size: player.deck.len(),
+                board_size: player.board.len(),
+            })
+            .collect()
+    }
+
+    pub fn declare_draw(&mut self, reason: DrawReason) {
+        if !self.is_finished() {
+            self.record_event(GameEvent::GameDrawn {
+                reason: reason.clone(),
+            });
+            self.record_event(self.summary());
+            self.draw = Some(reason);
+        }
     }
 
     pub fn declare_victory(&mut self, winner: PlayerId, reason: VictoryReason) -> VictoryState {
         let victory = VictoryState { winner, reason };
-        if self.outcome.is_none() {
+        if !self.is_finished() {
             self.record_event(GameEvent::GameWon {
                 winner: victory.winner,
                 reason: victory.reason.clone(),
             });
+            self.record_event(self.summary());
             self.outcome = Some(victory.clone());
         }
         victory
@@ -757,30 +3512,105 @@ impl GameState {
                     value: player.health,
                 });
             }
-            if player.max_mana > 10 {
+            if player.max_mana > MAX_MANA {
                 return Err(IntegrityError::ManaOutOfRange {
                     player_id: player.id,
                     value: player.max_mana,
                 });
             }
-            if player.mana > player.max_mana {
+            // 临时法力允许当前法力暂时超过水晶数，但不能超过上限
+            if player.mana > MAX_MANA {
                 return Err(IntegrityError::ManaOutOfRange {
                     player_id: player.id,
                     value: player.mana,
                 });
             }
+            if player.hand.len() > self.config.max_hand_size as usize {
+                return Err(IntegrityError::HandTooLarge {
+                    player_id: player.id,
+                    size: player.hand.len(),
+                    max: self.config.max_hand_size,
+                });
+            }
+            if player.board.len() > self.config.max_board_size as usize {
+                return Err(IntegrityError::BoardTooLarge {
+                    player_id: player.id,
+                    size: player.board.len(),
+                    max: self.config.max_board_size,
+                });
+            }
             for card in player
                 .hand
                 .iter()
                 .chain(player.board.iter())
                 .chain(player.deck.iter())
+                .chain(player.graveyard.iter())
+                .chain(player.secrets.iter())
+                .chain(player.weapon.iter())
             {
                 if !seen.insert(card.id) {
                     return Err(IntegrityError::DuplicateCardId { card_id: card.id });
                 }
+                card.check_stats()?;
+            }
+            // 攻击标记在离场后保留到墓地，但不应出现在手牌与牌库中
+            let mut unplayed = player.hand.iter().chain(player.deck.iter());
+            let exhausted = if self.phase == GamePhase::Mulligan {
+                player.board.iter().find(|card| card.exhausted)
+            } else {
+                None
+            };
+            if let Some(card) = exhausted.or_else(|| unplayed.find(|card| card.exhausted)) {
+                return Err(IntegrityError::ExhaustedOutOfPlay { card_id: card.id });
+            }
+        }
+
+        self.check_outcome_consistency()?;
+        self.check_pending_choices()
+    }
+
+    fn check_outcome_consistency(&self) -> Result<(), IntegrityError> {
+        if self.outcome.is_some() && self.draw.is_some() {
+            return Err(IntegrityError::ConflictingOutcome);
+        }
+        // 日志被裁剪或未保留时无法核对
+        let complete_log = self.event_log.first().is_some_and(|entry| entry.seq == 1);
+        if let (Some(outcome), true) = (&self.outcome, complete_log) {
+            let logged = self.events().any(|event| {
+                matches!(event, GameEvent::GameWon { winner, .. } if *winner == outcome.winner)
+            });
+            if !logged {
+                return Err(IntegrityError::OutcomeNotLogged {
+                    winner: outcome.winner,
+                });
             }
         }
+        Ok(())
+    }
 
+    fn check_pending_choices(&self) -> Result<(), IntegrityError> {
+        for choice in &self.pending_choices {
+            if self.get_player(choice.player_id).is_none() {
+                return Err(IntegrityError::PendingChoicePlayerMissing {
+                    choice_id: choice.id,
+                    player_id: choice.player_id,
+                });
+            }
+            let ChoiceKind::SelectTarget { targets, .. } = &choice.kind else {
+                continue;
+            };
+            for target in targets {
+                let on_board = self
+                    .get_player(target.player_id)
+                    .is_some_and(|player| player.find_card_on_board(target.card_id).is_some());
+                if !on_board {
+                    return Err(IntegrityError::PendingChoiceCardMissing {
+                        choice_id: choice.id,
+                        card_id: target.card_id,
+                    });
+                }
+            }
+        }
         Ok(())
     }
 
@@ -840,10 +3670,12 @@ impl GameState {
                     EffectKind::DirectDamage {
                         amount: 3,
                         target: EffectTarget::OpponentOfSource,
+                        scaling: None,
                     },
                     EffectKind::DrawCard {
                         count: 1,
                         target: EffectTarget::SourcePlayer,
+                        scaling: None,
                     },
                 ],
             },
@@ -886,7 +3718,7 @@ impl GameState {
             CardType::Unit,
             vec![footman_effect.clone()],
         );
-        footman_board_p1.exhausted = false;
+        footman_board_p1.refresh();
 
         let arcane_scholar_hand_p1 = Card::new(
             3,
@@ -947,7 +3779,7 @@ impl GameState {
             CardType::Unit,
             vec![bulwark_effect.clone()],
         );
-        bulwark_board_p2.exhausted = false;
+        bulwark_board_p2.refresh();
 
         let player_one = Player::new(
             0,
@@ -997,6 +3829,56 @@ impl GameState {
     }
 }
 
+/// `GameState::hash` 的编码内容：借用除事件日志、待弃牌与卡池以外的全部字段，
+/// 不必复制整个状态。
+#[derive(Serialize)]
+struct HashedState<'a> {
+    players: &'a [Player],
+    current_player: PlayerId,
+    turn: u32,
+    phase: &'a GamePhase,
+    config: &'a RuleConfig,
+    mulligan_completed: &'a [PlayerId],
+    pending_choices: &'a [PendingChoice],
+    scheduled_effects: &'a [ScheduledEffect],
+    pending_attack: &'a Option<PendingAttack>,
+    priority_window: &'a Option<PriorityWindow>,
+    next_event_seq: u64,
+    outcome: &'a Option<VictoryState>,
+    draw: &'a Option<DrawReason>,
+    next_pending_choice_id: u64,
+    next_scheduled_effect_id: u64,
+    next_card_instance_id: CardId,
+    version: u64,
+    schema_version: u32,
+    rng: &'a GameRng,
+    setup: &'a Option<GameSetup>,
+    game_start_resolved: bool,
+    victory_effects_resolved: bool,
+}
+
+/// 64 位 FNV-1a，以写入器的形式接收序列化输出。
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::io::Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self {
@@ -1004,14 +3886,27 @@ impl Default for GameState {
             current_player: 0,
             turn: 1,
             phase: GamePhase::default(),
-            max_hand_size: DEFAULT_MAX_HAND_SIZE,
-            max_board_size: DEFAULT_MAX_BOARD_SIZE,
+            config: RuleConfig::default(),
             mulligan_completed: Vec::new(),
+            pending_choices: Vec::new(),
             pending_discards: Vec::new(),
+            scheduled_effects: Vec::new(),
+            pending_attack: None,
+            priority_window: None,
             event_log: Vec::new(),
+            next_event_seq: 1,
             outcome: None,
-            next_pending_discard_id: 0,
+            draw: None,
+            next_pending_choice_id: 0,
+            next_scheduled_effect_id: 0,
+            next_card_instance_id: 0,
             version: 0,
+            schema_version: SCHEMA_VERSION,
+            rng: GameRng::default(),
+            setup: None,
+            game_start_resolved: false,
+            victory_effects_resolved: false,
+            card_pool: CardRegistry::new(),
         }
     }
 }
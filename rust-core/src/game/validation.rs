@@ -0,0 +1,499 @@
+//! 卡牌定义检查，帮助内容作者在运行前发现配置错误。
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    effects::{EffectCondition, EffectKind, EffectTrigger},
+    registry::{CardDefinition, DefId, Rarity},
+    rules::RuleEngine,
+    state::{Card, EffectId, Hero, DEFAULT_MAX_BOARD_SIZE, DEFAULT_MAX_HAND_SIZE, MAX_MANA},
+};
+
+/// 效果嵌套（组合、条件、延迟、抉择）允许的最大深度。
+pub const MAX_EFFECT_DEPTH: usize = 8;
+
+/// 卡牌定义中的问题。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum CardDefect {
+    DuplicateEffectId {
+        effect_id: EffectId,
+    },
+    /// 使用 `ContextTarget` 的效果挂在不提供目标的触发时机上。
+    ContextTargetWithoutTarget {
+        effect_id: EffectId,
+        trigger: EffectTrigger,
+    },
+    /// 条件在任何对局中都不可能成立。
+    UnsatisfiableCondition {
+        effect_id: EffectId,
+    },
+    NestingTooDeep {
+        effect_id: EffectId,
+        depth: usize,
+        max_depth: usize,
+    },
+}
+
+/// 检查卡牌定义，返回发现的全部问题。
+pub fn validate_card(card: &Card) -> Vec<CardDefect> {
+    let mut defects = Vec::new();
+    let mut seen = HashSet::new();
+
+    for effect in &card.effects {
+        if !seen.insert(effect.id) {
+            defects.push(CardDefect::DuplicateEffectId {
+                effect_id: effect.id,
+            });
+        }
+
+        if !trigger_provides_target(&effect.trigger)
+            && RuleEngine::requires_target_kind(&effect.kind)
+        {
+            defects.push(CardDefect::ContextTargetWithoutTarget {
+                effect_id: effect.id,
+                trigger: effect.trigger.clone(),
+            });
+        }
+
+        let mut conditions: Vec<&EffectCondition> = effect.condition.iter().collect();
+        collect_conditions(&effect.kind, &mut conditions);
+        if conditions.into_iter().any(never_holds) {
+            defects.push(CardDefect::UnsatisfiableCondition {
+                effect_id: effect.id,
+            });
+        }
+
+        let depth = nesting_depth(&effect.kind);
+        if depth > MAX_EFFECT_DEPTH {
+            defects.push(CardDefect::NestingTooDeep {
+                effect_id: effect.id,
+                depth,
+                max_depth: MAX_EFFECT_DEPTH,
+            });
+        }
+    }
+
+    defects
+}
+
+/// 构筑赛制：牌组大小、同名卡牌数量上限、可用的扩展包与职业以及禁卡表。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeckFormat {
+    pub name: String,
+    pub min_cards: usize,
+    pub max_cards: usize,
+    /// 同一卡牌定义最多可携带的张数。
+    pub max_copies: u8,
+    /// 传说卡牌最多可携带的张数。
+    pub max_legendary_copies: u8,
+    /// 允许的扩展包，为空表示不限；基础卡牌总是允许。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sets: Vec<String>,
+    /// 允许的职业，为空表示不限；中立卡牌总是允许。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub classes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub banned: Vec<DefId>,
+}
+
+impl Default for DeckFormat {
+    fn default() -> Self {
+        Self {
+            name: "standard".to_string(),
+            min_cards: 30,
+            max_cards: 30,
+            max_copies: 2,
+            max_legendary_copies: 1,
+            sets: Vec::new(),
+            classes: Vec::new(),
+            banned: Vec::new(),
+        }
+    }
+}
+
+impl DeckFormat {
+    pub fn with_deck_size(mut self, min_cards: usize, max_cards: usize) -> Self {
+        self.min_cards = min_cards;
+        self.max_cards = max_cards;
+        self
+    }
+
+    pub fn with_max_copies(mut self, max_copies: u8) -> Self {
+        self.max_copies = max_copies;
+        self
+    }
+
+    pub fn with_sets<I, S>(mut self, sets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sets = sets.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_classes<I, S>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.classes = classes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_banned(mut self, banned: impl IntoIterator<Item = DefId>) -> Self {
+        self.banned = banned.into_iter().collect();
+        self
+    }
+}
+
+/// 牌组不符合赛制的原因。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum DeckError {
+    TooFewCards {
+        count: usize,
+        min: usize,
+    },
+    TooManyCards {
+        count: usize,
+        max: usize,
+    },
+    TooManyCopies {
+        def_id: DefId,
+        count: usize,
+        max: u8,
+    },
+    Banned {
+        def_id: DefId,
+    },
+    /// 不可收集的衍生卡牌不能放入牌组。
+    NotCollectible {
+        def_id: DefId,
+    },
+    SetNotAllowed {
+        def_id: DefId,
+        set: String,
+    },
+    ClassNotAllowed {
+        def_id: DefId,
+        class: String,
+    },
+    /// 牌组中出现了多个职业的卡牌。
+    MixedClasses {
+        classes: Vec<String>,
+    },
+    /// 职业卡牌与所选英雄的职业不符。
+    WrongClass {
+        def_id: DefId,
+        class: String,
+        hero_class: String,
+    },
+}
+
+/// 按赛制检查牌组，返回发现的全部问题；同一定义的问题只报告一次。
+pub fn validate_deck(deck: &[CardDefinition], format: &DeckFormat) -> Result<(), Vec<DeckError>> {
+    let mut errors = Vec::new();
+    if deck.len() < format.min_cards {
+        errors.push(DeckError::TooFewCards {
+            count: deck.len(),
+            min: format.min_cards,
+        });
+    }
+    if deck.len() > format.max_cards {
+        errors.push(DeckError::TooManyCards {
+            count: deck.len(),
+            max: format.max_cards,
+        });
+    }
+
+    let mut copies: BTreeMap<DefId, (&CardDefinition, usize)> = BTreeMap::new();
+    for definition in deck {
+        copies.entry(definition.def_id).or_insert((definition, 0)).1 += 1;
+    }
+    let mut classes = BTreeSet::new();
+    for (&def_id, &(definition, count)) in &copies {
+        let max = match definition.rarity {
+            Rarity::Legendary => format.max_legendary_copies,
+            _ => format.max_copies,
+        };
+        if count > max as usize {
+            errors.push(DeckError::TooManyCopies { def_id, count, max });
+        }
+        if format.banned.contains(&def_id) {
+            errors.push(DeckError::Banned { def_id });
+        }
+        if !definition.collectible {
+            errors.push(DeckError::NotCollectible { def_id });
+        }
+        if let Some(set) = &definition.set {
+            if !format.sets.is_empty() && !format.sets.contains(set) {
+                errors.push(DeckError::SetNotAllowed {
+                    def_id,
+                    set: set.clone(),
+                });
+            }
+        }
+        if let Some(class) = &definition.class {
+            if !format.classes.is_empty() && !format.classes.contains(class) {
+                errors.push(DeckError::ClassNotAllowed {
+                    def_id,
+                    class: class.clone(),
+                });
+            }
+            classes.insert(class.clone());
+        }
+    }
+    if classes.len() > 1 {
+        errors.push(DeckError::MixedClasses {
+            classes: classes.into_iter().collect(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 在 `validate_deck` 的基础上，要求职业卡牌与英雄的职业一致。
+pub fn validate_hero_deck(
+    deck: &[CardDefinition],
+    format: &DeckFormat,
+    hero: &Hero,
+) -> Result<(), Vec<DeckError>> {
+    let mut errors = validate_deck(deck, format).err().unwrap_or_default();
+    let mut reported = HashSet::new();
+    for definition in deck {
+        let Some(class) = &definition.class else {
+            continue;
+        };
+        if class != &hero.class && reported.insert(definition.def_id) {
+            errors.push(DeckError::WrongClass {
+                def_id: definition.def_id,
+                class: class.clone(),
+                hero_class: hero.class.clone(),
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 这些触发时机构造的上下文不含目标，`ContextTarget` 永远解析为空。
+fn trigger_provides_target(trigger: &EffectTrigger) -> bool {
+    !matches!(
+        trigger,
+        EffectTrigger::OnDeath
+            | EffectTrigger::OnTurnStart
+            | EffectTrigger::OnTurnEnd
+            | EffectTrigger::OnSpellCast
+            | EffectTrigger::OnGameStart
+            | EffectTrigger::OnVictory
+            | EffectTrigger::OnPhaseEnter { .. }
+            | EffectTrigger::OnPhaseExit { .. }
+            | EffectTrigger::Passive
+    )
+}
+
+fn children(kind: &EffectKind) -> Vec<&EffectKind> {
+    match kind {
+        EffectKind::Composite { effects } => effects.iter().collect(),
+        EffectKind::Conditional { effect, .. }
+        | EffectKind::Delayed { effect, .. }
+        | EffectKind::SelectTarget { effect, .. } => vec![effect.as_ref()],
+        EffectKind::ChooseOne { options } => options.iter().map(|option| &option.effect).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn collect_conditions<'a>(kind: &'a EffectKind, conditions: &mut Vec<&'a EffectCondition>) {
+    if let EffectKind::Conditional { condition, .. } = kind {
+        conditions.push(condition);
+    }
+    for child in children(kind) {
+        collect_conditions(child, conditions);
+    }
+}
+
+fn nesting_depth(kind: &EffectKind) -> usize {
+    1 + children(kind)
+        .into_iter()
+        .map(nesting_depth)
+        .max()
+        .unwrap_or(0)
+}
+
+/// 按默认对局规则判断条件是否必然不成立。
+fn never_holds(condition: &EffectCondition) -> bool {
+    match condition {
+        // 生命值低于 1 时对局已经结束
+        EffectCondition::PlayerHealthBelow { threshold, .. } => *threshold <= 1,
+        EffectCondition::PlayerManaAtLeast { amount, .. } => *amount > MAX_MANA,
+        EffectCondition::BoardCountAtLeast { min, .. } => *min > DEFAULT_MAX_BOARD_SIZE as usize,
+        EffectCondition::HandSizeAtLeast { min, .. } => *min > DEFAULT_MAX_HAND_SIZE as usize,
+        EffectCondition::Any { conditions } => conditions.iter().all(never_holds),
+        EffectCondition::All { conditions } => conditions.iter().any(never_holds),
+        EffectCondition::ComboActive
+        | EffectCondition::ControlsTribe { .. }
+        | EffectCondition::CounterAtLeast { .. }
+        | EffectCondition::TurnAtLeast { .. }
+        | EffectCondition::UnitsDiedThisGameAtLeast { .. }
+        | EffectCondition::SourceCardDamaged
+        | EffectCondition::DeckSizeAtMost { .. }
+        | EffectCondition::GraveyardSizeAtLeast { .. }
+        | EffectCondition::HeroClass { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CardEffect, CardType, EffectTarget};
+
+    #[test]
+    fn reports_deck_legality_problems() {
+        let card = |def_id, class: Option<&str>, set: &str| {
+            let definition =
+                CardDefinition::new(def_id, "Card", 1, 1, 1, CardType::Unit, Vec::new())
+                    .with_set(set);
+            match class {
+                Some(class) => definition.with_class(class),
+                None => definition,
+            }
+        };
+        let format = DeckFormat::default()
+            .with_deck_size(4, 6)
+            .with_max_copies(2)
+            .with_sets(["core", "frost"])
+            .with_classes(["mage", "priest"])
+            .with_banned([3]);
+
+        let legal = vec![
+            card(1, None, "core"),
+            card(1, None, "core"),
+            card(2, Some("mage"), "frost"),
+            card(4, None, "core"),
+        ];
+        assert_eq!(validate_deck(&legal, &format), Ok(()));
+
+        let deck = vec![
+            card(1, None, "core"),
+            card(1, None, "core"),
+            card(1, None, "core"),
+            card(2, Some("mage"), "frost"),
+            card(3, None, "core"),
+            card(5, Some("priest"), "wild"),
+            card(6, Some("rogue"), "core"),
+            card(7, None, "core").with_rarity(Rarity::Legendary),
+            card(7, None, "core").with_rarity(Rarity::Legendary),
+            card(8, None, "core").uncollectible(),
+        ];
+        assert_eq!(
+            validate_deck(&deck, &format),
+            Err(vec![
+                DeckError::TooManyCards { count: 10, max: 6 },
+                DeckError::TooManyCopies {
+                    def_id: 1,
+                    count: 3,
+                    max: 2,
+                },
+                DeckError::Banned { def_id: 3 },
+                DeckError::SetNotAllowed {
+                    def_id: 5,
+                    set: "wild".to_string(),
+                },
+                DeckError::ClassNotAllowed {
+                    def_id: 6,
+                    class: "rogue".to_string(),
+                },
+                DeckError::TooManyCopies {
+                    def_id: 7,
+                    count: 2,
+                    max: 1,
+                },
+                DeckError::NotCollectible { def_id: 8 },
+                DeckError::MixedClasses {
+                    classes: vec![
+                        "mage".to_string(),
+                        "priest".to_string(),
+                        "rogue".to_string()
+                    ],
+                },
+            ])
+        );
+        assert_eq!(
+            validate_deck(&legal[..2], &format),
+            Err(vec![DeckError::TooFewCards { count: 2, min: 4 }])
+        );
+    }
+
+    #[test]
+    fn reports_common_definition_mistakes() {
+        let untargeted = CardEffect::direct_damage(
+            1,
+            "At the end of your turn, deal 2 damage to the chosen target",
+            EffectTrigger::OnTurnEnd,
+            0,
+            2,
+            EffectTarget::ContextTarget,
+        );
+        let mut nested = EffectKind::GainArmor {
+            amount: 1,
+            target: EffectTarget::SourcePlayer,
+        };
+        for _ in 0..MAX_EFFECT_DEPTH {
+            nested = EffectKind::Composite {
+                effects: vec![nested],
+            };
+        }
+        let impossible = CardEffect::new(
+            1,
+            "Deep and impossible",
+            EffectTrigger::OnPlay,
+            0,
+            EffectKind::Conditional {
+                condition: Box::new(EffectCondition::Any {
+                    conditions: Vec::new(),
+                }),
+                effect: Box::new(nested),
+            },
+        );
+        let card = Card::new(
+            1,
+            "Broken",
+            1,
+            0,
+            0,
+            CardType::Spell,
+            vec![untargeted, impossible],
+        );
+
+        let defects = validate_card(&card);
+        assert_eq!(
+            defects,
+            vec![
+                CardDefect::ContextTargetWithoutTarget {
+                    effect_id: 1,
+                    trigger: EffectTrigger::OnTurnEnd,
+                },
+                CardDefect::DuplicateEffectId { effect_id: 1 },
+                CardDefect::UnsatisfiableCondition { effect_id: 1 },
+                CardDefect::NestingTooDeep {
+                    effect_id: 1,
+                    depth: MAX_EFFECT_DEPTH + 2,
+                    max_depth: MAX_EFFECT_DEPTH,
+                },
+            ]
+        );
+    }
+}
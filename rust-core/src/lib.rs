@@ -4,21 +4,40 @@ pub mod utils;
 
 use gloo_timers::future::TimeoutFuture;
 use serde::Serialize;
-use serde_json;
-use serde_wasm_bindgen::{from_value, to_value};
+use serde_wasm_bindgen::{from_value, Serializer};
 use std::str::FromStr;
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::future_to_promise;
-use web_sys::js_sys::Promise;
+use web_sys::js_sys::{Function, Promise};
 
-pub use ai::{AiAgent, AiConfig, AiDecision, AiDifficulty, AiStrategy, GameAction};
+pub use ai::{
+    find_lethal, AiAgent, AiAlgorithm, AiConfig, AiDecision, AiDifficulty, AiProfile, AiProfiles,
+    AiSettings, AiStrategy, AiTask, AiTaskResult, AiWorker, AiWorkerRequest, AiWorkerResponse,
+    HintReason, MctsConfig, MoveSuggestion, PlannedStep, RolloutPolicy, SearchSession,
+    StrategyWeights, TurnPlan, AI_WORKER_PROTOCOL,
+};
 pub use game::{
-    AttackAction, Card, CardEffect, CardId, CardType, EffectCondition, EffectContext, EffectEngine,
-    EffectKind, EffectResolution, EffectStack, EffectTarget, EffectTrigger, GameEvent, GamePhase,
-    GameState, IntegrityError, MulliganAction, PlayCardAction, Player, PlayerId, RuleEngine,
-    RuleError, RuleResolution, VictoryReason, VictoryState, DiscardCardAction,
+    ActionHooks, ActionKind, AdditionalCost, AlternativeVictory, AmountScaling, AttackAction,
+    AttackTargetOption, AuraScope, Card, CardDefect, CardDefinition, CardEffect, CardId,
+    CardRegistry, CardType, CardUpgrade, ChoiceKind, ChoiceOption, ChoiceTarget, DeckFormat,
+    DeckOutRule, DefId, DiscardCardAction, DrawReason, EffectCondition, EffectContext,
+    EffectDuration, EffectEngine, EffectKind, EffectResolution, EffectStack, EffectTarget,
+    EffectTraceEntry, EffectTrigger, Enchantment, EnchantmentDuration, Expiry, FilterFailure,
+    GameAction, GameEvent, GameOutcome, GamePhase, GameRng, GameSetup, GameState, Hero,
+    HeroAttackAction, HeroPower, IntegrityError, Keyword, Locale, LoggedEvent, MigrationError,
+    MigrationReport, MulliganAction, MulliganPolicy, NarratedEvent, Narrator, PassPriorityAction,
+    PatchError, PatchOp, PendingAttack, PendingChoice, PlayCardAction, Player, PlayerEnchantment,
+    PlayerId, PlayerModifier, PlayerStats, PlayerSummary, PriorityWindow, Rarity, Replay,
+    ReplayError, ReplayFailure, ReplayStep, ResolveChoiceAction, ResolveCombatAction, RuleConfig,
+    RuleEngine, RuleError, RuleErrorReport, RuleResolution, SpectatorFeed, SpectatorFrame,
+    StackEntry, StackItemId, StatModifier, StatePatch, TargetFilter, TeamId, Tiebreaker,
+    TimedEffect, TimedEffectKind, Tribe, UseHeroPowerAction, VictoryReason, VictoryState,
 };
 
+/// `think_ai` 每段搜索的时长，两段之间让出主线程以便浏览器处理渲染与输入。
+const AI_SEARCH_SLICE: Duration = Duration::from_millis(12);
+
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
@@ -49,25 +68,56 @@ fn log_ai_reward(action: &GameAction, reward: f64, turn: u32) {
                 .unwrap_or_else(|| "英雄".to_string());
             format!("攻击 ({} -> {})", action.attacker_id, target)
         }
+        GameAction::HeroAttack { action } => {
+            let target = action
+                .defender_card
+                .map(|id| format!("卡牌 #{}", id))
+                .unwrap_or_else(|| "英雄".to_string());
+            format!("英雄攻击 ({})", target)
+        }
+        GameAction::UseHeroPower { .. } => "使用英雄技能".to_string(),
         GameAction::Mulligan { .. } => "调度手牌".to_string(),
+        GameAction::ResolveChoice { action } => {
+            format!("抉择 #{} 选项 {}", action.choice_id, action.option)
+        }
+        GameAction::DiscardCard { action } => format!("弃掉卡牌 #{}", action.discard_card_id),
+        GameAction::PassPriority { .. } => "放弃响应".to_string(),
+        GameAction::ResolveCombat { .. } => "结算战斗".to_string(),
         GameAction::AdvancePhase => "推进阶段".to_string(),
         GameAction::EndTurn => "结束回合".to_string(),
     };
-    let message = format!(
-        "[AI] 奖励 {:.2} ({}) 于回合 {}",
-        reward, description, turn
-    );
+    let message = format!("[AI] 奖励 {:.2} ({}) 于回合 {}", reward, description, turn);
     web_sys::console::log_1(&JsValue::from_str(&message));
 }
 
+/// 以 JSON 兼容的形式转换为 JS 值：映射与平铺字段都输出为普通对象而不是 `Map`。
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&Serializer::json_compatible())
+}
+
 fn to_js_error(error: RuleError) -> JsValue {
-    to_value(&error).unwrap_or_else(|serialize_err| JsValue::from_str(&serialize_err.to_string()))
+    report_to_js(RuleErrorReport::new(error))
+}
+
+fn report_to_js(report: RuleErrorReport) -> JsValue {
+    to_js(&report).unwrap_or_else(|serialize_err| JsValue::from_str(&serialize_err.to_string()))
+}
+
+fn replay_failure_to_js(failure: ReplayFailure) -> JsValue {
+    to_js(&failure).unwrap_or_else(|serialize_err| JsValue::from_str(&serialize_err.to_string()))
 }
 
 fn serde_to_js_error<E: std::fmt::Display>(error: E) -> JsValue {
     JsValue::from_str(&error.to_string())
 }
 
+/// 载入存档，失败时把迁移报告转换为 JS 错误。
+fn load_state(json: &str) -> Result<(GameState, MigrationReport), JsValue> {
+    GameState::load_json(json).map_err(|report| {
+        to_js(&report).unwrap_or_else(|error| JsValue::from_str(&error.to_string()))
+    })
+}
+
 fn make_resolution_json(resolution: RuleResolution) -> Result<String, JsValue> {
     serde_json::to_string(&resolution).map_err(serde_to_js_error)
 }
@@ -76,12 +126,52 @@ fn resolution_from_events(state: &GameState, events: Vec<GameEvent>) -> RuleReso
     RuleResolution::new(state.clone(), events)
 }
 
-fn execute_with_engine<F>(state: &mut GameState, action: F) -> Result<Vec<GameEvent>, JsValue>
-where
-    F: FnOnce(&mut RuleEngine, &mut GameState) -> Result<Vec<GameEvent>, RuleError>,
-{
-    let mut engine = RuleEngine::new();
-    action(&mut engine, state).map_err(to_js_error)
+/// 执行操作，失败时返回带操作回显与可替代操作的错误报告。
+fn execute_action(
+    engine: &mut RuleEngine,
+    state: &mut GameState,
+    action: GameAction,
+) -> Result<Vec<GameEvent>, JsValue> {
+    engine
+        .apply_action(state, &action)
+        .map_err(|error| report_to_js(engine.explain(state, &action, error)))
+}
+
+/// 原子地执行一组操作；某一步失败时整批回滚，并为失败的那一步生成与 `execute_action`
+/// 相同格式的错误报告，可替代操作按回滚后的局面给出。
+fn execute_batch(
+    engine: &mut RuleEngine,
+    state: &mut GameState,
+    actions: &[GameAction],
+) -> Result<Vec<GameEvent>, JsValue> {
+    engine
+        .apply_all(state, actions.to_vec())
+        .map_err(|error| match &error {
+            RuleError::BatchActionFailed { index, .. } => {
+                let action = &actions[*index];
+                report_to_js(engine.explain(state, action, error))
+            }
+            _ => to_js_error(error),
+        })
+}
+
+/// 按难度、策略与搜索算法名称构造 AI 配置，无法识别的名称沿用难度的默认值。
+fn ai_config(
+    difficulty: Option<&str>,
+    strategy: Option<&str>,
+    algorithm: Option<&str>,
+) -> AiConfig {
+    let difficulty = difficulty
+        .and_then(|value| AiDifficulty::from_str(value).ok())
+        .unwrap_or(AiDifficulty::Normal);
+    let mut config = AiConfig::from_difficulty(difficulty);
+    if let Some(strategy) = strategy.and_then(|value| AiStrategy::from_str(value).ok()) {
+        config = config.with_strategy(strategy);
+    }
+    if let Some(algorithm) = algorithm.and_then(|value| AiAlgorithm::from_str(value).ok()) {
+        config = config.with_algorithm(algorithm);
+    }
+    config
 }
 
 #[derive(Serialize)]
@@ -91,115 +181,435 @@ struct AiMoveResponse {
     applied: Option<RuleResolution>,
 }
 
+#[derive(Serialize)]
+struct EffectiveCost {
+    card_id: CardId,
+    base_cost: u8,
+    cost: u8,
+}
+
 #[wasm_bindgen]
 pub struct GameEngine {
     state: GameState,
+    rules: RuleEngine,
+    registry: CardRegistry,
+    /// 上一次 `diff_since_last_json` 时前端持有的状态。
+    synced: Option<serde_json::Value>,
+    /// `start_recording` 之后成功执行的步骤。
+    recording: Option<Replay>,
+    spectator: Option<SpectatorFeed>,
+    /// AI 公平模式的确定化次数，为 0 时 AI 读取对手真实的隐藏区域。
+    ai_determinizations: u8,
+    /// 前端设置的 AI 评估权重，代替策略自带的权重。
+    ai_weights: Option<StrategyWeights>,
+    /// `load_ai_profiles` 登记的性格档案。
+    ai_profiles: AiProfiles,
 }
 
 #[wasm_bindgen]
 impl GameEngine {
+    /// 传入 `seed` 时以该种子重置随机源，联机各端使用相同种子即可得到一致的抽牌与随机结果；
+    /// 未传入时新对局使用随机种子，载入的状态沿用其中保存的种子。
     #[wasm_bindgen(constructor)]
-    pub fn new(initial_state_json: Option<String>) -> Result<GameEngine, JsValue> {
-        let mut state = if let Some(json) = initial_state_json {
-            serde_json::from_str(&json).map_err(serde_to_js_error)?
-        } else {
-            GameState::sample()
+    pub fn new(
+        initial_state_json: Option<String>,
+        seed: Option<u64>,
+    ) -> Result<GameEngine, JsValue> {
+        let fresh = initial_state_json.is_none();
+        let mut state = match initial_state_json {
+            Some(json) => load_state(&json)?.0,
+            None => GameState::sample().with_seed(GameRng::entropy_seed()),
         };
-        state.reconcile_after_load();
-        state
-            .integrity_check()
-            .map_err(|error| to_js_error(RuleError::IntegrityViolation { error }))?;
-        Ok(GameEngine { state })
+        if let Some(seed) = seed {
+            state.rng = GameRng::new(seed);
+        }
+        let mut rules = RuleEngine::new();
+        // 示例对局跳过调度，搭建完成即开局
+        if fresh {
+            rules.begin_game(&mut state);
+        }
+        Ok(GameEngine {
+            state,
+            rules,
+            registry: CardRegistry::new(),
+            synced: None,
+            recording: None,
+            spectator: None,
+            ai_determinizations: 0,
+            ai_weights: None,
+            ai_profiles: AiProfiles::default(),
+        })
+    }
+
+    /// 当前对局随机源的初始种子。
+    pub fn seed(&self) -> u64 {
+        self.state.rng.seed()
+    }
+
+    /// 当前局面的稳定哈希（16 位十六进制），联机各端比对以检测不同步。
+    pub fn state_hash(&self) -> String {
+        format!("{:016x}", self.state.hash())
     }
 
     pub fn state_json(&self) -> Result<String, JsValue> {
         serde_json::to_string(&self.state).map_err(serde_to_js_error)
     }
 
-    pub fn set_state_json(&mut self, json: &str) -> Result<(), JsValue> {
-        let mut state: GameState = serde_json::from_str(json).map_err(serde_to_js_error)?;
-        state.reconcile_after_load();
-        state
-            .integrity_check()
-            .map_err(|error| to_js_error(RuleError::IntegrityViolation { error }))?;
+    /// 返回自上次调用以来的状态差量（JSON Patch 数组）；首次调用返回整体替换。
+    pub fn diff_since_last_json(&mut self) -> Result<String, JsValue> {
+        let current = serde_json::to_value(&self.state).map_err(serde_to_js_error)?;
+        let patch = match &self.synced {
+            Some(previous) => StatePatch::between(previous, &current),
+            None => StatePatch::replace_all(current.clone()),
+        };
+        self.synced = Some(current);
+        serde_json::to_string(&patch).map_err(serde_to_js_error)
+    }
+
+    /// 导出序号大于 `seq` 的事件日志记录，前端据此增量拉取事件。
+    pub fn events_since_json(&self, seq: u64) -> Result<String, JsValue> {
+        serde_json::to_string(self.state.events_since(seq)).map_err(serde_to_js_error)
+    }
+
+    /// 以指定语言（如 `zh-CN`、`en-US`）叙述事件日志，供对局历史面板展示。
+    pub fn render_events(&self, locale: &str) -> Result<String, JsValue> {
+        let narrated = Narrator::new(&self.state, Locale::parse(locale))
+            .with_registry(&self.registry)
+            .render(&self.state.event_log);
+        serde_json::to_string(&narrated).map_err(serde_to_js_error)
+    }
+
+    /// 导出各玩家的对局统计（伤害、治疗、抽牌、出牌、击杀等），供结算界面与平衡分析使用。
+    pub fn stats_json(&self) -> Result<String, JsValue> {
+        let stats = serde_json::json!({
+            "turns": self.state.turn,
+            "players": self.state.player_summaries(),
+        });
+        serde_json::to_string(&stats).map_err(serde_to_js_error)
+    }
+
+    /// 以指定玩家视角导出状态，对手的手牌、牌库与奥秘被隐藏。
+    pub fn state_for_player_json(&self, viewer: u8) -> Result<String, JsValue> {
+        serde_json::to_string(&self.state.redacted_for(viewer)).map_err(serde_to_js_error)
+    }
+
+    /// 导出尚未结算的效果栈，按结算顺序排列；响应窗口开启时栈暂存在窗口中。
+    pub fn effect_stack_json(&self) -> Result<String, JsValue> {
+        let stack = match &self.state.priority_window {
+            Some(window) => &window.stack,
+            None => self.rules.effect_stack(),
+        };
+        serde_json::to_string(&stack.entries()).map_err(serde_to_js_error)
+    }
+
+    /// 导出省略共享效果数据的状态，需先通过 `load_card_definitions_json` 载入卡牌定义。
+    pub fn compact_state_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.registry.compact(&self.state)).map_err(serde_to_js_error)
+    }
+
+    /// 导出供 AI worker 与联机同步使用的精简状态：省略事件日志与可由卡牌定义恢复的名称、描述，
+    /// 接收端以相同的卡牌定义调用 `set_state_json` 即可还原展示数据。
+    pub fn lightweight_state_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.registry.lightweight(&self.state)).map_err(serde_to_js_error)
+    }
+
+    /// 载入卡牌定义列表，替换已有的注册表与当前对局的发现卡池，返回定义数量。
+    pub fn load_card_definitions_json(&mut self, json: &str) -> Result<usize, JsValue> {
+        self.registry = serde_json::from_str(json).map_err(serde_to_js_error)?;
+        self.state.card_pool = self.registry.clone();
+        Ok(self.registry.len())
+    }
+
+    /// 以卡牌定义生成一张卡牌实例，定义不存在时返回 `undefined`。
+    pub fn instantiate_card_json(&self, def_id: u32, card_id: u32) -> Option<String> {
+        let card = self.registry.instantiate(def_id, card_id)?;
+        serde_json::to_string(&card).ok()
+    }
+
+    /// 载入任意版本的存档，返回迁移报告；失败时抛出带原因的迁移报告。
+    /// 存档未携带卡池时以已载入的卡牌定义作为发现卡池。
+    pub fn set_state_json(&mut self, json: &str) -> Result<JsValue, JsValue> {
+        let (mut state, report) = load_state(json)?;
+        self.registry.hydrate(&mut state);
+        if state.card_pool.is_empty() {
+            state.card_pool = self.registry.clone();
+        }
+        self.state = state;
+        self.rules = self.fresh_rules();
+        self.recording = None;
+        to_js(&report).map_err(JsValue::from)
+    }
+
+    /// 以本局开局时的牌组与规则配置再来一局，由本局的后手先行；未传入 `seed` 时由本局种子派生。
+    /// 返回新对局的开局结算（洗牌、掷硬币与发牌事件），状态不是由 `newGame` 创建时报错。
+    pub fn rematch(&mut self, seed: Option<u64>) -> Result<String, JsValue> {
+        let seed = seed.unwrap_or_else(|| GameRng::new(self.state.rng.seed()).next_u64());
+        let mut state = self
+            .state
+            .rematch(seed)
+            .ok_or_else(|| JsValue::from_str("state was not created from deck lists"))?;
+        self.registry.hydrate(&mut state);
         self.state = state;
+        self.rules = self.fresh_rules();
+        self.recording = None;
+        if let Some(feed) = &self.spectator {
+            self.spectator = Some(SpectatorFeed::new(&self.state, feed.delay()));
+        }
+        let events = self.state.events().cloned().collect();
+        make_resolution_json(resolution_from_events(&self.state, events))
+    }
+
+    /// 替换当前对局的规则配置，不会重置英雄生命。
+    pub fn set_rule_config_json(&mut self, json: &str) -> Result<(), JsValue> {
+        let config: RuleConfig = serde_json::from_str(json).map_err(serde_to_js_error)?;
+        config
+            .validate()
+            .map_err(|message| JsValue::from_str(&message))?;
+        self.state.config = config;
+        self.recording = None;
         Ok(())
     }
 
+    /// 查询手牌在当前场面下的实际费用，卡牌不在手牌中时返回 `undefined`。
+    pub fn effective_cost(&self, player_id: u8, card_id: u32) -> Option<u8> {
+        self.state.effective_cost(player_id, card_id)
+    }
+
+    pub fn effective_costs_json(&self, player_id: u8) -> Result<String, JsValue> {
+        let costs: Vec<EffectiveCost> = self
+            .state
+            .get_player(player_id)
+            .map(|player| {
+                player
+                    .hand
+                    .iter()
+                    .map(|card| EffectiveCost {
+                        card_id: card.id,
+                        base_cost: card.cost,
+                        cost: self.state.card_cost(player_id, card),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        serde_json::to_string(&costs).map_err(serde_to_js_error)
+    }
+
+    /// 列出玩家当前所有合法操作（含具体目标），格式与 AI 决策中的操作一致。
+    pub fn legal_actions_json(&self, player_id: u8) -> Result<String, JsValue> {
+        serde_json::to_string(&self.rules.legal_actions(&self.state, player_id))
+            .map_err(serde_to_js_error)
+    }
+
+    /// 校验一个 `GameAction` 当前能否执行，不修改对局状态。
+    pub fn can_apply_json(&self, action_json: &str) -> Result<bool, JsValue> {
+        let action: GameAction = serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        Ok(self.rules.validate(&self.state, &action).is_ok())
+    }
+
+    /// 列出单位当前可攻击的目标（英雄与单位），供前端高亮拖拽目标。
+    pub fn legal_defenders_json(
+        &self,
+        attacker_owner: u8,
+        attacker_id: u32,
+    ) -> Result<String, JsValue> {
+        let targets = self
+            .rules
+            .legal_defenders(&self.state, attacker_owner, attacker_id);
+        serde_json::to_string(&targets).map_err(serde_to_js_error)
+    }
+
     pub fn play_card_json(&mut self, action_json: &str) -> Result<String, JsValue> {
         let action: PlayCardAction =
             serde_json::from_str(action_json).map_err(serde_to_js_error)?;
-        let events = execute_with_engine(&mut self.state, |engine, state| {
-            engine.play_card(state, action.clone())
-        })?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        let resolution = self.apply_game_action(GameAction::PlayCard { action })?;
+        make_resolution_json(resolution)
     }
 
     pub fn mulligan_json(&mut self, action_json: &str) -> Result<String, JsValue> {
-        let action: MulliganAction = serde_json::from_str(action_json).map_err(serde_to_js_error)?;
-        let events = execute_with_engine(&mut self.state, |engine, state| {
-            engine.mulligan(state, action.clone())
-        })?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        let action: MulliganAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::Mulligan { action })?;
+        make_resolution_json(resolution)
+    }
+
+    /// 按调度策略替玩家完成调度，未传入策略时使用默认的费用曲线。
+    pub fn auto_mulligan_json(
+        &mut self,
+        player_id: u8,
+        policy_json: Option<String>,
+    ) -> Result<String, JsValue> {
+        let policy: MulliganPolicy = match policy_json {
+            Some(json) => serde_json::from_str(&json).map_err(serde_to_js_error)?,
+            None => MulliganPolicy::default(),
+        };
+        let resolution = self.apply_game_action(policy.action(&self.state, player_id))?;
+        make_resolution_json(resolution)
     }
 
     pub fn attack_json(&mut self, action_json: &str) -> Result<String, JsValue> {
         let action: AttackAction = serde_json::from_str(action_json).map_err(serde_to_js_error)?;
-        let events = execute_with_engine(&mut self.state, |engine, state| {
-            engine.attack(state, action.clone())
-        })?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        let resolution = self.apply_game_action(GameAction::Attack { action })?;
+        make_resolution_json(resolution)
+    }
+
+    pub fn resolve_combat_json(&mut self, action_json: &str) -> Result<String, JsValue> {
+        let action: ResolveCombatAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::ResolveCombat { action })?;
+        make_resolution_json(resolution)
+    }
+
+    pub fn hero_attack_json(&mut self, action_json: &str) -> Result<String, JsValue> {
+        let action: HeroAttackAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::HeroAttack { action })?;
+        make_resolution_json(resolution)
+    }
+
+    pub fn use_hero_power_json(&mut self, action_json: &str) -> Result<String, JsValue> {
+        let action: UseHeroPowerAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::UseHeroPower { action })?;
+        make_resolution_json(resolution)
     }
 
     pub fn resolve_discard_json(&mut self, action_json: &str) -> Result<String, JsValue> {
         let action: DiscardCardAction =
             serde_json::from_str(action_json).map_err(serde_to_js_error)?;
-        let events = execute_with_engine(&mut self.state, |engine, state| {
-            engine.resolve_pending_discard(state, action.clone())
-        })?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        let resolution = self.apply_game_action(GameAction::DiscardCard { action })?;
+        make_resolution_json(resolution)
+    }
+
+    pub fn resolve_choice_json(&mut self, action_json: &str) -> Result<String, JsValue> {
+        let action: ResolveChoiceAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::ResolveChoice { action })?;
+        make_resolution_json(resolution)
+    }
+
+    /// 开启或关闭效果结算追踪，开启后每次结算结果都会附带 `trace`。
+    pub fn set_effect_trace(&mut self, enabled: bool) {
+        self.rules.set_effect_trace(enabled);
+    }
+
+    /// 设置单次结算最多处理的栈条目数，超出时结算中止并产生 `ResolutionAborted` 事件。
+    pub fn set_resolution_budget(&mut self, budget: usize) {
+        self.rules.set_resolution_budget(budget);
+    }
+
+    /// 开启或关闭交互结算模式：打出卡牌后先等待对手响应再结算效果栈。
+    pub fn set_interactive_priority(&mut self, enabled: bool) {
+        self.rules.set_interactive_priority(enabled);
+    }
+
+    /// 当前持有响应优先权的玩家，没有等待中的窗口时返回 `undefined`。
+    pub fn priority_holder(&self) -> Option<u8> {
+        self.state
+            .priority_window
+            .as_ref()
+            .map(|window| window.player_id)
+    }
+
+    pub fn pass_priority_json(&mut self, action_json: &str) -> Result<String, JsValue> {
+        let action: PassPriorityAction =
+            serde_json::from_str(action_json).map_err(serde_to_js_error)?;
+        let resolution = self.apply_game_action(GameAction::PassPriority { action })?;
+        make_resolution_json(resolution)
+    }
+
+    /// 注册 JS 操作校验回调：以操作 JSON 调用，返回字符串即以该原因否决操作。
+    pub fn add_action_validator(&mut self, callback: Function) {
+        self.rules.add_pre_action_hook(move |_, action| {
+            let action_json = serde_json::to_string(action).unwrap_or_default();
+            match callback.call1(&JsValue::NULL, &JsValue::from_str(&action_json)) {
+                Ok(verdict) => match verdict.as_string() {
+                    Some(reason) => Err(RuleError::ActionVetoed { reason }),
+                    None => Ok(()),
+                },
+                Err(error) => Err(RuleError::ActionVetoed {
+                    reason: error
+                        .as_string()
+                        .unwrap_or_else(|| "validator threw".to_string()),
+                }),
+            }
+        });
+    }
+
+    /// 注册 JS 操作观察回调：操作成功后以操作 JSON 与事件 JSON 调用，
+    /// 返回事件数组的 JSON 字符串时追加这些事件。
+    pub fn add_action_observer(&mut self, callback: Function) {
+        self.rules.add_post_action_hook(move |_, action, events| {
+            let action_json = serde_json::to_string(action).unwrap_or_default();
+            let events_json = serde_json::to_string(events).unwrap_or_default();
+            callback
+                .call2(
+                    &JsValue::NULL,
+                    &JsValue::from_str(&action_json),
+                    &JsValue::from_str(&events_json),
+                )
+                .ok()
+                .and_then(|injected| injected.as_string())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        });
+    }
+
+    /// 移除所有已注册的操作回调。
+    pub fn clear_action_hooks(&mut self) {
+        self.rules.clear_hooks();
+    }
+
+    /// 原子地执行一组 `GameAction`；任一失败则整批回滚，错误中带有失败操作的下标。
+    pub fn apply_actions_json(&mut self, actions_json: &str) -> Result<String, JsValue> {
+        let actions: Vec<GameAction> =
+            serde_json::from_str(actions_json).map_err(serde_to_js_error)?;
+        let events = execute_batch(&mut self.rules, &mut self.state, &actions)?;
+        for action in actions {
+            self.record(ReplayStep::Action { action });
+        }
+        make_resolution_json(self.resolution(events))
     }
 
     pub fn start_turn(&mut self, player_id: u8) -> Result<String, JsValue> {
-        let mut engine = RuleEngine::new();
-        let events = engine
+        let events = self
+            .rules
             .start_turn(&mut self.state, player_id)
             .map_err(to_js_error)?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        self.record(ReplayStep::StartTurn { player_id });
+        make_resolution_json(self.resolution(events))
     }
 
     pub fn end_turn(&mut self) -> Result<String, JsValue> {
-        let mut engine = RuleEngine::new();
-        let events = engine.end_turn(&mut self.state).map_err(to_js_error)?;
-        make_resolution_json(resolution_from_events(&self.state, events))
+        let resolution = self.apply_game_action(GameAction::EndTurn)?;
+        make_resolution_json(resolution)
     }
 
     pub fn advance_phase(&mut self) -> Result<String, JsValue> {
-        RuleEngine::advance_phase(&mut self.state).map_err(to_js_error)?;
-        make_resolution_json(resolution_from_events(&self.state, Vec::new()))
+        let resolution = self.apply_game_action(GameAction::AdvancePhase)?;
+        make_resolution_json(resolution)
     }
 
+    /// `algorithm` 可为 `minimax`（默认）或 `mcts`。
     pub fn apply_ai_move(
         &mut self,
         player_id: u8,
         difficulty: Option<String>,
         strategy: Option<String>,
+        algorithm: Option<String>,
+        profile: Option<String>,
     ) -> Result<String, JsValue> {
-        let diff = difficulty
-            .as_deref()
-            .and_then(|value| AiDifficulty::from_str(value).ok())
-            .unwrap_or(AiDifficulty::Normal);
-        let mut config = AiConfig::from_difficulty(diff);
-        if let Some(strategy) = strategy
-            .as_deref()
-            .and_then(|value| AiStrategy::from_str(value).ok())
-        {
-            config = config.with_strategy(strategy);
-        }
+        let config = match profile {
+            Some(name) => self.profile_config(&name)?,
+            None => ai_config(
+                difficulty.as_deref(),
+                strategy.as_deref(),
+                algorithm.as_deref(),
+            ),
+        };
 
         // 先克隆状态用于 AI 决策
         let state_for_ai = self.state.clone();
-        let mut agent = AiAgent::new(config);
+        let mut agent = self.ai_agent(config);
         let decision = agent.decide_action(&state_for_ai, player_id);
         let chosen_action = decision.action.clone();
 
@@ -222,72 +632,264 @@ impl GameEngine {
         serde_json::to_string(&response).map_err(serde_to_js_error)
     }
 
+    /// 规划并执行 AI 的整个回合，返回计划及每一步实际的结算结果，供前端依次播放。
+    /// 任一步执行失败时整个回合回滚，对局保持规划前的状态。
+    pub fn apply_ai_turn(
+        &mut self,
+        player_id: u8,
+        difficulty: Option<String>,
+        strategy: Option<String>,
+    ) -> Result<String, JsValue> {
+        let config = ai_config(difficulty.as_deref(), strategy.as_deref(), None);
+        let mut agent = self.ai_agent(config);
+        let mut plan = agent.plan_turn(&self.state, player_id);
+        plan.apply(&mut self.rules, &mut self.state)
+            .map_err(to_js_error)?;
+        for step in &plan.steps {
+            self.record(ReplayStep::Action {
+                action: step.action.clone(),
+            });
+            if let Some(feed) = self.spectator.as_mut() {
+                feed.observe(&step.resolution.state);
+            }
+        }
+        serde_json::to_string(&plan).map_err(serde_to_js_error)
+    }
+
+    /// 提示：指定玩家本回合能取胜时返回致胜的操作序列（JSON 数组），否则返回 `null`。
+    /// 与其他提示一样只在该玩家可见的局面上搜索。
+    pub fn lethal_hint_json(&self, player_id: u8) -> Result<String, JsValue> {
+        let view = self.state.redacted_for(player_id);
+        let sequence = find_lethal(&self.fresh_rules(), &view, player_id);
+        serde_json::to_string(&sequence).map_err(serde_to_js_error)
+    }
+
+    /// 提示：按评估从高到低返回至多 `count` 个合法操作及推荐理由。
+    /// 搜索的是该玩家视角的局面，提示不会泄露对手的手牌、牌库与奥秘。
+    pub fn suggest_moves_json(&self, player_id: u8, count: u32) -> Result<String, JsValue> {
+        let mut agent = AiAgent::new(AiConfig::from_difficulty(AiDifficulty::Expert))
+            .with_rules(self.fresh_rules());
+        let view = self.state.redacted_for(player_id);
+        let suggestions = agent.suggest_moves(&view, player_id, count as usize);
+        serde_json::to_string(&suggestions).map_err(serde_to_js_error)
+    }
+
+    /// 登记 JSON 数组中的 AI 性格档案，同名档案被替换，返回已登记的档案总数。
+    /// 之后 `apply_ai_move` 与 `think_ai` 可按名称引用档案。
+    pub fn load_ai_profiles(&mut self, json: &str) -> Result<usize, JsValue> {
+        let profiles: Vec<AiProfile> = serde_json::from_str(json).map_err(serde_to_js_error)?;
+        for profile in profiles {
+            self.ai_profiles.register(profile);
+        }
+        Ok(self.ai_profiles.len())
+    }
+
+    /// 开启 AI 公平模式：以 `count` 个确定化局面代替对手的隐藏手牌与牌库，
+    /// 隐藏卡牌从已载入的卡牌定义中抽样；为 0 时关闭。
+    pub fn set_ai_determinizations(&mut self, count: u8) {
+        self.ai_determinizations = count;
+    }
+
+    /// 设置 AI 的评估权重（`StrategyWeights` 的 JSON，缺省字段取中性值），传入 `null` 恢复策略自带的权重。
+    pub fn set_ai_weights_json(&mut self, json: &str) -> Result<(), JsValue> {
+        self.ai_weights = serde_json::from_str(json).map_err(serde_to_js_error)?;
+        Ok(())
+    }
+
+    /// 延迟 `delay_ms` 后在后台分段搜索，每段之间让出主线程，返回 `AiDecision` 的 JSON。
     pub fn think_ai(
         &self,
         player_id: u8,
         difficulty: Option<String>,
         strategy: Option<String>,
         delay_ms: Option<u32>,
+        algorithm: Option<String>,
+        profile: Option<String>,
     ) -> Promise {
         let state = self.state.clone();
-        let diff = difficulty
-            .and_then(|value| AiDifficulty::from_str(&value).ok())
-            .unwrap_or(AiDifficulty::Normal);
-        let strat = strategy.and_then(|value| AiStrategy::from_str(&value).ok());
+        let config = match profile.map(|name| self.profile_config(&name)) {
+            Some(Ok(config)) => config,
+            Some(Err(error)) => return Promise::reject(&error),
+            None => ai_config(
+                difficulty.as_deref(),
+                strategy.as_deref(),
+                algorithm.as_deref(),
+            ),
+        };
         let delay = delay_ms.unwrap_or(0);
+        let mut agent = self.ai_agent(config);
 
         future_to_promise(async move {
             if delay > 0 {
                 TimeoutFuture::new(delay).await;
             }
-            let mut config = AiConfig::from_difficulty(diff);
-            if let Some(strategy) = strat {
-                config = config.with_strategy(strategy);
-            }
-            let mut agent = AiAgent::new(config);
-            let decision = agent.decide_action(&state, player_id);
+            let mut session = agent.start_search(&state, player_id);
+            let decision = loop {
+                if let Some(decision) = agent.search_slice(&mut session, AI_SEARCH_SLICE) {
+                    break decision;
+                }
+                TimeoutFuture::new(0).await;
+            };
             let json = serde_json::to_string(&decision).map_err(serde_to_js_error)?;
             Ok(JsValue::from_str(&json))
         })
     }
 
+    /// 以当前局面为起点开始记录回放，之前的记录被丢弃；载入存档或替换规则配置会结束记录。
+    pub fn start_recording(&mut self) {
+        let replay = Replay::from_state(&self.state)
+            .with_interactive_priority(self.rules.interactive_priority());
+        self.recording = Some(replay);
+    }
+
+    /// 导出当前记录的回放文件，附带当前局面的哈希供回放校验。
+    pub fn export_replay_json(&self) -> Result<String, JsValue> {
+        let mut replay = self
+            .recording
+            .clone()
+            .ok_or_else(|| JsValue::from_str("not recording"))?;
+        replay.finish(&self.state);
+        Ok(replay.to_json())
+    }
+
+    /// 载入并完整回放一个回放文件，成功后以回放的最终局面替换当前对局，
+    /// 返回包含全部回放事件的结算结果；失败时抛出 `ReplayFailure`。
+    pub fn import_replay_json(&mut self, json: &str) -> Result<String, JsValue> {
+        let replay = Replay::from_json(json).map_err(replay_failure_to_js)?;
+        let (state, events) = replay.verify().map_err(replay_failure_to_js)?;
+        self.state = state;
+        self.rules = self
+            .fresh_rules()
+            .with_interactive_priority(replay.interactive_priority);
+        self.recording = None;
+        make_resolution_json(resolution_from_events(&self.state, events))
+    }
+
+    /// 开始为观战界面生成画面，每帧在之后又发生 `delay_actions` 次操作时才放出。
+    pub fn start_spectating(&mut self, delay_actions: usize) {
+        self.spectator = Some(SpectatorFeed::new(&self.state, delay_actions));
+    }
+
+    pub fn stop_spectating(&mut self) {
+        self.spectator = None;
+    }
+
+    /// 取出已过延迟的观战画面（`SpectatorFrame` 数组），未开始观战时返回空数组。
+    pub fn spectator_frames_json(&mut self) -> Result<String, JsValue> {
+        let frames = self
+            .spectator
+            .as_mut()
+            .map(SpectatorFeed::take_ready)
+            .unwrap_or_default();
+        serde_json::to_string(&frames).map_err(serde_to_js_error)
+    }
+
+    /// 按名称取出已登记档案的 AI 配置，档案决定难度、策略、算法与权重。
+    fn profile_config(&self, name: &str) -> Result<AiConfig, JsValue> {
+        self.ai_profiles
+            .get(name)
+            .map(AiProfile::config)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown AI profile: {name}")))
+    }
+
+    /// 按引擎的公平模式与评估权重设置创建 AI，卡池取自已载入的卡牌定义。
+    fn ai_agent(&self, mut config: AiConfig) -> AiAgent {
+        if let Some(weights) = self.ai_weights {
+            config = config.with_evaluation_weights(weights);
+        }
+        AiAgent::new(config.with_determinizations(self.ai_determinizations))
+            .with_card_pool(&self.registry)
+            .with_rules(self.fresh_rules())
+    }
+
+    /// 沿用当前设置与回调、但不带任何结算中状态的规则引擎，用于替换对局与 AI 搜索。
+    fn fresh_rules(&self) -> RuleEngine {
+        RuleEngine::new()
+            .with_interactive_priority(self.rules.interactive_priority())
+            .with_resolution_budget(self.rules.resolution_budget())
+            .with_hooks(self.rules.hooks().clone())
+    }
+
+    fn resolution(&mut self, events: Vec<GameEvent>) -> RuleResolution {
+        if let Some(feed) = self.spectator.as_mut() {
+            feed.observe(&self.state);
+        }
+        resolution_from_events(&self.state, events).with_trace(self.rules.take_effect_trace())
+    }
+
     fn apply_game_action(&mut self, action: GameAction) -> Result<RuleResolution, JsValue> {
-        match action {
-            GameAction::PlayCard { action } => {
-                let events = execute_with_engine(&mut self.state, |engine, state| {
-                    engine.play_card(state, action.clone())
-                })?;
-                Ok(resolution_from_events(&self.state, events))
-            }
-            GameAction::Mulligan { action } => {
-                let events = execute_with_engine(&mut self.state, |engine, state| {
-                    engine.mulligan(state, action.clone())
-                })?;
-                Ok(resolution_from_events(&self.state, events))
-            }
-            GameAction::Attack { action } => {
-                let events = execute_with_engine(&mut self.state, |engine, state| {
-                    engine.attack(state, action.clone())
-                })?;
-                Ok(resolution_from_events(&self.state, events))
-            }
-            GameAction::AdvancePhase => {
-                RuleEngine::advance_phase(&mut self.state).map_err(to_js_error)?;
-                Ok(resolution_from_events(&self.state, Vec::new()))
-            }
-            GameAction::EndTurn => {
-                let mut engine = RuleEngine::new();
-                let events = engine.end_turn(&mut self.state).map_err(to_js_error)?;
-                Ok(resolution_from_events(&self.state, events))
-            }
+        let events = execute_action(&mut self.rules, &mut self.state, action.clone())?;
+        self.record(ReplayStep::Action { action });
+        Ok(self.resolution(events))
+    }
+
+    fn record(&mut self, step: ReplayStep) {
+        if let Some(replay) = self.recording.as_mut() {
+            replay.record(step);
+        }
+    }
+}
+
+/// Web Worker 内的 AI 入口：每条消息是一个 `AiWorkerRequest` 的 JSON，
+/// 返回对应的 `AiWorkerResponse` JSON，搜索在 worker 线程上同步完成。
+#[wasm_bindgen]
+pub struct AiWorkerHost {
+    worker: AiWorker,
+}
+
+#[wasm_bindgen]
+impl AiWorkerHost {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AiWorkerHost {
+        AiWorkerHost {
+            worker: AiWorker::new(),
         }
     }
+
+    /// 处理一条请求；消息不是合法的请求时抛出解析错误，此时无法得知请求的 `id`。
+    pub fn handle_message(&mut self, json: &str) -> Result<String, JsValue> {
+        let request: AiWorkerRequest = serde_json::from_str(json).map_err(serde_to_js_error)?;
+        serde_json::to_string(&self.worker.handle(request)).map_err(serde_to_js_error)
+    }
 }
 
-/// 返回一个示例游戏状态，方便前端调试或初始化。
+impl Default for AiWorkerHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 返回一个示例游戏状态，方便前端调试或初始化；未传入 `seed` 时使用随机种子。
 #[wasm_bindgen(js_name = "createGameState")]
-pub fn create_game_state() -> Result<JsValue, JsValue> {
-    to_value(&GameState::sample()).map_err(JsValue::from)
+pub fn create_game_state(seed: Option<u64>) -> Result<JsValue, JsValue> {
+    let state = GameState::sample().with_seed(seed.unwrap_or_else(GameRng::entropy_seed));
+    to_js(&state).map_err(JsValue::from)
+}
+
+/// 以两副牌组开局，洗牌、掷硬币并发放起手牌后返回处于调度阶段的状态。
+#[wasm_bindgen(js_name = "newGame")]
+pub fn new_game(
+    deck_a: JsValue,
+    deck_b: JsValue,
+    config: JsValue,
+    seed: u64,
+) -> Result<JsValue, JsValue> {
+    let deck_a: Vec<Card> = from_value(deck_a).map_err(JsValue::from)?;
+    let deck_b: Vec<Card> = from_value(deck_b).map_err(JsValue::from)?;
+    let config: Option<RuleConfig> = from_value(config).map_err(JsValue::from)?;
+    let config = config.unwrap_or_default();
+    config
+        .validate()
+        .map_err(|message| JsValue::from_str(&message))?;
+    let state = GameState::new_game(deck_a, deck_b, config, seed);
+    to_js(&state).map_err(JsValue::from)
+}
+
+/// 计算游戏状态的稳定哈希（16 位十六进制），不含事件日志。
+#[wasm_bindgen(js_name = "hashGameState")]
+pub fn hash_game_state(state: JsValue) -> Result<String, JsValue> {
+    let state: GameState = from_value(state).map_err(JsValue::from)?;
+    Ok(format!("{:016x}", state.hash()))
 }
 
 /// 将传入的游戏状态进行深拷贝后返回。
@@ -295,7 +897,37 @@ pub fn create_game_state() -> Result<JsValue, JsValue> {
 pub fn clone_game_state(state: JsValue) -> Result<JsValue, JsValue> {
     let state: GameState = from_value(state).map_err(JsValue::from)?;
     let cloned = state.clone();
-    to_value(&cloned).map_err(JsValue::from)
+    to_js(&cloned).map_err(JsValue::from)
+}
+
+/// 返回指定玩家视角下的状态，隐藏其他玩家的私密信息。
+#[wasm_bindgen(js_name = "redactGameState")]
+pub fn redact_game_state(state: JsValue, viewer: u8) -> Result<JsValue, JsValue> {
+    let state: GameState = from_value(state).map_err(JsValue::from)?;
+    to_js(&state.redacted_for(viewer)).map_err(JsValue::from)
+}
+
+/// 检查卡牌定义，返回发现的问题列表（为空表示未发现问题）。
+#[wasm_bindgen(js_name = "validateCard")]
+pub fn validate_card(card: JsValue) -> Result<JsValue, JsValue> {
+    let card: Card = from_value(card).map_err(JsValue::from)?;
+    to_js(&game::validate_card(&card)).map_err(JsValue::from)
+}
+
+/// 按赛制检查牌组，返回发现的问题列表（为空表示牌组合法）；未传赛制时使用默认赛制，
+/// 传入英雄时还要求职业卡牌与英雄职业一致。
+#[wasm_bindgen(js_name = "validateDeck")]
+pub fn validate_deck(deck: JsValue, format: JsValue, hero: JsValue) -> Result<JsValue, JsValue> {
+    let deck: Vec<CardDefinition> = from_value(deck).map_err(JsValue::from)?;
+    let format: Option<DeckFormat> = from_value(format).map_err(JsValue::from)?;
+    let format = format.unwrap_or_default();
+    let hero: Option<Hero> = from_value(hero).map_err(JsValue::from)?;
+    let errors = match &hero {
+        Some(hero) => game::validate_hero_deck(&deck, &format, hero),
+        None => game::validate_deck(&deck, &format),
+    }
+    .err();
+    to_js(&errors.unwrap_or_default()).map_err(JsValue::from)
 }
 
 /// 解析指定卡牌的效果，并返回更新后的状态与触发事件。
@@ -313,7 +945,7 @@ pub fn apply_card_effects(
     engine.queue_card_effects(&card, context);
     let events = engine.resolve_all(&mut state);
 
-    to_value(&make_resolution(state, events)).map_err(JsValue::from)
+    to_js(&make_resolution(state, events)).map_err(JsValue::from)
 }
 
 #[wasm_bindgen(js_name = "playCard")]
@@ -322,7 +954,7 @@ pub fn play_card(state: JsValue, action: JsValue) -> Result<JsValue, JsValue> {
     let action: PlayCardAction = from_value(action).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.play_card(&mut state, action) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -333,7 +965,7 @@ pub fn mulligan(state: JsValue, action: JsValue) -> Result<JsValue, JsValue> {
     let action: MulliganAction = from_value(action).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.mulligan(&mut state, action) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -344,7 +976,18 @@ pub fn attack(state: JsValue, action: JsValue) -> Result<JsValue, JsValue> {
     let action: AttackAction = from_value(action).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.attack(&mut state, action) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
+        Err(error) => Err(to_js_error(error)),
+    }
+}
+
+#[wasm_bindgen(js_name = "resolveCombat")]
+pub fn resolve_combat(state: JsValue, action: JsValue) -> Result<JsValue, JsValue> {
+    let mut state: GameState = from_value(state).map_err(JsValue::from)?;
+    let action: ResolveCombatAction = from_value(action).map_err(JsValue::from)?;
+    let mut engine = RuleEngine::new();
+    match engine.resolve_combat(&mut state, action) {
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -355,7 +998,18 @@ pub fn resolve_pending_discard(state: JsValue, action: JsValue) -> Result<JsValu
     let action: DiscardCardAction = from_value(action).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.resolve_pending_discard(&mut state, action) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
+        Err(error) => Err(to_js_error(error)),
+    }
+}
+
+#[wasm_bindgen(js_name = "resolveChoice")]
+pub fn resolve_choice(state: JsValue, action: JsValue) -> Result<JsValue, JsValue> {
+    let mut state: GameState = from_value(state).map_err(JsValue::from)?;
+    let action: ResolveChoiceAction = from_value(action).map_err(JsValue::from)?;
+    let mut engine = RuleEngine::new();
+    match engine.resolve_choice(&mut state, action) {
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -365,7 +1019,7 @@ pub fn start_turn(state: JsValue, player_id: u8) -> Result<JsValue, JsValue> {
     let mut state: GameState = from_value(state).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.start_turn(&mut state, player_id) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -375,7 +1029,7 @@ pub fn end_turn(state: JsValue) -> Result<JsValue, JsValue> {
     let mut state: GameState = from_value(state).map_err(JsValue::from)?;
     let mut engine = RuleEngine::new();
     match engine.end_turn(&mut state) {
-        Ok(events) => to_value(&make_resolution(state, events)).map_err(JsValue::from),
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -383,8 +1037,9 @@ pub fn end_turn(state: JsValue) -> Result<JsValue, JsValue> {
 #[wasm_bindgen(js_name = "advancePhase")]
 pub fn advance_phase(state: JsValue) -> Result<JsValue, JsValue> {
     let mut state: GameState = from_value(state).map_err(JsValue::from)?;
-    match RuleEngine::advance_phase(&mut state) {
-        Ok(_) => to_value(&make_resolution(state, Vec::new())).map_err(JsValue::from),
+    let mut engine = RuleEngine::new();
+    match engine.advance_phase(&mut state) {
+        Ok(events) => to_js(&make_resolution(state, events)).map_err(JsValue::from),
         Err(error) => Err(to_js_error(error)),
     }
 }
@@ -393,7 +1048,27 @@ pub fn advance_phase(state: JsValue) -> Result<JsValue, JsValue> {
 pub fn check_victory(state: JsValue) -> Result<JsValue, JsValue> {
     let mut state: GameState = from_value(state).map_err(JsValue::from)?;
     let outcome = RuleEngine::check_victory(&mut state);
-    to_value(&outcome).map_err(JsValue::from)
+    to_js(&outcome).map_err(JsValue::from)
+}
+
+/// 检查对局结果，平局以 `Draw` 表示。
+#[wasm_bindgen(js_name = "checkOutcome")]
+pub fn check_outcome(state: JsValue) -> Result<JsValue, JsValue> {
+    let mut state: GameState = from_value(state).map_err(JsValue::from)?;
+    let outcome = RuleEngine::check_outcome(&mut state);
+    to_js(&outcome).map_err(JsValue::from)
+}
+
+/// 从初始状态重放操作序列并返回最终状态；失败时返回首个分歧点（操作下标、操作、错误与当时的状态）。
+#[wasm_bindgen(js_name = "replayActions")]
+pub fn replay_actions(state: JsValue, actions: JsValue) -> Result<JsValue, JsValue> {
+    let state: GameState = from_value(state).map_err(JsValue::from)?;
+    let actions: Vec<GameAction> = from_value(actions).map_err(JsValue::from)?;
+    match RuleEngine::new().replay(&state, &actions) {
+        Ok(state) => to_js(&state).map_err(JsValue::from),
+        Err(error) => Err(to_js(&error)
+            .unwrap_or_else(|serialize_err| JsValue::from_str(&serialize_err.to_string()))),
+    }
 }
 
 #[wasm_bindgen(js_name = "validateState")]
@@ -411,22 +1086,17 @@ pub fn compute_ai_move(
     player_id: u8,
     difficulty: Option<String>,
     strategy: Option<String>,
+    algorithm: Option<String>,
 ) -> Result<JsValue, JsValue> {
     let state: GameState = from_value(state).map_err(JsValue::from)?;
-    let difficulty = difficulty
-        .as_deref()
-        .and_then(|value| AiDifficulty::from_str(value).ok())
-        .unwrap_or(AiDifficulty::Normal);
-    let mut config = AiConfig::from_difficulty(difficulty);
-    if let Some(strategy) = strategy
-        .as_deref()
-        .and_then(|value| AiStrategy::from_str(value).ok())
-    {
-        config = config.with_strategy(strategy);
-    }
+    let config = ai_config(
+        difficulty.as_deref(),
+        strategy.as_deref(),
+        algorithm.as_deref(),
+    );
     let mut agent = AiAgent::new(config);
     let decision = agent.decide_action(&state, player_id);
-    to_value(&decision).map_err(JsValue::from)
+    to_js(&decision).map_err(JsValue::from)
 }
 
 #[cfg(feature = "console_error_panic_hook")]
@@ -436,3 +1106,48 @@ fn set_panic_hook() {
 
 #[cfg(not(feature = "console_error_panic_hook"))]
 fn set_panic_hook() {}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use web_sys::js_sys::{Map, Reflect};
+
+    fn field(value: &JsValue, key: &str) -> JsValue {
+        Reflect::get(value, &JsValue::from_str(key)).expect("readable field")
+    }
+
+    #[wasm_bindgen_test]
+    fn flattened_rule_config_reaches_js_as_plain_fields() {
+        let state = GameState::sample().with_config(RuleConfig::default().with_max_hand_size(8));
+        let value = to_js(&state).unwrap();
+        assert!(value.is_object() && !value.is_instance_of::<Map>());
+        assert_eq!(field(&value, "max_hand_size").as_f64(), Some(8.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn batch_failures_report_the_failing_action() {
+        let mut engine = GameEngine::new(None, Some(7)).unwrap();
+        let before = engine.state_json().unwrap();
+        let missing = GameAction::PlayCard {
+            action: PlayCardAction {
+                player_id: 0,
+                card_id: 999,
+                target_player: None,
+                target_card: None,
+                position: None,
+            },
+        };
+        let actions = serde_json::to_string(&[GameAction::AdvancePhase, missing.clone()]).unwrap();
+
+        let report = engine.apply_actions_json(&actions).unwrap_err();
+        assert_eq!(field(&report, "index").as_f64(), Some(1.0));
+        let action: GameAction = from_value(field(&report, "action")).unwrap();
+        assert_eq!(action, missing);
+        assert_eq!(
+            engine.state_json().unwrap(),
+            before,
+            "the batch is rolled back"
+        );
+    }
+}
@@ -5,7 +5,7 @@ import type {
   PlayCardAction,
   RuleResolution,
 } from "@/types/domain";
-import type { AiDecision, AiMoveResponse } from "@/types/domain";
+import type { AiDecision, AiMoveResponse, TurnPlan } from "@/types/domain";
 import type { GameStateError } from "@/hooks/useGameState";
 import { EventBus, type EventBusEntry, type EventMap } from "./EventBus";
 
@@ -40,6 +40,7 @@ export interface GameEventMap extends EventMap {
   "state:error": { error: GameStateError };
   "ai:decision": { decision: AiDecision; playerId: number };
   "ai:applied": { response: AiMoveResponse; playerId: number };
+  "ai:turnApplied": { plan: TurnPlan; playerId: number };
   "canvas:invalidate": { reason: string; state: GameState | null };
   "canvas:interaction": CanvasInteractionEvent;
   "canvas:actionResult": {
@@ -179,12 +179,14 @@ const RULE_ERROR_MESSAGES: Record<string, (payload: RuleErrorPayload) => string>
     `当前阶段为 ${payload.actual ?? "未知"}，需要 ${payload.expected ?? "指定阶段"}`,
   CardNotFound: (payload) => `无法找到卡牌 #${payload.card_id ?? "?"}`,
   InvalidTarget: () => "无效的目标",
+  TargetFilterFailed: () => "该目标不满足卡牌的要求",
   InsufficientMana: (payload) =>
     `法力不足（需要 ${payload.required ?? "?"}，当前 ${payload.available ?? "?"}）`,
   CardTypeMismatch: (payload) =>
     `卡牌类型不符合要求（需要 ${payload.expected ?? "指定类型"}）`,
   UnitExhausted: () => "该单位已经攻击过了",
   InvalidAttackTarget: () => "无法攻击该目标",
+  CannotAttackAlly: () => "不能攻击队友",
   AttackerNotFound: () => "攻击者不存在或已被移除",
   ZeroAttackUnit: () => "该单位无法攻击",
   BoardFull: () => "战场已满，无法部署更多单位",
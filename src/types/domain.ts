@@ -14,7 +14,9 @@ export type EffectTarget =
   | { type: "ContextTarget" }
   | { type: "SourcePlayer" }
   | { type: "TargetPlayer" }
-  | { type: "OpponentOfSource" };
+  | { type: "OpponentOfSource" }
+  | { type: "AllAlliedHeroes" }
+  | { type: "AllEnemyHeroes" };
 
 export type EffectCondition =
   | {
@@ -33,14 +35,26 @@ export type EffectCondition =
       min: number;
     }
   | { type: "Any"; conditions: EffectCondition[] }
-  | { type: "All"; conditions: EffectCondition[] };
+  | { type: "All"; conditions: EffectCondition[] }
+  | { type: "HeroClass"; target: EffectTarget; class: string };
 
 export type EffectKind =
   | { type: "DirectDamage"; amount: number; target: EffectTarget }
   | { type: "Heal"; amount: number; target: EffectTarget }
   | { type: "DrawCard"; count: number; target: EffectTarget }
   | { type: "Composite"; effects: EffectKind[] }
-  | { type: "Conditional"; condition: EffectCondition; effect: EffectKind };
+  | { type: "Conditional"; condition: EffectCondition; effect: EffectKind }
+  | {
+      type: "TakeControl";
+      target: EffectTarget;
+      duration?: EffectDuration;
+    };
+
+export type EffectDuration = "Permanent" | "ThisTurn" | "UntilNextTurn";
+
+export type Expiry =
+  | { type: "EndOfTurn"; player_id: PlayerId }
+  | { type: "StartOfTurn"; player_id: PlayerId };
 
 export type VictoryReason =
   | { type: "HealthDepleted"; loser: PlayerId }
@@ -63,13 +77,37 @@ export interface CardEffect {
 
 export interface Card {
   id: CardId;
+  def_id?: number;
+  shared_effects?: boolean;
   name: string;
   cost: number;
   attack: number;
   health: number;
   card_type: CardType;
   exhausted?: boolean;
+  summoning_sick?: boolean;
   effects?: CardEffect[];
+  /** 被夺取控制权时的原拥有者。 */
+  owner?: PlayerId;
+}
+
+export type Rarity = "Common" | "Rare" | "Epic" | "Legendary";
+
+export interface CardDefinition {
+  def_id: number;
+  name: string;
+  cost: number;
+  attack: number;
+  health: number;
+  card_type?: CardType;
+  effects?: CardEffect[];
+  text?: string;
+  class?: string;
+  set?: string;
+  rarity?: Rarity;
+  collectible?: boolean;
+  tags?: string[];
+  flavor?: string;
 }
 
 export interface EffectContext {
@@ -100,6 +138,45 @@ export interface MulliganAction {
   replacements?: CardId[];
 }
 
+export interface Hero {
+  class: string;
+  hero_power?: Record<string, unknown>;
+  portrait_id?: string;
+  starting_armor?: number;
+}
+
+export interface PlayerStats {
+  damage_dealt: number;
+  damage_taken: number;
+  healing: number;
+  cards_drawn: number;
+  cards_played: number;
+  units_killed: number;
+  largest_hit: number;
+}
+
+export interface PlayerSummary extends PlayerStats {
+  player_id: PlayerId;
+  health: number;
+  armor: number;
+  mana: number;
+  max_mana: number;
+  hand_size: number;
+  deck_size: number;
+  board_size: number;
+}
+
+export interface MatchStats {
+  turns: number;
+  players: PlayerSummary[];
+}
+
+export interface NarratedEvent {
+  seq: number;
+  turn: number;
+  text: string;
+}
+
 export interface Player {
   id: PlayerId;
   health: number;
@@ -109,6 +186,9 @@ export interface Player {
   hand?: Card[];
   board?: Card[];
   deck?: Card[];
+  hero?: Hero;
+  team?: number;
+  stats?: PlayerStats;
 }
 
 export interface PendingDiscard {
@@ -157,10 +237,21 @@ export type GameEvent =
       card: Card;
     }
   | { type: "CardDiscarded"; player_id: PlayerId; card: Card }
+  | { type: "DeckShuffled"; player_id: PlayerId }
+  | { type: "CoinFlipped"; first_player: PlayerId }
   | { type: "MulliganApplied"; player_id: PlayerId; replaced: CardId[] }
   | { type: "TurnEnded"; player_id: PlayerId }
+  | {
+      type: "ControlChanged";
+      card_id: CardId;
+      from: PlayerId;
+      to: PlayerId;
+      expires?: Expiry;
+    }
   | { type: "GameWon"; winner: PlayerId; reason: VictoryReason };
 
+export type LoggedEvent = GameEvent & { seq: number; turn: number };
+
 export interface GameState {
   players: Player[];
   current_player: PlayerId;
@@ -170,10 +261,66 @@ export interface GameState {
   max_board_size?: number;
   mulligan_completed?: PlayerId[];
   pending_discards?: PendingDiscard[];
-  event_log?: GameEvent[];
+  event_log?: LoggedEvent[];
+  next_event_seq?: number;
+  schema_version?: number;
   outcome?: VictoryState;
   version?: number;
   next_pending_discard_id?: number;
+  rng?: GameRng;
+  setup?: GameSetup;
+}
+
+export interface GameSetup {
+  decks: Card[][];
+  first_player: PlayerId;
+}
+
+/** 64 位种子与内部状态以十进制字符串保存，避免精度丢失。 */
+export interface GameRng {
+  seed: string;
+  state: string;
+}
+
+export interface MigrationReport {
+  from_version: number;
+  to_version: number;
+  applied?: string[];
+  error?: { type: string } & Record<string, unknown>;
+}
+
+export type ReplayStart =
+  | { type: "State"; state: GameState }
+  | {
+      type: "NewGame";
+      deck_a: Card[];
+      deck_b: Card[];
+      config?: Record<string, unknown>;
+      seed: number;
+    };
+
+export type ReplayStep =
+  | { type: "Action"; action: GameAction }
+  | { type: "StartTurn"; player_id: PlayerId };
+
+export interface Replay {
+  format_version: number;
+  start: ReplayStart;
+  interactive_priority?: boolean;
+  steps?: ReplayStep[];
+  final_hash?: string;
+}
+
+export type ReplayFailure =
+  | { type: "InvalidFile"; message: string }
+  | { type: "UnsupportedVersion"; version: number; latest: number }
+  | { type: "StepFailed"; index: number; step: ReplayStep; error: RuleError }
+  | { type: "HashMismatch"; expected: string; actual: string };
+
+export interface SpectatorFrame {
+  index: number;
+  state: GameState;
+  events?: LoggedEvent[];
 }
 
 export interface RuleResolution {
@@ -186,7 +333,28 @@ export type IntegrityError =
   | { type: "InvalidPlayerIndex"; player_id: PlayerId }
   | { type: "DuplicateCardId"; card_id: CardId }
   | { type: "NegativeHealth"; player_id: PlayerId; value: number }
-  | { type: "ManaOutOfRange"; player_id: PlayerId; value: number };
+  | { type: "ManaOutOfRange"; player_id: PlayerId; value: number }
+  | { type: "HandTooLarge"; player_id: PlayerId; size: number; max: number }
+  | { type: "BoardTooLarge"; player_id: PlayerId; size: number; max: number }
+  | { type: "ExhaustedOutOfPlay"; card_id: CardId }
+  | { type: "OutcomeNotLogged"; winner: PlayerId }
+  | { type: "ConflictingOutcome" }
+  | { type: "PendingChoicePlayerMissing"; choice_id: number; player_id: PlayerId }
+  | { type: "PendingChoiceCardMissing"; choice_id: number; card_id: CardId }
+  | {
+      type: "CardStatOutOfRange";
+      card_id: CardId;
+      stat: "Cost" | "Attack" | "Health";
+      value: number;
+    };
+
+/** 目标未通过的具体筛选条件。 */
+export type FilterFailure =
+  | { type: "NotAUnit" }
+  | { type: "CardType"; expected: CardType; actual: CardType }
+  | { type: "CostTooHigh"; max_cost: number; actual: number }
+  | { type: "MissingTribe"; tribe: string }
+  | { type: "NotDamaged" };
 
 export type RuleError =
   | { type: "GameFinished" }
@@ -195,10 +363,12 @@ export type RuleError =
   | { type: "InvalidPhase"; expected: GamePhase; actual: GamePhase }
   | { type: "CardNotFound"; card_id: CardId }
   | { type: "InvalidTarget" }
+  | { type: "TargetFilterFailed"; filter: FilterFailure }
   | { type: "InsufficientMana"; required: number; available: number }
   | { type: "CardTypeMismatch"; expected: CardType; actual: CardType }
   | { type: "UnitExhausted"; card_id: CardId }
   | { type: "InvalidAttackTarget" }
+  | { type: "CannotAttackAlly"; player_id: PlayerId }
   | { type: "AttackerNotFound"; card_id: CardId }
   | { type: "ZeroAttackUnit"; card_id: CardId }
   | { type: "BoardFull" }
@@ -211,11 +381,14 @@ export type GameAction =
   | { type: "PlayCard"; action: PlayCardAction }
   | { type: "Mulligan"; action: MulliganAction }
   | { type: "Attack"; action: AttackAction }
+  | { type: "PassPriority"; action: { player_id: PlayerId } }
   | { type: "AdvancePhase" }
   | { type: "EndTurn" };
 
 export type AiDifficulty = "easy" | "normal" | "hard" | "expert";
 
+export type AiAlgorithm = "minimax" | "mcts";
+
 export type AiStrategy =
   | "aggressive"
   | "control"
@@ -223,6 +396,30 @@ export type AiStrategy =
   | "random"
   | "adaptive";
 
+/** AI 评估函数各项的权重，缺省的字段取中性值。 */
+export interface StrategyWeights {
+  hero?: number;
+  board?: number;
+  hand?: number;
+  mana?: number;
+  combo?: number;
+  armor?: number;
+  initiative?: number;
+}
+
+/** 具名的 AI 性格，未填写的字段沿用难度的默认配置。 */
+export interface AiProfile {
+  name: string;
+  difficulty?: AiDifficulty;
+  strategy?: AiStrategy;
+  algorithm?: AiAlgorithm;
+  weights?: StrategyWeights;
+  randomness?: number;
+  taunt_respect?: number;
+  /** 取值 [-1, 1]，为正时偏向打脸，为负时偏向交换。 */
+  face_bias?: number;
+}
+
 export interface AiDecision {
   action?: GameAction | null;
   evaluation: number;
@@ -232,6 +429,8 @@ export interface AiDecision {
   duration_ms: number;
   resolution?: RuleResolution | null;
   strategy: AiStrategy;
+  /** 展开的节点中提前剪枝的比例。 */
+  cutoff_rate?: number;
 }
 
 export interface AiMoveResponse {
@@ -239,6 +438,67 @@ export interface AiMoveResponse {
   applied?: RuleResolution | null;
 }
 
+export type HintReason =
+  | { type: "Lethal" }
+  | { type: "DamageDealt"; amount: number }
+  | { type: "UnitsDestroyed"; count: number }
+  | { type: "TempoGained"; amount: number };
+
+export interface MoveSuggestion {
+  action: GameAction;
+  evaluation: number;
+  reasons?: HintReason[];
+}
+
+export interface PlannedStep {
+  action: GameAction;
+  resolution: RuleResolution;
+}
+
+/** AI 一整个回合的操作序列，通常以结束回合收尾。 */
+export interface TurnPlan {
+  steps: PlannedStep[];
+  evaluation: number;
+  nodes: number;
+  timed_out: boolean;
+  duration_ms: number;
+}
+
+/** AI worker 单次请求的设置；指定 profile 时以档案为准。 */
+export interface AiSettings {
+  profile?: string;
+  difficulty?: AiDifficulty;
+  strategy?: AiStrategy;
+  algorithm?: AiAlgorithm;
+  weights?: StrategyWeights;
+  determinizations?: number;
+}
+
+export type AiTask =
+  | { type: "handshake" }
+  | { type: "load_cards"; cards: CardDefinition[] }
+  | { type: "load_profiles"; profiles: AiProfile[] }
+  | { type: "decide"; state: GameState; player_id: number; settings?: AiSettings }
+  | { type: "plan_turn"; state: GameState; player_id: number; settings?: AiSettings }
+  | { type: "suggest_moves"; state: GameState; player_id: number; count: number }
+  | { type: "lethal_hint"; state: GameState; player_id: number };
+
+export type AiTaskResult =
+  | { type: "handshake"; protocol: number }
+  | { type: "cards_loaded"; count: number }
+  | { type: "profiles_loaded"; count: number }
+  | { type: "decision"; decision: AiDecision }
+  | { type: "plan"; plan: TurnPlan }
+  | { type: "suggestions"; suggestions: MoveSuggestion[] }
+  | { type: "lethal"; actions?: GameAction[] }
+  | { type: "error"; message: string };
+
+/** 主线程发给 AI worker 的消息，id 由主线程分配。 */
+export type AiWorkerRequest = { id: number } & AiTask;
+
+/** AI worker 的回复，id 与对应请求相同。 */
+export type AiWorkerResponse = { id: number } & AiTaskResult;
+
 export type ControlScheme = "touch" | "keyboard" | "auto";
 
 export interface GameSettings {
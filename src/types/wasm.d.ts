@@ -1,14 +1,19 @@
 /// <reference types="vite/client" />
 
 import type {
+  AiAlgorithm,
   AiDecision,
   AiDifficulty,
   AiStrategy,
   AttackAction,
   Card,
+  CardDefinition,
   EffectContext,
+  GameAction,
   GameEvent,
   GameState,
+  Hero,
+  MigrationReport,
   MulliganAction,
   PlayCardAction,
   RuleResolution,
@@ -18,8 +23,22 @@ import type {
 declare module "../../rust-core/pkg/wasm_game.js" {
   export default function init(input?: RequestInfo | URL): Promise<void>;
   export function greet(name: string): string;
-  export function createGameState(): GameState;
+  export function createGameState(seed?: bigint | null): GameState;
+  export function newGame(
+    deckA: Card[],
+    deckB: Card[],
+    config: Record<string, unknown> | null | undefined,
+    seed: bigint
+  ): GameState;
   export function cloneGameState(state: GameState): GameState;
+  export function hashGameState(state: GameState): string;
+  export function redactGameState(state: GameState, viewer: number): GameState;
+  export function validateCard(card: Card): Array<{ type: string } & Record<string, unknown>>;
+  export function validateDeck(
+    deck: CardDefinition[],
+    format?: Record<string, unknown> | null,
+    hero?: Hero | null
+  ): Array<{ type: string } & Record<string, unknown>>;
   export function applyCardEffects(
     state: GameState,
     card: Card,
@@ -28,37 +47,105 @@ declare module "../../rust-core/pkg/wasm_game.js" {
   export function playCard(state: GameState, action: PlayCardAction): RuleResolution;
   export function mulligan(state: GameState, action: MulliganAction): RuleResolution;
   export function attack(state: GameState, action: AttackAction): RuleResolution;
+  export function resolveCombat(state: GameState, action: { player_id: number }): RuleResolution;
+  export function resolveChoice(
+    state: GameState,
+    action: { player_id: number; choice_id: number; option: number }
+  ): RuleResolution;
   export function startTurn(state: GameState, playerId: number): RuleResolution;
   export function endTurn(state: GameState): RuleResolution;
   export function advancePhase(state: GameState): RuleResolution;
   export function checkVictory(state: GameState): VictoryState | null;
+  export function checkOutcome(
+    state: GameState
+  ):
+    | ({ type: "Victory" } & VictoryState)
+    | { type: "Draw"; reason: { type: string } & Record<string, unknown> }
+    | null;
+  export function replayActions(state: GameState, actions: GameAction[]): GameState;
   export function validateState(state: GameState): void;
   export function computeAiMove(
     state: GameState,
     playerId: number,
     difficulty?: AiDifficulty,
-    strategy?: AiStrategy
+    strategy?: AiStrategy,
+    algorithm?: AiAlgorithm
   ): AiDecision;
+  export class AiWorkerHost {
+    constructor();
+    handle_message(json: string): string;
+  }
   export class GameEngine {
-    constructor(initialStateJson?: string | null);
+    constructor(initialStateJson?: string | null, seed?: bigint | null);
+    seed(): bigint;
+    state_hash(): string;
+    diff_since_last_json(): string;
+    events_since_json(seq: bigint): string;
+    render_events(locale: string): string;
+    stats_json(): string;
     state_json(): string;
-    set_state_json(json: string): void;
+    state_for_player_json(viewer: number): string;
+    compact_state_json(): string;
+    lightweight_state_json(): string;
+    effect_stack_json(): string;
+    load_card_definitions_json(json: string): number;
+    instantiate_card_json(defId: number, cardId: number): string | undefined;
+    effective_cost(playerId: number, cardId: number): number | undefined;
+    effective_costs_json(playerId: number): string;
+    set_state_json(json: string): MigrationReport;
+    rematch(seed?: bigint | null): string;
+    set_rule_config_json(json: string): void;
+    legal_actions_json(playerId: number): string;
+    can_apply_json(actionJson: string): boolean;
+    legal_defenders_json(attackerOwner: number, attackerId: number): string;
+    start_recording(): void;
+    export_replay_json(): string;
+    import_replay_json(json: string): string;
+    start_spectating(delayActions: number): void;
+    stop_spectating(): void;
+    spectator_frames_json(): string;
     play_card_json(actionJson: string): string;
     mulligan_json(actionJson: string): string;
+    auto_mulligan_json(playerId: number, policyJson?: string | null): string;
     attack_json(actionJson: string): string;
+    resolve_combat_json(actionJson: string): string;
+    hero_attack_json(actionJson: string): string;
+    use_hero_power_json(actionJson: string): string;
+    resolve_choice_json(actionJson: string): string;
+    set_effect_trace(enabled: boolean): void;
+    set_resolution_budget(budget: number): void;
+    set_interactive_priority(enabled: boolean): void;
+    priority_holder(): number | undefined;
+    pass_priority_json(actionJson: string): string;
+    add_action_validator(callback: (actionJson: string) => string | null | undefined): void;
+    add_action_observer(
+      callback: (actionJson: string, eventsJson: string) => string | null | undefined
+    ): void;
+    clear_action_hooks(): void;
+    apply_actions_json(actionsJson: string): string;
     start_turn(playerId: number): string;
     end_turn(): string;
     advance_phase(): string;
     apply_ai_move(
       playerId: number,
       difficulty?: AiDifficulty,
-      strategy?: AiStrategy
+      strategy?: AiStrategy,
+      algorithm?: AiAlgorithm,
+      profile?: string
     ): string;
+    apply_ai_turn(playerId: number, difficulty?: AiDifficulty, strategy?: AiStrategy): string;
+    lethal_hint_json(playerId: number): string;
+    suggest_moves_json(playerId: number, count: number): string;
+    set_ai_determinizations(count: number): void;
+    set_ai_weights_json(json: string): void;
+    load_ai_profiles(json: string): number;
     think_ai(
       playerId: number,
       difficulty?: AiDifficulty,
       strategy?: AiStrategy,
-      delayMs?: number
+      delayMs?: number,
+      algorithm?: AiAlgorithm,
+      profile?: string
     ): Promise<string>;
   }
 }
@@ -1,14 +1,20 @@
 import type {
+  AiAlgorithm,
   AiDecision,
   AiDifficulty,
   AiMoveResponse,
+  AiProfile,
   AiStrategy,
   AttackAction,
   DiscardCardAction,
+  GameAction,
   GameState,
+  MoveSuggestion,
   MulliganAction,
   PlayCardAction,
   RuleResolution,
+  StrategyWeights,
+  TurnPlan,
 } from "@/types/domain";
 import type { GameEventBus } from "@/events/GameEvents";
 import { summarizeResolution, summarizeState } from "@/events/GameEvents";
@@ -20,6 +26,7 @@ type WasmGameEngine = InstanceType<WasmModule["GameEngine"]> & { free?: () => vo
 export interface GameEngineServiceOptions {
   initialState?: GameState;
   initialStateJson?: string;
+  seed?: bigint;
   maxRetries?: number;
   retryDelay?: number;
   debugKey?: string | false;
@@ -32,6 +39,9 @@ export interface GameEngineServiceOptions {
 export interface ApplyAiOptions {
   difficulty?: AiDifficulty;
   strategy?: AiStrategy;
+  algorithm?: AiAlgorithm;
+  /** 已通过 loadAiProfiles 登记的性格档案名，设置后忽略难度、策略与算法。 */
+  profile?: string;
 }
 
 export interface ThinkAiOptions extends ApplyAiOptions {
@@ -108,7 +118,7 @@ export class GameEngineService {
   }
 
   public static async create(options: GameEngineServiceOptions = {}): Promise<GameEngineService> {
-    const { initialState, initialStateJson } = options;
+    const { initialState, initialStateJson, seed } = options;
     const maxRetries = options.maxRetries ?? 2;
     const retryDelay = options.retryDelay ?? DEFAULT_RETRY_DELAY;
     let attempt = 0;
@@ -118,7 +128,7 @@ export class GameEngineService {
       try {
         const module = await initGameCore();
         const json = initialStateJson ?? (initialState ? JSON.stringify(initialState) : null);
-        const engine = new module.GameEngine(json);
+        const engine = new module.GameEngine(json, seed ?? null);
         return new GameEngineService(module, engine, options);
       } catch (error) {
         lastError = error;
@@ -225,18 +235,67 @@ export class GameEngineService {
 
   public async applyAiMove(playerId: number, options: ApplyAiOptions = {}): Promise<AiMoveResponse> {
     const response = await this.withRetry(() => {
-      const json = this.engine.apply_ai_move(playerId, options.difficulty, options.strategy);
+      const json = this.engine.apply_ai_move(
+        playerId,
+        options.difficulty,
+        options.strategy,
+        options.algorithm,
+        options.profile
+      );
       return parseJson<AiMoveResponse>(json, "apply_ai_move");
     }, "apply_ai_move", { playerId, options });
     this.eventBus?.emit("ai:applied", { response, playerId });
     return response;
   }
 
+  public async applyAiTurn(playerId: number, options: ApplyAiOptions = {}): Promise<TurnPlan> {
+    const plan = await this.withRetry(() => {
+      const json = this.engine.apply_ai_turn(playerId, options.difficulty, options.strategy);
+      return parseJson<TurnPlan>(json, "apply_ai_turn");
+    }, "apply_ai_turn", { playerId, options });
+    this.eventBus?.emit("ai:turnApplied", { plan, playerId });
+    return plan;
+  }
+
+  public lethalHint(playerId: number): GameAction[] | null {
+    this.assertNotDisposed();
+    const json = this.engine.lethal_hint_json(playerId);
+    return parseJson<GameAction[] | null>(json, "lethal_hint");
+  }
+
+  public suggestMoves(playerId: number, count = 3): MoveSuggestion[] {
+    this.assertNotDisposed();
+    const json = this.engine.suggest_moves_json(playerId, count);
+    return parseJson<MoveSuggestion[]>(json, "suggest_moves");
+  }
+
+  public setAiDeterminizations(count: number): void {
+    this.assertNotDisposed();
+    this.engine.set_ai_determinizations(count);
+  }
+
+  public loadAiProfiles(profiles: AiProfile[]): number {
+    this.assertNotDisposed();
+    return this.engine.load_ai_profiles(JSON.stringify(profiles));
+  }
+
+  public setAiWeights(weights: StrategyWeights | null): void {
+    this.assertNotDisposed();
+    this.engine.set_ai_weights_json(JSON.stringify(weights));
+  }
+
   public async thinkAi(playerId: number, options: ThinkAiOptions = {}): Promise<AiDecision> {
     const difficulty = options.difficulty ?? "normal";
     const delay = options.delayMs ?? this.aiDelays[difficulty];
     const decision = await this.withRetry(async () => {
-      const json = await this.engine.think_ai(playerId, difficulty, options.strategy, delay);
+      const json = await this.engine.think_ai(
+        playerId,
+        difficulty,
+        options.strategy,
+        delay,
+        options.algorithm,
+        options.profile
+      );
       return parseJson<AiDecision>(json, "think_ai");
     }, "think_ai_async", { playerId, difficulty, delay });
     this.eventBus?.emit("ai:decision", { decision, playerId });
@@ -249,7 +308,13 @@ export class GameEngineService {
     options: ApplyAiOptions = {}
   ): Promise<AiDecision> {
     return this.withRetry(() => {
-      return this.module.computeAiMove(state, playerId, options.difficulty, options.strategy);
+      return this.module.computeAiMove(
+        state,
+        playerId,
+        options.difficulty,
+        options.strategy,
+        options.algorithm
+      );
     }, "compute_ai_move", { playerId, options });
   }
 
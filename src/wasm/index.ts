@@ -5,3 +5,4 @@ export {
   type ApplyAiOptions,
   type ThinkAiOptions
 } from "./GameEngineService";
+export { AiWorkerClient, AI_WORKER_PROTOCOL } from "./workers/AiWorkerClient";
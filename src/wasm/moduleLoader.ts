@@ -92,9 +92,9 @@ export function clearWasmCache() {
   setGlobalCache(null);
 }
 
-export async function createGameState(): Promise<GameState> {
+export async function createGameState(seed?: bigint): Promise<GameState> {
   const wasm = await initGameCore();
-  return wasm.createGameState();
+  return wasm.createGameState(seed ?? null);
 }
 
 export async function cloneGameState<T extends GameState>(state: T): Promise<GameState> {
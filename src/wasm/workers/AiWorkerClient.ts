@@ -0,0 +1,154 @@
+import type {
+  AiDecision,
+  AiProfile,
+  AiSettings,
+  AiTask,
+  AiTaskResult,
+  AiWorkerResponse,
+  CardDefinition,
+  GameAction,
+  GameState,
+  MoveSuggestion,
+  TurnPlan,
+} from "@/types/domain";
+
+// 与 rust-core 中 AI_WORKER_PROTOCOL 保持一致
+export const AI_WORKER_PROTOCOL = 1;
+
+type TaskResult<T extends AiTaskResult["type"]> = Extract<AiTaskResult, { type: T }>;
+
+interface PendingRequest {
+  expected: AiTaskResult["type"];
+  resolve: (result: AiTaskResult) => void;
+  reject: (error: Error) => void;
+}
+
+export class AiWorkerClient {
+  private nextId = 1;
+  private readonly pending = new Map<number, PendingRequest>();
+
+  constructor(private readonly worker: Worker) {
+    worker.addEventListener("message", this.handleMessage);
+    worker.addEventListener("error", this.handleError);
+  }
+
+  public static create(): AiWorkerClient {
+    const worker = new Worker(new URL("./aiWorker.ts", import.meta.url), { type: "module" });
+    return new AiWorkerClient(worker);
+  }
+
+  public async handshake(): Promise<number> {
+    const { protocol } = await this.request({ type: "handshake" }, "handshake");
+    if (protocol !== AI_WORKER_PROTOCOL) {
+      throw new Error(
+        `[AiWorkerClient] protocol mismatch: worker ${protocol}, client ${AI_WORKER_PROTOCOL}`
+      );
+    }
+    return protocol;
+  }
+
+  public async loadCards(cards: CardDefinition[]): Promise<number> {
+    const { count } = await this.request({ type: "load_cards", cards }, "cards_loaded");
+    return count;
+  }
+
+  public async loadProfiles(profiles: AiProfile[]): Promise<number> {
+    const { count } = await this.request({ type: "load_profiles", profiles }, "profiles_loaded");
+    return count;
+  }
+
+  public async decide(
+    state: GameState,
+    playerId: number,
+    settings: AiSettings = {}
+  ): Promise<AiDecision> {
+    const { decision } = await this.request(
+      { type: "decide", state, player_id: playerId, settings },
+      "decision"
+    );
+    return decision;
+  }
+
+  public async planTurn(
+    state: GameState,
+    playerId: number,
+    settings: AiSettings = {}
+  ): Promise<TurnPlan> {
+    const { plan } = await this.request(
+      { type: "plan_turn", state, player_id: playerId, settings },
+      "plan"
+    );
+    return plan;
+  }
+
+  public async suggestMoves(
+    state: GameState,
+    playerId: number,
+    count = 3
+  ): Promise<MoveSuggestion[]> {
+    const { suggestions } = await this.request(
+      { type: "suggest_moves", state, player_id: playerId, count },
+      "suggestions"
+    );
+    return suggestions;
+  }
+
+  public async lethalHint(state: GameState, playerId: number): Promise<GameAction[] | null> {
+    const { actions } = await this.request(
+      { type: "lethal_hint", state, player_id: playerId },
+      "lethal"
+    );
+    return actions ?? null;
+  }
+
+  public terminate(): void {
+    this.worker.removeEventListener("message", this.handleMessage);
+    this.worker.removeEventListener("error", this.handleError);
+    this.worker.terminate();
+    this.rejectAll(new Error("[AiWorkerClient] worker terminated"));
+  }
+
+  private request<T extends AiTaskResult["type"]>(
+    task: AiTask,
+    expected: T
+  ): Promise<TaskResult<T>> {
+    const id = this.nextId++;
+    return new Promise<TaskResult<T>>((resolve, reject) => {
+      this.pending.set(id, {
+        expected,
+        resolve: (result) => resolve(result as TaskResult<T>),
+        reject,
+      });
+      this.worker.postMessage({ id, ...task });
+    });
+  }
+
+  private readonly handleMessage = (event: MessageEvent<AiWorkerResponse>) => {
+    const result = event.data;
+    const pending = this.pending.get(result.id);
+    if (!pending) {
+      return;
+    }
+    this.pending.delete(result.id);
+    if (result.type === "error") {
+      pending.reject(new Error(`[AiWorkerClient] ${result.message}`));
+    } else if (result.type !== pending.expected) {
+      pending.reject(
+        new Error(`[AiWorkerClient] expected ${pending.expected}, got ${result.type}`)
+      );
+    } else {
+      pending.resolve(result);
+    }
+  };
+
+  private readonly handleError = (event: ErrorEvent) => {
+    this.rejectAll(new Error(`[AiWorkerClient] worker error: ${event.message}`));
+  };
+
+  private rejectAll(error: Error) {
+    for (const pending of this.pending.values()) {
+      pending.reject(error);
+    }
+    this.pending.clear();
+  }
+}
@@ -0,0 +1,33 @@
+// 在 Web Worker 中运行的 AI 服务：逐条处理 AiWorkerRequest，以同 id 的 AiWorkerResponse 回复。
+// 由 AiWorkerClient 通过 `new Worker(new URL("./aiWorker.ts", import.meta.url), { type: "module" })` 启动。
+import type { AiWorkerRequest, AiWorkerResponse } from "@/types/domain";
+import { initGameCore } from "../moduleLoader";
+
+type WasmModule = Awaited<ReturnType<typeof initGameCore>>;
+type AiWorkerHost = InstanceType<WasmModule["AiWorkerHost"]>;
+
+let hostPromise: Promise<AiWorkerHost> | null = null;
+
+const getHost = (): Promise<AiWorkerHost> => {
+  if (!hostPromise) {
+    hostPromise = initGameCore().then((wasm) => new wasm.AiWorkerHost());
+  }
+  return hostPromise;
+};
+
+const handleRequest = async (request: AiWorkerRequest): Promise<AiWorkerResponse> => {
+  try {
+    const host = await getHost();
+    const json = host.handle_message(JSON.stringify(request));
+    return JSON.parse(json) as AiWorkerResponse;
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    return { id: request.id, type: "error", message };
+  }
+};
+
+self.addEventListener("message", (event: MessageEvent<AiWorkerRequest>) => {
+  void handleRequest(event.data).then((response) => {
+    self.postMessage(response);
+  });
+});